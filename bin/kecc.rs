@@ -1,13 +1,15 @@
 use std::ffi::OsStr;
 use std::io::Write;
 use std::os::unix::io::{FromRawFd, IntoRawFd};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use kecc::asm::RelocationModel;
 use kecc::{
-    Asmgen, Deadcode, Gvn, IrParse, IrVisualizer, Irgen, Mem2reg, O1, Optimize, Parse, SimplifyCfg,
-    Translate, ir, ok_or_exit, write,
+    Asmgen, BraceStyle, CodeStyle, CommandLineMacro, Diagnostic, IrLink, IrParse, IrVisualizer,
+    Irgen, Lints, Parse, ParseError, PassManager, Preprocess, Severity, Toolchain, Translate, ir,
+    ok_or_exit, pass_names, write,
 };
 use lang_c::ast::TranslationUnit;
 use tempfile::tempdir;
@@ -15,6 +17,28 @@ use tempfile::tempdir;
 #[derive(Debug, Parser)]
 #[clap(name = "kecc", version, author, about)]
 struct KeccCli {
+    /// Adds the directory to the include search paths
+    #[clap(short = 'I', value_name = "DIR")]
+    include_dirs: Vec<String>,
+
+    /// Defines a macro, as `NAME` or `NAME=VALUE`
+    #[clap(short = 'D', value_name = "MACRO")]
+    defines: Vec<String>,
+
+    /// Undefines a macro
+    #[clap(short = 'U', value_name = "MACRO")]
+    undefines: Vec<String>,
+
+    /// Enables a warning as `-W<NAME>`, disables it as `-Wno-<NAME>`, or treats warnings as errors
+    /// as `-Werror`
+    #[clap(short = 'W', value_name = "WARNING")]
+    warnings: Vec<String>,
+
+    /// Generates position-independent code as `-fPIC`, which can be linked into shared objects, or
+    /// not as `-fno-PIC`
+    #[clap(short = 'f', value_name = "OPTION")]
+    codegen_options: Vec<String>,
+
     /// Parses the input C file
     #[clap(long)]
     parse: bool,
@@ -23,6 +47,14 @@ struct KeccCli {
     #[clap(short, long)]
     print: bool,
 
+    /// Sets the number of spaces of an indentation level for `--print`
+    #[clap(long, value_name = "WIDTH", default_value_t = 4)]
+    indent_width: usize,
+
+    /// Sets where to put opening braces for `--print`, as `same-line` or `next-line`
+    #[clap(long, value_name = "STYLE", default_value = "same-line")]
+    brace_style: BraceStyle,
+
     /// Generates IR
     #[clap(short, long)]
     irgen: bool,
@@ -63,60 +95,439 @@ struct KeccCli {
     #[clap(long)]
     gvn: bool,
 
+    /// Performs the pipeline of optimizations, e.g., `simplify-cfg,repeat(mem2reg,gvn,deadcode)`
+    #[clap(
+        long,
+        value_name = "PIPELINE",
+        conflicts_with_all = ["optimize", "simplify_cfg", "mem2reg", "deadcode", "gvn"]
+    )]
+    passes: Option<String>,
+
+    /// Prints IR to the standard error after each run of the optimization
+    #[clap(long, value_name = "PASS", value_delimiter = ',')]
+    print_after: Vec<String>,
+
+    /// Verifies IR after generating or parsing it, and after each optimization
+    #[clap(long)]
+    verify_each: bool,
+
+    /// Prints the number of runs, the number of runs changing IR and the time of each optimization
+    #[clap(long)]
+    time_passes: bool,
+
     /// Prints the output IR
     #[clap(long)]
     iroutput: bool,
 
+    /// Compiles each input file separately without assembling, writing `<NAME>.s`, or `<NAME>.ir` if
+    /// IR is printed
+    #[clap(short = 'S', conflicts_with = "print")]
+    assembly_only: bool,
+
+    /// Compiles and assembles each input file separately without linking, writing `<NAME>.o`
+    #[clap(
+        short = 'c',
+        conflicts_with_all = ["assembly_only", "parse", "print", "irgen", "irparse", "irprint", "iroutput", "irrun"]
+    )]
+    object_only: bool,
+
+    /// Executes the linked executable in `qemu-riscv64-static`
+    #[clap(
+        long,
+        conflicts_with_all = ["assembly_only", "object_only", "parse", "print", "irgen", "irparse", "irprint", "iroutput", "irrun"]
+    )]
+    run: bool,
+
+    /// Sets the C compiler driver of the target, which assembles and links
+    #[clap(long, value_name = "CC", default_value = "riscv64-linux-gnu-gcc")]
+    target_cc: String,
+
+    /// Sets the root directory of the target headers and libraries
+    #[clap(long, value_name = "DIR")]
+    sysroot: Option<String>,
+
     /// Sets the output file to use
     #[clap(short, long, value_name = "FILE")]
     output: Option<String>,
 
-    /// Sets the input file to use
-    input: String,
+    /// Sets the input C, IR, assembly or object files to use
+    #[clap(required = true)]
+    inputs: Vec<String>,
 }
 
 fn main() {
-    let matches = KeccCli::parse();
-    let input = Path::new(&matches.input);
+    let arg_matches = KeccCli::command().get_matches();
+    let matches = KeccCli::from_arg_matches(&arg_matches).unwrap_or_else(|e| e.exit());
+    let lints = lints(&matches);
+
+    let stops_before_assembly = matches.assembly_only
+        || matches.parse
+        || matches.print
+        || matches.irgen
+        || matches.irparse
+        || matches.irprint
+        || matches.iroutput
+        || matches.irrun;
+    if stops_before_assembly {
+        emit(&matches, &arg_matches, &lints);
+    } else {
+        build(&matches, &arg_matches, &lints);
+    }
+}
+
+/// Writes the output of the stage selected by `-S`, `--irgen`, etc. for the input files.
+fn emit(matches: &KeccCli, arg_matches: &clap::ArgMatches, lints: &Lints) {
+    if matches.assembly_only || matches.inputs.len() == 1 {
+        if matches.assembly_only && matches.inputs.len() > 1 && matches.output.is_some() {
+            eprintln!("error: cannot specify `-o` with `-S` and multiple input files");
+            ::std::process::exit(1);
+        }
+        for input in &matches.inputs {
+            let input = Path::new(input);
+            let output = if matches.assembly_only {
+                matches
+                    .output
+                    .clone()
+                    .unwrap_or_else(|| assembly_only_output(input, matches))
+            } else {
+                matches.output.clone().unwrap_or_else(|| "-".to_string())
+            };
+            let mut output = open_output(&output);
+            compile(input, &mut output, matches, arg_matches, lints);
+        }
+        return;
+    }
+
+    // Without `-S`, the input files are linked into one program in IR.
+    if matches.parse || matches.print {
+        eprintln!(
+            "error: cannot specify `--parse` or `--print` with multiple input files without `-S`"
+        );
+        ::std::process::exit(1);
+    }
+    let units = matches
+        .inputs
+        .iter()
+        .map(|input| translate_ir(Path::new(input), matches, arg_matches, lints))
+        .collect::<Vec<_>>();
+    let mut ir = match IrLink.translate(&units) {
+        Ok(ir) => ir,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ::std::process::exit(1);
+        }
+    };
+
+    let mut output = open_output(matches.output.as_deref().unwrap_or("-"));
+    if matches.irgen {
+        write(&ir, &mut output).unwrap();
+        return;
+    }
+    compile_ir(&mut ir, &mut output, matches);
+}
+
+/// Compiles, assembles and links the input files with the toolchain of the target, stopping after
+/// assembling for `-c` and executing the executable for `--run`.
+fn build(matches: &KeccCli, arg_matches: &clap::ArgMatches, lints: &Lints) {
+    if matches.object_only && matches.inputs.len() > 1 && matches.output.is_some() {
+        eprintln!("error: cannot specify `-o` with `-c` and multiple input files");
+        ::std::process::exit(1);
+    }
+
+    let toolchain = Toolchain {
+        cc: matches.target_cc.clone(),
+        sysroot: matches.sysroot.clone(),
+        ..Default::default()
+    };
+    let temp_dir = tempdir().expect("temp dir creation failed");
+
+    // The C and the IR files are compiled to assembly files, which are assembled and linked
+    // together with the input assembly and object files.
+    let mut objects = Vec::new();
+    for (index, input) in matches.inputs.iter().enumerate() {
+        let input = Path::new(input);
+        if matches!(
+            input.extension().and_then(OsStr::to_str),
+            Some("s" | "S" | "o")
+        ) {
+            objects.push(input.to_path_buf());
+            continue;
+        }
+        let stem = input.file_stem().expect("input file must have a name");
+        let asm_path = temp_dir
+            .path()
+            .join(format!("{index}-{}.s", stem.to_string_lossy()));
+        let mut output = open_output(&asm_path.display().to_string());
+        compile(input, &mut output, matches, arg_matches, lints);
+        objects.push(asm_path);
+    }
 
-    let output = matches.output.clone().unwrap_or_else(|| "-".to_string());
+    if matches.object_only {
+        for (input, object) in matches.inputs.iter().zip(&objects) {
+            // Object files are not assembled, as in GCC.
+            if object.extension() == Some(OsStr::new("o")) {
+                continue;
+            }
+            let output = matches.output.clone().map_or_else(
+                || {
+                    let stem = Path::new(input)
+                        .file_stem()
+                        .expect("input file must have a name");
+                    Path::new(stem).with_extension("o")
+                },
+                PathBuf::from,
+            );
+            execute(toolchain.assemble(object, &output));
+        }
+        return;
+    }
+
+    let executable = match &matches.output {
+        Some(output) => PathBuf::from(output),
+        None if matches.run => temp_dir.path().join("a.out"),
+        None => PathBuf::from("a.out"),
+    };
+    execute(toolchain.link(&objects, &executable));
+
+    if matches.run {
+        let status = match toolchain.run(&executable).status() {
+            Ok(status) => status,
+            Err(e) => {
+                eprintln!("error: failed to execute `{}`: {e}", toolchain.emulator);
+                ::std::process::exit(1);
+            }
+        };
+        temp_dir.close().expect("temp dir deletion failed");
+        ::std::process::exit(status.code().unwrap_or(1));
+    }
+}
 
-    let mut output: Box<dyn ::std::io::Write> = if output == "-" {
+/// Executes `command` of the toolchain, and exits if it fails.
+fn execute(mut command: Command) {
+    let program = command.get_program().to_string_lossy().into_owned();
+    match command.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => {
+            eprintln!("error: `{program}` failed with {status}");
+            ::std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("error: failed to execute `{program}`: {e}");
+            ::std::process::exit(1);
+        }
+    }
+}
+
+/// Opens the output file `path`, or the standard output if it is `-`.
+fn open_output(path: &str) -> Box<dyn ::std::io::Write> {
+    if path == "-" {
         Box::new(::std::io::stdout())
     } else {
-        Box::new(ok_or_exit!(::std::fs::File::create(output), 1))
+        Box::new(ok_or_exit!(::std::fs::File::create(path), 1))
+    }
+}
+
+/// Returns the output file for `input` with `-S` in the current directory, e.g., `a.s` for `a.c`,
+/// or `a.ir` if IR is printed. Nothing is written for `--parse` and `--irparse`, so the standard
+/// output is used then.
+fn assembly_only_output(input: &Path, matches: &KeccCli) -> String {
+    if matches.parse || matches.irparse || matches.irrun {
+        return "-".to_string();
+    }
+    let ext = if matches.irgen || matches.irprint || matches.iroutput {
+        "ir"
+    } else {
+        "s"
     };
+    let stem = input.file_stem().expect("input file must have a name");
+    Path::new(stem).with_extension(ext).display().to_string()
+}
 
+/// Compiles the input C or IR file `input` as specified by `matches`.
+fn compile(
+    input: &Path,
+    output: &mut dyn ::std::io::Write,
+    matches: &KeccCli,
+    arg_matches: &clap::ArgMatches,
+    lints: &Lints,
+) {
     let ext = input.extension();
     if ext == Some(OsStr::new("c")) {
-        let input = ok_or_exit!(Parse::default().translate(&input), 1);
-        compile_c(&input, &mut output, &matches);
+        let input = parse(input, matches, arg_matches, lints);
+        compile_c(&input, output, matches, lints);
     } else if ext == Some(OsStr::new("ir")) {
         let mut input = ok_or_exit!(IrParse::default().translate(&input), 1);
-        compile_ir(&mut input, &mut output, &matches);
+        compile_ir(&mut input, output, matches);
     } else {
-        panic!("Unsupported file extension: {ext:?}");
+        unsupported_input(input)
     }
 }
 
-fn compile_c(input: &TranslationUnit, output: &mut dyn ::std::io::Write, matches: &KeccCli) {
-    if matches.parse {
-        return;
+/// Translates the input C or IR file `input` to IR to be linked.
+fn translate_ir(
+    input: &Path,
+    matches: &KeccCli,
+    arg_matches: &clap::ArgMatches,
+    lints: &Lints,
+) -> ir::TranslationUnit {
+    let ext = input.extension();
+    if ext == Some(OsStr::new("c")) {
+        let input = parse(input, matches, arg_matches, lints);
+        irgen(&input, lints)
+    } else if ext == Some(OsStr::new("ir")) {
+        ok_or_exit!(IrParse::default().translate(&input), 1)
+    } else {
+        unsupported_input(input)
     }
+}
 
-    if matches.print {
-        write(input, output).unwrap();
-        return;
+/// Exits for the input file `input`, which is neither a C nor an IR file, in a stage that needs to
+/// translate it, e.g., `--irrun a.c b.s`.
+fn unsupported_input(input: &Path) -> ! {
+    eprintln!(
+        "error: `{}` is not a C or an IR file, which is required for this stage",
+        input.display()
+    );
+    ::std::process::exit(1);
+}
+
+/// Parses the input C file `input`, and exits if it fails.
+fn parse(
+    input: &Path,
+    matches: &KeccCli,
+    arg_matches: &clap::ArgMatches,
+    lints: &Lints,
+) -> TranslationUnit {
+    let mut parse = Parse {
+        preprocess: preprocess(matches, arg_matches),
+        lints: lints.clone(),
+        ..Default::default()
+    };
+    match parse.translate(&input) {
+        Ok(unit) => {
+            report_warnings(&parse.warnings);
+            unit
+        }
+        Err(ParseError::Unsupported(diags)) => {
+            for diag in &diags {
+                eprintln!("{diag}\n");
+            }
+            eprintln!(
+                "error: aborting due to {} unsupported construct(s)",
+                diags.len()
+            );
+            ::std::process::exit(1);
+        }
+        Err(ParseError::Semantic(diags)) => {
+            report_warnings(&diags);
+            ::std::process::exit(1);
+        }
+        Err(ParseError::Preprocess(e)) => {
+            eprintln!("{e}");
+            ::std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{e:?}");
+            ::std::process::exit(1);
+        }
     }
+}
 
-    let mut ir = match Irgen::default().translate(input) {
+/// Translates the parsed C file `input` to IR, and exits if it fails, so that the toolchain is not
+/// invoked with incomplete assembly.
+fn irgen(input: &TranslationUnit, lints: &Lints) -> ir::TranslationUnit {
+    let ir = match Irgen::default().translate(input) {
         Ok(ir) => ir,
         Err(irgen_error) => {
-            println!("{irgen_error}");
-            return;
+            eprintln!("{irgen_error}");
+            ::std::process::exit(1);
         }
     };
+    report_warnings(&lints.check_ir(&ir));
+    ir
+}
+
+/// Builds the lints from `-W`, applying the options in order.
+fn lints(matches: &KeccCli) -> Lints {
+    let mut lints = Lints::default();
+    for option in &matches.warnings {
+        if let Err(e) = lints.apply(option) {
+            eprintln!("error: {e}");
+            ::std::process::exit(1);
+        }
+    }
+    lints
+}
+
+/// Returns the relocation model from `-f`, where the last of `-fPIC` and `-fno-PIC` wins.
+fn relocation_model(matches: &KeccCli) -> RelocationModel {
+    let mut relocation_model = RelocationModel::default();
+    for option in &matches.codegen_options {
+        relocation_model = match option.as_str() {
+            "PIC" | "pic" => RelocationModel::Pic,
+            "no-PIC" | "no-pic" => RelocationModel::Static,
+            _ => {
+                eprintln!("error: unknown code generation option `-f{option}`");
+                ::std::process::exit(1);
+            }
+        };
+    }
+    relocation_model
+}
+
+/// Prints the warnings and the errors in `diags`, and exits if there is an error.
+fn report_warnings(diags: &[Diagnostic]) {
+    for diag in diags {
+        eprintln!("{diag}\n");
+    }
+    let errors = diags
+        .iter()
+        .filter(|diag| diag.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        eprintln!("error: aborting due to {errors} previous error(s)");
+        ::std::process::exit(1);
+    }
+}
+
+/// Builds the preprocessor from `-I`, `-D` and `-U`, keeping the order of `-D` and `-U`.
+fn preprocess(matches: &KeccCli, arg_matches: &clap::ArgMatches) -> Preprocess {
+    let indices = |id| arg_matches.indices_of(id).into_iter().flatten();
+    let defines = indices("defines")
+        .zip(&matches.defines)
+        .map(|(i, name)| (i, CommandLineMacro::Define(name.clone())));
+    let undefines = indices("undefines")
+        .zip(&matches.undefines)
+        .map(|(i, name)| (i, CommandLineMacro::Undefine(name.clone())));
+    let mut macros = defines.chain(undefines).collect::<Vec<_>>();
+    macros.sort_by_key(|(i, _)| *i);
+
+    Preprocess {
+        include_dirs: matches.include_dirs.iter().map(Into::into).collect(),
+        macros: macros.into_iter().map(|(_, m)| m).collect(),
+    }
+}
+
+fn compile_c(
+    input: &TranslationUnit,
+    output: &mut dyn ::std::io::Write,
+    matches: &KeccCli,
+    lints: &Lints,
+) {
+    if matches.parse {
+        return;
+    }
+
+    if matches.print {
+        let style = CodeStyle {
+            indent_width: matches.indent_width,
+            brace_style: matches.brace_style,
+        };
+        style.write(input, output).unwrap();
+        return;
+    }
 
+    let mut ir = irgen(input, lints);
     if matches.irgen {
         write(&ir, output).unwrap();
         return;
@@ -125,6 +536,45 @@ fn compile_c(input: &TranslationUnit, output: &mut dyn ::std::io::Write, matches
     compile_ir(&mut ir, output, matches)
 }
 
+/// Returns the pipeline of optimizations given by `--passes`, or by `-O` and the options of each
+/// optimization.
+fn pass_manager(matches: &KeccCli) -> PassManager {
+    let pipeline = if let Some(passes) = &matches.passes {
+        passes.clone()
+    } else if matches.optimize {
+        "repeat(simplify-cfg,mem2reg,gvn,deadcode)".to_string()
+    } else {
+        [
+            (matches.simplify_cfg, "simplify-cfg"),
+            (matches.mem2reg, "mem2reg"),
+            (matches.deadcode, "deadcode"),
+            (matches.gvn, "gvn"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, name)| enabled.then_some(name))
+        .collect::<Vec<_>>()
+        .join(",")
+    };
+    let mut pass_manager = if pipeline.is_empty() {
+        PassManager::default()
+    } else {
+        pipeline.parse().unwrap_or_else(|e| {
+            eprintln!("error: invalid `--passes`: {e}");
+            ::std::process::exit(1);
+        })
+    };
+
+    for name in &matches.print_after {
+        if !pass_names().any(|pass_name| pass_name == name) {
+            eprintln!("error: unknown pass `{name}` for `--print-after`");
+            ::std::process::exit(1);
+        }
+    }
+    pass_manager.print_after = matches.print_after.iter().cloned().collect();
+    pass_manager.verify_each = matches.verify_each;
+    pass_manager
+}
+
 fn compile_ir(
     input: &mut ir::TranslationUnit,
     output: &mut dyn ::std::io::Write,
@@ -139,23 +589,21 @@ fn compile_ir(
         return;
     }
 
-    if matches.optimize {
-        O1::default().optimize(input);
-    } else {
-        if matches.simplify_cfg {
-            SimplifyCfg::default().optimize(input);
-        }
-
-        if matches.mem2reg {
-            Mem2reg::default().optimize(input);
-        }
-
-        if matches.deadcode {
-            Deadcode::default().optimize(input);
+    if matches.verify_each {
+        if let Err(e) = ir::verify(input) {
+            eprintln!("error: ill-formed IR: {e}");
+            ::std::process::exit(1);
         }
+    }
 
-        if matches.gvn {
-            Gvn::default().optimize(input);
+    let mut pass_manager = pass_manager(matches);
+    if let Err(e) = pass_manager.run(input) {
+        eprintln!("error: {e}");
+        ::std::process::exit(1);
+    }
+    if matches.time_passes {
+        for statistics in pass_manager.statistics() {
+            eprintln!("{statistics}");
         }
     }
 
@@ -216,6 +664,6 @@ fn compile_ir(
         return;
     }
 
-    let asm = ok_or_exit!(Asmgen::default().translate(input), 1);
+    let asm = ok_or_exit!(Asmgen::new(relocation_model(matches)).translate(input), 1);
     write(&asm, output).unwrap();
 }
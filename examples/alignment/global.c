@@ -0,0 +1,18 @@
+struct point {
+    int x;
+    int y;
+};
+
+_Static_assert(sizeof(struct point) == 8, "unexpected size of struct point");
+_Static_assert(_Alignof(long) == 8, "unexpected alignment of long");
+
+_Alignas(16) int aligned;
+_Alignas(double) char by_type = 1;
+_Alignas(8) _Alignas(32) struct point strictest = {1, 2};
+_Alignas(0) short unaligned;
+extern int redeclared;
+_Alignas(16) int redeclared = 3;
+extern _Alignas(64) int declared;
+
+int *numbers = (int[]){1, 2, 3};
+struct point *origin = &(struct point){.y = 2};
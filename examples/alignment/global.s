@@ -0,0 +1,56 @@
+        .type	.compound0, @object
+        .section	.data
+        .align	2
+.compound0:
+        .word	0x1
+        .word	0x2
+        .word	0x3
+        .type	.compound1, @object
+        .section	.data
+        .align	2
+.compound1:
+        .zero	0x4
+        .word	0x2
+        .globl	aligned
+        .type	aligned, @object
+        .section	.bss
+        .align	4
+aligned:
+        .zero	0x4
+        .globl	by_type
+        .type	by_type, @object
+        .section	.data
+        .align	3
+by_type:
+        .byte	0x1
+        .globl	numbers
+        .type	numbers, @object
+        .section	.data
+        .align	3
+numbers:
+        .quad	.compound0
+        .globl	origin
+        .type	origin, @object
+        .section	.data
+        .align	3
+origin:
+        .quad	.compound1
+        .globl	redeclared
+        .type	redeclared, @object
+        .section	.data
+        .align	4
+redeclared:
+        .word	0x3
+        .globl	strictest
+        .type	strictest, @object
+        .section	.data
+        .align	5
+strictest:
+        .word	0x1
+        .word	0x2
+        .globl	unaligned
+        .type	unaligned, @object
+        .section	.bss
+        .align	1
+unaligned:
+        .zero	0x2
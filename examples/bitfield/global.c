@@ -0,0 +1,12 @@
+struct Flags {
+    unsigned int ready : 1;
+    int level : 3;
+    unsigned int : 0;
+    unsigned char mode : 4;
+    long count : 40;
+    short tail;
+};
+
+struct Flags zero;
+struct Flags flags = {1, -2, 9, 0x123456789, 7};
+struct Flags partial = {.level = 3, .count = -1};
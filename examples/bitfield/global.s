@@ -0,0 +1,23 @@
+        .globl	flags
+        .type	flags, @object
+        .section	.data
+        .align	3
+flags:
+        .word	0xd
+        .byte	0x9
+        .zero	0x3
+        .quad	0x7000123456789
+        .globl	partial
+        .type	partial, @object
+        .section	.data
+        .align	3
+partial:
+        .word	0x6
+        .zero	0x4
+        .quad	0xffffffffff
+        .globl	zero
+        .type	zero, @object
+        .section	.bss
+        .align	3
+zero:
+        .zero	0x10
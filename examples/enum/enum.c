@@ -0,0 +1,20 @@
+enum Color { RED, GREEN = 5, BLUE, ALPHA = (BLUE << 2) - sizeof(int) };
+
+typedef enum { OFF, ON } Switch;
+
+struct Pixel {
+    enum Color color;
+    Switch power;
+};
+
+struct Lamp {
+    enum { DARK, LIGHT } state;
+};
+
+enum Color color = BLUE;
+Switch power = ON;
+int alpha = ALPHA;
+int sizes[5] = {RED, GREEN, BLUE};
+struct Pixel pixel = {ALPHA > 0 ? GREEN : RED, !OFF};
+long size = sizeof(enum Color) + RED - 1;
+struct Lamp lamp = {LIGHT};
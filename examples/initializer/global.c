@@ -0,0 +1,28 @@
+enum { FIRST = 1, SECOND };
+
+struct point {
+    int x;
+    int y;
+};
+
+struct shape {
+    struct point origin;
+    union {
+        int radius;
+        char tag;
+    };
+    int sides[4];
+};
+
+int table[5] = { [SECOND] = 7, 1, [0] = -2 };
+struct point p = { .y = 4, .x = SECOND };
+struct shape s = { .origin.y = 5, .tag = 65, .sides = { [2] = 9 } };
+
+int *cursor = table + SECOND + 1;
+int *second = &table[1];
+int *origin_y = &s.origin.y;
+struct point *pp = &p;
+int **pcursor = &cursor;
+void (*handler)(void) = 0;
+int next(int *);
+int (*advance)(int *) = next;
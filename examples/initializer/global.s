@@ -0,0 +1,70 @@
+        .globl	advance
+        .type	advance, @object
+        .section	.data
+        .align	3
+advance:
+        .quad	next
+        .globl	cursor
+        .type	cursor, @object
+        .section	.data
+        .align	3
+cursor:
+        .quad	table+12
+        .globl	handler
+        .type	handler, @object
+        .section	.bss
+        .align	3
+handler:
+        .zero	0x8
+        .globl	origin_y
+        .type	origin_y, @object
+        .section	.data
+        .align	3
+origin_y:
+        .quad	s+4
+        .globl	p
+        .type	p, @object
+        .section	.data
+        .align	2
+p:
+        .word	0x2
+        .word	0x4
+        .globl	pcursor
+        .type	pcursor, @object
+        .section	.data
+        .align	3
+pcursor:
+        .quad	cursor
+        .globl	pp
+        .type	pp, @object
+        .section	.data
+        .align	3
+pp:
+        .quad	p
+        .globl	s
+        .type	s, @object
+        .section	.data
+        .align	2
+s:
+        .zero	0x4
+        .word	0x5
+        .byte	0x41
+        .zero	0xb
+        .word	0x9
+        .zero	0x4
+        .globl	second
+        .type	second, @object
+        .section	.data
+        .align	3
+second:
+        .quad	table+4
+        .globl	table
+        .type	table, @object
+        .section	.data
+        .align	2
+table:
+        .word	0xfffffffe
+        .zero	0x4
+        .word	0x7
+        .word	0x1
+        .zero	0x4
@@ -0,0 +1,9 @@
+int add();
+int add(int a, double b);
+
+double scale(long x, double factor);
+double scale();
+
+int apply();
+int (*unprototyped_pointer)();
+float (*prototyped_pointer)(float) = 0;
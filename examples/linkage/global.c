@@ -0,0 +1,10 @@
+extern int ext;
+extern int ext_defined = 1;
+extern int ext_later;
+int ext_later;
+int tentative;
+int tentative;
+static int internal;
+extern int internal;
+extern int ext_redeclared;
+extern int ext_redeclared;
@@ -0,0 +1,23 @@
+        .globl	ext_defined
+        .type	ext_defined, @object
+        .section	.data
+        .align	2
+ext_defined:
+        .word	0x1
+        .globl	ext_later
+        .type	ext_later, @object
+        .section	.bss
+        .align	2
+ext_later:
+        .zero	0x4
+        .type	internal, @object
+        .section	.bss
+        .align	2
+internal:
+        .zero	0x4
+        .globl	tentative
+        .type	tentative, @object
+        .section	.bss
+        .align	2
+tentative:
+        .zero	0x4
@@ -0,0 +1,21 @@
+int sum(int n, int unused_param) {
+    int unused;
+    int i;
+    int total = 0;
+    unsigned int limit = 10;
+    char narrow;
+
+    for (i = 0; i < limit; i++) {
+        total += i;
+    }
+    narrow = total;
+    return narrow + (n > 0u);
+}
+
+int main(void) {
+    char c = 'a';
+    float f = 1.5;
+    long l = sum(1, 2);
+    int i = l;
+    return c + f + i;
+}
@@ -0,0 +1,7 @@
+long double tenth = 0.1L;
+long double third = 1.0L / 3;
+long double big = 1e4000L;
+long double zero;
+double narrowed = 0.1L;
+long double widened = 0.1;
+long double integer = 9223372036854775807LL;
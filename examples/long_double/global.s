@@ -0,0 +1,47 @@
+        .globl	big
+        .type	big, @object
+        .section	.data
+        .align	4
+big:
+        .quad	0x18c21ab905450cc3
+        .quad	0x73e6a3750647fcab
+        .globl	integer
+        .type	integer, @object
+        .section	.data
+        .align	4
+integer:
+        .quad	0xfffc000000000000
+        .quad	0x403dffffffffffff
+        .globl	narrowed
+        .type	narrowed, @object
+        .section	.data
+        .align	3
+narrowed:
+        .quad	0x3fb999999999999a
+        .globl	tenth
+        .type	tenth, @object
+        .section	.data
+        .align	4
+tenth:
+        .quad	0x999999999999999a
+        .quad	0x3ffb999999999999
+        .globl	third
+        .type	third, @object
+        .section	.data
+        .align	4
+third:
+        .quad	0x5555555555555555
+        .quad	0x3ffd555555555555
+        .globl	widened
+        .type	widened, @object
+        .section	.data
+        .align	4
+widened:
+        .quad	0xa000000000000000
+        .quad	0x3ffb999999999999
+        .globl	zero
+        .type	zero, @object
+        .section	.bss
+        .align	4
+zero:
+        .zero	0x10
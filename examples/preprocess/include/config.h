@@ -0,0 +1,7 @@
+#pragma once
+
+#define SIZE 4
+#define SQUARE(x) ((x) * (x))
+#define MAX(a, b) ((a) > (b) ? (a) : (b))
+
+int config_version = 1;
@@ -0,0 +1,12 @@
+#ifndef GUARDED_H
+#define GUARDED_H
+
+#include "config.h"
+
+#define CONCAT(a, b) a##b
+#define STRINGIFY(x) #x
+#define VALUE(...) sum(__VA_ARGS__)
+
+int sum(int a, int b) { return a + b; }
+
+#endif
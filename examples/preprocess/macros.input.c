@@ -0,0 +1,57 @@
+#include <guarded.h>
+#include "include/guarded.h"
+#include <config.h>
+
+/* A macro defined on the command line. */
+#ifndef OFFSET
+#error "OFFSET must be defined"
+#endif
+
+#if defined(SIZE) && SIZE * 2 == 8 && !defined UNDEFINED
+int table[SIZE] = { 1, 2, 3, OFFSET };
+#elif SIZE > 0
+int table[1];
+#else
+int table[2];
+#endif
+
+#if 0
+This group is skipped, so it may contain anything like #bogus.
+#endif
+
+#define LONG_MACRO(a, b) \
+    ((a) + \
+     (b))
+#define f(x) (x + g)
+#define g f
+
+int CONCAT(var, 1) = SQUARE(SIZE + 1);
+
+int main() {
+    char *name = STRINGIFY(SIZE "s" + 1);
+    int line = __LINE__;
+    int f = 1;
+    int recursive = f(2);
+    return MAX(table[3], 2) == OFFSET && VALUE(var1, -25) == 0 && name[6] == 's' &&
+           LONG_MACRO(line, config_version) == 33 && recursive == 3;
+}
+
+/* `, ## __VA_ARGS__` drops the comma only if no variable argument is given. */
+#define LOG(fmt, ...) log_value(fmt, ## __VA_ARGS__)
+
+int log_value(int fmt, ...);
+
+int log_all() { return LOG(1) + LOG(2, SIZE) + LOG(3, 4, 5); }
+
+/* Integers are of `intmax_t` or `uintmax_t` in `#if`. */
+#if -1 > 0u && 0xffffffffffffffff == -1 && 18446744073709551615 > 0 && \
+    18446744073709551615u / 2 == 9223372036854775807 && (0 ? 1u : -1) > 0 && \
+    -2 >> 1 == -1 && 0xffffffffffffffff >> 63 == 1 && -7 / 2 == -3 && -7 % 2u == 1
+int is_unsigned = 1;
+#else
+int is_unsigned = 0;
+#endif
+
+#if -1 < 0u || -1 >= 0 || 'a' != 97 || (1 ? -1 : 0) > 0
+#error "integers in #if are wrongly signed"
+#endif
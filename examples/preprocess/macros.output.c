@@ -0,0 +1,22 @@
+int config_version = 1;
+
+int sum(int a, int b) { return a + b; }
+
+int table[4] = { 1, 2, 3, 7 };
+
+int var1 = ((4 + 1) * (4 + 1));
+
+int main() {
+    char *name = "SIZE \"s\" + 1";
+    int line = 32;
+    int f = 1;
+    int recursive = (2 + f);
+    return ((table[3]) > (2) ? (table[3]) : (2)) == 7 && sum(var1, -25) == 0 && name[6] == 's' &&
+           ((line) + (config_version)) == 33 && recursive == 3;
+}
+
+int log_value(int fmt, ...);
+
+int log_all() { return log_value(1) + log_value(2, 4) + log_value(3, 4, 5); }
+
+int is_unsigned = 1;
@@ -0,0 +1,56 @@
+struct point {
+    int x;
+    int y;
+};
+
+int f(int a, int *p);
+long f(int a, int *p);
+
+int g(void) {
+    const int c = 1;
+    struct point pt;
+    int *p = 3;
+    c = 2;
+    pt.z = 0;
+    undeclared = 1;
+    5 = c;
+    f(1);
+    if (pt) {
+        return;
+    }
+    break;
+}
+
+void h(const char *s) {
+    char *t = s;
+    return 0;
+}
+
+int k(int x) {
+    switch (x) {
+    case 1: {
+        x = 2;
+        break;
+    }
+    case 1: {
+        break;
+    }
+    }
+    goto missing;
+    return x;
+}
+
+int kr(a, a, b)
+    static int b = 1;
+    char c;
+    int b;
+{
+    return b;
+}
+
+int sum(int n) {
+    __builtin_va_list ap;
+    __builtin_va_start(ap, n);
+    __builtin_va_end(ap);
+    return n;
+}
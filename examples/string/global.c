@@ -0,0 +1,11 @@
+char *greeting = "hello";
+char buf[] = "abc";
+char name[8] = "kecc";
+char *tail = "world" + 2;
+char *words[] = { "a\tb", "\x41\102" };
+int sizes[] = { 1, [3] = 2 };
+const char message[] = "const";
+char quote[] = "say \"hi\"\n";
+char nul[6] = "a\0b";
+char empty[4] = "";
+char exact[3] = "abc";
@@ -0,0 +1,93 @@
+        .type	.str0, @object
+        .section	.rodata
+        .align	0
+.str0:
+        .string	"hello"
+        .type	.str1, @object
+        .section	.rodata
+        .align	0
+.str1:
+        .string	"world"
+        .type	.str2, @object
+        .section	.rodata
+        .align	0
+.str2:
+        .string	"a\011b"
+        .type	.str3, @object
+        .section	.rodata
+        .align	0
+.str3:
+        .string	"AB"
+        .globl	buf
+        .type	buf, @object
+        .section	.data
+        .align	0
+buf:
+        .string	"abc"
+        .globl	empty
+        .type	empty, @object
+        .section	.bss
+        .align	0
+empty:
+        .zero	0x4
+        .globl	exact
+        .type	exact, @object
+        .section	.data
+        .align	0
+exact:
+        .byte	0x61
+        .byte	0x62
+        .byte	0x63
+        .globl	greeting
+        .type	greeting, @object
+        .section	.data
+        .align	3
+greeting:
+        .quad	.str0
+        .globl	message
+        .type	message, @object
+        .section	.rodata
+        .align	0
+message:
+        .string	"const"
+        .globl	name
+        .type	name, @object
+        .section	.data
+        .align	0
+name:
+        .string	"kecc"
+        .zero	0x3
+        .globl	nul
+        .type	nul, @object
+        .section	.data
+        .align	0
+nul:
+        .string	"a\000b"
+        .zero	0x2
+        .globl	quote
+        .type	quote, @object
+        .section	.data
+        .align	0
+quote:
+        .string	"say \"hi\"\012"
+        .globl	sizes
+        .type	sizes, @object
+        .section	.data
+        .align	2
+sizes:
+        .word	0x1
+        .zero	0x8
+        .word	0x2
+        .globl	tail
+        .type	tail, @object
+        .section	.data
+        .align	3
+tail:
+        .quad	.str1+2
+        .globl	words
+        .type	words, @object
+        .section	.data
+        .align	3
+words:
+        .quad	.str2
+        .quad	.str3
@@ -0,0 +1,16 @@
+union Value {
+    int i;
+    char c[6];
+    long l;
+};
+
+struct Tagged {
+    char tag;
+    union Value value;
+    short count;
+};
+
+union Value zero;
+union Value word = {0x01020304};
+union Value chars = {.c = {'a', 'b'}};
+struct Tagged tagged = {1, {.l = -1}, 3};
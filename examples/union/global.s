@@ -0,0 +1,41 @@
+        .globl	chars
+        .type	chars, @object
+        .section	.data
+        .align	3
+chars:
+        .byte	0x61
+        .byte	0x62
+        .zero	0x6
+        .globl	tagged
+        .type	tagged, @object
+        .section	.data
+        .align	3
+tagged:
+        .byte	0x1
+        .zero	0x7
+        .byte	0xff
+        .byte	0xff
+        .byte	0xff
+        .byte	0xff
+        .byte	0xff
+        .byte	0xff
+        .byte	0xff
+        .byte	0xff
+        .half	0x3
+        .zero	0x6
+        .globl	word
+        .type	word, @object
+        .section	.data
+        .align	3
+word:
+        .byte	0x4
+        .byte	0x3
+        .byte	0x2
+        .byte	0x1
+        .zero	0x4
+        .globl	zero
+        .type	zero, @object
+        .section	.bss
+        .align	3
+zero:
+        .zero	0x8
@@ -0,0 +1,6 @@
+_Complex double z;
+
+int main() {
+  _Atomic int a = 0;
+  return _Generic(a, int: 1, default: 0);
+}
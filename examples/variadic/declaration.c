@@ -0,0 +1,6 @@
+#include <stdarg.h>
+
+int printf(const char *format, ...);
+int vprintf(const char *format, va_list ap);
+int (*logger)(const char *, ...) = printf;
+va_list saved;
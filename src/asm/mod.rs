@@ -96,6 +96,10 @@ pub enum Directive {
     Word(u32),
     /// .quad value
     Quad(u64),
+    /// .quad symbol+offset, i.e., a relocation to the address of `symbol` displaced by `offset`
+    QuadSymbol(Label, isize),
+    /// .string "bytes", which appends the terminating null character to `bytes`
+    String(Vec<u8>),
     /// .zero bytes
     Zero(usize),
 }
@@ -126,6 +130,25 @@ impl fmt::Display for Directive {
             Self::Half(value) => write!(f, ".half\t{value:#x?}"),
             Self::Word(value) => write!(f, ".word\t{value:#x?}"),
             Self::Quad(value) => write!(f, ".quad\t{value:#x?}"),
+            Self::QuadSymbol(symbol, offset) => {
+                if *offset == 0 {
+                    write!(f, ".quad\t{symbol}")
+                } else {
+                    write!(f, ".quad\t{symbol}{offset:+}")
+                }
+            }
+            Self::String(bytes) => {
+                write!(f, ".string\t\"")?;
+                for byte in bytes {
+                    match byte {
+                        b'"' => write!(f, "\\\"")?,
+                        b'\\' => write!(f, "\\\\")?,
+                        b' '..=b'~' => write!(f, "{}", *byte as char)?,
+                        _ => write!(f, "\\{byte:03o}")?,
+                    }
+                }
+                write!(f, "\"")
+            }
             Self::Zero(bytes) => write!(f, ".zero\t{bytes:#x?}"),
         }
     }
@@ -781,12 +804,14 @@ impl fmt::Display for BType {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UType {
     Lui,
+    Auipc,
 }
 
 impl fmt::Display for UType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Lui => write!(f, "lui"),
+            Self::Auipc => write!(f, "auipc"),
         }
     }
 }
@@ -800,6 +825,8 @@ impl fmt::Display for UType {
 pub enum Pseudo {
     /// la rd, symbol
     La { rd: Register, symbol: Label },
+    /// lla rd, symbol, which loads the PC-relative address of `symbol` even in PIC
+    Lla { rd: Register, symbol: Label },
     /// li rd, immediate
     Li {
         rd: Register,
@@ -842,6 +869,8 @@ pub enum Pseudo {
     Ret,
     /// call offset
     Call { offset: Label },
+    /// call offset@plt, which calls through the procedure linkage table in PIC
+    CallPlt { offset: Label },
 }
 
 impl Pseudo {
@@ -873,6 +902,7 @@ impl fmt::Display for Pseudo {
         };
         match self {
             Self::La { rd, symbol } => write!(f, "la\t{rd},{symbol}"),
+            Self::Lla { rd, symbol } => write!(f, "lla\t{rd},{symbol}"),
             Self::Li { rd, imm } => write!(f, "li\t{},{}", rd, *imm as i64),
             Self::Mv { rd, rs } => write!(f, "mv\t{rd},{rs}"),
             Self::Fmv { data_size, rd, rs } => write!(f, "fmv.{data_size}\t{rd},{rs}"),
@@ -888,6 +918,7 @@ impl fmt::Display for Pseudo {
             Self::Jalr { rs } => write!(f, "jalr\t{rs}"),
             Self::Ret => write!(f, "ret"),
             Self::Call { offset } => write!(f, "call\t{offset}"),
+            Self::CallPlt { offset } => write!(f, "call\t{offset}@plt"),
         }
     }
 }
@@ -896,7 +927,8 @@ impl fmt::Display for Pseudo {
 pub enum Immediate {
     // TODO: consider architecture dependency (current: 64-bit architecture)
     Value(u64),
-    /// %hi(symbol) or %lo(symbol)
+    /// %hi(symbol) or %lo(symbol), or %pcrel_lo(label) where `label` is that of the `auipc`
+    /// instruction with the corresponding %pcrel_hi(symbol) or %got_pcrel_hi(symbol)
     Relocation {
         relocation: RelocationFunction,
         symbol: Label,
@@ -934,6 +966,12 @@ pub enum RelocationFunction {
     Hi20,
     /// %lo
     Lo12,
+    /// %pcrel_hi
+    PcrelHi20,
+    /// %pcrel_lo
+    PcrelLo12,
+    /// %got_pcrel_hi, i.e., %pcrel_hi of the GOT entry of the symbol
+    GotPcrelHi20,
 }
 
 impl fmt::Display for RelocationFunction {
@@ -944,6 +982,9 @@ impl fmt::Display for RelocationFunction {
             match self {
                 Self::Hi20 => "%hi",
                 Self::Lo12 => "%lo",
+                Self::PcrelHi20 => "%pcrel_hi",
+                Self::PcrelLo12 => "%pcrel_lo",
+                Self::GotPcrelHi20 => "%got_pcrel_hi",
             }
         )
     }
@@ -960,6 +1001,25 @@ impl Label {
         let id = block_id.0;
         Self(format!(".{name}_L{id}"))
     }
+
+    /// Returns the `index`-th label in the function `name` of an `auipc` instruction, referred to
+    /// by `%pcrel_lo`.
+    pub fn pcrel_hi(name: &str, index: usize) -> Self {
+        Self(format!(".{name}_pcrel_hi{index}"))
+    }
+}
+
+/// How the addresses of symbols are calculated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RelocationModel {
+    /// The symbols are linked into the executable at fixed addresses.
+    #[default]
+    Static,
+    /// Position-independent code, as by `-fPIC`, which can be linked into shared objects. The
+    /// addresses of the symbols with the external linkage, which may be defined in other modules,
+    /// are loaded from the global offset table (GOT), and the functions with the external linkage
+    /// are called through the procedure linkage table (PLT).
+    Pic,
 }
 
 impl fmt::Display for Label {
@@ -1179,3 +1239,37 @@ impl fmt::Display for RegisterType {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pic_instructions() {
+        let label = Label(".L0".to_string());
+        let symbol = Label("counter".to_string());
+        let auipc = Instruction::UType {
+            instr: UType::Auipc,
+            rd: Register::A0,
+            imm: Immediate::relocation(RelocationFunction::GotPcrelHi20, symbol.clone()),
+        };
+        assert_eq!(auipc.to_string(), "auipc\ta0, %got_pcrel_hi(counter)");
+        let ld = Instruction::IType {
+            instr: IType::LD,
+            rd: Register::A0,
+            rs1: Register::A0,
+            imm: Immediate::relocation(RelocationFunction::PcrelLo12, label),
+        };
+        assert_eq!(ld.to_string(), "ld\ta0,%pcrel_lo(.L0)(a0)");
+        let pcrel_hi = Immediate::relocation(RelocationFunction::PcrelHi20, symbol.clone());
+        assert_eq!(pcrel_hi.to_string(), "%pcrel_hi(counter)");
+
+        let lla = Pseudo::Lla {
+            rd: Register::A0,
+            symbol: symbol.clone(),
+        };
+        assert_eq!(lla.to_string(), "lla\ta0,counter");
+        let call = Pseudo::CallPlt { offset: symbol };
+        assert_eq!(call.to_string(), "call\tcounter@plt");
+    }
+}
@@ -6,12 +6,17 @@ use crate::ir::HasDtype;
 use crate::opt::opt_utils;
 use crate::{Translate, asm, ir};
 
-#[derive(Debug)]
-pub struct Asmgen {}
+#[derive(Debug, Default)]
+pub struct Asmgen {
+    /// How the addresses of the global variables and the functions are calculated.
+    relocation_model: asm::RelocationModel,
+}
 
-impl Default for Asmgen {
-    fn default() -> Self {
-        todo!()
+impl Asmgen {
+    /// Creates an assembly generator with `relocation_model`, e.g., [`asm::RelocationModel::Pic`]
+    /// for `-fPIC`.
+    pub fn new(relocation_model: asm::RelocationModel) -> Self {
+        Self { relocation_model }
     }
 }
 
@@ -20,6 +25,1033 @@ impl Translate<ir::TranslationUnit> for Asmgen {
     type Error = ();
 
     fn translate(&mut self, source: &ir::TranslationUnit) -> Result<Self::Target, Self::Error> {
+        let mut functions = Vec::new();
+        let mut variables = Vec::new();
+        for (name, decl) in &source.decls {
+            match decl {
+                ir::Declaration::Variable {
+                    is_definition: false,
+                    ..
+                } => {}
+                ir::Declaration::Variable {
+                    dtype,
+                    initializer,
+                    linkage,
+                    alignment,
+                    ..
+                } => variables.push(translate_variable(
+                    name,
+                    dtype,
+                    initializer.as_ref(),
+                    *linkage,
+                    *alignment,
+                    source,
+                )?),
+                ir::Declaration::Function {
+                    signature,
+                    definition: Some(definition),
+                    linkage,
+                } => functions
+                    .push(self.translate_function(name, signature, definition, *linkage, source)?),
+                ir::Declaration::Function {
+                    definition: None, ..
+                } => {}
+            }
+        }
+
+        Ok(asm::Asm {
+            unit: asm::TranslationUnit {
+                functions,
+                variables,
+            },
+        })
+    }
+}
+
+impl Asmgen {
+    fn translate_function(
+        &mut self,
+        name: &str,
+        signature: &ir::FunctionSignature,
+        definition: &ir::FunctionDefinition,
+        linkage: ir::Linkage,
+        source: &ir::TranslationUnit,
+    ) -> Result<asm::Section<asm::Function>, ()> {
         todo!()
     }
 }
+
+/// Translates the global variable `name` to a section holding its initial value.
+///
+/// The initial value is laid out in bytes as the interpreter does. A variable of `const` elements,
+/// e.g., the anonymous variable holding a string literal, is placed in `.rodata`. Among the
+/// others, a variable without an initializer or with only zeros is placed in `.bss`, and the
+/// others in `.data`.
+fn translate_variable(
+    name: &str,
+    dtype: &ir::Dtype,
+    initializer: Option<&ast::Initializer>,
+    linkage: ir::Linkage,
+    alignment: Option<usize>,
+    source: &ir::TranslationUnit,
+) -> Result<asm::Section<asm::Variable>, ()> {
+    let (size, align) = dtype.size_align_of(&source.structs).map_err(|_| ())?;
+    let align = alignment.map_or(align, |alignment| alignment.max(align));
+    let directives = if let Some(initializer) = initializer {
+        // The interpreter identifies a global variable by its index in `decls`.
+        let symbols = source.decls.keys().collect::<Vec<_>>();
+        let globals = |name: &str| {
+            let bid = symbols.iter().position(|symbol| *symbol == name)?;
+            Some((bid, source.decls[name].dtype()))
+        };
+        let value = ir::Value::try_from_initializer(initializer, dtype, &source.structs, &globals)
+            .map_err(|_| ())?;
+        let bytes = ir::Byte::value_to_bytes(&value, &source.structs);
+        let is_string_literal = matches!(
+            initializer,
+            ast::Initializer::Expression(expr)
+                if matches!(expr.node, ast::Expression::StringLiteral(_))
+        );
+        let string = is_string_literal
+            .then(|| string_directives(&bytes))
+            .flatten();
+        match string {
+            Some(directives) => directives,
+            None => data_directives(&bytes, dtype, &source.structs, &symbols)?,
+        }
+    } else {
+        vec![asm::Directive::Zero(size)]
+    };
+    let section_type = if is_read_only(dtype) {
+        asm::SectionType::Rodata
+    } else if matches!(directives.as_slice(), [asm::Directive::Zero(_)]) {
+        asm::SectionType::Bss
+    } else {
+        asm::SectionType::Data
+    };
+
+    let label = asm::Label(name.to_string());
+    let mut header = symbol_directives(label.clone(), linkage, asm::SymbolType::Object);
+    header.push(asm::Directive::Section(section_type));
+    // `.align n` aligns to `2^n` bytes on RISC-V.
+    header.push(asm::Directive::Align(align.trailing_zeros() as usize));
+    Ok(asm::Section::new(
+        header,
+        asm::Variable::new(label, directives),
+    ))
+}
+
+/// Returns whether the object of `dtype` cannot be modified, i.e., it or its elements are `const`.
+fn is_read_only(dtype: &ir::Dtype) -> bool {
+    match dtype {
+        ir::Dtype::Array { inner, .. } => is_read_only(inner),
+        _ => dtype.is_const(),
+    }
+}
+
+/// Returns the directives emitting `bytes` of a character array initialized by a string literal
+/// as `.string`, followed by the zeros after the terminating null character.
+///
+/// Returns `None` if `bytes` have no terminating null character, e.g., `char s[2] = "ab";`, or
+/// only have zeros.
+fn string_directives(bytes: &[ir::Byte]) -> Option<Vec<asm::Directive>> {
+    let bytes = bytes
+        .iter()
+        .map(|byte| match byte {
+            ir::Byte::Concrete(byte) => Some(*byte),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let len = bytes.iter().rposition(|byte| *byte != 0)? + 1;
+    if len == bytes.len() {
+        return None;
+    }
+
+    let zeros = bytes.len() - len - 1;
+    let mut directives = vec![asm::Directive::String(bytes[..len].to_vec())];
+    if zeros > 0 {
+        directives.push(asm::Directive::Zero(zeros));
+    }
+    Some(directives)
+}
+
+/// Returns the directives emitting `bytes`, the initial value of an object of `dtype`.
+///
+/// Each scalar of `dtype` and each storage unit of bit-fields is emitted by a directive of its
+/// size, and a pointer to a global variable by a relocation. The other bytes, e.g., those of a
+/// union, are emitted one by one. Consecutive zeros, including padding, are merged into `.zero`.
+fn data_directives(
+    bytes: &[ir::Byte],
+    dtype: &ir::Dtype,
+    structs: &HashMap<String, Option<ir::Dtype>>,
+    symbols: &[&String],
+) -> Result<Vec<asm::Directive>, ()> {
+    let mut scalars = BTreeMap::new();
+    scalars_of(dtype, 0, structs, &mut scalars)?;
+
+    let mut directives = Vec::new();
+    let mut zeros = 0;
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let (size, value) = if let ir::Byte::Pointer {
+            bid,
+            offset: addend,
+            ..
+        } = &bytes[offset]
+        {
+            let size = ir::Dtype::SIZE_OF_POINTER;
+            match bid {
+                Some(bid) => {
+                    if zeros > 0 {
+                        directives.push(asm::Directive::Zero(zeros));
+                        zeros = 0;
+                    }
+                    directives.push(asm::Directive::QuadSymbol(
+                        asm::Label(symbols[*bid].clone()),
+                        *addend,
+                    ));
+                    offset += size;
+                    continue;
+                }
+                None => (size, *addend as u128),
+            }
+        } else {
+            let size = scalars
+                .get(&offset)
+                .copied()
+                .filter(|size| offset + size <= bytes.len())
+                .unwrap_or(1);
+            let value = bytes[offset..offset + size].iter().rev().try_fold(
+                0u128,
+                |value, byte| match byte {
+                    ir::Byte::Concrete(byte) => Ok((value << 8) | *byte as u128),
+                    ir::Byte::Undef => Ok(value << 8),
+                    ir::Byte::Pointer { .. } => Err(()),
+                },
+            )?;
+            (size, value)
+        };
+        offset += size;
+
+        if value == 0 {
+            zeros += size;
+            continue;
+        }
+        if zeros > 0 {
+            directives.push(asm::Directive::Zero(zeros));
+            zeros = 0;
+        }
+        match size {
+            1 => directives.push(asm::Directive::Byte(value as u8)),
+            2 => directives.push(asm::Directive::Half(value as u16)),
+            4 => directives.push(asm::Directive::Word(value as u32)),
+            8 => directives.push(asm::Directive::Quad(value as u64)),
+            16 => directives.extend([
+                asm::Directive::Quad(value as u64),
+                asm::Directive::Quad((value >> 64) as u64),
+            ]),
+            _ => return Err(()),
+        }
+    }
+    if zeros > 0 {
+        directives.push(asm::Directive::Zero(zeros));
+    }
+
+    Ok(directives)
+}
+
+/// Adds the offset and the size of each scalar of `dtype` at `offset` to `scalars`, where the
+/// storage unit of bit-fields counts as a scalar. The members of a union are not added, since
+/// they overlap each other.
+fn scalars_of(
+    dtype: &ir::Dtype,
+    offset: usize,
+    structs: &HashMap<String, Option<ir::Dtype>>,
+    scalars: &mut BTreeMap<usize, usize>,
+) -> Result<(), ()> {
+    match dtype {
+        ir::Dtype::Int { .. } | ir::Dtype::Float { .. } | ir::Dtype::Pointer { .. } => {
+            let (size, _) = dtype.size_align_of(structs).map_err(|_| ())?;
+            let _unused = scalars.insert(offset, size);
+        }
+        ir::Dtype::Array { inner, size } => {
+            let (inner_size, inner_align) = inner.size_align_of(structs).map_err(|_| ())?;
+            let stride = inner_size.max(inner_align);
+            for i in 0..*size {
+                scalars_of(inner, offset + i * stride, structs, scalars)?;
+            }
+        }
+        ir::Dtype::Struct {
+            name: Some(name),
+            is_union: false,
+            ..
+        } => {
+            let struct_type = structs.get(name).and_then(Option::as_ref).ok_or(())?;
+            let fields = struct_type
+                .get_struct_fields()
+                .and_then(Option::as_ref)
+                .ok_or(())?;
+            let (_, _, offsets) = struct_type
+                .get_struct_size_align_offsets()
+                .and_then(Option::as_ref)
+                .ok_or(())?;
+            let bit_fields = struct_type.get_struct_bit_fields(structs).map_err(|_| ())?;
+            for ((field, field_offset), bit_field) in fields.iter().zip(offsets).zip(bit_fields) {
+                if bit_field.is_some() {
+                    let (size, _) = field.size_align_of(structs).map_err(|_| ())?;
+                    let _unused = scalars.insert(offset + field_offset, size);
+                } else {
+                    scalars_of(field, offset + field_offset, structs, scalars)?;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Returns the directives declaring the symbol `label` of the global variable or function with
+/// `linkage`. Only the symbols with the external linkage are made visible to the linker by
+/// `.globl`, e.g., not the `static` local variables hoisted to global variables.
+fn symbol_directives(
+    label: asm::Label,
+    linkage: ir::Linkage,
+    symbol_type: asm::SymbolType,
+) -> Vec<asm::Directive> {
+    let globl = (linkage == ir::Linkage::External).then(|| asm::Directive::Globl(label.clone()));
+    globl
+        .into_iter()
+        .chain([asm::Directive::Type(label, symbol_type)])
+        .collect()
+}
+
+/// Returns the block loading the address of the global variable or function `symbol` with
+/// `linkage` to `rd`, labeled with `label` if needed. The instructions following them can be
+/// appended to the block.
+///
+/// In PIC, the address of a symbol with the external linkage, which may be defined in another
+/// module, is loaded from its GOT entry, addressed relative to the `auipc` instruction labeled with
+/// `label`. The other symbols are addressed relative to the PC as they are in the same module.
+fn load_symbol_address(
+    symbol: asm::Label,
+    linkage: ir::Linkage,
+    rd: asm::Register,
+    relocation_model: asm::RelocationModel,
+    label: asm::Label,
+) -> asm::Block {
+    match (relocation_model, linkage) {
+        (asm::RelocationModel::Static, _) => asm::Block::new(
+            None,
+            vec![asm::Instruction::Pseudo(asm::Pseudo::La { rd, symbol })],
+        ),
+        (asm::RelocationModel::Pic, ir::Linkage::Internal) => asm::Block::new(
+            None,
+            vec![asm::Instruction::Pseudo(asm::Pseudo::Lla { rd, symbol })],
+        ),
+        (asm::RelocationModel::Pic, ir::Linkage::External) => asm::Block::new(
+            Some(label.clone()),
+            vec![
+                asm::Instruction::UType {
+                    instr: asm::UType::Auipc,
+                    rd,
+                    imm: asm::Immediate::relocation(asm::RelocationFunction::GotPcrelHi20, symbol),
+                },
+                asm::Instruction::IType {
+                    instr: asm::IType::LD,
+                    rd,
+                    rs1: rd,
+                    imm: asm::Immediate::relocation(asm::RelocationFunction::PcrelLo12, label),
+                },
+            ],
+        ),
+    }
+}
+
+/// Returns the instruction calling the function `callee` with `linkage`. In PIC, a function with
+/// the external linkage is called through its PLT entry, since it may be defined in another module.
+fn call_function(
+    callee: asm::Label,
+    linkage: ir::Linkage,
+    relocation_model: asm::RelocationModel,
+) -> asm::Instruction {
+    let pseudo = match (relocation_model, linkage) {
+        (asm::RelocationModel::Pic, ir::Linkage::External) => {
+            asm::Pseudo::CallPlt { offset: callee }
+        }
+        _ => asm::Pseudo::Call { offset: callee },
+    };
+    asm::Instruction::Pseudo(pseudo)
+}
+
+/// The number of the registers of each type passing arguments in LP64D, i.e., `a0` to `a7` and
+/// `fa0` to `fa7`.
+const NUM_ARG_REGISTERS: usize = 8;
+
+/// A part of at most 8 bytes of an argument passed in LP64D.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArgumentPart {
+    Register(asm::Register),
+    /// The stack at the offset from the stack pointer of the caller at the call.
+    Stack(usize),
+}
+
+/// How an argument is passed in LP64D.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArgumentLocation {
+    /// The parts of the argument from its lowest address, or its address if it is passed by
+    /// reference.
+    parts: Vec<ArgumentPart>,
+    /// Whether the argument is copied to the memory of the caller and its address is passed, as a
+    /// struct larger than 16 bytes is.
+    by_reference: bool,
+}
+
+/// Returns how the arguments of `dtypes` are passed to a function with `num_params` fixed
+/// parameters in LP64D, where the arguments after the first `num_params` ones are variable
+/// arguments.
+///
+/// A fixed argument of `float` or `double`, or of a struct flattened to one or two members with
+/// such a member (see [`float_members`]), is passed in the floating-point registers while they
+/// remain. The other arguments, including all the variable arguments, are passed in 8-byte parts in
+/// the integer registers and then on the stack. A variable argument aligned to 16 bytes, e.g.,
+/// `long double`, starts at an even-numbered register, so that it is aligned in the register save
+/// area of the callee (see [`spill_variadic_registers`]) as well.
+fn argument_locations(
+    dtypes: &[ir::Dtype],
+    num_params: usize,
+    structs: &HashMap<String, Option<ir::Dtype>>,
+) -> Result<Vec<ArgumentLocation>, ()> {
+    let mut num_int = 0;
+    let mut num_float = 0;
+    let mut stack: usize = 0;
+    let mut locations = Vec::new();
+    for (index, dtype) in dtypes.iter().enumerate() {
+        let is_variadic = index >= num_params;
+        let (size, align) = dtype.size_align_of(structs).map_err(|_| ())?;
+
+        if let Some(members) = float_members(dtype, structs)?.filter(|_| !is_variadic) {
+            let num_float_members = members
+                .iter()
+                .filter(|member| member.get_float_width().is_some())
+                .count();
+            let num_int_members = members.len() - num_float_members;
+            if num_float + num_float_members <= NUM_ARG_REGISTERS
+                && num_int + num_int_members <= NUM_ARG_REGISTERS
+            {
+                let parts = members
+                    .iter()
+                    .map(|member| {
+                        let register = if member.get_float_width().is_some() {
+                            num_float += 1;
+                            asm::Register::arg(asm::RegisterType::FloatingPoint, num_float - 1)
+                        } else {
+                            num_int += 1;
+                            asm::Register::arg(asm::RegisterType::Integer, num_int - 1)
+                        };
+                        ArgumentPart::Register(register)
+                    })
+                    .collect();
+                locations.push(ArgumentLocation {
+                    parts,
+                    by_reference: false,
+                });
+                continue;
+            }
+        }
+
+        let by_reference = size > 2 * ir::Dtype::SIZE_OF_POINTER;
+        let (num_parts, align) = if by_reference {
+            (1, ir::Dtype::SIZE_OF_POINTER)
+        } else {
+            (size.div_ceil(ir::Dtype::SIZE_OF_POINTER), align)
+        };
+        if is_variadic && align == 2 * ir::Dtype::SIZE_OF_POINTER {
+            num_int = num_int.next_multiple_of(2);
+        }
+        let mut parts = Vec::new();
+        for _ in 0..num_parts {
+            if num_int < NUM_ARG_REGISTERS {
+                parts.push(ArgumentPart::Register(asm::Register::arg(
+                    asm::RegisterType::Integer,
+                    num_int,
+                )));
+                num_int += 1;
+            } else {
+                if parts.is_empty() {
+                    stack = stack.next_multiple_of(align.max(ir::Dtype::SIZE_OF_POINTER));
+                }
+                parts.push(ArgumentPart::Stack(stack));
+                stack += ir::Dtype::SIZE_OF_POINTER;
+            }
+        }
+        locations.push(ArgumentLocation {
+            parts,
+            by_reference,
+        });
+    }
+
+    Ok(locations)
+}
+
+/// Returns the scalar members of `dtype` if it may be passed in the floating-point registers,
+/// i.e., it is `float` or `double`, or a struct whose nested structs and arrays are flattened to
+/// one or two integers, pointers, `float`s or `double`s with at least one `float` or `double`.
+///
+/// The structs with bit-fields and the unions are passed in the integer registers.
+fn float_members(
+    dtype: &ir::Dtype,
+    structs: &HashMap<String, Option<ir::Dtype>>,
+) -> Result<Option<Vec<ir::Dtype>>, ()> {
+    let mut members = Vec::new();
+    if !flatten_members(dtype, structs, &mut members)? {
+        return Ok(None);
+    }
+    let has_float = members
+        .iter()
+        .any(|member| member.get_float_width().is_some());
+    Ok(has_float.then_some(members))
+}
+
+/// Appends the scalar members of `dtype` to `members`, returning `false` if there are more than
+/// two of them or one of them cannot be in a register, e.g., `long double`.
+fn flatten_members(
+    dtype: &ir::Dtype,
+    structs: &HashMap<String, Option<ir::Dtype>>,
+    members: &mut Vec<ir::Dtype>,
+) -> Result<bool, ()> {
+    match dtype {
+        ir::Dtype::Int { .. } | ir::Dtype::Pointer { .. } => members.push(dtype.clone()),
+        ir::Dtype::Float { width, .. }
+            if *width <= ir::Dtype::SIZE_OF_DOUBLE * ir::Dtype::BITS_OF_BYTE =>
+        {
+            members.push(dtype.clone())
+        }
+        ir::Dtype::Array { inner, size } => {
+            for _ in 0..*size {
+                if !flatten_members(inner, structs, members)? {
+                    return Ok(false);
+                }
+            }
+        }
+        ir::Dtype::Struct {
+            name: Some(name),
+            is_union: false,
+            ..
+        } => {
+            let struct_type = structs.get(name).and_then(Option::as_ref).ok_or(())?;
+            let fields = struct_type
+                .get_struct_fields()
+                .and_then(Option::as_ref)
+                .ok_or(())?;
+            let bit_fields = struct_type.get_struct_bit_fields(structs).map_err(|_| ())?;
+            if bit_fields.iter().any(Option::is_some) {
+                return Ok(false);
+            }
+            for field in fields {
+                if !flatten_members(field, structs, members)? {
+                    return Ok(false);
+                }
+            }
+        }
+        _ => return Ok(false),
+    }
+
+    Ok(members.len() <= 2)
+}
+
+/// Returns the number of the integer registers taken by the fixed parameters `params` of a
+/// function, after which the variable arguments of a variadic function start.
+fn num_fixed_integer_registers(
+    params: &[ir::Dtype],
+    structs: &HashMap<String, Option<ir::Dtype>>,
+) -> Result<usize, ()> {
+    let locations = argument_locations(params, params.len(), structs)?;
+    Ok(locations
+        .iter()
+        .flat_map(|location| &location.parts)
+        .filter(|part| {
+            matches!(
+                part,
+                ArgumentPart::Register(asm::Register::Arg(asm::RegisterType::Integer, _))
+            )
+        })
+        .count())
+}
+
+/// Returns the offset from `s0` of the slot of `a<index>` in the register save area of a variadic
+/// function.
+fn register_save_offset(index: usize) -> u64 {
+    let offset = (ir::Dtype::SIZE_OF_POINTER * (NUM_ARG_REGISTERS - index)) as i64;
+    (-offset) as u64
+}
+
+/// Returns the instructions in the prologue of a variadic function spilling the integer argument
+/// registers after the first `num_fixed` ones, which may hold variable arguments, to the register
+/// save area.
+///
+/// The register save area is the `8 * (8 - num_fixed)` bytes right below the frame pointer `s0`,
+/// i.e., the stack pointer at the entry, so that the variable arguments in the registers are
+/// followed by those passed on the stack. It must be at the top of the stack frame.
+fn spill_variadic_registers(num_fixed: usize) -> Vec<asm::Instruction> {
+    (num_fixed..NUM_ARG_REGISTERS)
+        .map(|index| asm::Instruction::SType {
+            instr: asm::SType::SD,
+            rs1: asm::Register::S0,
+            rs2: asm::Register::arg(asm::RegisterType::Integer, index),
+            imm: asm::Immediate::Value(register_save_offset(index)),
+        })
+        .collect()
+}
+
+/// Returns the instructions of `va_start`, storing the address of the first variable argument to
+/// the `va_list` at the address in `ptr`, where the fixed parameters take `num_fixed` integer
+/// registers. `scratch` is clobbered.
+///
+/// A `va_list` is a pointer into the register save area or the arguments on the stack, so
+/// `va_end` needs no instruction.
+fn va_start(ptr: asm::Register, num_fixed: usize, scratch: asm::Register) -> Vec<asm::Instruction> {
+    vec![
+        asm::Instruction::IType {
+            instr: asm::IType::ADDI,
+            rd: scratch,
+            rs1: asm::Register::S0,
+            imm: asm::Immediate::Value(register_save_offset(num_fixed)),
+        },
+        asm::Instruction::SType {
+            instr: asm::SType::SD,
+            rs1: ptr,
+            rs2: scratch,
+            imm: asm::Immediate::Value(0),
+        },
+    ]
+}
+
+/// Returns the instructions of `va_arg`, reading the next variable argument of `dtype` from the
+/// `va_list` at the address in `ptr` to `rd`, and advancing the `va_list` past it. `scratch` is
+/// clobbered, and must differ from `ptr` and `rd`.
+///
+/// An integer, a pointer, a `float` or a `double` is loaded to `rd`, which must be a
+/// floating-point register for the latter two. For a `long double` or a struct, `rd` gets the
+/// address to copy it from, which is read from its slot if it is passed by reference. The slots
+/// are laid out as [`argument_locations`] passes the variable arguments.
+fn va_arg(
+    ptr: asm::Register,
+    dtype: &ir::Dtype,
+    rd: asm::Register,
+    scratch: asm::Register,
+    structs: &HashMap<String, Option<ir::Dtype>>,
+) -> Result<Vec<asm::Instruction>, ()> {
+    let (size, align) = dtype.size_align_of(structs).map_err(|_| ())?;
+    let by_reference = size > 2 * ir::Dtype::SIZE_OF_POINTER;
+    let mut instructions = vec![asm::Instruction::IType {
+        instr: asm::IType::LD,
+        rd: scratch,
+        rs1: ptr,
+        imm: asm::Immediate::Value(0),
+    }];
+    if !by_reference && align == 2 * ir::Dtype::SIZE_OF_POINTER {
+        instructions.extend([
+            asm::Instruction::IType {
+                instr: asm::IType::ADDI,
+                rd: scratch,
+                rs1: scratch,
+                imm: asm::Immediate::Value(align as u64 - 1),
+            },
+            asm::Instruction::IType {
+                instr: asm::IType::Andi,
+                rd: scratch,
+                rs1: scratch,
+                imm: asm::Immediate::Value((-(align as i64)) as u64),
+            },
+        ]);
+    }
+
+    let is_scalar = match dtype {
+        ir::Dtype::Int { .. } | ir::Dtype::Pointer { .. } => true,
+        ir::Dtype::Float { width, .. } => {
+            *width <= ir::Dtype::SIZE_OF_DOUBLE * ir::Dtype::BITS_OF_BYTE
+        }
+        _ => false,
+    };
+    let (read, slot_size) = if by_reference {
+        let load = asm::Instruction::IType {
+            instr: asm::IType::LD,
+            rd,
+            rs1: scratch,
+            imm: asm::Immediate::Value(0),
+        };
+        (load, ir::Dtype::SIZE_OF_POINTER)
+    } else if is_scalar {
+        let load = asm::Instruction::IType {
+            instr: asm::IType::load(dtype.clone()),
+            rd,
+            rs1: scratch,
+            imm: asm::Immediate::Value(0),
+        };
+        (load, ir::Dtype::SIZE_OF_POINTER)
+    } else {
+        let address = asm::Instruction::Pseudo(asm::Pseudo::Mv { rd, rs: scratch });
+        (address, size.next_multiple_of(ir::Dtype::SIZE_OF_POINTER))
+    };
+    instructions.extend([
+        read,
+        asm::Instruction::IType {
+            instr: asm::IType::ADDI,
+            rd: scratch,
+            rs1: scratch,
+            imm: asm::Immediate::Value(slot_size as u64),
+        },
+        asm::Instruction::SType {
+            instr: asm::SType::SD,
+            rs1: ptr,
+            rs2: scratch,
+            imm: asm::Immediate::Value(0),
+        },
+    ]);
+
+    Ok(instructions)
+}
+
+/// Returns the soft-float routine of libgcc calculating `op` on `long double`, i.e., `f128`, which
+/// RISC-V has no instructions for. In LP64D, an `f128` operand is passed in a pair of integer
+/// registers, e.g., `a0` and `a1`.
+///
+/// The comparison routines return an `int` which should be compared with zero by `op`, e.g.,
+/// `lhs < rhs` if and only if `__lttf2(lhs, rhs) < 0`.
+fn long_double_binary_libcall(op: &ast::BinaryOperator) -> Option<&'static str> {
+    let libcall = match op {
+        ast::BinaryOperator::Plus => "__addtf3",
+        ast::BinaryOperator::Minus => "__subtf3",
+        ast::BinaryOperator::Multiply => "__multf3",
+        ast::BinaryOperator::Divide => "__divtf3",
+        ast::BinaryOperator::Equals => "__eqtf2",
+        ast::BinaryOperator::NotEquals => "__netf2",
+        ast::BinaryOperator::Less => "__lttf2",
+        ast::BinaryOperator::LessOrEqual => "__letf2",
+        ast::BinaryOperator::Greater => "__gttf2",
+        ast::BinaryOperator::GreaterOrEqual => "__getf2",
+        _ => return None,
+    };
+
+    Some(libcall)
+}
+
+/// Returns the soft-float routine of libgcc casting `from` to `to`, or `None` if none of them is
+/// `long double`, i.e., `f128`.
+///
+/// Casting from or to an integer narrower than 32 bits should be done with the 32-bit routines
+/// and an extension or a truncation of the integer.
+fn long_double_typecast_libcall(from: &ir::Dtype, to: &ir::Dtype) -> Option<&'static str> {
+    let long_double_width = ir::Dtype::SIZE_OF_LONG_DOUBLE * ir::Dtype::BITS_OF_BYTE;
+    let is_long = |width: usize| width > ir::Dtype::SIZE_OF_INT * ir::Dtype::BITS_OF_BYTE;
+
+    let libcall = match (from, to) {
+        (ir::Dtype::Float { width: from, .. }, ir::Dtype::Float { width: to, .. })
+            if *from == long_double_width && *to != long_double_width =>
+        {
+            if *to == ir::Dtype::SIZE_OF_FLOAT * ir::Dtype::BITS_OF_BYTE {
+                "__trunctfsf2"
+            } else {
+                "__trunctfdf2"
+            }
+        }
+        (ir::Dtype::Float { width: from, .. }, ir::Dtype::Float { width: to, .. })
+            if *from != long_double_width && *to == long_double_width =>
+        {
+            if *from == ir::Dtype::SIZE_OF_FLOAT * ir::Dtype::BITS_OF_BYTE {
+                "__extendsftf2"
+            } else {
+                "__extenddftf2"
+            }
+        }
+        (
+            ir::Dtype::Int {
+                width, is_signed, ..
+            },
+            ir::Dtype::Float { width: to, .. },
+        ) if *to == long_double_width => match (is_signed, is_long(*width)) {
+            (true, false) => "__floatsitf",
+            (true, true) => "__floatditf",
+            (false, false) => "__floatunsitf",
+            (false, true) => "__floatunditf",
+        },
+        (
+            ir::Dtype::Float { width: from, .. },
+            ir::Dtype::Int {
+                width, is_signed, ..
+            },
+        ) if *from == long_double_width => match (is_signed, is_long(*width)) {
+            (true, false) => "__fixtfsi",
+            (true, true) => "__fixtfdi",
+            (false, false) => "__fixunstfsi",
+            (false, true) => "__fixunstfdi",
+        },
+        _ => return None,
+    };
+
+    Some(libcall)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assembly(instructions: &[asm::Instruction]) -> Vec<String> {
+        instructions.iter().map(ToString::to_string).collect()
+    }
+
+    fn registers(registers: &[asm::Register]) -> ArgumentLocation {
+        ArgumentLocation {
+            parts: registers
+                .iter()
+                .map(|register| ArgumentPart::Register(*register))
+                .collect(),
+            by_reference: false,
+        }
+    }
+
+    #[test]
+    fn variadic_argument_locations() {
+        let mut structs = HashMap::new();
+        let point = ir::Dtype::structure(
+            Some("point".to_string()),
+            Some(vec![
+                ir::Named::new(Some("x".to_string()), ir::Dtype::DOUBLE),
+                ir::Named::new(Some("tag".to_string()), ir::Dtype::INT),
+            ]),
+        )
+        .resolve_structs(&mut structs, &mut 0)
+        .unwrap();
+
+        // `printf("%f %d %Lf", 1.0, 2, 3.0L)`: the variable arguments are in the integer
+        // registers, and `long double` starts at an even-numbered one.
+        let format = ir::Dtype::pointer(ir::Dtype::CHAR);
+        let args = [
+            format,
+            ir::Dtype::DOUBLE,
+            ir::Dtype::INT,
+            ir::Dtype::LONG_DOUBLE,
+        ];
+        assert_eq!(
+            argument_locations(&args, 1, &structs).unwrap(),
+            [
+                registers(&[asm::Register::A0]),
+                registers(&[asm::Register::A1]),
+                registers(&[asm::Register::A2]),
+                registers(&[asm::Register::A4, asm::Register::A5]),
+            ]
+        );
+
+        // The fixed `double` and `struct point` use the floating-point registers.
+        let params = [ir::Dtype::DOUBLE, point.clone(), ir::Dtype::LONG_DOUBLE];
+        assert_eq!(
+            argument_locations(&params, params.len(), &structs).unwrap(),
+            [
+                registers(&[asm::Register::FA0]),
+                registers(&[asm::Register::FA1, asm::Register::A0]),
+                registers(&[asm::Register::A1, asm::Register::A2]),
+            ]
+        );
+        assert_eq!(num_fixed_integer_registers(&params, &structs), Ok(3));
+
+        // Once the registers run out, `long double` is aligned to 16 bytes on the stack, and a
+        // struct larger than 16 bytes is passed by reference.
+        let mut args = vec![ir::Dtype::LONG; 7];
+        args.extend([
+            ir::Dtype::LONG_DOUBLE,
+            ir::Dtype::array(ir::Dtype::LONG, 3),
+            point,
+        ]);
+        let locations = argument_locations(&args, 1, &structs).unwrap();
+        assert_eq!(
+            locations[7..],
+            [
+                ArgumentLocation {
+                    parts: vec![ArgumentPart::Stack(0), ArgumentPart::Stack(8)],
+                    by_reference: false,
+                },
+                ArgumentLocation {
+                    parts: vec![ArgumentPart::Stack(16)],
+                    by_reference: true,
+                },
+                ArgumentLocation {
+                    parts: vec![ArgumentPart::Stack(24), ArgumentPart::Stack(32)],
+                    by_reference: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn variadic_register_save_area() {
+        assert_eq!(
+            assembly(&spill_variadic_registers(5)),
+            ["sd\ta5,-24(s0)", "sd\ta6,-16(s0)", "sd\ta7,-8(s0)"]
+        );
+        assert!(spill_variadic_registers(8).is_empty());
+        assert_eq!(
+            assembly(&va_start(asm::Register::T0, 5, asm::Register::T1)),
+            ["addi\tt1,s0,-24", "sd\tt1,0(t0)"]
+        );
+        assert_eq!(
+            assembly(&va_start(asm::Register::T0, 8, asm::Register::T1)),
+            ["addi\tt1,s0,0", "sd\tt1,0(t0)"]
+        );
+    }
+
+    #[test]
+    fn variadic_va_arg() {
+        let structs = HashMap::new();
+        let va_arg = |dtype: ir::Dtype, rd| {
+            assembly(&va_arg(asm::Register::T0, &dtype, rd, asm::Register::T1, &structs).unwrap())
+        };
+
+        assert_eq!(
+            va_arg(ir::Dtype::INT, asm::Register::A0),
+            [
+                "ld\tt1,0(t0)",
+                "lw\ta0,0(t1)",
+                "addi\tt1,t1,8",
+                "sd\tt1,0(t0)"
+            ]
+        );
+        assert_eq!(
+            va_arg(ir::Dtype::DOUBLE, asm::Register::FA0),
+            [
+                "ld\tt1,0(t0)",
+                "fld\tfa0,0(t1)",
+                "addi\tt1,t1,8",
+                "sd\tt1,0(t0)"
+            ]
+        );
+        assert_eq!(
+            va_arg(ir::Dtype::LONG_DOUBLE, asm::Register::A0),
+            [
+                "ld\tt1,0(t0)",
+                "addi\tt1,t1,15",
+                "andi\tt1,t1,-16",
+                "mv\ta0,t1",
+                "addi\tt1,t1,16",
+                "sd\tt1,0(t0)"
+            ]
+        );
+        assert_eq!(
+            va_arg(ir::Dtype::array(ir::Dtype::LONG, 3), asm::Register::A0),
+            [
+                "ld\tt1,0(t0)",
+                "ld\ta0,0(t1)",
+                "addi\tt1,t1,8",
+                "sd\tt1,0(t0)"
+            ]
+        );
+    }
+
+    #[test]
+    fn long_double_libcalls() {
+        assert_eq!(
+            long_double_binary_libcall(&ast::BinaryOperator::Plus),
+            Some("__addtf3")
+        );
+        assert_eq!(
+            long_double_binary_libcall(&ast::BinaryOperator::Multiply),
+            Some("__multf3")
+        );
+        assert_eq!(
+            long_double_binary_libcall(&ast::BinaryOperator::LessOrEqual),
+            Some("__letf2")
+        );
+        assert_eq!(
+            long_double_binary_libcall(&ast::BinaryOperator::BitwiseAnd),
+            None
+        );
+
+        let long_double = ir::Dtype::LONG_DOUBLE;
+        let cases = [
+            (
+                ir::Dtype::DOUBLE,
+                long_double.clone(),
+                Some("__extenddftf2"),
+            ),
+            (ir::Dtype::FLOAT, long_double.clone(), Some("__extendsftf2")),
+            (long_double.clone(), ir::Dtype::FLOAT, Some("__trunctfsf2")),
+            (long_double.clone(), ir::Dtype::DOUBLE, Some("__trunctfdf2")),
+            (ir::Dtype::INT, long_double.clone(), Some("__floatsitf")),
+            (ir::Dtype::LONG, long_double.clone(), Some("__floatditf")),
+            (
+                ir::Dtype::INT.set_signed(false),
+                long_double.clone(),
+                Some("__floatunsitf"),
+            ),
+            (long_double.clone(), ir::Dtype::CHAR, Some("__fixtfsi")),
+            (
+                long_double.clone(),
+                ir::Dtype::LONG.set_signed(false),
+                Some("__fixunstfdi"),
+            ),
+            (long_double.clone(), long_double, None),
+            (ir::Dtype::INT, ir::Dtype::DOUBLE, None),
+        ];
+        for (from, to, libcall) in cases {
+            assert_eq!(
+                long_double_typecast_libcall(&from, &to),
+                libcall,
+                "{from} to {to}"
+            );
+        }
+    }
+
+    #[test]
+    fn symbol_address_and_call() {
+        let symbol = asm::Label("counter".to_string());
+        let label = asm::Label::pcrel_hi("main", 0);
+        let load = |linkage, relocation_model| {
+            let block = load_symbol_address(
+                symbol.clone(),
+                linkage,
+                asm::Register::A0,
+                relocation_model,
+                label.clone(),
+            );
+            (
+                block.label.map(|label| label.to_string()),
+                assembly(&block.instructions),
+            )
+        };
+
+        assert_eq!(
+            load(ir::Linkage::External, asm::RelocationModel::Static),
+            (None, vec!["la\ta0,counter".to_string()])
+        );
+        assert_eq!(
+            load(ir::Linkage::Internal, asm::RelocationModel::Pic),
+            (None, vec!["lla\ta0,counter".to_string()])
+        );
+        assert_eq!(
+            load(ir::Linkage::External, asm::RelocationModel::Pic),
+            (
+                Some(".main_pcrel_hi0".to_string()),
+                vec![
+                    "auipc\ta0, %got_pcrel_hi(counter)".to_string(),
+                    "ld\ta0,%pcrel_lo(.main_pcrel_hi0)(a0)".to_string(),
+                ]
+            )
+        );
+
+        let call = |linkage, relocation_model| {
+            call_function(symbol.clone(), linkage, relocation_model).to_string()
+        };
+        assert_eq!(
+            call(ir::Linkage::External, asm::RelocationModel::Static),
+            "call\tcounter"
+        );
+        assert_eq!(
+            call(ir::Linkage::Internal, asm::RelocationModel::Pic),
+            "call\tcounter"
+        );
+        assert_eq!(
+            call(ir::Linkage::External, asm::RelocationModel::Pic),
+            "call\tcounter@plt"
+        );
+    }
+}
@@ -22,6 +22,9 @@ impl IsEquiv for ExternalDeclaration {
             (Self::FunctionDefinition(fdef), Self::FunctionDefinition(other_fdef)) => {
                 fdef.is_equiv(other_fdef)
             }
+            (Self::StaticAssert(static_assert), Self::StaticAssert(other_static_assert)) => {
+                static_assert.is_equiv(other_static_assert)
+            }
             _ => false,
         }
     }
@@ -60,12 +63,33 @@ impl IsEquiv for Initializer {
 
 impl IsEquiv for InitializerListItem {
     fn is_equiv(&self, other: &Self) -> bool {
-        self.designation.is_empty()
-            && other.designation.is_empty()
+        self.designation.is_equiv(&other.designation)
             && self.initializer.is_equiv(&other.initializer)
     }
 }
 
+impl IsEquiv for Designator {
+    fn is_equiv(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Index(expr), Self::Index(other_expr)) => expr.is_equiv(other_expr),
+            (Self::Member(identifier), Self::Member(other_identifier)) => {
+                identifier.is_equiv(other_identifier)
+            }
+            (Self::Range(range), Self::Range(other_range)) => {
+                range.node.from.is_equiv(&other_range.node.from)
+                    && range.node.to.is_equiv(&other_range.node.to)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl IsEquiv for StaticAssert {
+    fn is_equiv(&self, other: &Self) -> bool {
+        self.expression.is_equiv(&other.expression) && self.message.is_equiv(&other.message)
+    }
+}
+
 impl IsEquiv for Declarator {
     fn is_equiv(&self, other: &Self) -> bool {
         self.kind.is_equiv(&other.kind) && self.derived.is_equiv(&other.derived)
@@ -102,6 +126,7 @@ impl IsEquiv for DerivedDeclarator {
                 let params = &func_decl.node.parameters;
                 let other_params = &other_func_decl.node.parameters;
                 params.is_equiv(other_params)
+                    && func_decl.node.ellipsis == other_func_decl.node.ellipsis
             }
             (Self::KRFunction(kr_func_decl), Self::KRFunction(other_kr_func_decl)) => {
                 kr_func_decl.is_equiv(other_kr_func_decl)
@@ -228,6 +253,9 @@ impl IsEquiv for ForInitializer {
             (Self::Empty, Self::Empty) => true,
             (Self::Expression(expr), Self::Expression(other_expr)) => expr.is_equiv(other_expr),
             (Self::Declaration(decl), Self::Declaration(other_decl)) => decl.is_equiv(other_decl),
+            (Self::StaticAssert(static_assert), Self::StaticAssert(other_static_assert)) => {
+                static_assert.is_equiv(other_static_assert)
+            }
             _ => false,
         }
     }
@@ -247,6 +275,16 @@ impl IsEquiv for Expression {
             }
             (Self::Member(member), Self::Member(other_member)) => member.is_equiv(other_member),
             (Self::Call(call), Self::Call(other_call)) => call.is_equiv(other_call),
+            (Self::CompoundLiteral(literal), Self::CompoundLiteral(other_literal)) => {
+                literal
+                    .node
+                    .type_name
+                    .is_equiv(&other_literal.node.type_name)
+                    && literal
+                        .node
+                        .initializer_list
+                        .is_equiv(&other_literal.node.initializer_list)
+            }
             (Self::SizeOfTy(size_of_ty), Self::SizeOfTy(other_size_of_ty)) => {
                 size_of_ty.is_equiv(other_size_of_ty)
             }
@@ -286,6 +324,10 @@ impl IsEquiv for Expression {
             (Self::Comma(exprs), Self::Comma(other_exprs)) => {
                 exprs.as_ref().is_equiv(other_exprs.as_ref())
             }
+            (Self::VaArg(va_arg), Self::VaArg(other_va_arg)) => {
+                va_arg.node.va_list.is_equiv(&other_va_arg.node.va_list)
+                    && va_arg.node.type_name.is_equiv(&other_va_arg.node.type_name)
+            }
             _ => false,
         }
     }
@@ -388,6 +430,9 @@ impl IsEquiv for BlockItem {
             (Self::Statement(statement), Self::Statement(other_statement)) => {
                 statement.is_equiv(other_statement)
             }
+            (Self::StaticAssert(static_assert), Self::StaticAssert(other_static_assert)) => {
+                static_assert.is_equiv(other_static_assert)
+            }
             _ => false,
         }
     }
@@ -406,6 +451,21 @@ impl IsEquiv for DeclarationSpecifier {
             (Self::TypeQualifier(type_qualifier), Self::TypeQualifier(other_type_qualifier)) => {
                 type_qualifier.is_equiv(other_type_qualifier)
             }
+            (Self::Alignment(alignment), Self::Alignment(other_alignment)) => {
+                alignment.is_equiv(other_alignment)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl IsEquiv for AlignmentSpecifier {
+    fn is_equiv(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Type(type_name), Self::Type(other_type_name)) => {
+                type_name.is_equiv(other_type_name)
+            }
+            (Self::Constant(expr), Self::Constant(other_expr)) => expr.is_equiv(other_expr),
             _ => false,
         }
     }
@@ -464,6 +524,9 @@ impl IsEquiv for StructDeclaration {
             (Self::Field(struct_field), Self::Field(other_struct_field)) => {
                 struct_field.is_equiv(other_struct_field)
             }
+            (Self::StaticAssert(static_assert), Self::StaticAssert(other_static_assert)) => {
+                static_assert.is_equiv(other_static_assert)
+            }
             _ => false,
         }
     }
@@ -0,0 +1,172 @@
+//! Utilities on the C AST shared by the semantic analysis and IR generation.
+
+use lang_c::ast::*;
+use lang_c::span::{Node, Span};
+
+use crate::c::const_eval::{ConstEvalError, to_literal};
+use crate::ir;
+
+/// The builtins for variable arguments, e.g., `__builtin_va_start`.
+pub(crate) const VA_BUILTINS: [&str; 3] = [
+    "__builtin_va_start",
+    "__builtin_va_end",
+    "__builtin_va_copy",
+];
+
+/// Returns the storage class specifier of `specifiers`, if any.
+pub(crate) fn storage_class_of_specifiers(
+    specifiers: &[Node<DeclarationSpecifier>],
+) -> Option<&StorageClassSpecifier> {
+    specifiers.iter().find_map(|spec| match &spec.node {
+        DeclarationSpecifier::StorageClass(storage_class) => Some(&storage_class.node),
+        _ => None,
+    })
+}
+
+#[inline]
+pub(crate) fn name_of_declarator(declarator: &Declarator) -> String {
+    let declarator_kind = &declarator.kind;
+    match &declarator_kind.node {
+        DeclaratorKind::Abstract => panic!("DeclaratorKind::Abstract is unsupported"),
+        DeclaratorKind::Identifier(identifier) => identifier.node.name.clone(),
+        DeclaratorKind::Declarator(declarator) => name_of_declarator(&declarator.node),
+    }
+}
+
+/// Renames the identifier declared by `declarator` to `name`. An abstract declarator, e.g., `[]` of
+/// the type name `int[]`, is made to declare `name`.
+pub(crate) fn rename_declarator(declarator: &Declarator, name: &str) -> Declarator {
+    let mut declarator = declarator.clone();
+    match &mut declarator.kind.node {
+        DeclaratorKind::Abstract => {
+            let identifier = Identifier {
+                name: name.to_string(),
+            };
+            declarator.kind.node =
+                DeclaratorKind::Identifier(Node::new(identifier, declarator.kind.span));
+        }
+        DeclaratorKind::Identifier(identifier) => identifier.node.name = name.to_string(),
+        DeclaratorKind::Declarator(inner) => inner.node = rename_declarator(&inner.node, name),
+    }
+    declarator
+}
+
+/// Returns the derived declarator of `declarator` that declares the parameters of the function,
+/// e.g., `(int a)` of `int (*f(int a))(char)`.
+pub(crate) fn function_derived_declarator(declarator: &Declarator) -> Option<&DerivedDeclarator> {
+    let from_derived = || {
+        declarator.derived.iter().find_map(|derived_decl| {
+            matches!(
+                derived_decl.node,
+                DerivedDeclarator::Function(_) | DerivedDeclarator::KRFunction(_)
+            )
+            .then_some(&derived_decl.node)
+        })
+    };
+    match &declarator.kind.node {
+        DeclaratorKind::Abstract => panic!("DeclaratorKind::Abstract is unsupported"),
+        DeclaratorKind::Identifier(_) => from_derived(),
+        DeclaratorKind::Declarator(next_declarator) => {
+            function_derived_declarator(&next_declarator.node).or_else(from_derived)
+        }
+    }
+}
+
+/// Creates the declaration of the variable `name` initialized by the compound literal `literal`,
+/// e.g., `int .compound0[] = {1, 2};` for `(int[]){1, 2}`.
+pub(crate) fn compound_literal_declaration(
+    literal: &CompoundLiteral,
+    name: &str,
+    span: Span,
+) -> Declaration {
+    let specifiers = literal
+        .type_name
+        .node
+        .specifiers
+        .iter()
+        .map(|spec| {
+            let spec_node = match &spec.node {
+                SpecifierQualifier::TypeSpecifier(type_specifier) => {
+                    DeclarationSpecifier::TypeSpecifier(type_specifier.clone())
+                }
+                SpecifierQualifier::TypeQualifier(type_qualifier) => {
+                    DeclarationSpecifier::TypeQualifier(type_qualifier.clone())
+                }
+                SpecifierQualifier::Extension(extensions) => {
+                    DeclarationSpecifier::Extension(extensions.clone())
+                }
+            };
+            Node::new(spec_node, spec.span)
+        })
+        .collect();
+
+    let declarator = match &literal.type_name.node.declarator {
+        Some(declarator) => Node::new(rename_declarator(&declarator.node, name), declarator.span),
+        None => {
+            let identifier = Identifier {
+                name: name.to_string(),
+            };
+            let declarator = Declarator {
+                kind: Node::new(
+                    DeclaratorKind::Identifier(Node::new(identifier, span)),
+                    span,
+                ),
+                derived: Vec::new(),
+                extensions: Vec::new(),
+            };
+            Node::new(declarator, span)
+        }
+    };
+    let initializer = Initializer::List(literal.initializer_list.clone());
+    let init_decl = InitDeclarator {
+        declarator,
+        initializer: Some(Node::new(initializer, span)),
+    };
+
+    Declaration {
+        specifiers,
+        declarators: vec![Node::new(init_decl, span)],
+    }
+}
+
+/// Completes the size of the array of unknown size declared by `declarator` with its
+/// `initializer`, e.g., `a[]` of `int a[] = {1, 2};` to `a[2]`.
+pub(crate) fn complete_array_size(
+    declarator: &Declarator,
+    initializer: Option<&Node<Initializer>>,
+) -> Result<Declarator, ConstEvalError> {
+    let mut declarator = declarator.clone();
+    let Some(initializer) = initializer else {
+        return Ok(declarator);
+    };
+    if !matches!(declarator.kind.node, DeclaratorKind::Identifier(_)) {
+        return Ok(declarator);
+    }
+
+    // The first array declarator is the outermost one, e.g., `[]` of `a[][3]`.
+    let Some(array_decl) =
+        declarator
+            .derived
+            .iter_mut()
+            .find_map(|derived| match &mut derived.node {
+                DerivedDeclarator::Array(array_decl) => Some(array_decl),
+                _ => None,
+            })
+    else {
+        return Ok(declarator);
+    };
+    if array_decl.node.size != ArraySize::Unknown {
+        return Ok(declarator);
+    }
+
+    let size =
+        ir::array_size_of_initializer(&initializer.node).map_err(|_| ConstEvalError::Misc {
+            message: "invalid initializer for an array of unknown size".to_string(),
+        })?;
+    let size = ir::Constant::int(size as u128, ir::Dtype::LONG);
+    let literal = to_literal(&size).expect("integer must be representable");
+    array_decl.node.size =
+        ArraySize::VariableExpression(Box::new(Node::new(literal, initializer.span)));
+
+    Ok(declarator)
+}
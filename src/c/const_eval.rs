@@ -0,0 +1,416 @@
+//! Evaluation of C constant expressions, e.g., the values of enumerators and `case` labels.
+
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use std::collections::HashMap;
+
+use lang_c::ast::*;
+use lang_c::span::{Node, Span};
+use thiserror::Error;
+
+use crate::ir::{self, DtypeError, HasDtype, calculator};
+
+/// An error raised while evaluating a constant expression.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub(crate) enum ConstEvalError {
+    #[error("{message}")]
+    Misc { message: String },
+    #[error("{dtype_error}")]
+    InvalidDtype { dtype_error: DtypeError },
+}
+
+/// Evaluates constant expressions to [`ir::Constant`]s.
+pub(crate) struct ConstEval<'a> {
+    /// Looks up the value of a named constant, i.e., an enumerator.
+    lookup: &'a dyn Fn(&str) -> Option<ir::Constant>,
+    typedefs: &'a HashMap<String, ir::Dtype>,
+    structs: &'a HashMap<String, Option<ir::Dtype>>,
+}
+
+impl<'a> ConstEval<'a> {
+    pub(crate) fn new(
+        lookup: &'a dyn Fn(&str) -> Option<ir::Constant>,
+        typedefs: &'a HashMap<String, ir::Dtype>,
+        structs: &'a HashMap<String, Option<ir::Dtype>>,
+    ) -> Self {
+        Self {
+            lookup,
+            typedefs,
+            structs,
+        }
+    }
+
+    /// Evaluates `expr` following the C semantics, e.g., the usual arithmetic conversions.
+    ///
+    /// The result is always an integer or a floating point constant.
+    pub(crate) fn eval(&self, expr: &Expression) -> Result<ir::Constant, ConstEvalError> {
+        match expr {
+            Expression::Constant(constant) => {
+                ir::Constant::try_from(&constant.node).map_err(|_| not_constant(expr))
+            }
+            Expression::Identifier(identifier) => {
+                (self.lookup)(&identifier.node.name).ok_or_else(|| ConstEvalError::Misc {
+                    message: format!("`{}` is not a constant", identifier.node.name),
+                })
+            }
+            Expression::UnaryOperator(unary) => {
+                let operand = self.eval(&unary.node.operand.node)?;
+                self.eval_unary(&unary.node.operator.node, operand)
+                    .ok_or_else(|| not_constant(expr))
+            }
+            Expression::BinaryOperator(binary) => self.eval_binary(&binary.node),
+            Expression::Conditional(conditional) => {
+                let condition = self.eval(&conditional.node.condition.node)?;
+                if is_nonzero(&condition) {
+                    self.eval(&conditional.node.then_expression.node)
+                } else {
+                    self.eval(&conditional.node.else_expression.node)
+                }
+            }
+            Expression::Cast(cast) => {
+                let dtype = self.dtype_of(&cast.node.type_name.node)?;
+                let value = self.eval(&cast.node.expression.node)?;
+                if !matches!(dtype, ir::Dtype::Int { .. } | ir::Dtype::Float { .. }) {
+                    return Err(not_constant(expr));
+                }
+                Ok(value.typecast(dtype.set_const(false)))
+            }
+            Expression::SizeOfTy(size_of) => {
+                let (size, _) = self.size_align_of(&size_of.node.0.node)?;
+                Ok(ir::Constant::int(size as u128, size_t()))
+            }
+            Expression::AlignOf(align_of) => {
+                let (_, align) = self.size_align_of(&align_of.node.0.node)?;
+                Ok(ir::Constant::int(align as u128, size_t()))
+            }
+            _ => Err(not_constant(expr)),
+        }
+    }
+
+    fn eval_unary(&self, op: &UnaryOperator, operand: ir::Constant) -> Option<ir::Constant> {
+        match op {
+            UnaryOperator::Plus => Some(promote(operand)),
+            UnaryOperator::Minus => {
+                let operand = ir::Value::try_from(promote(operand)).ok()?;
+                let result = calculator::calculate_unary_operator_expression(op, operand).ok()?;
+                from_value(result)
+            }
+            UnaryOperator::Complement => {
+                let operand = promote(operand);
+                let (value, width, is_signed) = operand.get_int()?;
+                Some(int_constant(!value, width, is_signed))
+            }
+            UnaryOperator::Negate => Some(bool_constant(!is_nonzero(&operand))),
+            _ => None,
+        }
+    }
+
+    fn eval_binary(
+        &self,
+        binary: &BinaryOperatorExpression,
+    ) -> Result<ir::Constant, ConstEvalError> {
+        let op = &binary.operator.node;
+        let lhs = self.eval(&binary.lhs.node)?;
+
+        // The right-hand side of the logical operators is not evaluated if the result is
+        // determined by the left-hand side.
+        match op {
+            BinaryOperator::LogicalAnd => {
+                return Ok(bool_constant(
+                    is_nonzero(&lhs) && is_nonzero(&self.eval(&binary.rhs.node)?),
+                ));
+            }
+            BinaryOperator::LogicalOr => {
+                return Ok(bool_constant(
+                    is_nonzero(&lhs) || is_nonzero(&self.eval(&binary.rhs.node)?),
+                ));
+            }
+            _ => (),
+        }
+
+        let rhs = self.eval(&binary.rhs.node)?;
+        let (lhs, rhs) = match op {
+            // The type of a shift is that of its promoted left operand.
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
+                let lhs = promote(lhs);
+                let rhs = promote(rhs);
+                if lhs.get_int().is_none() || rhs.get_int().is_none() {
+                    return Err(invalid_operands(op));
+                }
+                let width = lhs.get_int().expect("`lhs` must be an integer").1;
+                let (amount, _, is_signed) = rhs.get_int().expect("`rhs` must be an integer");
+                if (is_signed && (amount as i128) < 0) || amount >= width as u128 {
+                    return Err(ConstEvalError::Misc {
+                        message: "shift amount is out of range".to_string(),
+                    });
+                }
+                let dtype = lhs.dtype();
+                (lhs, rhs.typecast(dtype))
+            }
+            _ => {
+                let dtype = common_dtype(&lhs, &rhs).ok_or_else(|| invalid_operands(op))?;
+                (lhs.typecast(dtype.clone()), rhs.typecast(dtype))
+            }
+        };
+
+        if matches!(op, BinaryOperator::Divide | BinaryOperator::Modulo)
+            && rhs.get_int().is_some()
+            && !is_nonzero(&rhs)
+        {
+            return Err(ConstEvalError::Misc {
+                message: "division by zero in constant expression".to_string(),
+            });
+        }
+
+        let is_supported = matches!(
+            op,
+            BinaryOperator::Multiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Plus
+                | BinaryOperator::Minus
+                | BinaryOperator::ShiftLeft
+                | BinaryOperator::ShiftRight
+                | BinaryOperator::Less
+                | BinaryOperator::Greater
+                | BinaryOperator::LessOrEqual
+                | BinaryOperator::GreaterOrEqual
+                | BinaryOperator::Equals
+                | BinaryOperator::NotEquals
+        ) || (matches!(
+            op,
+            BinaryOperator::Modulo
+                | BinaryOperator::BitwiseAnd
+                | BinaryOperator::BitwiseXor
+                | BinaryOperator::BitwiseOr
+        ) && lhs.get_int().is_some());
+        if !is_supported {
+            return Err(invalid_operands(op));
+        }
+
+        let lhs = ir::Value::try_from(lhs).map_err(|_| invalid_operands(op))?;
+        let rhs = ir::Value::try_from(rhs).map_err(|_| invalid_operands(op))?;
+        let result = calculator::calculate_binary_operator_expression(op, lhs, rhs)
+            .ok()
+            .and_then(from_value)
+            .ok_or_else(|| invalid_operands(op))?;
+
+        // Comparisons result in `int` in C, while the calculator results in `u1`.
+        Ok(
+            if result.get_int().is_some_and(|(_, width, _)| width == 1) {
+                result.typecast(ir::Dtype::INT)
+            } else {
+                result
+            },
+        )
+    }
+
+    fn dtype_of(&self, type_name: &TypeName) -> Result<ir::Dtype, ConstEvalError> {
+        ir::Dtype::try_from(type_name)
+            .and_then(|dtype| dtype.resolve_typedefs(self.typedefs))
+            .map_err(|e| ConstEvalError::InvalidDtype { dtype_error: e })
+    }
+
+    fn size_align_of(&self, type_name: &TypeName) -> Result<(usize, usize), ConstEvalError> {
+        self.dtype_of(type_name)?
+            .size_align_of(self.structs)
+            .map_err(|e| ConstEvalError::InvalidDtype { dtype_error: e })
+    }
+}
+
+fn not_constant(expr: &Expression) -> ConstEvalError {
+    ConstEvalError::Misc {
+        message: format!("expression is not a constant: {expr:?}"),
+    }
+}
+
+fn invalid_operands(op: &BinaryOperator) -> ConstEvalError {
+    ConstEvalError::Misc {
+        message: format!("invalid operands to `{op:?}` in constant expression"),
+    }
+}
+
+/// The type of `sizeof` and `_Alignof`, i.e., `size_t`.
+fn size_t() -> ir::Dtype {
+    ir::Dtype::LONG.set_signed(false)
+}
+
+fn is_nonzero(constant: &ir::Constant) -> bool {
+    match constant {
+        ir::Constant::Int { value, .. } => *value != 0,
+        ir::Constant::Float { value, .. } => !value.is_zero(),
+        _ => true,
+    }
+}
+
+fn bool_constant(value: bool) -> ir::Constant {
+    ir::Constant::int(value.into(), ir::Dtype::INT)
+}
+
+/// Creates an integer constant, truncating `value` to `width` bits.
+fn int_constant(value: u128, width: usize, is_signed: bool) -> ir::Constant {
+    let value = ir::trim_unnecessary_bits(value, width as u128);
+    let value = if is_signed {
+        ir::sign_extension(value, width as u128)
+    } else {
+        value
+    };
+
+    ir::Constant::Int {
+        value,
+        width,
+        is_signed,
+    }
+}
+
+fn from_value(value: ir::Value) -> Option<ir::Constant> {
+    match value {
+        ir::Value::Int {
+            value,
+            width,
+            is_signed,
+        } => Some(ir::Constant::Int {
+            value,
+            width,
+            is_signed,
+        }),
+        ir::Value::Float { value, width } => Some(ir::Constant::Float { value, width }),
+        _ => None,
+    }
+}
+
+/// Applies the integer promotions.
+fn promote(constant: ir::Constant) -> ir::Constant {
+    match constant.get_int() {
+        Some((_, width, _)) if width < ir::Dtype::SIZE_OF_INT * ir::Dtype::BITS_OF_BYTE => {
+            constant.typecast(ir::Dtype::INT)
+        }
+        _ => constant,
+    }
+}
+
+/// Finds the common type of the operands by the usual arithmetic conversions.
+fn common_dtype(lhs: &ir::Constant, rhs: &ir::Constant) -> Option<ir::Dtype> {
+    let lhs = promote(lhs.clone()).dtype();
+    let rhs = promote(rhs.clone()).dtype();
+
+    match (&lhs, &rhs) {
+        (ir::Dtype::Float { width: l, .. }, ir::Dtype::Float { width: r, .. }) => {
+            Some(ir::Dtype::float(*l.max(r)))
+        }
+        (ir::Dtype::Float { .. }, ir::Dtype::Int { .. }) => Some(lhs),
+        (ir::Dtype::Int { .. }, ir::Dtype::Float { .. }) => Some(rhs),
+        (
+            ir::Dtype::Int {
+                width: l,
+                is_signed: l_signed,
+                ..
+            },
+            ir::Dtype::Int {
+                width: r,
+                is_signed: r_signed,
+                ..
+            },
+        ) => Some(match l.cmp(r) {
+            Ordering::Greater => lhs,
+            Ordering::Less => rhs,
+            Ordering::Equal => ir::Dtype::int(*l).set_signed(*l_signed && *r_signed),
+        }),
+        _ => None,
+    }
+}
+
+/// Converts `constant` to a literal expression, e.g., `-(5)` for `-5:i32`. The literal has a type
+/// large enough for the value, so it must be converted to the type of `constant` to be used.
+///
+/// Returns `None` if `constant` is not a finite integer or floating point number.
+pub(crate) fn to_literal(constant: &ir::Constant) -> Option<Expression> {
+    let (constant, is_negative) = match constant {
+        ir::Constant::Int {
+            value,
+            width,
+            is_signed,
+        } => {
+            let value = if *is_signed {
+                ir::sign_extension(*value, *width as u128) as i128
+            } else {
+                *value as i128
+            };
+            let magnitude = value.unsigned_abs();
+            let (size, unsigned) = if magnitude <= i32::MAX as u128 {
+                (IntegerSize::Int, false)
+            } else if magnitude <= i64::MAX as u128 {
+                (IntegerSize::Long, false)
+            } else {
+                (IntegerSize::Long, true)
+            };
+            let integer = Integer {
+                base: IntegerBase::Decimal,
+                number: magnitude.to_string().into_boxed_str(),
+                suffix: IntegerSuffix {
+                    size,
+                    unsigned,
+                    imaginary: false,
+                },
+            };
+            (Constant::Integer(integer), value < 0)
+        }
+        ir::Constant::Float { value, width }
+            if *width == ir::Dtype::SIZE_OF_LONG_DOUBLE * ir::Dtype::BITS_OF_BYTE =>
+        {
+            if value.is_nan() || value.is_infinite() {
+                return None;
+            }
+            let number = value.to_string();
+            let number = number.trim_start_matches('-').trim_start_matches("0x");
+            let float = Float {
+                base: FloatBase::Hexadecimal,
+                number: number.into(),
+                suffix: FloatSuffix {
+                    format: FloatFormat::LongDouble,
+                    imaginary: false,
+                },
+            };
+            (Constant::Float(float), value.is_sign_negative())
+        }
+        ir::Constant::Float { value, width } => {
+            let value = value.to_f64();
+            if !value.is_finite() {
+                return None;
+            }
+            let (number, format) = if *width == ir::Dtype::SIZE_OF_FLOAT * ir::Dtype::BITS_OF_BYTE {
+                ((value.abs() as f32).to_string(), FloatFormat::Float)
+            } else {
+                (value.abs().to_string(), FloatFormat::Double)
+            };
+            let number = if number.contains('.') {
+                number
+            } else {
+                format!("{number}.0")
+            };
+            let float = Float {
+                base: FloatBase::Decimal,
+                number: number.into_boxed_str(),
+                suffix: FloatSuffix {
+                    format,
+                    imaginary: false,
+                },
+            };
+            (Constant::Float(float), value.is_sign_negative())
+        }
+        _ => return None,
+    };
+
+    let literal = Expression::Constant(Box::new(Node::new(constant, Span::none())));
+    if !is_negative {
+        return Some(literal);
+    }
+
+    let unary = UnaryOperatorExpression {
+        operator: Node::new(UnaryOperator::Minus, Span::none()),
+        operand: Box::new(Node::new(literal, Span::none())),
+    };
+    Some(Expression::UnaryOperator(Box::new(Node::new(
+        unary,
+        Span::none(),
+    ))))
+}
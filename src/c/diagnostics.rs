@@ -0,0 +1,248 @@
+//! Diagnostics for C constructs that KECC does not support, that are ill-formed, or that are
+//! suspicious.
+
+use core::fmt;
+
+use lang_c::loc::get_location_for_offset;
+use lang_c::span::Span;
+
+use crate::lint::{Lint, Lints};
+
+/// How severe a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The C file cannot be compiled.
+    Error,
+    /// The C file can be compiled, but it is suspicious.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+/// An unsupported, ill-formed or suspicious construct found in a C file, resolved to its location
+/// in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The lint that reported the construct, if it is a warning or a warning treated as an error.
+    pub lint: Option<Lint>,
+    /// What is wrong with the construct.
+    pub message: String,
+    /// Byte offsets of the construct in the preprocessed source.
+    pub span: (usize, usize),
+    /// The file the construct comes from. Empty if the location is unknown.
+    pub file: String,
+    /// 1-based line number in `file`.
+    pub line: usize,
+    /// 1-based column number in `line`.
+    pub column: usize,
+    /// The source line containing the construct, used as an excerpt.
+    pub source_line: String,
+}
+
+impl Diagnostic {
+    /// Resolves `report` against the preprocessed `source`.
+    fn resolve(report: Report, source: &str) -> Self {
+        let Report {
+            severity,
+            lint,
+            message,
+            span,
+        } = report;
+        if span.is_none() || span.start > source.len() {
+            return Self {
+                severity,
+                lint,
+                message,
+                span: (0, 0),
+                file: String::new(),
+                line: 0,
+                column: 0,
+                source_line: String::new(),
+            };
+        }
+
+        let (location, _) = get_location_for_offset(source, span.start);
+        let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[span.start..]
+            .find('\n')
+            .map_or(source.len(), |i| span.start + i);
+
+        Self {
+            severity,
+            lint,
+            message,
+            span: (span.start, span.end),
+            file: location.file.to_string(),
+            line: location.line,
+            column: span.start - line_start + 1,
+            source_line: source[line_start..line_end].to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    /// Formats the diagnostic in the style of rustc, e.g.,
+    ///
+    /// ```text
+    /// error: unsupported `union` type
+    ///  --> foo.c:3:3
+    ///   |
+    /// 3 |   union U u;
+    ///   |   ^^^^^^^
+    /// ```
+    ///
+    /// The lint of a warning is named after the message as in GCC, e.g., `[-Wunused-variable]`, or
+    /// `[-Werror=unused-variable]` if it is treated as an error.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+        match (self.lint, self.severity) {
+            (Some(lint), Severity::Warning) => writeln!(f, " [-W{lint}]")?,
+            (Some(lint), Severity::Error) => writeln!(f, " [-Werror={lint}]")?,
+            (None, _) => writeln!(f)?,
+        }
+        if self.line == 0 {
+            return Ok(());
+        }
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter, self.file, self.line, self.column
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+
+        // The construct may span several lines. Only the part on the first line is underlined.
+        // Spans reported by `lang_c` may include trailing whitespace, which is not underlined.
+        let excerpt = self.source_line.get(self.column - 1..).unwrap_or_default();
+        let width = excerpt
+            .get(..self.span.1 - self.span.0)
+            .unwrap_or(excerpt)
+            .trim_end()
+            .len()
+            .max(1);
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(self.column - 1),
+            "^".repeat(width)
+        )
+    }
+}
+
+/// Collects unsupported, ill-formed or suspicious constructs while traversing an AST.
+///
+/// The traversal reports each [`lang_c::span::Node`] it enters, so that a diagnostic is attributed
+/// to the innermost node that has a known span.
+#[derive(Default, Debug)]
+pub struct Diagnostics {
+    spans: Vec<Span>,
+    reports: Vec<Report>,
+    /// The lints whose warnings are reported.
+    lints: Lints,
+}
+
+/// A diagnostic not resolved against the source yet.
+#[derive(Debug)]
+struct Report {
+    severity: Severity,
+    lint: Option<Lint>,
+    message: String,
+    span: Span,
+}
+
+impl Diagnostics {
+    /// Creates diagnostics reporting the warnings of `lints`.
+    pub(crate) fn new(lints: Lints) -> Self {
+        Self {
+            spans: Vec::new(),
+            reports: Vec::new(),
+            lints,
+        }
+    }
+
+    /// Enters a node whose span is `span`.
+    pub(crate) fn enter(&mut self, span: Span) {
+        self.spans.push(span);
+    }
+
+    /// Exits the node entered last.
+    pub(crate) fn exit(&mut self) {
+        let _unused = self.spans.pop();
+    }
+
+    /// Reports that the current node is unsupported.
+    pub(crate) fn unsupported<S: Into<String>>(&mut self, message: S) {
+        self.error(message);
+    }
+
+    /// Reports an error in the current node.
+    pub(crate) fn error<S: Into<String>>(&mut self, message: S) {
+        self.report(Severity::Error, None, message.into());
+    }
+
+    /// Reports a warning of `lint` in the current node if `lint` is enabled.
+    pub(crate) fn warn<S: Into<String>>(&mut self, lint: Lint, message: S) {
+        if !self.lints.is_enabled(lint) {
+            return;
+        }
+        let severity = if self.lints.is_error {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        self.report(severity, Some(lint), message.into());
+    }
+
+    fn report(&mut self, severity: Severity, lint: Option<Lint>, message: String) {
+        let span = self
+            .spans
+            .iter()
+            .rev()
+            .find(|span| !span.is_none())
+            .copied()
+            .unwrap_or_else(Span::none);
+        self.reports.push(Report {
+            severity,
+            lint,
+            message,
+            span,
+        });
+    }
+
+    /// Reports that the current node is unsupported if `cond` does not hold.
+    pub(crate) fn check<S: Into<String>>(&mut self, cond: bool, message: S) {
+        if !cond {
+            self.unsupported(message);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+
+    /// Returns whether an error is reported, including a warning treated as an error.
+    pub(crate) fn has_errors(&self) -> bool {
+        self.reports
+            .iter()
+            .any(|report| report.severity == Severity::Error)
+    }
+
+    /// Resolves the collected reports against the preprocessed `source`.
+    pub(crate) fn resolve(self, source: &str) -> Vec<Diagnostic> {
+        self.reports
+            .into_iter()
+            .map(|report| Diagnostic::resolve(report, source))
+            .collect()
+    }
+}
@@ -1,5 +1,13 @@
 mod ast_equiv;
+pub(crate) mod ast_utils;
+pub(crate) mod const_eval;
+mod diagnostics;
 mod parse;
+mod preprocess;
+mod sema;
 mod write_c;
 
-pub use parse::Parse;
+pub use diagnostics::{Diagnostic, Diagnostics, Severity};
+pub use parse::{Error as ParseError, Parse};
+pub use preprocess::{CommandLineMacro, Error as PreprocessError, Preprocess};
+pub use write_c::{BraceStyle, CodeStyle};
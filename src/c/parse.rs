@@ -2,125 +2,171 @@ use core::ops::Deref;
 use std::path::Path;
 
 use lang_c::ast::*;
-use lang_c::driver::{Config, Error as ParseError, parse};
+use lang_c::driver::{Config, Error as ParseError, parse_preprocessed};
 use lang_c::span::Node;
 
 use crate::Translate;
+use crate::c::diagnostics::{Diagnostic, Diagnostics};
+use crate::c::preprocess::{Error as PreprocessError, Preprocess};
+use crate::c::sema::Sema;
+use crate::lint::Lints;
 use crate::utils::AssertSupported;
 
 /// Parse Error
 #[derive(Debug)]
 pub enum Error {
     ParseError(ParseError),
-    Unsupported,
+    /// The C file fails to be preprocessed.
+    Preprocess(PreprocessError),
+    /// The C file contains constructs that KECC does not support.
+    Unsupported(Vec<Diagnostic>),
+    /// The C file is ill-formed, e.g., it is ill-typed. The warnings on the C file are included.
+    Semantic(Vec<Diagnostic>),
 }
 
 /// C file Parser.
-#[derive(Default, Clone, Copy, Debug)]
-pub struct Parse;
+#[derive(Default, Clone, Debug)]
+pub struct Parse {
+    /// The preprocessor run on the C file before parsing.
+    pub preprocess: Preprocess,
+    /// The lints checked on the C file.
+    pub lints: Lints,
+    /// The warnings on the C file parsed last.
+    pub warnings: Vec<Diagnostic>,
+}
 
 impl<P: AsRef<Path>> Translate<P> for Parse {
     type Target = TranslationUnit;
     type Error = Error;
 
     fn translate(&mut self, source: &P) -> Result<Self::Target, Self::Error> {
+        self.warnings.clear();
+        let source = self
+            .preprocess
+            .translate(source)
+            .map_err(Error::Preprocess)?;
         let config = Config::default();
-        let ast = parse(&config, source).map_err(Error::ParseError)?;
+        let ast = parse_preprocessed(&config, source)
+            .map_err(|e| Error::ParseError(ParseError::SyntaxError(e)))?;
         let unit = ast.unit;
 
-        unit.assert_supported();
+        let mut diags = Diagnostics::default();
+        unit.assert_supported(&mut diags);
+        if !diags.is_empty() {
+            return Err(Error::Unsupported(diags.resolve(&ast.source)));
+        }
+
+        let diags = Sema::check(&unit, &self.lints);
+        if diags.has_errors() {
+            return Err(Error::Semantic(diags.resolve(&ast.source)));
+        }
+        self.warnings = diags.resolve(&ast.source);
         Ok(unit)
     }
 }
 
 impl<T: AssertSupported> AssertSupported for Node<T> {
-    fn assert_supported(&self) {
-        self.node.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        diags.enter(self.span);
+        self.node.assert_supported(diags);
+        diags.exit();
     }
 }
 
 impl<T: AssertSupported> AssertSupported for Option<T> {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         if let Some(this) = self {
-            this.assert_supported();
+            this.assert_supported(diags);
         }
     }
 }
 
 impl<T: AssertSupported> AssertSupported for Box<T> {
-    fn assert_supported(&self) {
-        self.deref().assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.deref().assert_supported(diags);
     }
 }
 
 impl<T: AssertSupported> AssertSupported for Vec<T> {
-    fn assert_supported(&self) {
-        self.iter().for_each(AssertSupported::assert_supported);
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.iter().for_each(|this| this.assert_supported(diags));
     }
 }
 
 impl<T: AssertSupported> AssertSupported for [T] {
-    fn assert_supported(&self) {
-        self.iter().for_each(AssertSupported::assert_supported);
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.iter().for_each(|this| this.assert_supported(diags));
     }
 }
 
 impl AssertSupported for TranslationUnit {
-    fn assert_supported(&self) {
-        self.0.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.0.assert_supported(diags);
     }
 }
 
 impl AssertSupported for ExternalDeclaration {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::Declaration(decl) => {
-                assert!(is_valid_global_variable_declaration(&decl.node));
-                decl.assert_supported()
-            }
-            Self::StaticAssert(_) => panic!("ExternalDeclaration::StaticAssert"),
-            Self::FunctionDefinition(fdef) => fdef.assert_supported(),
+            Self::Declaration(decl) => decl.assert_supported(diags),
+            Self::StaticAssert(static_assert) => static_assert.assert_supported(diags),
+            Self::FunctionDefinition(fdef) => fdef.assert_supported(diags),
         }
     }
 }
 
 impl AssertSupported for Declaration {
-    fn assert_supported(&self) {
-        self.specifiers.assert_supported();
-        self.declarators.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.specifiers.assert_supported(diags);
+        self.declarators.assert_supported(diags);
+    }
+}
+
+impl AssertSupported for StaticAssert {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.expression.assert_supported(diags);
+        diags.check(
+            self.message.node.iter().all(|piece| piece.starts_with('"')),
+            "unsupported wide string literal",
+        );
     }
 }
 
 impl AssertSupported for FunctionDefinition {
-    fn assert_supported(&self) {
-        self.specifiers.assert_supported();
-        self.declarator.assert_supported();
-        assert!(self.declarations.is_empty());
-        self.statement.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.specifiers.assert_supported(diags);
+        self.declarator.assert_supported(diags);
+        self.declarations.assert_supported(diags);
+        self.statement.assert_supported(diags);
     }
 }
 
 impl AssertSupported for DeclarationSpecifier {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::StorageClass(storage_class) => storage_class.assert_supported(),
-            Self::TypeSpecifier(type_specifier) => type_specifier.assert_supported(),
-            Self::TypeQualifier(type_qualifier) => type_qualifier.assert_supported(),
-            Self::Function(_) => panic!("DeclarationSpecifier::Function"),
-            Self::Alignment(_) => panic!("DeclarationSpecifier::Alignment"),
-            Self::Extension(_) => panic!("DeclarationSpecifier::Extension"),
+            Self::StorageClass(storage_class) => storage_class.assert_supported(diags),
+            Self::TypeSpecifier(type_specifier) => type_specifier.assert_supported(diags),
+            Self::TypeQualifier(type_qualifier) => type_qualifier.assert_supported(diags),
+            Self::Function(_) => diags.unsupported("unsupported `DeclarationSpecifier::Function`"),
+            Self::Alignment(alignment) => alignment.assert_supported(diags),
+            Self::Extension(_) => {
+                diags.unsupported("unsupported `DeclarationSpecifier::Extension`")
+            }
         }
     }
 }
 
 impl AssertSupported for StorageClassSpecifier {
-    fn assert_supported(&self) {
-        assert_eq!(*self, Self::Typedef)
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        diags.check(
+            matches!(self, Self::Typedef | Self::Static | Self::Extern),
+            format!("unsupported storage class `{self:?}`"),
+        )
     }
 }
 
 impl AssertSupported for TypeSpecifier {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
             Self::Void => (),
             Self::Char => (),
@@ -132,517 +178,547 @@ impl AssertSupported for TypeSpecifier {
             Self::Signed => (),
             Self::Unsigned => (),
             Self::Bool => (),
-            Self::Complex => panic!("TypeSpecifier::Complex"),
-            Self::Atomic(_) => panic!("TypeSpecifier::Atomic"),
-            Self::Struct(struct_type) => struct_type.assert_supported(),
-            Self::Enum(_) => panic!("TypeSpecifier::Enum"),
+            Self::Complex => diags.unsupported("unsupported `TypeSpecifier::Complex`"),
+            Self::Atomic(_) => diags.unsupported("unsupported `TypeSpecifier::Atomic`"),
+            Self::Struct(struct_type) => struct_type.assert_supported(diags),
+            Self::Enum(enum_type) => enum_type.assert_supported(diags),
             Self::TypedefName(_) => (),
-            Self::TypeOf(_) => panic!("TypeSpecifier::TypeOf"),
-            Self::TS18661Float(_) => panic!("TypeSpecifier::TS18661Float"),
+            Self::TypeOf(_) => diags.unsupported("unsupported `TypeSpecifier::TypeOf`"),
+            Self::TS18661Float(_) => diags.unsupported("unsupported `TypeSpecifier::TS18661Float`"),
         }
     }
 }
 
+impl AssertSupported for EnumType {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.enumerators.assert_supported(diags);
+    }
+}
+
+impl AssertSupported for Enumerator {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.expression.assert_supported(diags);
+        diags.check(
+            self.extensions.is_empty(),
+            "unsupported enumerator extension",
+        );
+    }
+}
+
 impl AssertSupported for StructType {
-    fn assert_supported(&self) {
-        self.kind.assert_supported();
-        self.declarations.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.kind.assert_supported(diags);
+        self.declarations.assert_supported(diags);
     }
 }
 
 impl AssertSupported for StructDeclaration {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::Field(field) => field.assert_supported(),
-            Self::StaticAssert(_) => panic!("StructDeclaration::StaticAssert"),
+            Self::Field(field) => field.assert_supported(diags),
+            Self::StaticAssert(_) => {
+                diags.unsupported("unsupported `StructDeclaration::StaticAssert`")
+            }
         }
     }
 }
 
 impl AssertSupported for StructField {
-    fn assert_supported(&self) {
-        self.specifiers.assert_supported();
-        self.declarators.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.specifiers.assert_supported(diags);
+        self.declarators.assert_supported(diags);
     }
 }
 
 impl AssertSupported for StructDeclarator {
-    fn assert_supported(&self) {
-        self.declarator.assert_supported();
-        assert!(self.bit_width.is_none());
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.declarator.assert_supported(diags);
+        self.bit_width.assert_supported(diags);
     }
 }
 
 impl AssertSupported for StructKind {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
             Self::Struct => (),
-            Self::Union => panic!("StructKind::Union"),
+            Self::Union => (),
         }
     }
 }
 
 impl AssertSupported for AlignmentSpecifier {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::Type(typename) => typename.assert_supported(),
-            Self::Constant(_) => std::panic::panic_any(AlignmentSpecifier::Constant),
+            Self::Type(typename) => typename.assert_supported(diags),
+            Self::Constant(expr) => expr.assert_supported(diags),
         }
     }
 }
 
 impl AssertSupported for InitDeclarator {
-    fn assert_supported(&self) {
-        self.declarator.assert_supported();
-        self.initializer.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.declarator.assert_supported(diags);
+        self.initializer.assert_supported(diags);
     }
 }
 
 impl AssertSupported for Initializer {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::Expression(expr) => expr.assert_supported(),
-            Self::List(items) => items.assert_supported(),
+            Self::Expression(expr) => expr.assert_supported(diags),
+            Self::List(items) => items.assert_supported(diags),
         }
     }
 }
 
 impl AssertSupported for InitializerListItem {
-    fn assert_supported(&self) {
-        assert!(self.designation.is_empty());
-        self.initializer.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.designation.assert_supported(diags);
+        self.initializer.assert_supported(diags);
+    }
+}
+
+impl AssertSupported for Designator {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        match self {
+            Self::Index(expr) => expr.assert_supported(diags),
+            Self::Member(_) => (),
+            Self::Range(_) => diags.unsupported("unsupported range designator"),
+        }
     }
 }
 
 impl AssertSupported for Declarator {
-    fn assert_supported(&self) {
-        self.kind.assert_supported();
-        self.derived.assert_supported();
-        assert!(self.extensions.is_empty());
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.kind.assert_supported(diags);
+        self.derived.assert_supported(diags);
+        diags.check(
+            self.extensions.is_empty(),
+            "unsupported declarator extension",
+        );
     }
 }
 
 impl AssertSupported for DerivedDeclarator {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::Pointer(pointer_qualifiers) => pointer_qualifiers.assert_supported(),
-            Self::Array(array_decl) => array_decl.assert_supported(),
-            Self::Function(func_decl) => func_decl.assert_supported(),
-            // Support when K&R function has no parameter
-            Self::KRFunction(kr_func_decl) => assert!(kr_func_decl.is_empty()),
-            Self::Block(_) => panic!("DerivedDeclarator::Block"),
+            Self::Pointer(pointer_qualifiers) => pointer_qualifiers.assert_supported(diags),
+            Self::Array(array_decl) => array_decl.assert_supported(diags),
+            Self::Function(func_decl) => func_decl.assert_supported(diags),
+            Self::KRFunction(_) => (),
+            Self::Block(_) => diags.unsupported("unsupported `DerivedDeclarator::Block`"),
         }
     }
 }
 
 impl AssertSupported for PointerQualifier {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::TypeQualifier(type_qualifier) => type_qualifier.assert_supported(),
-            Self::Extension(_) => panic!("PointerQualifier::Extension"),
+            Self::TypeQualifier(type_qualifier) => type_qualifier.assert_supported(diags),
+            Self::Extension(_) => diags.unsupported("unsupported `PointerQualifier::Extension`"),
         }
     }
 }
 
 impl AssertSupported for ArrayDeclarator {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         // In C99, type qualifier(e.g., const) is allowed when
         // array declarator is used as function parameter.
         // However, KECC does not allow this feature because
         // it complicates IR generating logic.
-        assert!(self.qualifiers.is_empty());
-        self.size.assert_supported();
+        diags.check(
+            self.qualifiers.is_empty(),
+            "unsupported type qualifier in array declarator",
+        );
+        self.size.assert_supported(diags);
     }
 }
 
 impl AssertSupported for TypeQualifier {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
             Self::Const => (),
-            _ => panic!("TypeQualifier::_"),
+            _ => diags.unsupported(format!("unsupported `TypeQualifier::{self:?}`")),
         }
     }
 }
 
 impl AssertSupported for ArraySize {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::VariableExpression(expr) => expr.assert_supported(),
-            _ => panic!("ArraySize::_"),
+            // The size of an array of unknown size is completed by its initializer.
+            Self::Unknown => (),
+            Self::VariableExpression(expr) => expr.assert_supported(diags),
+            _ => diags.unsupported("unsupported array size"),
         }
     }
 }
 
 impl AssertSupported for FunctionDeclarator {
-    fn assert_supported(&self) {
-        self.parameters.assert_supported();
-        assert_eq!(self.ellipsis, Ellipsis::None);
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.parameters.assert_supported(diags);
     }
 }
 
 impl AssertSupported for ParameterDeclaration {
-    fn assert_supported(&self) {
-        self.specifiers.assert_supported();
-        self.declarator.assert_supported();
-        assert!(self.extensions.is_empty());
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.specifiers.assert_supported(diags);
+        self.declarator.assert_supported(diags);
+        diags.check(
+            self.extensions.is_empty(),
+            "unsupported parameter extension",
+        );
     }
 }
 
 impl AssertSupported for DeclaratorKind {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
             Self::Abstract => (),
             Self::Identifier(_) => (),
-            Self::Declarator(decl) => decl.assert_supported(),
+            Self::Declarator(decl) => decl.assert_supported(diags),
         }
     }
 }
 
 impl AssertSupported for BlockItem {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
             Self::Declaration(decl) => {
-                decl.node.declarators.assert_supported();
+                decl.node.declarators.assert_supported(diags);
 
                 for spec in &decl.node.specifiers {
-                    spec.assert_supported();
-                    match &spec.node {
-                        DeclarationSpecifier::StorageClass(_) => {
-                            // In C, `typedef` can be declared within the function.
-                            // However, KECC does not allow this feature
-                            // because it complicates IR generating logic.
-                            // For example, KECC does not allow a declaration using `typedef`
-                            // such as `typedef int i32_t;` declaration in a function definition.
-                            panic!("`StorageClassifier` is not allowed at `BlockItem`")
+                    spec.assert_supported(diags);
+                    // In C, `enum` can be declared within the function.
+                    // However, KECC does not allow this feature
+                    // because it complicates IR generating logic.
+                    // For example, KECC allows `enum E var;` declaration
+                    // using pre-declared `enum E`, but not `enum E { A, B } var;`
+                    // which tries to declare `enum E` newly.
+                    if let DeclarationSpecifier::TypeSpecifier(type_specifier) = &spec.node {
+                        if let TypeSpecifier::Enum(enum_type) = &type_specifier.node {
+                            diags.check(
+                                enum_type.node.enumerators.is_empty(),
+                                "unsupported local `enum` definition",
+                            );
                         }
-                        DeclarationSpecifier::TypeSpecifier(type_specifier) => {
-                            if let TypeSpecifier::Struct(struct_type) = &type_specifier.node {
-                                struct_type.node.kind.assert_supported();
-                                // In C, `struct` can be declared within the function.
-                                // However, KECC does not allow this feature
-                                // because it complicates IR generating logic.
-                                // For example, KECC allows `struct A var;` declaration
-                                // using pre-declared `struct A`, but not `struct A { int a; } var;`
-                                // which tries to declare `struct A` newly.
-                                assert!(struct_type.node.declarations.is_none());
-                            }
-                        }
-                        _ => (),
                     }
                 }
             }
-            Self::StaticAssert(_) => panic!("BlockItem::StaticAssert"),
-            Self::Statement(stmt) => stmt.assert_supported(),
+            Self::StaticAssert(static_assert) => static_assert.assert_supported(diags),
+            Self::Statement(stmt) => stmt.assert_supported(diags),
         }
     }
 }
 
 impl AssertSupported for ForInitializer {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
             Self::Empty => (),
-            Self::Expression(expr) => expr.assert_supported(),
-            Self::Declaration(decl) => decl.assert_supported(),
-            Self::StaticAssert(_) => panic!("ForInitializer::StaticAssert"),
+            Self::Expression(expr) => expr.assert_supported(diags),
+            Self::Declaration(decl) => decl.assert_supported(diags),
+            Self::StaticAssert(static_assert) => static_assert.assert_supported(diags),
         }
     }
 }
 
 impl AssertSupported for Statement {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::Labeled(_) => panic!("Statement::Labeled"),
-            Self::Compound(items) => items.assert_supported(),
-            Self::Expression(expr) => expr.assert_supported(),
+            Self::Labeled(stmt) => {
+                // `case` and `default` labels are only supported in `switch`, which is checked
+                // separately.
+                diags.check(
+                    matches!(stmt.node.label.node, Label::Identifier(_)),
+                    "`case` and `default` labels are unsupported outside of `switch`",
+                );
+                stmt.node.statement.assert_supported(diags);
+            }
+            Self::Compound(items) => items.assert_supported(diags),
+            Self::Expression(expr) => expr.assert_supported(diags),
             Self::If(stmt) => {
-                stmt.node.condition.assert_supported();
-                stmt.node.then_statement.assert_supported();
-                stmt.node.else_statement.assert_supported();
+                stmt.node.condition.assert_supported(diags);
+                stmt.node.then_statement.assert_supported(diags);
+                stmt.node.else_statement.assert_supported(diags);
             }
-            Self::Switch(stmt) => stmt.assert_supported(),
+            Self::Switch(stmt) => stmt.assert_supported(diags),
             Self::While(stmt) => {
-                stmt.node.expression.assert_supported();
-                stmt.node.statement.assert_supported();
+                stmt.node.expression.assert_supported(diags);
+                stmt.node.statement.assert_supported(diags);
             }
             Self::DoWhile(stmt) => {
-                stmt.node.statement.assert_supported();
-                stmt.node.expression.assert_supported();
+                stmt.node.statement.assert_supported(diags);
+                stmt.node.expression.assert_supported(diags);
             }
             Self::For(stmt) => {
-                stmt.node.initializer.assert_supported();
-                stmt.node.condition.assert_supported();
-                stmt.node.step.assert_supported();
-                stmt.node.statement.assert_supported();
+                stmt.node.initializer.assert_supported(diags);
+                stmt.node.condition.assert_supported(diags);
+                stmt.node.step.assert_supported(diags);
+                stmt.node.statement.assert_supported(diags);
             }
-            Self::Goto(_) => panic!("Statement::Goto"),
+            Self::Goto(_) => (),
             Self::Continue | Self::Break => (),
-            Self::Return(expr) => expr.assert_supported(),
-            Self::Asm(_) => panic!("Statement::Asm"),
+            Self::Return(expr) => expr.assert_supported(diags),
+            Self::Asm(_) => diags.unsupported("unsupported `Statement::Asm`"),
         }
     }
 }
 
 impl AssertSupported for SwitchStatement {
-    fn assert_supported(&self) {
-        self.expression.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.expression.assert_supported(diags);
 
-        let items = if let Statement::Compound(items) = &self.statement.node {
-            items
-        } else {
-            panic!("`Statement` in the `switch` is unsupported except `Statement::Compound`")
+        let Statement::Compound(items) = &self.statement.node else {
+            diags.enter(self.statement.span);
+            diags.unsupported(
+                "`Statement` in the `switch` is unsupported except `Statement::Compound`",
+            );
+            diags.exit();
+            return;
         };
 
         for item in items {
-            let stmt = if let BlockItem::Statement(stmt) = &item.node {
-                &stmt.node
-            } else {
-                panic!(
-                    "`BlockItem` in the `Statement::Compound` of the `switch` \
-                     is unsupported except `BlockItem::Statement`"
-                )
-            };
-
-            let stmt_in_label = if let Statement::Labeled(label_stmt) = stmt {
-                label_stmt.node.label.assert_supported();
-                &label_stmt.node.statement.node
-            } else {
-                panic!(
-                    "`BlockItem::Statement` in the `Statement::Compound` of the `switch` \
-                     is unsupported except `Statement::Labeled`"
-                )
-            };
-
-            let items = if let Statement::Compound(items) = stmt_in_label {
-                items
-            } else {
-                panic!("`Statement` in the `label` is unsupported except `Statement::Compound`")
-            };
-
-            // Split last and all the rest of the elements of the `Compound` items
-            let (last, items) = items
-                .split_last()
-                .unwrap_or_else(|| panic!("`Statement::Compound` has no item"));
-
-            for item in items {
-                match &item.node {
-                    BlockItem::Declaration(decl) => decl.assert_supported(),
-                    BlockItem::StaticAssert(_) => panic!("BlockItem::StaticAssert"),
-                    BlockItem::Statement(stmt) => {
-                        assert_ne!(
-                            &stmt.node,
-                            &Statement::Break,
-                            "`BlockItem::Statement` in the `Statement::Compound` of the \
-                             `label` should not be `Statement::Break` except the last one"
-                        );
-                        stmt.assert_supported();
-                    }
-                }
-            }
+            diags.enter(item.span);
+            assert_supported_switch_item(&item.node, diags);
+            diags.exit();
+        }
+    }
+}
 
-            // The last element of the `items` must be `Statement::Break`
-            let stmt = if let BlockItem::Statement(stmt) = &last.node {
-                &stmt.node
-            } else {
-                panic!(
-                    "`BlockItem` in the `Statement::Compound` of the `label` \
-                     is unsupported except `BlockItem::Statement`"
-                )
-            };
-
-            assert_eq!(
-                stmt,
-                &Statement::Break,
-                "the last `BlockItem` in the `Statement::Compound` \
-                 of the `label` must be `Statement::Break`"
-            );
+fn assert_supported_switch_item(item: &BlockItem, diags: &mut Diagnostics) {
+    let BlockItem::Statement(stmt) = item else {
+        diags.unsupported(
+            "`BlockItem` in the `Statement::Compound` of the `switch` \
+             is unsupported except `BlockItem::Statement`",
+        );
+        return;
+    };
+
+    let Statement::Labeled(label_stmt) = &stmt.node else {
+        diags.unsupported(
+            "`BlockItem::Statement` in the `Statement::Compound` of the `switch` \
+             is unsupported except `Statement::Labeled`",
+        );
+        return;
+    };
+    label_stmt.node.label.assert_supported(diags);
+    diags.check(
+        !matches!(label_stmt.node.label.node, Label::Identifier(_)),
+        "`BlockItem::Statement` in the `Statement::Compound` of the `switch` \
+         is unsupported except `case` and `default` labels",
+    );
+
+    let Statement::Compound(items) = &label_stmt.node.statement.node else {
+        diags.unsupported("`Statement` in the `label` is unsupported except `Statement::Compound`");
+        return;
+    };
+
+    // Split last and all the rest of the elements of the `Compound` items
+    let Some((last, items)) = items.split_last() else {
+        diags.unsupported("`Statement::Compound` has no item");
+        return;
+    };
+
+    for item in items {
+        diags.enter(item.span);
+        match &item.node {
+            BlockItem::Declaration(decl) => decl.assert_supported(diags),
+            BlockItem::StaticAssert(_) => {
+                diags.unsupported("unsupported `BlockItem::StaticAssert`")
+            }
+            BlockItem::Statement(stmt) => {
+                diags.check(
+                    stmt.node != Statement::Break,
+                    "`BlockItem::Statement` in the `Statement::Compound` of the \
+                     `label` should not be `Statement::Break` except the last one",
+                );
+                stmt.assert_supported(diags);
+            }
         }
+        diags.exit();
     }
+
+    // The last element of the `items` must be `Statement::Break`
+    diags.enter(last.span);
+    diags.check(
+        matches!(&last.node, BlockItem::Statement(stmt) if stmt.node == Statement::Break),
+        "the last `BlockItem` in the `Statement::Compound` \
+         of the `label` must be `Statement::Break`",
+    );
+    diags.exit();
 }
 
 impl AssertSupported for Expression {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
             Self::Identifier(_) => (),
-            Self::Constant(constant) => constant.assert_supported(),
-            Self::StringLiteral(_) => panic!("Expression::StringLiteral"),
-            Self::GenericSelection(_) => panic!("Expression::GenericSelection"),
-            Self::Member(member) => member.assert_supported(),
-            Self::Call(call) => call.assert_supported(),
-            Self::CompoundLiteral(_) => panic!("Expression::CompoundLiteral"),
-            Self::SizeOfTy(size_of_ty) => size_of_ty.assert_supported(),
-            Self::SizeOfVal(size_of_val) => size_of_val.assert_supported(),
-            Self::AlignOf(align_of) => align_of.assert_supported(),
-            Self::UnaryOperator(unary) => unary.assert_supported(),
-            Self::Cast(cast) => cast.assert_supported(),
-            Self::BinaryOperator(binary) => binary.assert_supported(),
-            Self::Conditional(conditional) => conditional.assert_supported(),
-            Self::Comma(exprs) => exprs.assert_supported(),
-            Self::OffsetOf(_) => panic!("Expression::OffsetOf"),
-            Self::VaArg(_) => panic!("Expression::VaArg"),
-            Self::Statement(_) => panic!("Expression::Statement"),
+            Self::Constant(constant) => constant.assert_supported(diags),
+            Self::StringLiteral(literal) => diags.check(
+                literal.node.iter().all(|piece| piece.starts_with('"')),
+                "unsupported wide string literal",
+            ),
+            Self::GenericSelection(_) => {
+                diags.unsupported("unsupported `Expression::GenericSelection`")
+            }
+            Self::Member(member) => member.assert_supported(diags),
+            Self::Call(call) => call.assert_supported(diags),
+            Self::CompoundLiteral(literal) => literal.assert_supported(diags),
+            Self::SizeOfTy(size_of_ty) => size_of_ty.assert_supported(diags),
+            Self::SizeOfVal(size_of_val) => size_of_val.assert_supported(diags),
+            Self::AlignOf(align_of) => align_of.assert_supported(diags),
+            Self::UnaryOperator(unary) => unary.assert_supported(diags),
+            Self::Cast(cast) => cast.assert_supported(diags),
+            Self::BinaryOperator(binary) => binary.assert_supported(diags),
+            Self::Conditional(conditional) => conditional.assert_supported(diags),
+            Self::Comma(exprs) => exprs.assert_supported(diags),
+            Self::OffsetOf(_) => diags.unsupported("unsupported `Expression::OffsetOf`"),
+            Self::VaArg(va_arg) => va_arg.assert_supported(diags),
+            Self::Statement(_) => diags.unsupported("unsupported `Expression::Statement`"),
         }
     }
 }
 
 impl AssertSupported for Label {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::Identifier(_) => panic!("Label::Identifier"),
+            Self::Identifier(_) => (),
             Self::Case(_) => (),
-            Self::CaseRange(_) => panic!("Label::CaseRange"),
+            Self::CaseRange(_) => diags.unsupported("unsupported `Label::CaseRange`"),
             Self::Default => (),
         }
     }
 }
 
 impl AssertSupported for MemberExpression {
-    fn assert_supported(&self) {
-        self.expression.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.expression.assert_supported(diags);
     }
 }
 
 impl AssertSupported for CallExpression {
-    fn assert_supported(&self) {
-        self.callee.assert_supported();
-        self.arguments.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.callee.assert_supported(diags);
+        self.arguments.assert_supported(diags);
     }
 }
 
 impl AssertSupported for TypeName {
-    fn assert_supported(&self) {
-        self.specifiers.assert_supported();
-        self.declarator.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.specifiers.assert_supported(diags);
+        self.declarator.assert_supported(diags);
     }
 }
 
 impl AssertSupported for SpecifierQualifier {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::TypeSpecifier(type_specifier) => type_specifier.assert_supported(),
-            Self::TypeQualifier(type_qualifier) => type_qualifier.assert_supported(),
-            Self::Extension(_) => panic!("SpecifierQualifier::Extension"),
+            Self::TypeSpecifier(type_specifier) => type_specifier.assert_supported(diags),
+            Self::TypeQualifier(type_qualifier) => type_qualifier.assert_supported(diags),
+            Self::Extension(_) => diags.unsupported("unsupported `SpecifierQualifier::Extension`"),
         }
     }
 }
 
 impl AssertSupported for UnaryOperatorExpression {
-    fn assert_supported(&self) {
-        self.operator.assert_supported();
-        self.operand.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.operator.assert_supported(diags);
+        self.operand.assert_supported(diags);
+    }
+}
+
+impl AssertSupported for CompoundLiteral {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.type_name.assert_supported(diags);
+        self.initializer_list.assert_supported(diags);
     }
 }
 
 impl AssertSupported for CastExpression {
-    fn assert_supported(&self) {
-        self.type_name.assert_supported();
-        self.expression.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.type_name.assert_supported(diags);
+        self.expression.assert_supported(diags);
+    }
+}
+
+impl AssertSupported for VaArgExpression {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.va_list.assert_supported(diags);
+        self.type_name.assert_supported(diags);
     }
 }
 
 impl AssertSupported for BinaryOperatorExpression {
-    fn assert_supported(&self) {
-        self.operator.assert_supported();
-        self.lhs.assert_supported();
-        self.rhs.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.operator.assert_supported(diags);
+        self.lhs.assert_supported(diags);
+        self.rhs.assert_supported(diags);
     }
 }
 
 impl AssertSupported for Constant {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
-            Self::Integer(integer) => integer.assert_supported(),
-            Self::Float(float) => float.assert_supported(),
+            Self::Integer(integer) => integer.assert_supported(diags),
+            Self::Float(float) => float.assert_supported(diags),
             Self::Character(_) => (),
         }
     }
 }
 
 impl AssertSupported for Integer {
-    fn assert_supported(&self) {
-        assert!(!self.suffix.imaginary);
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        diags.check(!self.suffix.imaginary, "unsupported imaginary constant");
     }
 }
 
 impl AssertSupported for Float {
-    fn assert_supported(&self) {
-        self.suffix.format.assert_supported();
-        assert!(!self.suffix.imaginary);
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.suffix.format.assert_supported(diags);
+        diags.check(!self.suffix.imaginary, "unsupported imaginary constant");
     }
 }
 
 impl AssertSupported for FloatFormat {
-    fn assert_supported(&self) {
+    fn assert_supported(&self, diags: &mut Diagnostics) {
         match self {
             Self::Float => (),
             Self::Double => (),
             Self::LongDouble => (),
-            Self::TS18661Format(_) => panic!("TS18861"),
+            Self::TS18661Format(_) => diags.unsupported("unsupported `FloatFormat::TS18661Format`"),
         }
     }
 }
 
 impl AssertSupported for UnaryOperator {
-    fn assert_supported(&self) {}
+    fn assert_supported(&self, diags: &mut Diagnostics) {}
 }
 
 impl AssertSupported for BinaryOperator {
-    fn assert_supported(&self) {}
+    fn assert_supported(&self, diags: &mut Diagnostics) {}
 }
 
 impl AssertSupported for ConditionalExpression {
-    fn assert_supported(&self) {
-        self.condition.assert_supported();
-        self.then_expression.assert_supported();
-        self.else_expression.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.condition.assert_supported(diags);
+        self.then_expression.assert_supported(diags);
+        self.else_expression.assert_supported(diags);
     }
 }
 
 impl AssertSupported for SizeOfTy {
-    fn assert_supported(&self) {
-        self.0.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.0.assert_supported(diags);
     }
 }
 
 impl AssertSupported for SizeOfVal {
-    fn assert_supported(&self) {
-        self.0.assert_supported();
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.0.assert_supported(diags);
     }
 }
 
 impl AssertSupported for AlignOf {
-    fn assert_supported(&self) {
-        self.0.assert_supported();
-    }
-}
-
-#[inline]
-fn is_valid_global_variable_declaration(decl: &Declaration) -> bool {
-    let declarators = &decl.declarators;
-
-    declarators.iter().all(|init_decl| {
-        if let Some(initializer) = &init_decl.node.initializer {
-            is_valid_global_variable_initializer(&initializer.node)
-        } else {
-            true
-        }
-    })
-}
-
-#[inline]
-fn is_valid_global_variable_initializer(initializer: &Initializer) -> bool {
-    match initializer {
-        Initializer::Expression(expr) => match &expr.node {
-            Expression::Constant(_) => true,
-            Expression::UnaryOperator(unary) => {
-                matches!(
-                    &unary.node.operator.node,
-                    UnaryOperator::Minus | UnaryOperator::Plus
-                ) && matches!(&unary.node.operand.node, Expression::Constant(_))
-            }
-            _ => false,
-        },
-        Initializer::List(items) => items
-            .iter()
-            .all(|item| is_valid_global_variable_initializer(&item.node.initializer.node)),
+    fn assert_supported(&self, diags: &mut Diagnostics) {
+        self.0.assert_supported(diags);
     }
 }
@@ -0,0 +1,1521 @@
+//! C preprocessor.
+//!
+//! It supports `#include` with search paths, object-like and function-like macros, conditional
+//! inclusion with constant expressions, and `#line`. The output is annotated with line markers in
+//! the format of `gcc -E`, e.g., `# 1 "foo.h" 1`, so that an offset in the preprocessed source is
+//! resolved to the file and line it comes from.
+//!
+//! `<stdarg.h>` is built in, so that variadic functions are written without the system headers.
+
+use core::fmt;
+use core::mem;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Translate;
+use crate::ir;
+
+/// The maximum depth of nested `#include`s.
+const MAX_INCLUDE_DEPTH: usize = 200;
+
+/// The maximum number of blank lines emitted to keep the line number of the output in sync. A
+/// larger gap is bridged with a line marker.
+const MAX_BLANK_LINES: usize = 8;
+
+/// Macros predefined by the preprocessor.
+const PREDEFINED_MACROS: &str = "\
+#define __STDC__ 1
+#define __STDC_VERSION__ 201112L
+#define __STDC_HOSTED__ 1
+";
+
+/// `<stdarg.h>`, whose macros expand to the builtins for variable arguments.
+const STDARG_H: &str = "\
+#ifndef __STDARG_H
+#define __STDARG_H
+typedef __builtin_va_list va_list;
+#define va_start(ap, param) __builtin_va_start(ap, param)
+#define va_arg(ap, type) __builtin_va_arg(ap, type)
+#define va_end(ap) __builtin_va_end(ap)
+#define va_copy(dest, src) __builtin_va_copy(dest, src)
+#endif
+";
+
+/// Headers provided by the preprocessor itself, so that they are found without the system headers.
+const BUILTIN_HEADERS: [(&str, &str); 1] = [("stdarg.h", STDARG_H)];
+
+/// Punctuators, longest first.
+const PUNCTUATORS: [&str; 48] = [
+    "...", "<<=", ">>=", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "*=",
+    "/=", "%=", "+=", "-=", "&=", "^=", "|=", "##", "[", "]", "(", ")", "{", "}", ".", "&", "*",
+    "+", "-", "~", "!", "/", "%", "<", ">", "^", "|", "?", ":", ";", "=", ",", "#",
+];
+
+/// Binary operators of `#if` expressions, from the lowest precedence to the highest.
+const BINARY_OPERATORS: [&[&str]; 10] = [
+    &["||"],
+    &["&&"],
+    &["|"],
+    &["^"],
+    &["&"],
+    &["==", "!="],
+    &["<", ">", "<=", ">="],
+    &["<<", ">>"],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+/// A macro defined or undefined on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandLineMacro {
+    /// `-D NAME` defines `NAME` as `1`, and `-D NAME=VALUE` defines it as `VALUE`.
+    Define(String),
+    /// `-U NAME` undefines `NAME`.
+    Undefine(String),
+}
+
+/// C preprocessor.
+#[derive(Default, Clone, Debug)]
+pub struct Preprocess {
+    /// Directories searched for included files, in order (`-I`).
+    ///
+    /// `#include "file"` searches the directory of the including file first.
+    pub include_dirs: Vec<PathBuf>,
+    /// Macros defined or undefined on the command line, in order (`-D` and `-U`).
+    pub macros: Vec<CommandLineMacro>,
+}
+
+/// Preprocessing error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// The file where the error occurs.
+    pub file: String,
+    /// 1-based line number in `file`. `0` if the location is unknown.
+    pub line: usize,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl Error {
+    fn new<S: Into<String>>(file: &str, line: usize, message: S) -> Self {
+        Self {
+            file: file.to_string(),
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}: error: {}", self.file, self.message)
+        } else {
+            write!(f, "{}:{}: error: {}", self.file, self.line, self.message)
+        }
+    }
+}
+
+impl<P: AsRef<Path>> Translate<P> for Preprocess {
+    type Target = String;
+    type Error = Error;
+
+    fn translate(&mut self, source: &P) -> Result<Self::Target, Self::Error> {
+        let mut preprocessor = Preprocessor::new(&self.include_dirs);
+        preprocessor.process_source(PREDEFINED_MACROS, "<built-in>", None, 0)?;
+
+        let command_line = self
+            .macros
+            .iter()
+            .map(|command_line_macro| match command_line_macro {
+                CommandLineMacro::Define(definition) => match definition.split_once('=') {
+                    Some((name, value)) => format!("#define {name} {value}\n"),
+                    None => format!("#define {definition} 1\n"),
+                },
+                CommandLineMacro::Undefine(name) => format!("#undef {name}\n"),
+            })
+            .collect::<String>();
+        preprocessor.process_source(&command_line, "<command-line>", None, 0)?;
+
+        let path = source.as_ref();
+        preprocessor.line_marker(1, &path.display().to_string(), None);
+        preprocessor.process_file(path, 0)?;
+        preprocessor.output.push('\n');
+
+        Ok(preprocessor.output)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Identifier,
+    Number,
+    Character,
+    String,
+    Punctuator,
+    /// A character that is not a part of any other token, e.g., `$` or an unmatched `'`.
+    Other,
+}
+
+/// A preprocessing token.
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    /// The line of the token. A token produced by a macro expansion has the line of the macro
+    /// invocation.
+    line: usize,
+    /// Whether the token is preceded by whitespace.
+    has_space: bool,
+    /// Whether the token is the first one of a line.
+    is_line_start: bool,
+    /// The number of characters preceding the token in its line if it is the first one.
+    indent: usize,
+    /// Whether the token is produced by a macro expansion.
+    is_expanded: bool,
+    /// The macros whose expansion produced the token, which are not expanded again in it.
+    hideset: HashSet<String>,
+}
+
+impl Token {
+    fn new(kind: TokenKind, text: String, line: usize) -> Self {
+        Self {
+            kind,
+            text,
+            line,
+            has_space: false,
+            is_line_start: false,
+            indent: 0,
+            is_expanded: false,
+            hideset: HashSet::new(),
+        }
+    }
+
+    fn is_punctuator(&self, text: &str) -> bool {
+        self.kind == TokenKind::Punctuator && self.text == text
+    }
+}
+
+/// A macro definition.
+#[derive(Debug, Clone)]
+enum Macro {
+    /// e.g., `#define N 10`
+    Object { body: Vec<Token> },
+    /// e.g., `#define MAX(a, b) ((a) > (b) ? (a) : (b))`. The parameters of a variadic macro end
+    /// with `__VA_ARGS__`.
+    Function {
+        params: Vec<String>,
+        is_variadic: bool,
+        body: Vec<Token>,
+    },
+}
+
+/// A conditional inclusion, i.e., a group of `#if`, `#elif`, `#else` and `#endif`.
+#[derive(Debug, Clone, Copy)]
+struct Conditional {
+    /// The line of its `#if`.
+    line: usize,
+    /// Whether the enclosing group is included.
+    is_parent_active: bool,
+    /// Whether the current branch is included.
+    is_active: bool,
+    /// Whether a branch has been included.
+    is_taken: bool,
+    /// Whether `#else` has been seen.
+    has_else: bool,
+}
+
+/// The state of preprocessing a C file.
+#[derive(Debug)]
+struct Preprocessor<'a> {
+    include_dirs: &'a [PathBuf],
+    macros: HashMap<String, Macro>,
+    /// Files marked with `#pragma once`.
+    once_files: HashSet<PathBuf>,
+    output: String,
+    /// The line of the source that the current line of `output` corresponds to.
+    output_line: usize,
+    /// The last character of the last token on the current line of `output`, and whether the
+    /// token is produced by a macro expansion.
+    last_token: Option<(char, bool)>,
+}
+
+impl<'a> Preprocessor<'a> {
+    fn new(include_dirs: &'a [PathBuf]) -> Self {
+        Self {
+            include_dirs,
+            macros: HashMap::new(),
+            once_files: HashSet::new(),
+            output: String::new(),
+            output_line: 0,
+            last_token: None,
+        }
+    }
+
+    fn process_file(&mut self, path: &Path, depth: usize) -> Result<(), Error> {
+        let file = path.display().to_string();
+        let source = fs::read_to_string(path).map_err(|e| Error::new(&file, 0, e.to_string()))?;
+        self.process_source(&source, &file, Some(path), depth)
+    }
+
+    /// Preprocesses `source` of `file`, which is read from `path` if it is a real file.
+    fn process_source(
+        &mut self,
+        source: &str,
+        file: &str,
+        path: Option<&Path>,
+        depth: usize,
+    ) -> Result<(), Error> {
+        let tokens = lex(source).map_err(|(line, message)| Error::new(file, line, message))?;
+
+        // `#line` changes the file name and the line numbers of the following lines.
+        let mut file = file.to_string();
+        let mut line_delta = 0;
+        let mut conditionals = Vec::<Conditional>::new();
+        let mut text = Vec::new();
+
+        for line in split_lines(tokens) {
+            let (hash, rest) = line.split_first().expect("a line must not be empty");
+            let is_active = conditionals.last().is_none_or(|c| c.is_active);
+            if !hash.is_punctuator("#") {
+                if is_active {
+                    text.extend(line.into_iter().map(|mut token| {
+                        token.line = shift_line(token.line, line_delta);
+                        token
+                    }));
+                }
+                continue;
+            }
+
+            self.flush(&mut text, &file)?;
+            let line = shift_line(hash.line, line_delta);
+            // The null directive, i.e., `#` alone.
+            let Some((directive, args)) = rest.split_first() else {
+                continue;
+            };
+
+            match directive.text.as_str() {
+                "if" | "ifdef" | "ifndef" => {
+                    let value = is_active
+                        && match directive.text.as_str() {
+                            "if" => self.evaluate_condition(args, &file, line)?,
+                            "ifdef" => self.is_defined(args, &file, line)?,
+                            _ => !self.is_defined(args, &file, line)?,
+                        };
+                    conditionals.push(Conditional {
+                        line,
+                        is_parent_active: is_active,
+                        is_active: value,
+                        is_taken: value,
+                        has_else: false,
+                    });
+                }
+                "elif" => {
+                    let conditional = conditionals
+                        .last_mut()
+                        .ok_or_else(|| Error::new(&file, line, "#elif without #if"))?;
+                    if conditional.has_else {
+                        return Err(Error::new(&file, line, "#elif after #else"));
+                    }
+                    conditional.is_active = conditional.is_parent_active
+                        && !conditional.is_taken
+                        && self.evaluate_condition(args, &file, line)?;
+                    conditional.is_taken |= conditional.is_active;
+                }
+                "else" => {
+                    let conditional = conditionals
+                        .last_mut()
+                        .ok_or_else(|| Error::new(&file, line, "#else without #if"))?;
+                    if conditional.has_else {
+                        return Err(Error::new(&file, line, "#else after #else"));
+                    }
+                    conditional.is_active = conditional.is_parent_active && !conditional.is_taken;
+                    conditional.is_taken = true;
+                    conditional.has_else = true;
+                }
+                "endif" => {
+                    let _unused = conditionals
+                        .pop()
+                        .ok_or_else(|| Error::new(&file, line, "#endif without #if"))?;
+                }
+                // The other directives in a skipped group are ignored.
+                _ if !is_active => (),
+                "define" => self.define(args, &file, line)?,
+                "undef" => {
+                    let name = macro_name(args, &file, line)?;
+                    let _unused = self.macros.remove(name);
+                }
+                "include" => self.include(args, &file, path, line, depth)?,
+                "line" => {
+                    let (new_line, new_file) = self.line_directive(args, &file, line)?;
+                    line_delta = new_line as isize - (hash.line as isize + 1);
+                    if let Some(new_file) = new_file {
+                        file = new_file;
+                    }
+                    self.line_marker(new_line, &file, None);
+                }
+                "error" => {
+                    let message = spell(args);
+                    return Err(Error::new(&file, line, format!("#error {message}")));
+                }
+                "pragma" => {
+                    if args.first().is_some_and(|token| token.text == "once") {
+                        if let Some(path) = path {
+                            let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+                            let _unused = self.once_files.insert(path);
+                        }
+                    }
+                }
+                "warning" => (),
+                _ => {
+                    return Err(Error::new(
+                        &file,
+                        line,
+                        format!("invalid preprocessing directive #{}", directive.text),
+                    ));
+                }
+            }
+        }
+
+        self.flush(&mut text, &file)?;
+        if let Some(conditional) = conditionals.last() {
+            return Err(Error::new(
+                &file,
+                conditional.line,
+                "unterminated conditional directive",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Expands the macros in `text` and writes it to the output.
+    fn flush(&mut self, text: &mut Vec<Token>, file: &str) -> Result<(), Error> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        for token in self.expand(mem::take(text), file)? {
+            if token.line > self.output_line {
+                let gap = token.line - self.output_line;
+                if gap <= MAX_BLANK_LINES {
+                    self.output.push_str(&"\n".repeat(gap));
+                    self.output_line = token.line;
+                    self.last_token = None;
+                } else {
+                    self.line_marker(token.line, file, None);
+                }
+            }
+
+            if let Some((last, is_last_expanded)) = self.last_token {
+                // Tokens put together by a macro expansion are separated not to form another
+                // token, e.g., `+` and `+` from `#define PLUS +` and `+PLUS`.
+                let first = token
+                    .text
+                    .chars()
+                    .next()
+                    .expect("a token must not be empty");
+                if token.has_space
+                    || ((token.is_expanded || is_last_expanded) && would_paste(last, first))
+                {
+                    self.output.push(' ');
+                }
+            } else {
+                // The indentation is kept so that diagnostics point to the right column.
+                self.output.push_str(&" ".repeat(token.indent));
+            }
+
+            self.output.push_str(&token.text);
+            let last = token
+                .text
+                .chars()
+                .last()
+                .expect("a token must not be empty");
+            self.last_token = Some((last, token.is_expanded));
+        }
+
+        Ok(())
+    }
+
+    /// Writes a line marker, after which the output corresponds to `line` of `file`.
+    ///
+    /// `flag` is `1` when entering an included file, and `2` when returning to the including file.
+    fn line_marker(&mut self, line: usize, file: &str, flag: Option<usize>) {
+        if !self.output.is_empty() && !self.output.ends_with('\n') {
+            self.output.push('\n');
+        }
+
+        self.output
+            .push_str(&format!("# {line} \"{}\"", escape(file)));
+        if let Some(flag) = flag {
+            self.output.push_str(&format!(" {flag}"));
+        }
+        self.output.push('\n');
+        self.output_line = line;
+        self.last_token = None;
+    }
+
+    /// Processes `#define`.
+    fn define(&mut self, args: &[Token], file: &str, line: usize) -> Result<(), Error> {
+        let name = macro_name(args, file, line)?;
+        if name == "defined" {
+            return Err(Error::new(
+                file,
+                line,
+                "`defined` cannot be used as a macro name",
+            ));
+        }
+
+        let rest = &args[1..];
+        // A function-like macro has `(` right after its name.
+        let (params, body) = match rest.first() {
+            Some(lparen) if lparen.is_punctuator("(") && !lparen.has_space => {
+                let (params, is_variadic, body) = macro_params(&rest[1..], file, line)?;
+                (Some((params, is_variadic)), body)
+            }
+            _ => (None, rest),
+        };
+
+        if body.first().is_some_and(|token| token.is_punctuator("##"))
+            || body.last().is_some_and(|token| token.is_punctuator("##"))
+        {
+            return Err(Error::new(
+                file,
+                line,
+                "`##` cannot appear at either end of a macro expansion",
+            ));
+        }
+
+        let body = body.to_vec();
+        let definition = match params {
+            Some((params, is_variadic)) => {
+                for (i, token) in body.iter().enumerate() {
+                    if token.is_punctuator("#")
+                        && !body
+                            .get(i + 1)
+                            .is_some_and(|next| params.contains(&next.text))
+                    {
+                        return Err(Error::new(
+                            file,
+                            line,
+                            "`#` is not followed by a macro parameter",
+                        ));
+                    }
+                }
+
+                Macro::Function {
+                    params,
+                    is_variadic,
+                    body,
+                }
+            }
+            None => Macro::Object { body },
+        };
+
+        let _unused = self.macros.insert(name.to_string(), definition);
+        Ok(())
+    }
+
+    /// Processes `#include` in `file` read from `path`.
+    fn include(
+        &mut self,
+        args: &[Token],
+        file: &str,
+        path: Option<&Path>,
+        line: usize,
+        depth: usize,
+    ) -> Result<(), Error> {
+        // The file name may be given by a macro, e.g., `#include HEADER`.
+        let args = match args.first() {
+            Some(token) if token.kind == TokenKind::String || token.is_punctuator("<") => {
+                args.to_vec()
+            }
+            _ => self.expand(args.to_vec(), file)?,
+        };
+
+        let invalid = || Error::new(file, line, "#include expects \"FILENAME\" or <FILENAME>");
+        let (name, is_quoted) = match args.split_first() {
+            Some((token, [])) if token.kind == TokenKind::String && token.text.starts_with('"') => {
+                (token.text[1..token.text.len() - 1].to_string(), true)
+            }
+            Some((token, rest)) if token.is_punctuator("<") => match rest.split_last() {
+                Some((rangle, name)) if rangle.is_punctuator(">") && !name.is_empty() => {
+                    (spell(name), false)
+                }
+                _ => return Err(invalid()),
+            },
+            _ => return Err(invalid()),
+        };
+
+        // `#include "file"` searches the directory of the including file first.
+        let current_dir = path
+            .filter(|_| is_quoted)
+            .map(|path| path.parent().unwrap_or_else(|| Path::new("")));
+        let included = current_dir
+            .into_iter()
+            .chain(self.include_dirs.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(&name))
+            .find(|path| path.is_file());
+
+        if depth >= MAX_INCLUDE_DEPTH {
+            return Err(Error::new(file, line, "#include nested too deeply"));
+        }
+
+        // The built-in headers are searched last, so that they can be overridden.
+        let Some(included) = included else {
+            let (_, source) = BUILTIN_HEADERS
+                .iter()
+                .find(|(header, _)| *header == name)
+                .ok_or_else(|| Error::new(file, line, format!("`{name}` file not found")))?;
+            self.line_marker(1, &name, Some(1));
+            self.process_source(source, &name, None, depth + 1)?;
+            self.line_marker(line + 1, file, Some(2));
+            return Ok(());
+        };
+
+        let canonical = fs::canonicalize(&included).unwrap_or_else(|_| included.clone());
+        if self.once_files.contains(&canonical) {
+            return Ok(());
+        }
+
+        self.line_marker(1, &included.display().to_string(), Some(1));
+        self.process_file(&included, depth + 1)?;
+        self.line_marker(line + 1, file, Some(2));
+        Ok(())
+    }
+
+    /// Processes `#line`, returning the line number of the next line and the new file name.
+    fn line_directive(
+        &self,
+        args: &[Token],
+        file: &str,
+        line: usize,
+    ) -> Result<(usize, Option<String>), Error> {
+        let args = self.expand(args.to_vec(), file)?;
+        let (new_line, rest) = match args.split_first() {
+            Some((number, rest)) if number.kind == TokenKind::Number => {
+                let new_line = number.text.parse::<usize>().map_err(|_| {
+                    Error::new(
+                        file,
+                        line,
+                        format!("`{}` is not a positive integer", number.text),
+                    )
+                })?;
+                (new_line, rest)
+            }
+            _ => return Err(Error::new(file, line, "#line expects a line number")),
+        };
+
+        match rest {
+            [] => Ok((new_line, None)),
+            [name] if name.kind == TokenKind::String && name.text.starts_with('"') => Ok((
+                new_line,
+                Some(name.text[1..name.text.len() - 1].to_string()),
+            )),
+            _ => Err(Error::new(file, line, "invalid file name in #line")),
+        }
+    }
+
+    /// Processes the argument of `#ifdef` and `#ifndef`.
+    fn is_defined(&self, args: &[Token], file: &str, line: usize) -> Result<bool, Error> {
+        let name = macro_name(args, file, line)?;
+        Ok(self.is_macro(name))
+    }
+
+    fn is_macro(&self, name: &str) -> bool {
+        self.macros.contains_key(name) || name == "__FILE__" || name == "__LINE__"
+    }
+
+    /// Evaluates the expression of `#if` and `#elif`.
+    fn evaluate_condition(&self, args: &[Token], file: &str, line: usize) -> Result<bool, Error> {
+        // `defined NAME` and `defined(NAME)` are evaluated before macro expansion.
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while let Some(token) = args.get(i) {
+            i += 1;
+            if token.kind != TokenKind::Identifier || token.text != "defined" {
+                tokens.push(token.clone());
+                continue;
+            }
+
+            let has_paren = args.get(i).is_some_and(|next| next.is_punctuator("("));
+            if has_paren {
+                i += 1;
+            }
+            let name = macro_name(&args[i..], file, line)?;
+            i += 1;
+            if has_paren {
+                if !args.get(i).is_some_and(|next| next.is_punctuator(")")) {
+                    return Err(Error::new(file, line, "missing `)` after `defined`"));
+                }
+                i += 1;
+            }
+
+            let value = if self.is_macro(name) { "1" } else { "0" };
+            tokens.push(Token::new(TokenKind::Number, value.to_string(), line));
+        }
+
+        // The identifiers remaining after macro expansion are evaluated to `0`.
+        let tokens = self
+            .expand(tokens, file)?
+            .into_iter()
+            .map(|token| {
+                if token.kind == TokenKind::Identifier {
+                    Token::new(TokenKind::Number, "0".to_string(), token.line)
+                } else {
+                    token
+                }
+            })
+            .collect::<Vec<_>>();
+        if tokens.is_empty() {
+            return Err(Error::new(file, line, "#if with no expression"));
+        }
+
+        let mut evaluator = Evaluator {
+            tokens: &tokens,
+            pos: 0,
+            unevaluated: 0,
+            file,
+            line,
+        };
+        let value = evaluator.conditional()?;
+        if let Some(token) = tokens.get(evaluator.pos) {
+            return Err(Error::new(
+                file,
+                line,
+                format!("missing binary operator before `{}`", token.text),
+            ));
+        }
+
+        Ok(!value.is_zero())
+    }
+
+    /// Expands the macros in `tokens`.
+    ///
+    /// A token is not expanded by a macro in its hideset, which prevents the recursive expansion
+    /// of a macro.
+    fn expand(&self, tokens: Vec<Token>, file: &str) -> Result<Vec<Token>, Error> {
+        let mut input = VecDeque::from(tokens);
+        let mut output = Vec::new();
+
+        while let Some(token) = input.pop_front() {
+            if token.kind != TokenKind::Identifier || token.hideset.contains(&token.text) {
+                output.push(token);
+                continue;
+            }
+
+            let (replacement, mut hideset) =
+                match (token.text.as_str(), self.macros.get(&token.text)) {
+                    ("__FILE__", None) => {
+                        let text = format!("\"{}\"", escape(file));
+                        (
+                            vec![Token::new(TokenKind::String, text, token.line)],
+                            HashSet::new(),
+                        )
+                    }
+                    ("__LINE__", None) => {
+                        let text = token.line.to_string();
+                        (
+                            vec![Token::new(TokenKind::Number, text, token.line)],
+                            HashSet::new(),
+                        )
+                    }
+                    (_, Some(Macro::Object { body })) => {
+                        let replacement = self.substitute(body, &[], &[], file, token.line)?;
+                        (replacement, token.hideset.clone())
+                    }
+                    (
+                        _,
+                        Some(Macro::Function {
+                            params,
+                            is_variadic,
+                            body,
+                        }),
+                    ) if input.front().is_some_and(|next| next.is_punctuator("(")) => {
+                        let (args, rparen) =
+                            macro_args(&mut input, &token, params, *is_variadic, file)?;
+                        let replacement = self.substitute(body, params, &args, file, token.line)?;
+                        let hideset = token
+                            .hideset
+                            .intersection(&rparen.hideset)
+                            .cloned()
+                            .collect();
+                        (replacement, hideset)
+                    }
+                    _ => {
+                        output.push(token);
+                        continue;
+                    }
+                };
+
+            let _unused = hideset.insert(token.text.clone());
+            for (i, mut replaced) in replacement.into_iter().enumerate().rev() {
+                replaced.hideset.extend(hideset.iter().cloned());
+                replaced.line = token.line;
+                replaced.is_line_start = false;
+                replaced.is_expanded = true;
+                if i == 0 {
+                    replaced.has_space = token.has_space;
+                    replaced.indent = token.indent;
+                } else {
+                    replaced.indent = 0;
+                }
+                input.push_front(replaced);
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Substitutes the parameters in `body` of a macro with `args`, and processes `#` and `##`.
+    ///
+    /// An argument is macro-expanded before substitution unless it is an operand of `#` or `##`.
+    fn substitute(
+        &self,
+        body: &[Token],
+        params: &[String],
+        args: &[Vec<Token>],
+        file: &str,
+        line: usize,
+    ) -> Result<Vec<Token>, Error> {
+        let param_of = |token: &Token| {
+            if token.kind == TokenKind::Identifier {
+                params.iter().position(|param| *param == token.text)
+            } else {
+                None
+            }
+        };
+        let at_either_end = || {
+            Error::new(
+                file,
+                line,
+                "`##` cannot appear at either end of a macro expansion",
+            )
+        };
+
+        let mut output = Vec::<Token>::new();
+        let mut i = 0;
+        while let Some(token) = body.get(i) {
+            let next = body.get(i + 1);
+
+            // `#param` is replaced with the spelling of the argument as a string literal.
+            if token.is_punctuator("#") {
+                if let Some(param) = next.and_then(param_of) {
+                    let mut string = Token::new(TokenKind::String, stringify(&args[param]), line);
+                    string.has_space = token.has_space;
+                    output.push(string);
+                    i += 2;
+                    continue;
+                }
+            }
+
+            if token.is_punctuator("##") {
+                let rhs = next.ok_or_else(at_either_end)?;
+                let lhs = output.pop().ok_or_else(at_either_end)?;
+                match param_of(rhs).map(|param| &args[param]) {
+                    // As in GCC, `, ## __VA_ARGS__` drops the comma if no variable argument is
+                    // given, and is `, __VA_ARGS__` otherwise.
+                    Some(arg) if lhs.is_punctuator(",") && rhs.text == "__VA_ARGS__" => {
+                        if !arg.is_empty() {
+                            output.push(lhs);
+                            output.extend(arg.iter().cloned());
+                        }
+                    }
+                    Some(arg) => match arg.split_first() {
+                        Some((first, rest)) => {
+                            output.push(paste(&lhs, first, file, line)?);
+                            output.extend(rest.iter().cloned());
+                        }
+                        None => output.push(lhs),
+                    },
+                    None => output.push(paste(&lhs, rhs, file, line)?),
+                }
+                i += 2;
+                continue;
+            }
+
+            if let Some(param) = param_of(token) {
+                let arg = &args[param];
+                if next.is_some_and(|next| next.is_punctuator("##")) {
+                    if arg.is_empty() {
+                        // An empty argument is a placemarker, so `a ## b` is `b` if `a` is empty.
+                        let rhs = body.get(i + 2).ok_or_else(at_either_end)?;
+                        match param_of(rhs) {
+                            Some(param) => output.extend(args[param].iter().cloned()),
+                            None => output.push(rhs.clone()),
+                        }
+                        i += 3;
+                    } else {
+                        output.extend(arg.iter().cloned());
+                        i += 1;
+                    }
+                    continue;
+                }
+
+                let mut expanded = self.expand(arg.clone(), file)?;
+                if let Some(first) = expanded.first_mut() {
+                    first.has_space = token.has_space;
+                }
+                output.extend(expanded);
+                i += 1;
+                continue;
+            }
+
+            output.push(token.clone());
+            i += 1;
+        }
+
+        Ok(output)
+    }
+}
+
+/// A value of an `#if` expression, in which every integer is of `intmax_t` or `uintmax_t`, i.e.,
+/// `long` or `unsigned long` (C11 6.10.1p4).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Integer {
+    /// The bits of the value, which is negative if `is_unsigned` is false and the sign bit is set.
+    bits: u64,
+    is_unsigned: bool,
+}
+
+impl Integer {
+    fn signed(value: i64) -> Self {
+        Self {
+            bits: value as u64,
+            is_unsigned: false,
+        }
+    }
+
+    fn from_bool(value: bool) -> Self {
+        Self::signed(i64::from(value))
+    }
+
+    fn is_zero(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Converts `self` and `other` to their common type by the usual arithmetic conversions, in
+    /// which a signed operand is converted to unsigned if the other is unsigned.
+    fn common(self, other: Self) -> (Self, Self) {
+        let is_unsigned = self.is_unsigned || other.is_unsigned;
+        (
+            Self {
+                bits: self.bits,
+                is_unsigned,
+            },
+            Self {
+                bits: other.bits,
+                is_unsigned,
+            },
+        )
+    }
+}
+
+/// Evaluates the expression of `#if`.
+#[derive(Debug)]
+struct Evaluator<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    /// The depth of operands not evaluated, e.g., `1 / 0` of `0 && 1 / 0`, in which division by
+    /// zero is not an error.
+    unevaluated: usize,
+    file: &'a str,
+    line: usize,
+}
+
+impl Evaluator<'_> {
+    fn error<S: Into<String>>(&self, message: S) -> Error {
+        Error::new(self.file, self.line, message)
+    }
+
+    fn eat(&mut self, punctuator: &str) -> bool {
+        let is_eaten = self
+            .tokens
+            .get(self.pos)
+            .is_some_and(|token| token.is_punctuator(punctuator));
+        if is_eaten {
+            self.pos += 1;
+        }
+        is_eaten
+    }
+
+    /// Evaluates an operand, which is not evaluated in effect if `is_evaluated` is false.
+    fn operand<F>(&mut self, is_evaluated: bool, evaluate: F) -> Result<Integer, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<Integer, Error>,
+    {
+        if is_evaluated {
+            return evaluate(self);
+        }
+
+        self.unevaluated += 1;
+        let value = evaluate(self);
+        self.unevaluated -= 1;
+        value
+    }
+
+    fn conditional(&mut self) -> Result<Integer, Error> {
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+
+        let then_value = self.operand(!condition.is_zero(), Self::conditional)?;
+        if !self.eat(":") {
+            return Err(self.error("expected `:` in preprocessor expression"));
+        }
+        let else_value = self.operand(condition.is_zero(), Self::conditional)?;
+        // The result is of the common type of both operands, even if one is not evaluated.
+        let (then_value, else_value) = then_value.common(else_value);
+        Ok(if condition.is_zero() {
+            else_value
+        } else {
+            then_value
+        })
+    }
+
+    /// Evaluates the binary operators whose precedence is `level` or higher.
+    fn binary(&mut self, level: usize) -> Result<Integer, Error> {
+        let Some(operators) = BINARY_OPERATORS.get(level) else {
+            return self.unary();
+        };
+
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(operator) = operators.iter().find(|operator| self.eat(operator)) {
+            let is_evaluated = match *operator {
+                "||" => lhs.is_zero(),
+                "&&" => !lhs.is_zero(),
+                _ => true,
+            };
+            let rhs = self.operand(is_evaluated, |this| this.binary(level + 1))?;
+            lhs = self.binary_operation(operator, lhs, rhs)?;
+        }
+
+        Ok(lhs)
+    }
+
+    fn binary_operation(
+        &self,
+        operator: &str,
+        lhs: Integer,
+        rhs: Integer,
+    ) -> Result<Integer, Error> {
+        // The result of a shift is of the type of the left operand.
+        match operator {
+            "||" => return Ok(Integer::from_bool(!lhs.is_zero() || !rhs.is_zero())),
+            "&&" => return Ok(Integer::from_bool(!lhs.is_zero() && !rhs.is_zero())),
+            "<<" => {
+                return Ok(Integer {
+                    bits: lhs.bits.wrapping_shl(rhs.bits as u32),
+                    ..lhs
+                });
+            }
+            ">>" => {
+                let bits = if lhs.is_unsigned {
+                    lhs.bits.wrapping_shr(rhs.bits as u32)
+                } else {
+                    (lhs.bits as i64).wrapping_shr(rhs.bits as u32) as u64
+                };
+                return Ok(Integer { bits, ..lhs });
+            }
+            _ => (),
+        }
+
+        let (lhs, rhs) = lhs.common(rhs);
+        let is_unsigned = lhs.is_unsigned;
+        let ordering = if is_unsigned {
+            lhs.bits.cmp(&rhs.bits)
+        } else {
+            (lhs.bits as i64).cmp(&(rhs.bits as i64))
+        };
+        let bits = match operator {
+            "==" => return Ok(Integer::from_bool(ordering.is_eq())),
+            "!=" => return Ok(Integer::from_bool(ordering.is_ne())),
+            "<" => return Ok(Integer::from_bool(ordering.is_lt())),
+            ">" => return Ok(Integer::from_bool(ordering.is_gt())),
+            "<=" => return Ok(Integer::from_bool(ordering.is_le())),
+            ">=" => return Ok(Integer::from_bool(ordering.is_ge())),
+            "|" => lhs.bits | rhs.bits,
+            "^" => lhs.bits ^ rhs.bits,
+            "&" => lhs.bits & rhs.bits,
+            "+" => lhs.bits.wrapping_add(rhs.bits),
+            "-" => lhs.bits.wrapping_sub(rhs.bits),
+            "*" => lhs.bits.wrapping_mul(rhs.bits),
+            "/" | "%" if rhs.is_zero() && self.unevaluated == 0 => {
+                return Err(self.error("division by zero in preprocessor expression"));
+            }
+            "/" | "%" if rhs.is_zero() => 0,
+            "/" if is_unsigned => lhs.bits / rhs.bits,
+            "%" if is_unsigned => lhs.bits % rhs.bits,
+            "/" => (lhs.bits as i64).wrapping_div(rhs.bits as i64) as u64,
+            "%" => (lhs.bits as i64).wrapping_rem(rhs.bits as i64) as u64,
+            _ => panic!("`{operator}` is not a binary operator"),
+        };
+
+        Ok(Integer { bits, is_unsigned })
+    }
+
+    fn unary(&mut self) -> Result<Integer, Error> {
+        if self.eat("+") {
+            self.unary()
+        } else if self.eat("-") {
+            let value = self.unary()?;
+            Ok(Integer {
+                bits: value.bits.wrapping_neg(),
+                ..value
+            })
+        } else if self.eat("~") {
+            let value = self.unary()?;
+            Ok(Integer {
+                bits: !value.bits,
+                ..value
+            })
+        } else if self.eat("!") {
+            Ok(Integer::from_bool(self.unary()?.is_zero()))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Integer, Error> {
+        if self.eat("(") {
+            let value = self.conditional()?;
+            if !self.eat(")") {
+                return Err(self.error("missing `)` in preprocessor expression"));
+            }
+            return Ok(value);
+        }
+
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| self.error("unexpected end of preprocessor expression"))?;
+        self.pos += 1;
+        match token.kind {
+            TokenKind::Number => integer_value(&token.text).ok_or_else(|| {
+                self.error(format!(
+                    "invalid integer constant `{}` in preprocessor expression",
+                    token.text
+                ))
+            }),
+            TokenKind::Character => character_value(&token.text)
+                .map(Integer::signed)
+                .ok_or_else(|| self.error(format!("invalid character constant `{}`", token.text))),
+            _ => Err(self.error(format!(
+                "`{}` is not valid in preprocessor expressions",
+                token.text
+            ))),
+        }
+    }
+}
+
+/// Splits `source` into preprocessing tokens.
+///
+/// Comments are removed, and a backslash followed by a newline joins the lines. On error, returns
+/// the line where it occurs and the message.
+fn lex(source: &str) -> Result<Vec<Token>, (usize, String)> {
+    // Each character with its line, after joining the lines ending with a backslash.
+    let mut chars = Vec::new();
+    let mut line = 1;
+    let mut iter = source.chars().filter(|c| *c != '\r').peekable();
+    while let Some(c) = iter.next() {
+        if c == '\\' && iter.peek() == Some(&'\n') {
+            let _unused = iter.next();
+            line += 1;
+            continue;
+        }
+        chars.push((c, line));
+        if c == '\n' {
+            line += 1;
+        }
+    }
+
+    let char_at = |i: usize| chars.get(i).map(|(c, _)| *c);
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    let mut tokens = Vec::new();
+    let mut has_space = false;
+    let mut is_line_start = true;
+    let mut line_begin = 0;
+    let mut i = 0;
+    while let Some(c) = char_at(i) {
+        if c == '\n' {
+            has_space = false;
+            is_line_start = true;
+            i += 1;
+            line_begin = i;
+            continue;
+        }
+        if c.is_whitespace() {
+            has_space = true;
+            i += 1;
+            continue;
+        }
+        if c == '/' && char_at(i + 1) == Some('/') {
+            while char_at(i).is_some_and(|c| c != '\n') {
+                i += 1;
+            }
+            has_space = true;
+            continue;
+        }
+        if c == '/' && char_at(i + 1) == Some('*') {
+            let start = i;
+            i += 2;
+            while !(char_at(i) == Some('*') && char_at(i + 1) == Some('/')) {
+                if char_at(i).is_none() {
+                    return Err((chars[start].1, "unterminated comment".to_string()));
+                }
+                i += 1;
+            }
+            i += 2;
+            has_space = true;
+            continue;
+        }
+
+        let start = i;
+        let kind = if let Some((kind, end)) = quoted(&chars, i) {
+            i = end;
+            kind
+        } else if is_word(Some(c)) && !c.is_ascii_digit() {
+            while is_word(char_at(i)) {
+                i += 1;
+            }
+            TokenKind::Identifier
+        } else if c.is_ascii_digit()
+            || (c == '.' && char_at(i + 1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            i += 1;
+            // An exponent has a sign, e.g., `1e+5`.
+            let is_sign = |i: usize| {
+                matches!(char_at(i), Some('+' | '-'))
+                    && matches!(char_at(i - 1), Some('e' | 'E' | 'p' | 'P'))
+            };
+            while is_word(char_at(i)) || char_at(i) == Some('.') || is_sign(i) {
+                i += 1;
+            }
+            TokenKind::Number
+        } else if let Some(punctuator) = PUNCTUATORS.iter().find(|punctuator| {
+            punctuator
+                .chars()
+                .enumerate()
+                .all(|(j, p)| char_at(i + j) == Some(p))
+        }) {
+            i += punctuator.len();
+            TokenKind::Punctuator
+        } else {
+            i += 1;
+            TokenKind::Other
+        };
+
+        let text = chars[start..i].iter().map(|(c, _)| *c).collect();
+        let mut token = Token::new(kind, text, chars[start].1);
+        token.has_space = has_space;
+        token.is_line_start = is_line_start;
+        if is_line_start {
+            token.indent = start - line_begin;
+        }
+        tokens.push(token);
+        has_space = false;
+        is_line_start = false;
+    }
+
+    Ok(tokens)
+}
+
+/// Lexes the character constant or string literal starting at `start` of `chars`, returning its
+/// kind and end. Returns `None` if there is no such token or it is not terminated.
+fn quoted(chars: &[(char, usize)], start: usize) -> Option<(TokenKind, usize)> {
+    let char_at = |i: usize| chars.get(i).map(|(c, _)| *c);
+
+    let prefix_len = ["u8", "u", "U", "L", ""]
+        .iter()
+        .find(|prefix| {
+            prefix
+                .chars()
+                .enumerate()
+                .all(|(j, p)| char_at(start + j) == Some(p))
+                && matches!(char_at(start + prefix.len()), Some('"' | '\''))
+        })?
+        .len();
+    let quote = char_at(start + prefix_len)?;
+    let kind = if quote == '"' {
+        TokenKind::String
+    } else {
+        TokenKind::Character
+    };
+
+    let mut i = start + prefix_len + 1;
+    loop {
+        match char_at(i)? {
+            '\\' => i += 2,
+            '\n' => return None,
+            c if c == quote => return Some((kind, i + 1)),
+            _ => i += 1,
+        }
+    }
+}
+
+/// Splits `tokens` into lines.
+fn split_lines(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+    let mut lines = Vec::<Vec<Token>>::new();
+    for token in tokens {
+        match lines.last_mut() {
+            Some(line) if !token.is_line_start => line.push(token),
+            _ => lines.push(vec![token]),
+        }
+    }
+    lines
+}
+
+/// Reads the arguments of the invocation of the function-like macro `name`, whose `(` is the
+/// front of `input`. Returns the arguments and the closing `)`.
+fn macro_args(
+    input: &mut VecDeque<Token>,
+    name: &Token,
+    params: &[String],
+    is_variadic: bool,
+    file: &str,
+) -> Result<(Vec<Vec<Token>>, Token), Error> {
+    let _lparen = input.pop_front();
+    let mut args = vec![Vec::new()];
+    let mut depth = 0;
+
+    let rparen = loop {
+        let token = input.pop_front().ok_or_else(|| {
+            Error::new(
+                file,
+                name.line,
+                format!("unterminated argument list invoking macro `{}`", name.text),
+            )
+        })?;
+
+        if token.is_punctuator("(") {
+            depth += 1;
+        } else if token.is_punctuator(")") {
+            if depth == 0 {
+                break token;
+            }
+            depth -= 1;
+        } else if token.is_punctuator(",")
+            && depth == 0
+            // The commas in the variable arguments are a part of them.
+            && !(is_variadic && args.len() == params.len())
+        {
+            args.push(Vec::new());
+            continue;
+        }
+        args.last_mut()
+            .expect("`args` must not be empty")
+            .push(token);
+    };
+
+    // `F()` gives no argument to a macro without parameters.
+    if params.is_empty() && args.len() == 1 && args[0].is_empty() {
+        args.clear();
+    }
+    // The variable arguments may be omitted, e.g., `F(a)` for `#define F(x, ...)`.
+    if is_variadic && args.len() + 1 == params.len() {
+        args.push(Vec::new());
+    }
+    if args.len() != params.len() {
+        return Err(Error::new(
+            file,
+            name.line,
+            format!(
+                "macro `{}` requires {} arguments, but {} given",
+                name.text,
+                params.len(),
+                args.len()
+            ),
+        ));
+    }
+
+    Ok((args, rparen))
+}
+
+/// Parses the parameters of a function-like macro after its `(`, returning the parameters,
+/// whether it is variadic and its body.
+fn macro_params<'t>(
+    tokens: &'t [Token],
+    file: &str,
+    line: usize,
+) -> Result<(Vec<String>, bool, &'t [Token]), Error> {
+    let missing_rparen = || Error::new(file, line, "missing `)` in macro parameter list");
+    let mut params = Vec::new();
+    let mut i = 0;
+
+    if tokens.first().is_some_and(|token| token.is_punctuator(")")) {
+        return Ok((params, false, &tokens[1..]));
+    }
+
+    loop {
+        let param = tokens.get(i).ok_or_else(missing_rparen)?;
+        if param.is_punctuator("...") {
+            params.push("__VA_ARGS__".to_string());
+            if !tokens
+                .get(i + 1)
+                .is_some_and(|token| token.is_punctuator(")"))
+            {
+                return Err(Error::new(file, line, "missing `)` after `...`"));
+            }
+            return Ok((params, true, &tokens[i + 2..]));
+        }
+        if param.kind != TokenKind::Identifier || param.text == "__VA_ARGS__" {
+            return Err(Error::new(
+                file,
+                line,
+                format!("expected parameter name, found `{}`", param.text),
+            ));
+        }
+        if params.contains(&param.text) {
+            return Err(Error::new(
+                file,
+                line,
+                format!("duplicate macro parameter `{}`", param.text),
+            ));
+        }
+        params.push(param.text.clone());
+
+        let separator = tokens.get(i + 1).ok_or_else(missing_rparen)?;
+        if separator.is_punctuator(")") {
+            return Ok((params, false, &tokens[i + 2..]));
+        }
+        if !separator.is_punctuator(",") {
+            return Err(Error::new(
+                file,
+                line,
+                format!("expected `,` or `)`, found `{}`", separator.text),
+            ));
+        }
+        i += 2;
+    }
+}
+
+/// Returns the macro name given to a directive, e.g., `N` of `#ifdef N`.
+fn macro_name<'t>(args: &'t [Token], file: &str, line: usize) -> Result<&'t str, Error> {
+    match args.first() {
+        Some(token) if token.kind == TokenKind::Identifier => Ok(&token.text),
+        _ => Err(Error::new(file, line, "macro names must be identifiers")),
+    }
+}
+
+/// Pastes `lhs` and `rhs` into a token for `##`.
+fn paste(lhs: &Token, rhs: &Token, file: &str, line: usize) -> Result<Token, Error> {
+    let text = format!("{}{}", lhs.text, rhs.text);
+    let invalid = || {
+        Error::new(
+            file,
+            line,
+            format!(
+                "pasting `{}` and `{}` does not give a valid preprocessing token",
+                lhs.text, rhs.text
+            ),
+        )
+    };
+
+    let mut tokens = lex(&text).map_err(|_| invalid())?;
+    let mut token = tokens.pop().ok_or_else(invalid)?;
+    if !tokens.is_empty() {
+        return Err(invalid());
+    }
+
+    token.line = lhs.line;
+    token.has_space = lhs.has_space;
+    token.is_line_start = false;
+    token.hideset = lhs.hideset.clone();
+    Ok(token)
+}
+
+/// Spells `tokens` as a string literal for `#`.
+fn stringify(tokens: &[Token]) -> String {
+    let mut string = String::from("\"");
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && token.has_space {
+            string.push(' ');
+        }
+        if matches!(token.kind, TokenKind::String | TokenKind::Character) {
+            string.push_str(&escape(&token.text));
+        } else {
+            string.push_str(&token.text);
+        }
+    }
+    string.push('"');
+    string
+}
+
+/// Spells `tokens`, separating them by a space where they are separated by whitespace.
+fn spell(tokens: &[Token]) -> String {
+    let mut spelling = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && token.has_space {
+            spelling.push(' ');
+        }
+        spelling.push_str(&token.text);
+    }
+    spelling
+}
+
+/// Escapes `"` and `\` in `text` to put it in a string literal.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Returns whether two tokens ending and starting with `last` and `first` would be lexed
+/// differently if they were put together.
+fn would_paste(last: char, first: char) -> bool {
+    const PUNCTUATION: &str = "+-*/%<>=!&|^#.:";
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.';
+
+    (is_word(last) && (is_word(first) || first == '"' || first == '\''))
+        || (PUNCTUATION.contains(last) && PUNCTUATION.contains(first))
+}
+
+/// Shifts `line` by `delta` lines, which is set by `#line`.
+fn shift_line(line: usize, delta: isize) -> usize {
+    line.saturating_add_signed(delta)
+}
+
+/// Returns the value of the integer constant `text`, e.g., `0x10UL`.
+///
+/// The constant is unsigned if it has the suffix `u` or `U`, or it is too large for `intmax_t`,
+/// which GCC allows with a warning.
+fn integer_value(text: &str) -> Option<Integer> {
+    let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    let suffix = &text[digits.len()..];
+    let (digits, radix) = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        (hex, 16)
+    } else if let Some(binary) = digits
+        .strip_prefix("0b")
+        .or_else(|| digits.strip_prefix("0B"))
+    {
+        (binary, 2)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (&digits[1..], 8)
+    } else {
+        (digits, 10)
+    };
+
+    let bits = u64::from_str_radix(digits, radix).ok()?;
+    Some(Integer {
+        bits,
+        is_unsigned: suffix.contains(['u', 'U']) || bits > i64::MAX as u64,
+    })
+}
+
+/// Returns the value of the character constant `text`, e.g., `'\n'`.
+fn character_value(text: &str) -> Option<i64> {
+    let inner = text
+        .trim_start_matches(['u', 'U', 'L'])
+        .strip_prefix('\'')?
+        .strip_suffix('\'')?;
+    let bytes = ir::string_literal_bytes(&[format!("\"{inner}\"")]).ok()?;
+    match bytes.as_slice() {
+        // `char` is signed.
+        [byte] => Some(i64::from(*byte as i8)),
+        _ => None,
+    }
+}
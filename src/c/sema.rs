@@ -0,0 +1,2361 @@
+//! Semantic analysis of C files.
+//!
+//! [`Sema`] checks that a parsed C file is well-typed before it is translated to IR: it computes
+//! the type of every expression, and checks the lvalue and const-correctness rules, the implicit
+//! conversions, and the compatibility of redeclarations. All errors in the C file are reported at
+//! once, so that IR generation can assume its input is well-typed.
+//!
+//! A construct whose type cannot be determined, e.g., an expression using an undeclared
+//! identifier, is reported once and then ignored by the checks of the enclosing constructs.
+
+use core::ops::Deref;
+use std::collections::{HashMap, HashSet};
+
+use lang_c::ast::*;
+use lang_c::span::{Node, Span};
+
+use crate::c::ast_utils::{
+    VA_BUILTINS, complete_array_size, compound_literal_declaration, function_derived_declarator,
+    name_of_declarator, storage_class_of_specifiers,
+};
+use crate::c::const_eval::{ConstEval, ConstEvalError, to_literal};
+use crate::c::diagnostics::Diagnostics;
+use crate::c::write_c::binary_operator;
+use crate::ir::{self, HasDtype};
+use crate::lint::{Lint, Lints};
+
+/// Checks the semantics of a C file.
+#[derive(Debug)]
+pub(crate) struct Sema {
+    /// Ordinary identifiers and struct tags declared in each scope, the file scope first.
+    scopes: Vec<Scope>,
+    /// Struct types declared in the C file. The structs defined in functions have unique names,
+    /// e.g., `A.0` for `A`.
+    structs: HashMap<String, Option<ir::Dtype>>,
+    /// Counter for anonymous structs.
+    struct_tempid_counter: usize,
+    /// Counter for the unique names of the structs defined in functions.
+    local_struct_counter: usize,
+    /// Functions declared only without a prototype at file scope, e.g., `f` of `int f();`.
+    unprototyped_functions: HashSet<String>,
+    /// The function being checked, if any.
+    function: Option<FunctionContext>,
+    diags: Diagnostics,
+}
+
+/// Names declared in a scope.
+#[derive(Debug, Default)]
+struct Scope {
+    /// Variables, functions, enumerators and typedef names.
+    symbols: HashMap<String, Symbol>,
+    /// Struct tags with their unique names in `structs`.
+    structs: HashMap<String, String>,
+    /// Variables and parameters without linkage, with their declarators and the lints reporting
+    /// them if they are never used.
+    locals: Vec<(String, Span, Lint)>,
+    /// Variables and parameters without linkage that are used.
+    used: HashSet<String>,
+}
+
+/// An ordinary identifier.
+#[derive(Debug, Clone)]
+enum Symbol {
+    /// A variable or a function.
+    Declaration {
+        decl: ir::Declaration,
+        /// Whether it has linkage, i.e., it is declared at file scope, or is a function or an
+        /// `extern` variable declared at block scope. Only such ones can be redeclared.
+        has_linkage: bool,
+        /// Whether the variable is initialized or the function has a body.
+        is_defined: bool,
+    },
+    Enumerator(ir::Constant),
+    Typedef(ir::Dtype),
+}
+
+/// A function being checked.
+#[derive(Debug)]
+struct FunctionContext {
+    return_type: ir::Dtype,
+    is_variadic: bool,
+    /// Labels defined in the function.
+    labels: HashSet<String>,
+    /// The number of loops enclosing the current statement.
+    loops: usize,
+    /// The `switch` statements enclosing the current statement, the innermost last.
+    switches: Vec<SwitchContext>,
+}
+
+/// A `switch` statement being checked.
+#[derive(Debug, Default)]
+struct SwitchContext {
+    /// The values of its `case` labels.
+    cases: HashSet<u128>,
+    has_default: bool,
+}
+
+/// The type of an expression.
+#[derive(Debug, Clone)]
+struct Typed {
+    dtype: ir::Dtype,
+    /// Whether the expression designates an object, i.e., it is an lvalue.
+    is_lvalue: bool,
+    /// Whether the expression designates a bit-field, whose address cannot be taken.
+    is_bit_field: bool,
+}
+
+impl Typed {
+    fn rvalue(dtype: ir::Dtype) -> Self {
+        Self {
+            dtype,
+            is_lvalue: false,
+            is_bit_field: false,
+        }
+    }
+
+    fn lvalue(dtype: ir::Dtype) -> Self {
+        Self {
+            dtype,
+            is_lvalue: true,
+            is_bit_field: false,
+        }
+    }
+
+    /// Returns the type of the value of the expression after the array-to-pointer and the
+    /// function-to-pointer conversions, without qualifiers.
+    fn value_dtype(&self) -> ir::Dtype {
+        match &self.dtype {
+            ir::Dtype::Array { inner, .. } => ir::Dtype::pointer(inner.deref().clone()),
+            ir::Dtype::Function { .. } => ir::Dtype::pointer(self.dtype.clone()),
+            dtype => dtype.clone().set_const(false),
+        }
+    }
+}
+
+/// An implicit conversion as if by assignment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conversion {
+    Assignment,
+    Initialization,
+    Argument,
+    Return,
+}
+
+impl Conversion {
+    /// Describes the conversion of a value of `from` to `to`, e.g., "assigning to `int *` from
+    /// `long`".
+    fn describe(self, to: &ir::Dtype, from: &ir::Dtype) -> String {
+        let to = type_name(to);
+        let from = type_name(from);
+        match self {
+            Self::Assignment => format!("assigning to `{to}` from `{from}`"),
+            Self::Initialization => format!("initializing `{to}` with an expression of `{from}`"),
+            Self::Argument => format!("passing `{from}` to parameter of type `{to}`"),
+            Self::Return => format!("returning `{from}` from a function with result type `{to}`"),
+        }
+    }
+}
+
+impl Sema {
+    /// Checks the C file `unit`, and returns the errors and the warnings of `lints` found.
+    pub(crate) fn check(unit: &TranslationUnit, lints: &Lints) -> Diagnostics {
+        let mut sema = Self {
+            scopes: vec![Scope::default()],
+            structs: HashMap::new(),
+            struct_tempid_counter: 0,
+            local_struct_counter: 0,
+            unprototyped_functions: HashSet::new(),
+            function: None,
+            diags: Diagnostics::new(lints.clone()),
+        };
+
+        for ext_decl in &unit.0 {
+            sema.diags.enter(ext_decl.span);
+            match &ext_decl.node {
+                ExternalDeclaration::Declaration(decl) => sema.declaration(decl),
+                ExternalDeclaration::StaticAssert(static_assert) => {
+                    sema.static_assert(static_assert)
+                }
+                ExternalDeclaration::FunctionDefinition(func) => sema.function_definition(func),
+            }
+            sema.diags.exit();
+        }
+
+        sema.diags
+    }
+
+    fn error<S: Into<String>>(&mut self, message: S) {
+        self.diags.error(message);
+    }
+
+    fn is_file_scope(&self) -> bool {
+        self.scopes.len() == 1
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    /// Exits the current scope, reporting the variables and the parameters declared in it that
+    /// are never used.
+    fn exit_scope(&mut self) {
+        let scope = self.scopes.pop().expect("no scope");
+        debug_assert!(!self.scopes.is_empty());
+        for (name, span, lint) in scope.locals {
+            if scope.used.contains(&name) {
+                continue;
+            }
+            let kind = match lint {
+                Lint::UnusedParameter => "parameter",
+                _ => "variable",
+            };
+            self.diags.enter(span);
+            self.diags.warn(lint, format!("unused {kind} `{name}`"));
+            self.diags.exit();
+        }
+    }
+
+    /// Marks the variable `name` in the innermost scope declaring it as used.
+    fn mark_used(&mut self, name: &str) {
+        if let Some(scope) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.symbols.contains_key(name))
+        {
+            let _unused = scope.used.insert(name.to_string());
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.symbols.get(name))
+    }
+
+    /// Returns the typedefs visible in the current scope, where the inner ones shadow the outer
+    /// ones.
+    fn visible_typedefs(&self) -> HashMap<String, ir::Dtype> {
+        let mut typedefs = HashMap::new();
+        for scope in &self.scopes {
+            for (name, symbol) in &scope.symbols {
+                match symbol {
+                    Symbol::Typedef(dtype) => {
+                        let _unused = typedefs.insert(name.clone(), dtype.clone());
+                    }
+                    _ => {
+                        let _unused = typedefs.remove(name);
+                    }
+                }
+            }
+        }
+        typedefs
+    }
+
+    /// Evaluates the constant expression `expr`, in which the visible enumerators can be used.
+    fn const_eval(&self, expr: &Expression) -> Result<ir::Constant, ConstEvalError> {
+        let lookup = |name: &str| match self.lookup(name) {
+            Some(Symbol::Enumerator(value)) => Some(value.clone()),
+            _ => None,
+        };
+        let typedefs = self.visible_typedefs();
+        ConstEval::new(&lookup, &typedefs, &self.structs).eval(expr)
+    }
+
+    /// Evaluates the integer constant expression `expr`, e.g., the value of a `case` label.
+    ///
+    /// Reports an error and returns `None` if `expr` is not an integer constant expression.
+    fn integer_constant(&mut self, expr: &Node<Expression>, what: &str) -> Option<ir::Constant> {
+        self.diags.enter(expr.span);
+        let constant = match self.const_eval(&expr.node) {
+            Ok(constant) if constant.is_integer_constant() => Some(constant),
+            _ => {
+                self.error(format!("{what} is not an integer constant expression"));
+                None
+            }
+        };
+        self.diags.exit();
+        constant
+    }
+
+    /// Declares `name` as `symbol` in the current scope.
+    ///
+    /// A variable or a function with linkage can be declared again with a compatible type, and a
+    /// typedef name can be declared again with the same type.
+    fn declare(&mut self, name: &str, symbol: Symbol) {
+        let is_file_scope = self.is_file_scope();
+        let scope = self.scopes.last_mut().expect("no scope");
+        let Some(old_symbol) = scope.symbols.get_mut(name) else {
+            let _unused = scope.symbols.insert(name.to_string(), symbol);
+            return;
+        };
+
+        let error = match (old_symbol, symbol) {
+            (Symbol::Typedef(old_dtype), Symbol::Typedef(dtype)) => (*old_dtype != dtype)
+                .then(|| format!("typedef redefinition with different types for `{name}`")),
+            (
+                Symbol::Declaration {
+                    decl: old_decl,
+                    has_linkage: true,
+                    is_defined: old_is_defined,
+                },
+                Symbol::Declaration {
+                    decl,
+                    has_linkage: true,
+                    is_defined,
+                },
+            ) => {
+                let is_unprototyped = is_file_scope && self.unprototyped_functions.contains(name);
+                let is_compatible = old_decl.is_compatible(&decl)
+                    || match (old_decl.get_function(), decl.get_function()) {
+                        (Some((old_signature, _)), Some((signature, _))) => {
+                            (is_unprototyped && old_signature.is_unprototyped_of(signature))
+                                || signature.is_unprototyped_of(old_signature)
+                        }
+                        _ => false,
+                    };
+                if !is_compatible {
+                    Some(format!("conflicting types for `{name}`"))
+                } else if *old_is_defined && is_defined {
+                    Some(format!("redefinition of `{name}`"))
+                } else {
+                    // A declaration without a prototype does not replace the prototype.
+                    let is_prototype = decl.get_function().is_some_and(|(signature, _)| {
+                        !signature.params.is_empty() || signature.is_variadic
+                    });
+                    if is_prototype || (is_unprototyped && is_defined) {
+                        *old_decl = decl;
+                        let _unused = self.unprototyped_functions.remove(name);
+                    }
+                    *old_is_defined |= is_defined;
+                    None
+                }
+            }
+            (Symbol::Declaration { .. }, Symbol::Declaration { .. })
+            | (Symbol::Enumerator(_), Symbol::Enumerator(_)) => {
+                Some(format!("redefinition of `{name}`"))
+            }
+            _ => Some(format!(
+                "redefinition of `{name}` as a different kind of symbol"
+            )),
+        };
+        if let Some(error) = error {
+            self.error(error);
+        }
+    }
+
+    fn static_assert(&mut self, static_assert: &Node<StaticAssert>) {
+        self.diags.enter(static_assert.span);
+        let result = self
+            .const_eval(&static_assert.node.expression.node)
+            .and_then(|condition| check_static_assert(&static_assert.node, &condition));
+        if let Err(e) = result {
+            self.error(e.to_string());
+        }
+        self.diags.exit();
+    }
+
+    /// Translates the base dtype of the declaration specifiers `specifiers`, and whether they
+    /// declare typedef names. The enumerators defined in `specifiers`, including those in the
+    /// fields of a struct, are declared.
+    fn base_dtype(
+        &mut self,
+        specifiers: &[Node<DeclarationSpecifier>],
+    ) -> Option<(ir::Dtype, bool)> {
+        for spec in specifiers {
+            if let DeclarationSpecifier::TypeSpecifier(type_specifier) = &spec.node {
+                self.enumerators(&type_specifier.node);
+            }
+        }
+
+        let mut specifiers = specifiers.to_vec();
+        for spec in &mut specifiers {
+            if let DeclarationSpecifier::TypeSpecifier(type_specifier) = &mut spec.node {
+                if !self.fold_type_specifier(&mut type_specifier.node) {
+                    return None;
+                }
+            }
+        }
+
+        let (dtype, is_typedef) = match ir::Dtype::try_from_ast_declaration_specifiers(&specifiers)
+        {
+            Ok(result) => result,
+            Err(e) => {
+                self.error(e.to_string());
+                return None;
+            }
+        };
+        let dtype = self.resolve_dtype(dtype)?;
+        Some((dtype, is_typedef))
+    }
+
+    /// Resolves the struct tags and the typedef names in `dtype` visible in the current scope. A
+    /// struct type defined in `dtype` is added to `structs`, with a unique name if it is defined in
+    /// a function.
+    fn resolve_dtype(&mut self, dtype: ir::Dtype) -> Option<ir::Dtype> {
+        let is_file_scope = self.is_file_scope();
+        let scopes = &mut self.scopes;
+        let local_struct_counter = &mut self.local_struct_counter;
+        let dtype = dtype.rename_structs(&mut |tag, is_definition| {
+            if is_definition {
+                let name = if is_file_scope {
+                    tag.to_string()
+                } else {
+                    let name = format!("{tag}.{local_struct_counter}");
+                    *local_struct_counter += 1;
+                    name
+                };
+                let _unused = scopes
+                    .last_mut()
+                    .expect("no scope")
+                    .structs
+                    .insert(tag.to_string(), name.clone());
+                return name;
+            }
+
+            scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.structs.get(tag))
+                .cloned()
+                .unwrap_or_else(|| tag.to_string())
+        });
+
+        let result = dtype
+            .resolve_typedefs(&self.visible_typedefs())
+            .and_then(|dtype| match dtype.get_struct_fields() {
+                Some(Some(_)) => {
+                    dtype.resolve_structs(&mut self.structs, &mut self.struct_tempid_counter)
+                }
+                Some(None) => {
+                    let name = dtype
+                        .get_struct_name()
+                        .and_then(Option::as_ref)
+                        .expect("struct without fields must be named");
+                    let _unused = self.structs.entry(name.clone()).or_insert(None);
+                    Ok(dtype)
+                }
+                None => Ok(dtype),
+            });
+        match result {
+            Ok(dtype) => Some(dtype),
+            Err(e) => {
+                self.error(e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Translates the dtype declared by `declarator` whose base dtype is `base_dtype`. The size of
+    /// an array of unknown size is given by `initializer`.
+    fn declarator_dtype(
+        &mut self,
+        base_dtype: &ir::Dtype,
+        declarator: &Declarator,
+        initializer: Option<&Node<Initializer>>,
+    ) -> Option<ir::Dtype> {
+        let mut declarator = match complete_array_size(declarator, initializer) {
+            Ok(declarator) => declarator,
+            Err(e) => {
+                self.error(e.to_string());
+                return None;
+            }
+        };
+        if !self.fold_declarator(&mut declarator, false) {
+            return None;
+        }
+
+        let result = base_dtype
+            .clone()
+            .with_ast_declarator(&declarator)
+            .and_then(|dtype| {
+                dtype
+                    .into_inner()
+                    .resolve_typedefs(&self.visible_typedefs())
+            });
+        match result {
+            Ok(dtype) => Some(dtype),
+            Err(e) => {
+                self.error(e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Translates the dtype of the type name `type_name`, e.g., of a cast.
+    fn type_name_dtype(&mut self, type_name: &Node<TypeName>) -> Option<ir::Dtype> {
+        self.diags.enter(type_name.span);
+        let dtype = self.type_name_dtype_inner(&type_name.node);
+        self.diags.exit();
+        dtype
+    }
+
+    fn type_name_dtype_inner(&mut self, type_name: &TypeName) -> Option<ir::Dtype> {
+        let mut type_name = type_name.clone();
+        for spec in &mut type_name.specifiers {
+            if let SpecifierQualifier::TypeSpecifier(type_specifier) = &mut spec.node {
+                if !self.fold_type_specifier(&mut type_specifier.node) {
+                    return None;
+                }
+            }
+        }
+        if let Some(declarator) = &mut type_name.declarator {
+            if !self.fold_declarator(&mut declarator.node, false) {
+                return None;
+            }
+        }
+
+        match ir::Dtype::try_from(&type_name) {
+            Ok(dtype) => self.resolve_dtype(dtype),
+            Err(e) => {
+                self.error(e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Folds the sizes of the arrays in the struct types defined by `type_specifier` to integer
+    /// literals, so that their dtypes can be derived.
+    ///
+    /// Returns `false` if an array size is not an integer constant expression, which is reported.
+    fn fold_type_specifier(&mut self, type_specifier: &mut TypeSpecifier) -> bool {
+        let TypeSpecifier::Struct(struct_type) = type_specifier else {
+            return true;
+        };
+        let Some(declarations) = &mut struct_type.node.declarations else {
+            return true;
+        };
+
+        for decl in declarations {
+            let StructDeclaration::Field(field) = &mut decl.node else {
+                continue;
+            };
+            for spec in &mut field.node.specifiers {
+                if let SpecifierQualifier::TypeSpecifier(type_specifier) = &mut spec.node {
+                    if !self.fold_type_specifier(&mut type_specifier.node) {
+                        return false;
+                    }
+                }
+            }
+            for struct_declarator in &mut field.node.declarators {
+                if let Some(declarator) = &mut struct_declarator.node.declarator {
+                    if !self.fold_declarator(&mut declarator.node, false) {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Folds the sizes of the arrays declared by `declarator` to integer literals, e.g., `a[N + 1]`
+    /// to `a[3]`, so that its dtype can be derived. The unknown size of an array parameter is
+    /// given a dummy value, since the parameter is adjusted to a pointer.
+    ///
+    /// Returns `false` if an array size is not an integer constant expression, which is reported.
+    fn fold_declarator(&mut self, declarator: &mut Declarator, is_parameter: bool) -> bool {
+        let is_adjusted =
+            is_parameter && !matches!(declarator.kind.node, DeclaratorKind::Declarator(_));
+        let mut is_outermost = true;
+        for derived in &mut declarator.derived {
+            match &mut derived.node {
+                DerivedDeclarator::Array(array_decl) => {
+                    let size = &mut array_decl.node.size;
+                    match size {
+                        ArraySize::VariableExpression(expr) => {
+                            let Some(size_literal) = self.array_size(expr) else {
+                                return false;
+                            };
+                            *expr = Box::new(Node::new(size_literal, expr.span));
+                        }
+                        ArraySize::Unknown if is_adjusted && is_outermost => {
+                            let one = ir::Constant::int(1, ir::Dtype::INT);
+                            let literal = to_literal(&one).expect("integer must be representable");
+                            *size = ArraySize::VariableExpression(Box::new(Node::new(
+                                literal,
+                                Span::none(),
+                            )));
+                        }
+                        _ => (),
+                    }
+                    is_outermost = false;
+                }
+                DerivedDeclarator::Function(func_decl) => {
+                    for param in &mut func_decl.node.parameters {
+                        for spec in &mut param.node.specifiers {
+                            if let DeclarationSpecifier::TypeSpecifier(type_specifier) =
+                                &mut spec.node
+                            {
+                                if !self.fold_type_specifier(&mut type_specifier.node) {
+                                    return false;
+                                }
+                            }
+                        }
+                        if let Some(declarator) = &mut param.node.declarator {
+                            if !self.fold_declarator(&mut declarator.node, true) {
+                                return false;
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        match &mut declarator.kind.node {
+            DeclaratorKind::Declarator(declarator) => {
+                self.fold_declarator(&mut declarator.node, is_parameter)
+            }
+            _ => true,
+        }
+    }
+
+    /// Evaluates the array size `expr` to an integer literal.
+    fn array_size(&mut self, expr: &Node<Expression>) -> Option<Expression> {
+        let size = self.integer_constant(expr, "array size")?;
+        let (value, width, is_signed) = size.get_int().expect("size must be an integer");
+        if is_signed && (ir::sign_extension(value, width as u128) as i128) < 0 {
+            self.diags.enter(expr.span);
+            self.error("array has negative size");
+            self.diags.exit();
+            return None;
+        }
+        to_literal(&size)
+    }
+
+    /// Declares the enumerators defined in `type_specifier`.
+    fn enumerators(&mut self, type_specifier: &TypeSpecifier) {
+        let enum_type = match type_specifier {
+            TypeSpecifier::Enum(enum_type) => enum_type,
+            // The enumerators defined in the fields of a struct are declared in the scope of the
+            // struct, e.g., `A` of `struct { enum { A } e; } s;`.
+            TypeSpecifier::Struct(struct_type) => {
+                for field in struct_type.node.declarations.iter().flatten() {
+                    let StructDeclaration::Field(field) = &field.node else {
+                        continue;
+                    };
+                    for spec in &field.node.specifiers {
+                        if let SpecifierQualifier::TypeSpecifier(type_specifier) = &spec.node {
+                            self.enumerators(&type_specifier.node);
+                        }
+                    }
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        let mut value = ir::Constant::int(0, ir::Dtype::INT);
+        for enumerator in &enum_type.node.enumerators {
+            self.diags.enter(enumerator.span);
+            if let Some(expr) = &enumerator.node.expression {
+                if let Some(constant) = self.integer_constant(expr, "enumerator value") {
+                    value = constant.typecast(ir::Dtype::INT);
+                }
+            }
+            self.declare(
+                &enumerator.node.identifier.node.name,
+                Symbol::Enumerator(value.clone()),
+            );
+            let (next, _, _) = value.get_int().expect("enumerator must be an integer");
+            value = ir::Constant::int(next.wrapping_add(1), ir::Dtype::INT);
+            self.diags.exit();
+        }
+    }
+
+    fn declaration(&mut self, decl: &Node<Declaration>) {
+        self.diags.enter(decl.span);
+        self.declaration_inner(&decl.node);
+        self.diags.exit();
+    }
+
+    fn declaration_inner(&mut self, decl: &Declaration) {
+        let Some((base_dtype, is_typedef)) = self.base_dtype(&decl.specifiers) else {
+            return;
+        };
+        let storage_class = storage_class_of_specifiers(&decl.specifiers);
+        let is_extern = storage_class == Some(&StorageClassSpecifier::Extern);
+
+        for init_decl in &decl.declarators {
+            self.diags.enter(init_decl.span);
+            let declarator = &init_decl.node.declarator.node;
+            let initializer = init_decl.node.initializer.as_ref();
+            let name = name_of_declarator(declarator);
+            let Some(dtype) = self.declarator_dtype(&base_dtype, declarator, initializer) else {
+                if let Some(initializer) = initializer {
+                    self.initializer_expressions(initializer);
+                }
+                self.diags.exit();
+                continue;
+            };
+
+            if is_typedef {
+                self.declare(&name, Symbol::Typedef(dtype));
+                self.diags.exit();
+                continue;
+            }
+
+            let decl = match ir::Declaration::try_from(dtype.clone()) {
+                Ok(decl) => decl,
+                Err(_) => {
+                    self.error(format!("variable `{name}` has incomplete type `void`"));
+                    self.diags.exit();
+                    continue;
+                }
+            };
+            let is_function = decl.get_function().is_some();
+            if !is_function && !is_extern && dtype.is_invalid_structure(&self.structs) {
+                self.error(format!(
+                    "variable `{name}` has incomplete type `{}`",
+                    type_name(&dtype)
+                ));
+            }
+            if is_function && self.is_file_scope() {
+                if let Some(DerivedDeclarator::KRFunction(identifiers)) =
+                    function_derived_declarator(declarator)
+                {
+                    if !identifiers.is_empty() {
+                        self.error("parameter names without types in function declaration");
+                    }
+                    if self.lookup(&name).is_none() {
+                        let _unused = self.unprototyped_functions.insert(name.clone());
+                    }
+                }
+            }
+
+            let has_linkage = self.is_file_scope() || is_function || is_extern;
+            self.declare(
+                &name,
+                Symbol::Declaration {
+                    decl,
+                    has_linkage,
+                    is_defined: initializer.is_some(),
+                },
+            );
+            if !has_linkage {
+                self.scopes.last_mut().expect("no scope").locals.push((
+                    name.clone(),
+                    init_decl.node.declarator.span,
+                    Lint::UnusedVariable,
+                ));
+            }
+
+            if let Some(initializer) = initializer {
+                if is_function {
+                    self.error("illegal initializer (only variables can be initialized)");
+                } else if is_extern && !self.is_file_scope() {
+                    self.error(format!(
+                        "declaration of block scope identifier `{name}` with linkage cannot have \
+                         an initializer"
+                    ));
+                }
+                self.initializer(&dtype, initializer);
+            }
+            self.diags.exit();
+        }
+    }
+
+    fn function_definition(&mut self, func: &Node<FunctionDefinition>) {
+        let func = &func.node;
+        let Some((base_dtype, _)) = self.base_dtype(&func.specifiers) else {
+            return;
+        };
+        self.diags.enter(func.declarator.span);
+        let dtype = self.declarator_dtype(&base_dtype, &func.declarator.node, None);
+        self.diags.exit();
+        let Some(dtype) = dtype else {
+            return;
+        };
+        let Some((return_type, params)) = dtype.get_function_inner() else {
+            self.error("function definition must have a function type");
+            return;
+        };
+        let return_type = return_type.clone();
+        let name = name_of_declarator(&func.declarator.node);
+
+        // The parameters of a K&R-style definition are declared in the declaration list, and the
+        // arguments are passed after the default argument promotions.
+        let (param_dtypes, signature) = match function_derived_declarator(&func.declarator.node) {
+            Some(DerivedDeclarator::KRFunction(identifiers)) => {
+                let param_dtypes = self.kr_parameters(identifiers, &func.declarations);
+                let promoted = param_dtypes
+                    .iter()
+                    .map(ir::Dtype::promote_argument)
+                    .collect();
+                (
+                    param_dtypes,
+                    ir::Dtype::function(return_type.clone(), promoted),
+                )
+            }
+            _ => (params.clone(), dtype.clone()),
+        };
+        let param_names = param_names(&func.declarator.node);
+
+        let decl =
+            ir::Declaration::try_from(signature).expect("function must have a function type");
+        self.declare(
+            &name,
+            Symbol::Declaration {
+                decl,
+                has_linkage: true,
+                is_defined: true,
+            },
+        );
+
+        let Statement::Compound(items) = &func.statement.node else {
+            panic!("the body of a function definition must be a compound statement")
+        };
+        let mut labels = HashSet::new();
+        self.labels(&func.statement, &mut labels);
+        self.function = Some(FunctionContext {
+            return_type,
+            is_variadic: dtype.is_variadic(),
+            labels,
+            loops: 0,
+            switches: Vec::new(),
+        });
+
+        // The parameters are in the same scope as the outermost block of the body.
+        self.enter_scope();
+        for (name, dtype) in param_names.into_iter().zip(param_dtypes) {
+            let Some((name, span)) = name else {
+                continue;
+            };
+            let decl = ir::Declaration::Variable {
+                dtype,
+                initializer: None,
+                linkage: ir::Linkage::default(),
+                is_definition: true,
+                alignment: None,
+            };
+            self.declare(
+                &name,
+                Symbol::Declaration {
+                    decl,
+                    has_linkage: false,
+                    is_defined: true,
+                },
+            );
+            self.scopes.last_mut().expect("no scope").locals.push((
+                name,
+                span,
+                Lint::UnusedParameter,
+            ));
+        }
+        self.diags.enter(func.statement.span);
+        self.block_items(items);
+        self.diags.exit();
+        self.exit_scope();
+        self.function = None;
+    }
+
+    /// Returns the declared types of the parameters `identifiers` of a K&R-style definition,
+    /// whose declaration list is `declarations`. A parameter without a declaration is `int`.
+    fn kr_parameters(
+        &mut self,
+        identifiers: &[Node<Identifier>],
+        declarations: &[Node<Declaration>],
+    ) -> Vec<ir::Dtype> {
+        let mut param_dtypes = HashMap::new();
+        for decl in declarations {
+            self.diags.enter(decl.span);
+            let Some((base_dtype, is_typedef)) = self.base_dtype(&decl.node.specifiers) else {
+                self.diags.exit();
+                continue;
+            };
+            if is_typedef || storage_class_of_specifiers(&decl.node.specifiers).is_some() {
+                self.error("invalid storage class for parameter");
+            }
+            for init_decl in &decl.node.declarators {
+                self.diags.enter(init_decl.span);
+                let declarator = &init_decl.node.declarator.node;
+                let name = name_of_declarator(declarator);
+                if init_decl.node.initializer.is_some() {
+                    self.error(format!("parameter `{name}` is initialized"));
+                }
+                if !identifiers
+                    .iter()
+                    .any(|identifier| identifier.node.name == name)
+                {
+                    self.error(format!(
+                        "declaration for `{name}`, which is not a parameter"
+                    ));
+                } else if let Some(dtype) = self.declarator_dtype(&base_dtype, declarator, None) {
+                    // An array parameter is adjusted to a pointer as in a prototype.
+                    let dtype = match dtype.get_array_inner() {
+                        Some(inner) => ir::Dtype::pointer(inner.clone()),
+                        None => dtype,
+                    };
+                    if param_dtypes.insert(name.clone(), dtype).is_some() {
+                        self.error(format!("redefinition of parameter `{name}`"));
+                    }
+                }
+                self.diags.exit();
+            }
+            self.diags.exit();
+        }
+
+        identifiers
+            .iter()
+            .map(|identifier| {
+                param_dtypes
+                    .remove(&identifier.node.name)
+                    .unwrap_or(ir::Dtype::INT)
+            })
+            .collect()
+    }
+
+    /// Collects the labels defined in `stmt` to `labels`, reporting the duplicate ones.
+    fn labels(&mut self, stmt: &Node<Statement>, labels: &mut HashSet<String>) {
+        self.diags.enter(stmt.span);
+        match &stmt.node {
+            Statement::Labeled(labeled) => {
+                if let Label::Identifier(identifier) = &labeled.node.label.node {
+                    if !labels.insert(identifier.node.name.clone()) {
+                        self.error(format!("redefinition of label `{}`", identifier.node.name));
+                    }
+                }
+                self.labels(&labeled.node.statement, labels);
+            }
+            Statement::Compound(items) => {
+                for item in items {
+                    if let BlockItem::Statement(stmt) = &item.node {
+                        self.labels(stmt, labels);
+                    }
+                }
+            }
+            Statement::If(if_stmt) => {
+                self.labels(&if_stmt.node.then_statement, labels);
+                if let Some(else_stmt) = &if_stmt.node.else_statement {
+                    self.labels(else_stmt, labels);
+                }
+            }
+            Statement::Switch(switch) => self.labels(&switch.node.statement, labels),
+            Statement::While(while_stmt) => self.labels(&while_stmt.node.statement, labels),
+            Statement::DoWhile(do_while) => self.labels(&do_while.node.statement, labels),
+            Statement::For(for_stmt) => self.labels(&for_stmt.node.statement, labels),
+            _ => (),
+        }
+        self.diags.exit();
+    }
+
+    fn block_items(&mut self, items: &[Node<BlockItem>]) {
+        for item in items {
+            match &item.node {
+                BlockItem::Declaration(decl) => self.declaration(decl),
+                BlockItem::StaticAssert(static_assert) => self.static_assert(static_assert),
+                BlockItem::Statement(stmt) => self.statement(stmt),
+            }
+        }
+    }
+
+    fn function_context(&mut self) -> &mut FunctionContext {
+        self.function
+            .as_mut()
+            .expect("statements must be in a function")
+    }
+
+    fn statement(&mut self, stmt: &Node<Statement>) {
+        self.diags.enter(stmt.span);
+        match &stmt.node {
+            Statement::Labeled(labeled) => {
+                self.label(&labeled.node.label);
+                self.statement(&labeled.node.statement);
+            }
+            Statement::Compound(items) => {
+                self.enter_scope();
+                self.block_items(items);
+                self.exit_scope();
+            }
+            Statement::Expression(expr) => {
+                if let Some(expr) = expr {
+                    let _unused = self.expression(expr);
+                }
+            }
+            Statement::If(if_stmt) => {
+                self.condition(&if_stmt.node.condition);
+                self.statement(&if_stmt.node.then_statement);
+                if let Some(else_stmt) = &if_stmt.node.else_statement {
+                    self.statement(else_stmt);
+                }
+            }
+            Statement::Switch(switch) => {
+                if let Some(typed) = self.expression(&switch.node.expression) {
+                    let dtype = typed.value_dtype();
+                    if !matches!(dtype, ir::Dtype::Int { .. }) {
+                        self.diags.enter(switch.node.expression.span);
+                        self.error(format!(
+                            "statement requires expression of integer type (`{}` invalid)",
+                            type_name(&dtype)
+                        ));
+                        self.diags.exit();
+                    }
+                }
+                self.function_context()
+                    .switches
+                    .push(SwitchContext::default());
+                self.statement(&switch.node.statement);
+                let _unused = self.function_context().switches.pop();
+            }
+            Statement::While(while_stmt) => {
+                self.condition(&while_stmt.node.expression);
+                self.loop_body(&while_stmt.node.statement);
+            }
+            Statement::DoWhile(do_while) => {
+                self.loop_body(&do_while.node.statement);
+                self.condition(&do_while.node.expression);
+            }
+            Statement::For(for_stmt) => {
+                let for_stmt = &for_stmt.node;
+                self.enter_scope();
+                match &for_stmt.initializer.node {
+                    ForInitializer::Empty => (),
+                    ForInitializer::Expression(expr) => {
+                        let _unused = self.expression(expr);
+                    }
+                    ForInitializer::Declaration(decl) => self.declaration(decl),
+                    ForInitializer::StaticAssert(static_assert) => {
+                        self.static_assert(static_assert)
+                    }
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    self.condition(condition);
+                }
+                if let Some(step) = &for_stmt.step {
+                    let _unused = self.expression(step);
+                }
+                self.loop_body(&for_stmt.statement);
+                self.exit_scope();
+            }
+            Statement::Goto(label) => {
+                if !self.function_context().labels.contains(&label.node.name) {
+                    self.error(format!("use of undeclared label `{}`", label.node.name));
+                }
+            }
+            Statement::Continue => {
+                if self.function_context().loops == 0 {
+                    self.error("`continue` statement not in a loop statement");
+                }
+            }
+            Statement::Break => {
+                let context = self.function_context();
+                if context.loops == 0 && context.switches.is_empty() {
+                    self.error("`break` statement not in a loop or switch statement");
+                }
+            }
+            Statement::Return(expr) => self.return_statement(expr.as_deref()),
+            Statement::Asm(_) => (),
+        }
+        self.diags.exit();
+    }
+
+    fn loop_body(&mut self, stmt: &Node<Statement>) {
+        self.function_context().loops += 1;
+        self.statement(stmt);
+        self.function_context().loops -= 1;
+    }
+
+    fn label(&mut self, label: &Node<Label>) {
+        self.diags.enter(label.span);
+        match &label.node {
+            Label::Identifier(_) => (),
+            Label::Case(expr) => {
+                let value = self.integer_constant(expr, "case value");
+                match self.function_context().switches.last_mut() {
+                    Some(switch) => {
+                        if let Some(value) = value {
+                            let (value, _, _) = value.get_int().expect("case must be an integer");
+                            if !switch.cases.insert(value) {
+                                self.error("duplicate case value");
+                            }
+                        }
+                    }
+                    None => self.error("`case` label not in a switch statement"),
+                }
+            }
+            Label::CaseRange(_) => (),
+            Label::Default => match self.function_context().switches.last_mut() {
+                Some(switch) => {
+                    if core::mem::replace(&mut switch.has_default, true) {
+                        self.error("multiple default labels in one switch");
+                    }
+                }
+                None => self.error("`default` label not in a switch statement"),
+            },
+        }
+        self.diags.exit();
+    }
+
+    fn return_statement(&mut self, expr: Option<&Node<Expression>>) {
+        let return_type = self.function_context().return_type.clone();
+        let is_void = matches!(return_type, ir::Dtype::Unit { .. });
+        let Some(expr) = expr else {
+            if !is_void {
+                self.error("non-void function should return a value");
+            }
+            return;
+        };
+
+        let Some(typed) = self.expression(expr) else {
+            return;
+        };
+        if is_void {
+            if !matches!(typed.dtype, ir::Dtype::Unit { .. }) {
+                self.error("void function should not return a value");
+            }
+            return;
+        }
+        self.convert(&return_type, &typed, expr, Conversion::Return);
+    }
+
+    /// Checks the condition `expr` of a statement, which must be of a scalar type.
+    fn condition(&mut self, expr: &Node<Expression>) {
+        let Some(typed) = self.expression(expr) else {
+            return;
+        };
+        let dtype = typed.value_dtype();
+        if !is_scalar(&dtype) {
+            self.diags.enter(expr.span);
+            self.error(format!(
+                "statement requires expression of scalar type (`{}` invalid)",
+                type_name(&dtype)
+            ));
+            self.diags.exit();
+        }
+    }
+
+    /// Checks the initializer `initializer` of an object of `dtype`.
+    fn initializer(&mut self, dtype: &ir::Dtype, initializer: &Node<Initializer>) {
+        self.diags.enter(initializer.span);
+        match &initializer.node {
+            Initializer::Expression(expr) => {
+                let is_string = matches!(expr.node, Expression::StringLiteral(_));
+                match dtype {
+                    ir::Dtype::Array { inner, .. } => {
+                        let _unused = self.expression(expr);
+                        if !is_string || !matches!(inner.deref(), ir::Dtype::Int { width: 8, .. }) {
+                            self.error(
+                                "array initializer must be an initializer list or string literal",
+                            );
+                        }
+                    }
+                    _ => {
+                        if let Some(typed) = self.expression(expr) {
+                            self.convert(dtype, &typed, expr, Conversion::Initialization);
+                        }
+                    }
+                }
+            }
+            Initializer::List(items) => self.initializer_list(dtype, items),
+        }
+        self.diags.exit();
+    }
+
+    fn initializer_list(&mut self, dtype: &ir::Dtype, items: &[Node<InitializerListItem>]) {
+        // The subobjects initialized in order, i.e., the elements of an array, the fields of a
+        // struct, or the first field of a union.
+        let subobjects = match dtype {
+            ir::Dtype::Array { inner, size } => vec![inner.deref().clone(); *size],
+            ir::Dtype::Struct { name, .. } => {
+                match self
+                    .structs
+                    .get(name.as_ref().expect("struct must be named"))
+                {
+                    Some(Some(struct_type)) => {
+                        let fields = struct_type
+                            .get_struct_fields()
+                            .expect("struct must have fields")
+                            .as_ref()
+                            .expect("struct must have fields");
+                        let count = if struct_type.is_union() {
+                            1
+                        } else {
+                            fields.len()
+                        };
+                        fields
+                            .iter()
+                            .take(count)
+                            .map(|field| field.deref().clone())
+                            .collect()
+                    }
+                    _ => {
+                        self.error(format!(
+                            "variable has incomplete type `{}`",
+                            type_name(dtype)
+                        ));
+                        Vec::new()
+                    }
+                }
+            }
+            // A scalar can be initialized by a braced expression, e.g., `int x = {1};`.
+            _ => vec![dtype.clone()],
+        };
+
+        let mut position = 0;
+        for item in items {
+            self.diags.enter(item.span);
+            let target = if item.node.designation.is_empty() {
+                let target = subobjects.get(position).cloned();
+                position += 1;
+                if target.is_none() {
+                    self.error(if is_scalar(dtype) {
+                        "excess elements in scalar initializer"
+                    } else {
+                        "excess elements in initializer"
+                    });
+                }
+                target
+            } else {
+                match self.designated_dtype(dtype, &item.node.designation) {
+                    Some((index, target)) => {
+                        position = index + 1;
+                        Some(target)
+                    }
+                    None => None,
+                }
+            };
+
+            match target {
+                // An aggregate whose braces are elided is initialized by the following items,
+                // which are not checked further.
+                Some(target)
+                    if is_aggregate(&target)
+                        && matches!(item.node.initializer.node, Initializer::Expression(_))
+                        && !self.is_aggregate_initializer(&target, &item.node.initializer) =>
+                {
+                    for item in items
+                        .iter()
+                        .skip_while(|other| !core::ptr::eq(*other, item))
+                    {
+                        self.initializer_expressions(&item.node.initializer);
+                    }
+                    self.diags.exit();
+                    return;
+                }
+                Some(target) => self.initializer(&target, &item.node.initializer),
+                None => self.initializer_expressions(&item.node.initializer),
+            }
+            self.diags.exit();
+        }
+    }
+
+    /// Checks if the expression `initializer` initializes the aggregate of `dtype` as a whole,
+    /// i.e., it is a string literal for a character array, or it is of the same struct type.
+    fn is_aggregate_initializer(
+        &mut self,
+        dtype: &ir::Dtype,
+        initializer: &Node<Initializer>,
+    ) -> bool {
+        let Initializer::Expression(expr) = &initializer.node else {
+            return true;
+        };
+        match dtype {
+            ir::Dtype::Array { inner, .. } => {
+                matches!(expr.node, Expression::StringLiteral(_))
+                    && matches!(inner.deref(), ir::Dtype::Int { width: 8, .. })
+            }
+            ir::Dtype::Struct { name, .. } => {
+                // The expression is checked again as the initializer.
+                let diags = core::mem::take(&mut self.diags);
+                let typed = self.expression(expr);
+                self.diags = diags;
+                typed.is_some_and(|typed| typed.value_dtype().get_struct_name() == Some(name))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the subobject of an object of `dtype` designated by `designation`, and the index
+    /// of the subobject designated by its first designator.
+    fn designated_dtype(
+        &mut self,
+        dtype: &ir::Dtype,
+        designation: &[Node<Designator>],
+    ) -> Option<(usize, ir::Dtype)> {
+        let mut first_index = None;
+        let mut dtype = dtype.clone();
+        for designator in designation {
+            self.diags.enter(designator.span);
+            let result = self.designated_subobject(&dtype, &designator.node);
+            self.diags.exit();
+            let (index, subobject) = result?;
+            let _unused = first_index.get_or_insert(index);
+            dtype = subobject;
+        }
+        Some((first_index?, dtype))
+    }
+
+    fn designated_subobject(
+        &mut self,
+        dtype: &ir::Dtype,
+        designator: &Designator,
+    ) -> Option<(usize, ir::Dtype)> {
+        match (designator, dtype) {
+            (Designator::Index(expr), ir::Dtype::Array { inner, size }) => {
+                let index = self.integer_constant(expr, "array designator")?;
+                let (index, _, _) = index.get_int().expect("index must be an integer");
+                if index >= *size as u128 {
+                    self.error("array designator index exceeds array bounds");
+                    return None;
+                }
+                Some((index as usize, inner.deref().clone()))
+            }
+            (Designator::Member(identifier), ir::Dtype::Struct { name, .. }) => {
+                let name = name.as_ref().expect("struct must be named");
+                let field = self
+                    .structs
+                    .get(name)
+                    .and_then(Option::as_ref)
+                    .and_then(|struct_type| struct_type.get_struct_fields())
+                    .and_then(Option::as_ref)
+                    .and_then(|fields| {
+                        // A field of an anonymous struct or union is designated as if it is a
+                        // field of the enclosing one.
+                        fields.iter().enumerate().find_map(|(index, field)| {
+                            if field.name() == Some(&identifier.node.name) {
+                                return Some((index, field.deref().clone()));
+                            }
+                            if field.name().is_some() {
+                                return None;
+                            }
+                            field
+                                .get_offset_bit_field_struct_field(
+                                    &identifier.node.name,
+                                    &self.structs,
+                                )
+                                .map(|(_, dtype, _)| (index, dtype))
+                        })
+                    });
+                if field.is_none() {
+                    self.error(format!(
+                        "field designator `{}` does not refer to any field in type `{}`",
+                        identifier.node.name,
+                        type_name(dtype)
+                    ));
+                }
+                field
+            }
+            (Designator::Index(_) | Designator::Range(_), _) => {
+                self.error(format!(
+                    "array designator cannot initialize non-array type `{}`",
+                    type_name(dtype)
+                ));
+                None
+            }
+            (Designator::Member(_), _) => {
+                self.error(format!(
+                    "field designator cannot initialize a non-struct, non-union type `{}`",
+                    type_name(dtype)
+                ));
+                None
+            }
+        }
+    }
+
+    /// Checks the expressions in `initializer` without their types known.
+    fn initializer_expressions(&mut self, initializer: &Node<Initializer>) {
+        match &initializer.node {
+            Initializer::Expression(expr) => {
+                let _unused = self.expression(expr);
+            }
+            Initializer::List(items) => {
+                for item in items {
+                    self.initializer_expressions(&item.node.initializer);
+                }
+            }
+        }
+    }
+
+    /// Checks the implicit conversion of the value of `expr` of `typed` to `dtype` as if by
+    /// assignment.
+    fn convert(
+        &mut self,
+        dtype: &ir::Dtype,
+        typed: &Typed,
+        expr: &Node<Expression>,
+        conversion: Conversion,
+    ) {
+        let to = dtype.clone().set_const(false);
+        let from = typed.value_dtype();
+        let is_compatible = match (&to, &from) {
+            _ if is_arithmetic(&to) && is_arithmetic(&from) => {
+                self.check_narrowing(&to, &from, expr);
+                true
+            }
+            (ir::Dtype::Int { width: 1, .. }, ir::Dtype::Pointer { .. }) => true,
+            (ir::Dtype::Struct { name, .. }, ir::Dtype::Struct { name: other, .. }) => {
+                name == other
+            }
+            (ir::Dtype::Pointer { inner, .. }, ir::Dtype::Pointer { inner: other, .. }) => {
+                let is_void = matches!(inner.deref(), ir::Dtype::Unit { .. })
+                    || matches!(other.deref(), ir::Dtype::Unit { .. });
+                if !is_void && !is_compatible(inner, other) {
+                    false
+                } else {
+                    let is_object = |dtype: &ir::Dtype| {
+                        !matches!(dtype, ir::Dtype::Function { .. } | ir::Dtype::Array { .. })
+                    };
+                    if is_object(inner) && is_object(other) && other.is_const() && !inner.is_const()
+                    {
+                        self.diags.enter(expr.span);
+                        self.error(format!(
+                            "{} discards the `const` qualifier",
+                            conversion.describe(&to, &from)
+                        ));
+                        self.diags.exit();
+                    }
+                    true
+                }
+            }
+            (ir::Dtype::Pointer { .. }, ir::Dtype::Int { .. }) => {
+                self.is_null_pointer_constant(&expr.node)
+            }
+            _ => false,
+        };
+
+        if !is_compatible {
+            self.diags.enter(expr.span);
+            self.error(format!(
+                "incompatible types {}",
+                conversion.describe(&to, &from)
+            ));
+            self.diags.exit();
+        }
+    }
+
+    /// Warns if the implicit conversion of the value of `expr` from `from` to `to` may change the
+    /// value, e.g., from `long` to `int`. The conversion of a constant representable in `to` does
+    /// not change it.
+    fn check_narrowing(&mut self, to: &ir::Dtype, from: &ir::Dtype, expr: &Node<Expression>) {
+        let is_narrowing = match (to, from) {
+            (ir::Dtype::Int { width: 1, .. }, _) => false,
+            (ir::Dtype::Int { width, .. }, ir::Dtype::Int { width: other, .. })
+            | (ir::Dtype::Float { width, .. }, ir::Dtype::Float { width: other, .. }) => {
+                width < other
+            }
+            (ir::Dtype::Int { .. }, ir::Dtype::Float { .. }) => true,
+            _ => false,
+        };
+        if !is_narrowing {
+            return;
+        }
+        if let Ok(constant) = self.const_eval(&expr.node) {
+            let dtype = constant.dtype();
+            if constant.clone().typecast(to.clone()).typecast(dtype) == constant {
+                return;
+            }
+        }
+
+        self.diags.enter(expr.span);
+        self.diags.warn(
+            Lint::Conversion,
+            format!(
+                "implicit conversion from `{}` to `{}` may change the value",
+                type_name(from),
+                type_name(to)
+            ),
+        );
+        self.diags.exit();
+    }
+
+    /// Warns if the comparison of the integers `lhs` and `rhs` of `lhs_dtype` and `rhs_dtype`
+    /// converts a signed one to unsigned. A non-negative constant is not changed by the
+    /// conversion.
+    fn check_sign_compare(
+        &mut self,
+        lhs: &Node<Expression>,
+        rhs: &Node<Expression>,
+        lhs_dtype: &ir::Dtype,
+        rhs_dtype: &ir::Dtype,
+    ) {
+        let is_signed = |dtype: &ir::Dtype| {
+            matches!(
+                integer_promotion(dtype),
+                ir::Dtype::Int {
+                    is_signed: true,
+                    ..
+                }
+            )
+        };
+        if is_signed(&usual_arithmetic_conversion(lhs_dtype, rhs_dtype)) {
+            return;
+        }
+        let is_changed = |expr: &Node<Expression>, dtype: &ir::Dtype| {
+            is_signed(dtype)
+                && !self
+                    .const_eval(&expr.node)
+                    .ok()
+                    .and_then(|constant| constant.get_int())
+                    .is_some_and(|(value, width, _)| {
+                        (ir::sign_extension(value, width as u128) as i128) >= 0
+                    })
+        };
+        if is_changed(lhs, lhs_dtype) || is_changed(rhs, rhs_dtype) {
+            self.diags.warn(
+                Lint::SignCompare,
+                format!(
+                    "comparison of integers of different signs: `{}` and `{}`",
+                    type_name(lhs_dtype),
+                    type_name(rhs_dtype)
+                ),
+            );
+        }
+    }
+
+    /// Checks if `expr` is a null pointer constant, i.e., an integer constant expression of value
+    /// 0, possibly cast to `void *`.
+    fn is_null_pointer_constant(&self, expr: &Expression) -> bool {
+        if let Expression::Cast(cast) = expr {
+            let is_void_pointer = ir::Dtype::try_from(&cast.node.type_name.node)
+                .ok()
+                .and_then(|dtype| dtype.get_pointer_inner().cloned())
+                .is_some_and(|inner| matches!(inner, ir::Dtype::Unit { .. }));
+            if is_void_pointer {
+                return self.is_null_pointer_constant(&cast.node.expression.node);
+            }
+        }
+
+        self.const_eval(expr)
+            .ok()
+            .and_then(|constant| constant.get_int())
+            .is_some_and(|(value, _, _)| value == 0)
+    }
+
+    fn expression(&mut self, expr: &Node<Expression>) -> Option<Typed> {
+        self.diags.enter(expr.span);
+        let typed = self.expression_inner(&expr.node);
+        self.diags.exit();
+        typed
+    }
+
+    fn expression_inner(&mut self, expr: &Expression) -> Option<Typed> {
+        match expr {
+            Expression::Identifier(identifier) => {
+                let name = &identifier.node.name;
+                match self.lookup(name) {
+                    Some(Symbol::Declaration { decl, .. }) => {
+                        let typed = match decl {
+                            ir::Declaration::Variable { dtype, .. } => Typed::lvalue(dtype.clone()),
+                            ir::Declaration::Function { signature, .. } => {
+                                Typed::rvalue(signature.dtype())
+                            }
+                        };
+                        self.mark_used(name);
+                        Some(typed)
+                    }
+                    Some(Symbol::Enumerator(_)) => Some(Typed::rvalue(ir::Dtype::INT)),
+                    Some(Symbol::Typedef(_)) => {
+                        self.error(format!(
+                            "unexpected type name `{name}`: expected expression"
+                        ));
+                        None
+                    }
+                    None => {
+                        self.error(format!("use of undeclared identifier `{name}`"));
+                        None
+                    }
+                }
+            }
+            Expression::Constant(constant) => match &constant.node {
+                Constant::Character(_) => Some(Typed::rvalue(ir::Dtype::INT)),
+                constant => match ir::Constant::try_from(constant) {
+                    Ok(constant) => Some(Typed::rvalue(constant.dtype())),
+                    Err(_) => {
+                        self.error("invalid constant");
+                        None
+                    }
+                },
+            },
+            Expression::StringLiteral(literal) => match ir::string_literal_bytes(&literal.node) {
+                Ok(bytes) => Some(Typed::lvalue(ir::Dtype::array(
+                    ir::Dtype::CHAR,
+                    bytes.len() + 1,
+                ))),
+                Err(_) => {
+                    self.error("invalid string literal");
+                    None
+                }
+            },
+            Expression::Member(member) => self.member(&member.node),
+            Expression::Call(call) => self.call(&call.node),
+            Expression::CompoundLiteral(literal) => {
+                // The size of an array of unknown size is given by the initializer list as in a
+                // declaration, e.g., `int .compound0[] = {1, 2};` for `(int[]){1, 2}`.
+                let decl = compound_literal_declaration(&literal.node, ".compound", literal.span);
+                let (base_dtype, _) = self.base_dtype(&decl.specifiers)?;
+                let init_decl = &decl.declarators[0].node;
+                let dtype = self.declarator_dtype(
+                    &base_dtype,
+                    &init_decl.declarator.node,
+                    init_decl.initializer.as_ref(),
+                )?;
+                self.initializer_list(&dtype, &literal.node.initializer_list);
+                Some(Typed::lvalue(dtype))
+            }
+            Expression::SizeOfTy(size_of) => {
+                let dtype = self.type_name_dtype(&size_of.node.0)?;
+                self.check_sized(&dtype, "sizeof")?;
+                Some(Typed::rvalue(size_t()))
+            }
+            Expression::SizeOfVal(size_of) => {
+                let typed = self.expression(&size_of.node.0)?;
+                if typed.is_bit_field {
+                    self.error("invalid application of `sizeof` to bit-field");
+                    return None;
+                }
+                self.check_sized(&typed.dtype, "sizeof")?;
+                Some(Typed::rvalue(size_t()))
+            }
+            Expression::AlignOf(align_of) => {
+                let dtype = self.type_name_dtype(&align_of.node.0)?;
+                self.check_sized(&dtype, "_Alignof")?;
+                Some(Typed::rvalue(size_t()))
+            }
+            Expression::UnaryOperator(unary) => self.unary(&unary.node),
+            Expression::Cast(cast) => {
+                let dtype = self.type_name_dtype(&cast.node.type_name);
+                let typed = self.expression(&cast.node.expression)?;
+                let dtype = dtype?;
+                if matches!(dtype, ir::Dtype::Unit { .. }) {
+                    return Some(Typed::rvalue(dtype));
+                }
+
+                let from = typed.value_dtype();
+                let is_valid = match (&dtype, &from) {
+                    (ir::Dtype::Float { .. }, ir::Dtype::Pointer { .. })
+                    | (ir::Dtype::Pointer { .. }, ir::Dtype::Float { .. }) => false,
+                    _ => is_scalar(&dtype) && is_scalar(&from),
+                };
+                if !is_valid {
+                    if !is_scalar(&dtype) {
+                        self.error(format!(
+                            "used type `{}` where arithmetic or pointer type is required",
+                            type_name(&dtype)
+                        ));
+                    } else {
+                        self.error(format!(
+                            "operand of type `{}` cannot be cast to `{}`",
+                            type_name(&from),
+                            type_name(&dtype)
+                        ));
+                    }
+                    return None;
+                }
+                Some(Typed::rvalue(dtype.set_const(false)))
+            }
+            Expression::BinaryOperator(binary) => self.binary(&binary.node),
+            Expression::Conditional(conditional) => self.conditional(&conditional.node),
+            Expression::Comma(exprs) => {
+                let mut typed = None;
+                for expr in exprs.iter() {
+                    typed = self.expression(expr);
+                }
+                typed.map(|typed| Typed::rvalue(typed.value_dtype()))
+            }
+            Expression::VaArg(va_arg) => {
+                let _unused = self.expression(&va_arg.node.va_list);
+                let dtype = self.type_name_dtype(&va_arg.node.type_name)?;
+                Some(Typed::rvalue(dtype))
+            }
+            Expression::OffsetOf(_) => Some(Typed::rvalue(size_t())),
+            Expression::GenericSelection(_) | Expression::Statement(_) => None,
+        }
+    }
+
+    /// Checks that the size of `dtype` is known for the operator `operator`, e.g., `sizeof`.
+    fn check_sized(&mut self, dtype: &ir::Dtype, operator: &str) -> Option<()> {
+        let is_incomplete =
+            matches!(dtype, ir::Dtype::Unit { .. }) || dtype.is_invalid_structure(&self.structs);
+        if is_incomplete {
+            self.error(format!(
+                "invalid application of `{operator}` to an incomplete type `{}`",
+                type_name(dtype)
+            ));
+            return None;
+        }
+        if matches!(dtype, ir::Dtype::Function { .. }) {
+            self.error(format!(
+                "invalid application of `{operator}` to a function type"
+            ));
+            return None;
+        }
+        Some(())
+    }
+
+    fn member(&mut self, member: &MemberExpression) -> Option<Typed> {
+        let typed = self.expression(&member.expression)?;
+        let (struct_dtype, is_lvalue) = match member.operator.node {
+            MemberOperator::Direct => (typed.dtype.clone(), typed.is_lvalue),
+            MemberOperator::Indirect => match typed.value_dtype() {
+                ir::Dtype::Pointer { inner, .. } => (*inner, true),
+                dtype => {
+                    self.error(format!(
+                        "member reference type `{}` is not a pointer",
+                        type_name(&dtype)
+                    ));
+                    return None;
+                }
+            },
+        };
+
+        if struct_dtype.get_struct_name().is_none() {
+            self.error(format!(
+                "member reference base type `{}` is not a struct or union",
+                type_name(&struct_dtype)
+            ));
+            return None;
+        }
+        if struct_dtype.is_invalid_structure(&self.structs) {
+            self.error(format!(
+                "member access into incomplete type `{}`",
+                type_name(&struct_dtype)
+            ));
+            return None;
+        }
+
+        let name = &member.identifier.node.name;
+        let Some((_, dtype, bit_field)) =
+            struct_dtype.get_offset_bit_field_struct_field(name, &self.structs)
+        else {
+            self.error(format!(
+                "no member named `{name}` in `{}`",
+                type_name(&struct_dtype)
+            ));
+            return None;
+        };
+        let dtype = if struct_dtype.is_const() && !matches!(dtype, ir::Dtype::Array { .. }) {
+            dtype.set_const(true)
+        } else {
+            dtype
+        };
+        Some(Typed {
+            dtype,
+            is_lvalue,
+            is_bit_field: bit_field.is_some(),
+        })
+    }
+
+    fn call(&mut self, call: &CallExpression) -> Option<Typed> {
+        // The builtins for variable arguments are not declared.
+        if let Expression::Identifier(identifier) = &call.callee.node {
+            let name = identifier.node.name.as_str();
+            if VA_BUILTINS.contains(&name) && self.lookup(name).is_none() {
+                for arg in &call.arguments {
+                    let _unused = self.expression(arg);
+                }
+                let is_variadic = self
+                    .function
+                    .as_ref()
+                    .is_some_and(|function| function.is_variadic);
+                if name == "__builtin_va_start" && !is_variadic {
+                    self.error("`va_start` used in function with fixed arguments");
+                }
+                return Some(Typed::rvalue(ir::Dtype::unit()));
+            }
+        }
+
+        let callee = self.expression(&call.callee);
+        let args = call
+            .arguments
+            .iter()
+            .map(|arg| self.expression(arg))
+            .collect::<Vec<_>>();
+        let callee = callee?.value_dtype();
+        let Some(function) = callee
+            .get_pointer_inner()
+            .filter(|inner| matches!(inner, ir::Dtype::Function { .. }))
+        else {
+            self.error(format!(
+                "called object type `{}` is not a function or function pointer",
+                type_name(&callee)
+            ));
+            return None;
+        };
+        let (ret, params) = function.get_function_inner().expect("must be a function");
+
+        // A function declared without a prototype takes any arguments.
+        let is_prototyped = !params.is_empty() || function.is_variadic();
+        if is_prototyped {
+            if args.len() < params.len() {
+                self.error(format!(
+                    "too few arguments to function call, expected {}, have {}",
+                    params.len(),
+                    args.len()
+                ));
+            } else if args.len() > params.len() && !function.is_variadic() {
+                self.error(format!(
+                    "too many arguments to function call, expected {}, have {}",
+                    params.len(),
+                    args.len()
+                ));
+            }
+            for ((param, arg), typed) in params.iter().zip(&call.arguments).zip(&args) {
+                if let Some(typed) = typed {
+                    self.convert(param, typed, arg, Conversion::Argument);
+                }
+            }
+        }
+
+        Some(Typed::rvalue(ret.clone().set_const(false)))
+    }
+
+    fn unary(&mut self, unary: &UnaryOperatorExpression) -> Option<Typed> {
+        let operator = &unary.operator.node;
+        let typed = self.expression(&unary.operand)?;
+        let dtype = typed.value_dtype();
+        match operator {
+            UnaryOperator::PostIncrement
+            | UnaryOperator::PostDecrement
+            | UnaryOperator::PreIncrement
+            | UnaryOperator::PreDecrement => {
+                if !is_arithmetic(&dtype) && !matches!(dtype, ir::Dtype::Pointer { .. }) {
+                    self.error(format!(
+                        "cannot increment or decrement value of type `{}`",
+                        type_name(&dtype)
+                    ));
+                    return None;
+                }
+                self.check_modifiable(&typed);
+                Some(Typed::rvalue(dtype))
+            }
+            UnaryOperator::Address => {
+                if typed.is_bit_field {
+                    self.error("address of bit-field requested");
+                    return None;
+                }
+                if !typed.is_lvalue && !matches!(typed.dtype, ir::Dtype::Function { .. }) {
+                    self.error(format!(
+                        "cannot take the address of an rvalue of type `{}`",
+                        type_name(&typed.dtype)
+                    ));
+                    return None;
+                }
+                Some(Typed::rvalue(ir::Dtype::pointer(typed.dtype)))
+            }
+            UnaryOperator::Indirection => match dtype {
+                ir::Dtype::Pointer { inner, .. } => {
+                    let is_function = matches!(inner.deref(), ir::Dtype::Function { .. });
+                    Some(Typed {
+                        dtype: *inner,
+                        is_lvalue: !is_function,
+                        is_bit_field: false,
+                    })
+                }
+                _ => {
+                    self.error(format!(
+                        "indirection requires pointer operand (`{}` invalid)",
+                        type_name(&dtype)
+                    ));
+                    None
+                }
+            },
+            UnaryOperator::Plus | UnaryOperator::Minus | UnaryOperator::Complement => {
+                let is_valid = match operator {
+                    UnaryOperator::Complement => matches!(dtype, ir::Dtype::Int { .. }),
+                    _ => is_arithmetic(&dtype),
+                };
+                if !is_valid {
+                    self.error(format!(
+                        "invalid argument type `{}` to unary expression",
+                        type_name(&dtype)
+                    ));
+                    return None;
+                }
+                Some(Typed::rvalue(integer_promotion(&dtype)))
+            }
+            UnaryOperator::Negate => {
+                if !is_scalar(&dtype) {
+                    self.error(format!(
+                        "invalid argument type `{}` to unary expression",
+                        type_name(&dtype)
+                    ));
+                    return None;
+                }
+                Some(Typed::rvalue(ir::Dtype::INT))
+            }
+        }
+    }
+
+    /// Checks that `typed` designates a modifiable object, i.e., it can be assigned to.
+    fn check_modifiable(&mut self, typed: &Typed) {
+        if !typed.is_lvalue {
+            self.error("expression is not assignable");
+            return;
+        }
+        match &typed.dtype {
+            ir::Dtype::Array { .. } => {
+                self.error(format!(
+                    "array type `{}` is not assignable",
+                    type_name(&typed.dtype)
+                ));
+            }
+            ir::Dtype::Function { .. } => self.error("function is not assignable"),
+            dtype if dtype.is_invalid_structure(&self.structs) => {
+                self.error(format!(
+                    "incomplete type `{}` is not assignable",
+                    type_name(dtype)
+                ));
+            }
+            dtype if dtype.is_immutable(&self.structs) => {
+                self.error(format!(
+                    "cannot assign to read-only location of type `{}`",
+                    type_name(dtype)
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    fn binary(&mut self, binary: &BinaryOperatorExpression) -> Option<Typed> {
+        let operator = &binary.operator.node;
+        let lhs = self.expression(&binary.lhs);
+        let rhs = self.expression(&binary.rhs);
+        let (lhs, rhs) = (lhs?, rhs?);
+        let lhs_dtype = lhs.value_dtype();
+        let rhs_dtype = rhs.value_dtype();
+
+        if *operator == BinaryOperator::Index {
+            let (pointer, index) = match (&lhs_dtype, &rhs_dtype) {
+                (ir::Dtype::Pointer { .. }, _) => (&lhs_dtype, &rhs_dtype),
+                (_, ir::Dtype::Pointer { .. }) => (&rhs_dtype, &lhs_dtype),
+                _ => {
+                    self.error("subscripted value is not an array or pointer");
+                    return None;
+                }
+            };
+            if !matches!(index, ir::Dtype::Int { .. }) {
+                self.error("array subscript is not an integer");
+                return None;
+            }
+            let inner = pointer.get_pointer_inner().expect("must be a pointer");
+            return Some(Typed::lvalue(inner.clone()));
+        }
+
+        let (_, token) = binary_operator(operator);
+        let invalid_operands = |sema: &mut Self| {
+            sema.error(format!(
+                "invalid operands to binary expression `{token}` (`{}` and `{}`)",
+                type_name(&lhs_dtype),
+                type_name(&rhs_dtype)
+            ));
+            None
+        };
+
+        let result = match operator {
+            BinaryOperator::Multiply | BinaryOperator::Divide => {
+                if !is_arithmetic(&lhs_dtype) || !is_arithmetic(&rhs_dtype) {
+                    return invalid_operands(self);
+                }
+                usual_arithmetic_conversion(&lhs_dtype, &rhs_dtype)
+            }
+            BinaryOperator::Modulo
+            | BinaryOperator::BitwiseAnd
+            | BinaryOperator::BitwiseXor
+            | BinaryOperator::BitwiseOr => {
+                if !is_integer(&lhs_dtype) || !is_integer(&rhs_dtype) {
+                    return invalid_operands(self);
+                }
+                usual_arithmetic_conversion(&lhs_dtype, &rhs_dtype)
+            }
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
+                if !is_integer(&lhs_dtype) || !is_integer(&rhs_dtype) {
+                    return invalid_operands(self);
+                }
+                integer_promotion(&lhs_dtype)
+            }
+            BinaryOperator::Plus => match (&lhs_dtype, &rhs_dtype) {
+                _ if is_arithmetic(&lhs_dtype) && is_arithmetic(&rhs_dtype) => {
+                    usual_arithmetic_conversion(&lhs_dtype, &rhs_dtype)
+                }
+                (ir::Dtype::Pointer { .. }, ir::Dtype::Int { .. }) => lhs_dtype.clone(),
+                (ir::Dtype::Int { .. }, ir::Dtype::Pointer { .. }) => rhs_dtype.clone(),
+                _ => return invalid_operands(self),
+            },
+            BinaryOperator::Minus => match (&lhs_dtype, &rhs_dtype) {
+                _ if is_arithmetic(&lhs_dtype) && is_arithmetic(&rhs_dtype) => {
+                    usual_arithmetic_conversion(&lhs_dtype, &rhs_dtype)
+                }
+                (ir::Dtype::Pointer { .. }, ir::Dtype::Int { .. }) => lhs_dtype.clone(),
+                (ir::Dtype::Pointer { inner, .. }, ir::Dtype::Pointer { inner: other, .. })
+                    if is_compatible(inner, other) =>
+                {
+                    ir::Dtype::LONG
+                }
+                _ => return invalid_operands(self),
+            },
+            BinaryOperator::Less
+            | BinaryOperator::Greater
+            | BinaryOperator::LessOrEqual
+            | BinaryOperator::GreaterOrEqual
+            | BinaryOperator::Equals
+            | BinaryOperator::NotEquals => {
+                let is_valid = match (&lhs_dtype, &rhs_dtype) {
+                    (ir::Dtype::Int { .. }, ir::Dtype::Int { .. }) => {
+                        self.check_sign_compare(&binary.lhs, &binary.rhs, &lhs_dtype, &rhs_dtype);
+                        true
+                    }
+                    _ if is_arithmetic(&lhs_dtype) && is_arithmetic(&rhs_dtype) => true,
+                    (ir::Dtype::Pointer { .. }, ir::Dtype::Pointer { .. }) => true,
+                    (ir::Dtype::Pointer { .. }, ir::Dtype::Int { .. }) => {
+                        self.is_null_pointer_constant(&binary.rhs.node)
+                    }
+                    (ir::Dtype::Int { .. }, ir::Dtype::Pointer { .. }) => {
+                        self.is_null_pointer_constant(&binary.lhs.node)
+                    }
+                    _ => false,
+                };
+                if !is_valid {
+                    return invalid_operands(self);
+                }
+                ir::Dtype::INT
+            }
+            BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr => {
+                if !is_scalar(&lhs_dtype) || !is_scalar(&rhs_dtype) {
+                    return invalid_operands(self);
+                }
+                ir::Dtype::INT
+            }
+            BinaryOperator::Assign => {
+                self.check_modifiable(&lhs);
+                self.convert(&lhs.dtype, &rhs, &binary.rhs, Conversion::Assignment);
+                lhs.dtype.clone().set_const(false)
+            }
+            BinaryOperator::AssignPlus | BinaryOperator::AssignMinus => {
+                let is_valid = (is_arithmetic(&lhs_dtype) && is_arithmetic(&rhs_dtype))
+                    || (matches!(lhs_dtype, ir::Dtype::Pointer { .. }) && is_integer(&rhs_dtype));
+                if !is_valid {
+                    return invalid_operands(self);
+                }
+                self.check_modifiable(&lhs);
+                lhs_dtype.clone()
+            }
+            BinaryOperator::AssignMultiply | BinaryOperator::AssignDivide => {
+                if !is_arithmetic(&lhs_dtype) || !is_arithmetic(&rhs_dtype) {
+                    return invalid_operands(self);
+                }
+                self.check_modifiable(&lhs);
+                lhs_dtype.clone()
+            }
+            BinaryOperator::AssignModulo
+            | BinaryOperator::AssignShiftLeft
+            | BinaryOperator::AssignShiftRight
+            | BinaryOperator::AssignBitwiseAnd
+            | BinaryOperator::AssignBitwiseXor
+            | BinaryOperator::AssignBitwiseOr => {
+                if !is_integer(&lhs_dtype) || !is_integer(&rhs_dtype) {
+                    return invalid_operands(self);
+                }
+                self.check_modifiable(&lhs);
+                lhs_dtype.clone()
+            }
+            BinaryOperator::Index => unreachable!("`Index` is checked above"),
+        };
+        Some(Typed::rvalue(result))
+    }
+
+    fn conditional(&mut self, conditional: &ConditionalExpression) -> Option<Typed> {
+        self.condition(&conditional.condition);
+        let then_typed = self.expression(&conditional.then_expression);
+        let else_typed = self.expression(&conditional.else_expression);
+        let then_dtype = then_typed?.value_dtype();
+        let else_dtype = else_typed?.value_dtype();
+
+        let dtype = match (&then_dtype, &else_dtype) {
+            _ if is_arithmetic(&then_dtype) && is_arithmetic(&else_dtype) => {
+                Some(usual_arithmetic_conversion(&then_dtype, &else_dtype))
+            }
+            (ir::Dtype::Unit { .. }, ir::Dtype::Unit { .. }) => Some(then_dtype.clone()),
+            (ir::Dtype::Struct { name, .. }, ir::Dtype::Struct { name: other, .. })
+                if name == other =>
+            {
+                Some(then_dtype.clone())
+            }
+            (ir::Dtype::Pointer { inner, .. }, ir::Dtype::Pointer { inner: other, .. }) => {
+                if matches!(inner.deref(), ir::Dtype::Unit { .. }) || is_compatible(inner, other) {
+                    Some(then_dtype.clone())
+                } else if matches!(other.deref(), ir::Dtype::Unit { .. }) {
+                    Some(else_dtype.clone())
+                } else {
+                    None
+                }
+            }
+            (ir::Dtype::Pointer { .. }, ir::Dtype::Int { .. })
+                if self.is_null_pointer_constant(&conditional.else_expression.node) =>
+            {
+                Some(then_dtype.clone())
+            }
+            (ir::Dtype::Int { .. }, ir::Dtype::Pointer { .. })
+                if self.is_null_pointer_constant(&conditional.then_expression.node) =>
+            {
+                Some(else_dtype.clone())
+            }
+            _ => None,
+        };
+
+        match dtype {
+            Some(dtype) => Some(Typed::rvalue(dtype)),
+            None => {
+                self.error(format!(
+                    "incompatible operand types (`{}` and `{}`)",
+                    type_name(&then_dtype),
+                    type_name(&else_dtype)
+                ));
+                None
+            }
+        }
+    }
+}
+
+/// Returns the names of the parameters declared by the function declarator `declarator` with the
+/// spans of their declarators, or `None` for an unnamed one.
+fn param_names(declarator: &Declarator) -> Vec<Option<(String, Span)>> {
+    match function_derived_declarator(declarator) {
+        Some(DerivedDeclarator::Function(func_decl)) => func_decl
+            .node
+            .parameters
+            .iter()
+            .filter_map(|param| {
+                let Some(declarator) = &param.node.declarator else {
+                    // `(void)` declares no parameters.
+                    return Some(None).filter(|_| func_decl.node.parameters.len() > 1);
+                };
+                match &declarator.node.kind.node {
+                    DeclaratorKind::Abstract => Some(None),
+                    _ => Some(Some((
+                        name_of_declarator(&declarator.node),
+                        declarator.span,
+                    ))),
+                }
+            })
+            .collect(),
+        Some(DerivedDeclarator::KRFunction(identifiers)) => identifiers
+            .iter()
+            .map(|identifier| Some((identifier.node.name.clone(), identifier.span)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The type of `sizeof` and `_Alignof`, i.e., `size_t`.
+fn size_t() -> ir::Dtype {
+    ir::Dtype::LONG.set_signed(false)
+}
+
+fn is_integer(dtype: &ir::Dtype) -> bool {
+    matches!(dtype, ir::Dtype::Int { .. })
+}
+
+fn is_arithmetic(dtype: &ir::Dtype) -> bool {
+    matches!(dtype, ir::Dtype::Int { .. } | ir::Dtype::Float { .. })
+}
+
+fn is_scalar(dtype: &ir::Dtype) -> bool {
+    matches!(
+        dtype,
+        ir::Dtype::Int { .. } | ir::Dtype::Float { .. } | ir::Dtype::Pointer { .. }
+    )
+}
+
+fn is_aggregate(dtype: &ir::Dtype) -> bool {
+    matches!(dtype, ir::Dtype::Array { .. } | ir::Dtype::Struct { .. })
+}
+
+/// Applies the integer promotions to `dtype`, e.g., `int` for `char`.
+fn integer_promotion(dtype: &ir::Dtype) -> ir::Dtype {
+    match dtype {
+        ir::Dtype::Int { width, .. }
+            if *width < ir::Dtype::SIZE_OF_INT * ir::Dtype::BITS_OF_BYTE =>
+        {
+            ir::Dtype::INT
+        }
+        _ => dtype.clone().set_const(false),
+    }
+}
+
+/// Finds the common type of the arithmetic types `lhs` and `rhs` by the usual arithmetic
+/// conversions.
+fn usual_arithmetic_conversion(lhs: &ir::Dtype, rhs: &ir::Dtype) -> ir::Dtype {
+    let lhs = integer_promotion(lhs);
+    let rhs = integer_promotion(rhs);
+    match (&lhs, &rhs) {
+        (ir::Dtype::Float { width, .. }, ir::Dtype::Float { width: other, .. }) => {
+            ir::Dtype::float(*width.max(other))
+        }
+        (ir::Dtype::Float { .. }, _) => lhs,
+        (_, ir::Dtype::Float { .. }) => rhs,
+        (
+            ir::Dtype::Int {
+                width, is_signed, ..
+            },
+            ir::Dtype::Int {
+                width: other,
+                is_signed: other_is_signed,
+                ..
+            },
+        ) => match width.cmp(other) {
+            core::cmp::Ordering::Greater => lhs,
+            core::cmp::Ordering::Less => rhs,
+            core::cmp::Ordering::Equal => {
+                ir::Dtype::int(*width).set_signed(*is_signed && *other_is_signed)
+            }
+        },
+        _ => panic!("operands of the usual arithmetic conversions must be arithmetic"),
+    }
+}
+
+/// Checks if `lhs` and `rhs` are compatible types, ignoring their top-level qualifiers. A function
+/// type without a prototype is compatible with the prototypes whose parameters are not changed by
+/// the default argument promotions.
+fn is_compatible(lhs: &ir::Dtype, rhs: &ir::Dtype) -> bool {
+    match (lhs, rhs) {
+        (
+            ir::Dtype::Pointer { inner, .. },
+            ir::Dtype::Pointer {
+                inner: other_inner, ..
+            },
+        ) => inner.is_const() == other_inner.is_const() && is_compatible(inner, other_inner),
+        (
+            ir::Dtype::Array { inner, size },
+            ir::Dtype::Array {
+                inner: other_inner,
+                size: other_size,
+            },
+        ) => size == other_size && is_compatible(inner, other_inner),
+        (ir::Dtype::Function { .. }, ir::Dtype::Function { .. }) => {
+            let signature = ir::FunctionSignature::new(lhs.clone());
+            let other = ir::FunctionSignature::new(rhs.clone());
+            let is_prototype_compatible = signature.params.len() == other.params.len()
+                && signature.is_variadic == other.is_variadic
+                && signature
+                    .params
+                    .iter()
+                    .zip(&other.params)
+                    .all(|(param, other)| is_compatible(param, other));
+            is_compatible(&signature.ret, &other.ret)
+                && (is_prototype_compatible
+                    || signature.is_unprototyped_of(&other)
+                    || other.is_unprototyped_of(&signature))
+        }
+        _ => lhs.clone().set_const(false) == rhs.clone().set_const(false),
+    }
+}
+
+/// Returns the name of `dtype` in C, e.g., `const int *` or `int (*)[3]`.
+fn type_name(dtype: &ir::Dtype) -> String {
+    declaration_of(dtype, String::new())
+}
+
+/// Returns the declaration of `declarator` of `dtype` in C, e.g., `int *p` for `int *` and `p`.
+fn declaration_of(dtype: &ir::Dtype, declarator: String) -> String {
+    let qualifier = |is_const: bool| if is_const { "const " } else { "" };
+    let base = match dtype {
+        ir::Dtype::Unit { is_const } => format!("{}void", qualifier(*is_const)),
+        ir::Dtype::Int {
+            width,
+            is_signed,
+            is_const,
+        } => {
+            let name = match width {
+                1 => "_Bool",
+                8 => "char",
+                16 => "short",
+                32 => "int",
+                _ => "long",
+            };
+            let sign = if *is_signed || *width == 1 {
+                ""
+            } else {
+                "unsigned "
+            };
+            format!("{}{sign}{name}", qualifier(*is_const))
+        }
+        ir::Dtype::Float { width, is_const } => {
+            let name = match width {
+                32 => "float",
+                64 => "double",
+                _ => "long double",
+            };
+            format!("{}{name}", qualifier(*is_const))
+        }
+        ir::Dtype::Struct {
+            name,
+            is_const,
+            is_union,
+            ..
+        } => {
+            let keyword = if *is_union { "union" } else { "struct" };
+            // The structs defined in functions have unique names, e.g., `A.0` for `A`, and the
+            // anonymous ones have temporary names, e.g., `%t0`.
+            let tag = match name.as_deref() {
+                Some(name) if !name.starts_with('%') => name.split('.').next().unwrap_or(name),
+                _ => "<anonymous>",
+            };
+            format!("{}{keyword} {tag}", qualifier(*is_const))
+        }
+        ir::Dtype::Typedef { name, is_const } => format!("{}{name}", qualifier(*is_const)),
+        ir::Dtype::Pointer { inner, is_const } => {
+            let declarator = match (*is_const, declarator.is_empty()) {
+                (true, true) => "*const".to_string(),
+                (true, false) => format!("*const {declarator}"),
+                (false, _) => format!("*{declarator}"),
+            };
+            let declarator = match inner.deref() {
+                ir::Dtype::Array { .. } | ir::Dtype::Function { .. } => format!("({declarator})"),
+                _ => declarator,
+            };
+            return declaration_of(inner, declarator);
+        }
+        ir::Dtype::Array { inner, size } => {
+            return declaration_of(inner, format!("{declarator}[{size}]"));
+        }
+        ir::Dtype::Function {
+            ret,
+            params,
+            is_variadic,
+        } => {
+            let mut params = params.iter().map(type_name).collect::<Vec<_>>();
+            if *is_variadic {
+                params.push("...".to_string());
+            }
+            return declaration_of(ret, format!("{declarator}({})", params.join(", ")));
+        }
+    };
+
+    if declarator.is_empty() {
+        base
+    } else if declarator.starts_with('[') {
+        format!("{base}{declarator}")
+    } else {
+        format!("{base} {declarator}")
+    }
+}
+
+/// Checks the `_Static_assert` declaration `static_assert`, whose expression is evaluated to
+/// `condition`.
+fn check_static_assert(
+    static_assert: &StaticAssert,
+    condition: &ir::Constant,
+) -> Result<(), ConstEvalError> {
+    let Some((value, _, _)) = condition.get_int() else {
+        return Err(ConstEvalError::Misc {
+            message: "static assertion expression is not an integer constant expression"
+                .to_string(),
+        });
+    };
+    if value != 0 {
+        return Ok(());
+    }
+
+    let message = ir::string_literal_bytes(&static_assert.message.node).map_err(|_| {
+        ConstEvalError::Misc {
+            message: "invalid string literal".to_string(),
+        }
+    })?;
+    Err(ConstEvalError::Misc {
+        message: format!(
+            "static assertion failed: \"{}\"",
+            String::from_utf8_lossy(&message)
+        ),
+    })
+}
@@ -1,3 +1,4 @@
+use core::str::FromStr;
 use std::io::{Result, Write};
 
 use lang_c::ast::*;
@@ -5,6 +6,60 @@ use lang_c::span::Node;
 
 use crate::write_base::*;
 
+/// The style of the C code written by [`CodeStyle::write`].
+///
+/// The written code is parsed to an AST equivalent to the original one regardless of the style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeStyle {
+    /// The number of spaces of an indentation level.
+    pub indent_width: usize,
+    /// Where to put the opening brace of a block.
+    pub brace_style: BraceStyle,
+}
+
+impl Default for CodeStyle {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            brace_style: BraceStyle::default(),
+        }
+    }
+}
+
+impl CodeStyle {
+    /// Write the C file `unit` to `write` in this style.
+    pub fn write(&self, unit: &TranslationUnit, write: &mut dyn Write) -> Result<()> {
+        let mut printer = Printer::new(*self, 0);
+        printer.translation_unit(unit);
+        write.write_all(printer.out.as_bytes())
+    }
+}
+
+/// Where to put the opening brace of a function body, a compound statement, or the body of a
+/// struct, union or enum.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BraceStyle {
+    /// At the end of the line, e.g., `int main() {`.
+    #[default]
+    SameLine,
+    /// On the next line, i.e., `{` at the start of the line after `int main()`.
+    NextLine,
+}
+
+impl FromStr for BraceStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        match s {
+            "same-line" => Ok(Self::SameLine),
+            "next-line" => Ok(Self::NextLine),
+            _ => Err(format!(
+                "invalid brace style `{s}`, expected `same-line` or `next-line`"
+            )),
+        }
+    }
+}
+
 impl<T: WriteLine> WriteLine for Node<T> {
     fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()> {
         self.node.write_line(indent, write)
@@ -18,14 +73,1159 @@ impl<T: WriteString> WriteString for Node<T> {
 }
 
 impl WriteLine for TranslationUnit {
-    /// VERY BIG HINT: You should start by understanding the [`writeln!`](https://doc.rust-lang.org/std/macro.writeln.html) macro.
     fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()> {
-        todo!()
+        let mut printer = Printer::new(CodeStyle::default(), indent);
+        printer.translation_unit(self);
+        write.write_all(printer.out.as_bytes())
     }
 }
 
 impl WriteString for Initializer {
     fn write_string(&self) -> String {
-        todo!()
+        Printer::new(CodeStyle::default(), 0).initializer(self)
+    }
+}
+
+/// The precedence of an expression, i.e., the kind of the operands in which it can be written
+/// without parentheses. A larger one binds tighter.
+pub(crate) type Precedence = u8;
+
+const COMMA: Precedence = 0;
+const ASSIGNMENT: Precedence = 1;
+const CONDITIONAL: Precedence = 2;
+const CAST: Precedence = 13;
+const UNARY: Precedence = 14;
+const POSTFIX: Precedence = 15;
+const PRIMARY: Precedence = 16;
+
+/// Returns the precedence and the token of the binary operator `op` other than `Index`.
+pub(crate) fn binary_operator(op: &BinaryOperator) -> (Precedence, &'static str) {
+    match op {
+        BinaryOperator::Index => panic!("`BinaryOperator::Index` is not an infix operator"),
+        BinaryOperator::Multiply => (12, "*"),
+        BinaryOperator::Divide => (12, "/"),
+        BinaryOperator::Modulo => (12, "%"),
+        BinaryOperator::Plus => (11, "+"),
+        BinaryOperator::Minus => (11, "-"),
+        BinaryOperator::ShiftLeft => (10, "<<"),
+        BinaryOperator::ShiftRight => (10, ">>"),
+        BinaryOperator::Less => (9, "<"),
+        BinaryOperator::Greater => (9, ">"),
+        BinaryOperator::LessOrEqual => (9, "<="),
+        BinaryOperator::GreaterOrEqual => (9, ">="),
+        BinaryOperator::Equals => (8, "=="),
+        BinaryOperator::NotEquals => (8, "!="),
+        BinaryOperator::BitwiseAnd => (7, "&"),
+        BinaryOperator::BitwiseXor => (6, "^"),
+        BinaryOperator::BitwiseOr => (5, "|"),
+        BinaryOperator::LogicalAnd => (4, "&&"),
+        BinaryOperator::LogicalOr => (3, "||"),
+        BinaryOperator::Assign => (ASSIGNMENT, "="),
+        BinaryOperator::AssignMultiply => (ASSIGNMENT, "*="),
+        BinaryOperator::AssignDivide => (ASSIGNMENT, "/="),
+        BinaryOperator::AssignModulo => (ASSIGNMENT, "%="),
+        BinaryOperator::AssignPlus => (ASSIGNMENT, "+="),
+        BinaryOperator::AssignMinus => (ASSIGNMENT, "-="),
+        BinaryOperator::AssignShiftLeft => (ASSIGNMENT, "<<="),
+        BinaryOperator::AssignShiftRight => (ASSIGNMENT, ">>="),
+        BinaryOperator::AssignBitwiseAnd => (ASSIGNMENT, "&="),
+        BinaryOperator::AssignBitwiseXor => (ASSIGNMENT, "^="),
+        BinaryOperator::AssignBitwiseOr => (ASSIGNMENT, "|="),
+    }
+}
+
+/// Writes C code in `style`.
+///
+/// Statements and declarations are written to `out` line by line, while expressions, types and
+/// initializers are built as strings. A string spanning multiple lines, e.g., a struct type with
+/// its body, is indented as if it starts at the current line.
+struct Printer {
+    style: CodeStyle,
+    /// The current indentation level
+    indent: usize,
+    out: String,
+}
+
+impl Printer {
+    fn new(style: CodeStyle, indent: usize) -> Self {
+        Self {
+            style,
+            indent,
+            out: String::new(),
+        }
+    }
+
+    fn indentation(&self, indent: usize) -> String {
+        " ".repeat(indent * self.style.indent_width)
+    }
+
+    /// Writes `line` at the current indentation level.
+    fn line(&mut self, line: &str) {
+        let indentation = self.indentation(self.indent);
+        self.out.push_str(&indentation);
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    /// Writes `header` followed by the opening brace of a block, and enters the block.
+    fn open_brace(&mut self, header: &str) {
+        match self.style.brace_style {
+            BraceStyle::SameLine if header.is_empty() => self.line("{"),
+            BraceStyle::SameLine => self.line(&format!("{header} {{")),
+            BraceStyle::NextLine => {
+                if !header.is_empty() {
+                    self.line(header);
+                }
+                self.line("{");
+            }
+        }
+        self.indent += 1;
+    }
+
+    /// Returns the opening brace of a body following `header` in a string, e.g., ` {\n` for
+    /// [`BraceStyle::SameLine`].
+    fn brace_after(&self, header: &str) -> String {
+        match self.style.brace_style {
+            BraceStyle::SameLine => format!("{header} {{\n"),
+            BraceStyle::NextLine => format!("{header}\n{}{{\n", self.indentation(self.indent)),
+        }
+    }
+
+    fn translation_unit(&mut self, unit: &TranslationUnit) {
+        for (i, decl) in unit.0.iter().enumerate() {
+            // Function definitions are separated from the others by a blank line.
+            let is_function = |decl: &Node<ExternalDeclaration>| {
+                matches!(decl.node, ExternalDeclaration::FunctionDefinition(_))
+            };
+            if i > 0 && (is_function(decl) || is_function(&unit.0[i - 1])) {
+                self.out.push('\n');
+            }
+
+            match &decl.node {
+                ExternalDeclaration::Declaration(decl) => {
+                    let decl = self.declaration(&decl.node);
+                    self.line(&format!("{decl};"));
+                }
+                ExternalDeclaration::StaticAssert(static_assert) => {
+                    let static_assert = self.static_assert(&static_assert.node);
+                    self.line(&format!("{static_assert};"));
+                }
+                ExternalDeclaration::FunctionDefinition(func) => {
+                    self.function_definition(&func.node)
+                }
+            }
+        }
+    }
+
+    fn function_definition(&mut self, func: &FunctionDefinition) {
+        let header = self.declaration_with_specifiers(&func.specifiers, &func.declarator.node);
+        let Statement::Compound(items) = &func.statement.node else {
+            panic!("the body of a function definition must be a compound statement")
+        };
+
+        if func.declarations.is_empty() {
+            self.open_brace(&header);
+        } else {
+            // The parameter declarations of a K&R-style definition are between the declarator
+            // and the body.
+            self.line(&header);
+            for decl in &func.declarations {
+                let decl = self.declaration(&decl.node);
+                self.line(&format!("{decl};"));
+            }
+            self.line("{");
+            self.indent += 1;
+        }
+        self.block_items(items);
+        self.indent -= 1;
+        self.line("}");
+    }
+
+    fn block_items(&mut self, items: &[Node<BlockItem>]) {
+        for item in items {
+            match &item.node {
+                BlockItem::Declaration(decl) => {
+                    let decl = self.declaration(&decl.node);
+                    self.line(&format!("{decl};"));
+                }
+                BlockItem::StaticAssert(static_assert) => {
+                    let static_assert = self.static_assert(&static_assert.node);
+                    self.line(&format!("{static_assert};"));
+                }
+                BlockItem::Statement(stmt) => self.statement(&stmt.node),
+            }
+        }
+    }
+
+    fn statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Labeled(labeled) => {
+                // Labels are outdented from the statements, e.g., `case` labels are aligned with
+                // the `switch` containing them.
+                let label = match &labeled.node.label.node {
+                    Label::Identifier(identifier) => format!("{}:", identifier.node.name),
+                    Label::Case(expr) => format!("case {}:", self.expression(expr, CONDITIONAL)),
+                    Label::CaseRange(range) => format!(
+                        "case {} ... {}:",
+                        self.expression(&range.node.low, CONDITIONAL),
+                        self.expression(&range.node.high, CONDITIONAL)
+                    ),
+                    Label::Default => "default:".to_string(),
+                };
+                let indent = self.indent;
+                self.indent = indent.saturating_sub(1);
+                if let Statement::Compound(items) = &labeled.node.statement.node {
+                    self.open_brace(&label);
+                    self.block_items(items);
+                    self.indent -= 1;
+                    self.line("}");
+                    self.indent = indent;
+                } else {
+                    self.line(&label);
+                    self.indent = indent;
+                    self.statement(&labeled.node.statement.node);
+                }
+            }
+            Statement::Compound(items) => {
+                self.open_brace("");
+                self.block_items(items);
+                self.indent -= 1;
+                self.line("}");
+            }
+            Statement::Expression(expr) => {
+                let expr = expr
+                    .as_ref()
+                    .map(|expr| self.expression(expr, COMMA))
+                    .unwrap_or_default();
+                self.line(&format!("{expr};"));
+            }
+            Statement::If(if_stmt) => self.if_statement("", &if_stmt.node),
+            Statement::Switch(switch) => {
+                let header = format!(
+                    "switch ({})",
+                    self.expression(&switch.node.expression, COMMA)
+                );
+                self.body(&header, &switch.node.statement.node, false);
+                self.close_body(&switch.node.statement.node, "");
+            }
+            Statement::While(while_stmt) => {
+                let header = format!(
+                    "while ({})",
+                    self.expression(&while_stmt.node.expression, COMMA)
+                );
+                self.body(&header, &while_stmt.node.statement.node, false);
+                self.close_body(&while_stmt.node.statement.node, "");
+            }
+            Statement::DoWhile(do_while) => {
+                let stmt = &do_while.node.statement.node;
+                let tail = format!(
+                    "while ({});",
+                    self.expression(&do_while.node.expression, COMMA)
+                );
+                self.body("do", stmt, false);
+                if matches!(stmt, Statement::Compound(_)) {
+                    self.close_body(stmt, &tail);
+                } else {
+                    self.line(&tail);
+                }
+            }
+            Statement::For(for_stmt) => {
+                let for_stmt = &for_stmt.node;
+                let initializer = match &for_stmt.initializer.node {
+                    ForInitializer::Empty => ";".to_string(),
+                    ForInitializer::Expression(expr) => {
+                        format!("{};", self.expression(expr, COMMA))
+                    }
+                    ForInitializer::Declaration(decl) => {
+                        format!("{};", self.declaration(&decl.node))
+                    }
+                    ForInitializer::StaticAssert(static_assert) => {
+                        format!("{};", self.static_assert(&static_assert.node))
+                    }
+                };
+                let condition = for_stmt
+                    .condition
+                    .as_ref()
+                    .map(|expr| format!(" {}", self.expression(expr, COMMA)))
+                    .unwrap_or_default();
+                let step = for_stmt
+                    .step
+                    .as_ref()
+                    .map(|expr| format!(" {}", self.expression(expr, COMMA)))
+                    .unwrap_or_default();
+                let header = format!("for ({initializer}{condition};{step})");
+                self.body(&header, &for_stmt.statement.node, false);
+                self.close_body(&for_stmt.statement.node, "");
+            }
+            Statement::Goto(label) => self.line(&format!("goto {};", label.node.name)),
+            Statement::Continue => self.line("continue;"),
+            Statement::Break => self.line("break;"),
+            Statement::Return(expr) => match expr {
+                Some(expr) => {
+                    let expr = self.expression(expr, COMMA);
+                    self.line(&format!("return {expr};"));
+                }
+                None => self.line("return;"),
+            },
+            Statement::Asm(asm) => {
+                let asm = self.asm_statement(&asm.node);
+                self.line(&format!("{asm};"));
+            }
+        }
+    }
+
+    /// Writes the `if` statement `if_stmt`, whose header is preceded by `prefix`, e.g., `} else `.
+    fn if_statement(&mut self, prefix: &str, if_stmt: &IfStatement) {
+        let header = format!(
+            "{prefix}if ({})",
+            self.expression(&if_stmt.condition, COMMA)
+        );
+        let then_stmt = &if_stmt.then_statement.node;
+
+        let Some(else_stmt) = &if_stmt.else_statement else {
+            self.body(&header, then_stmt, false);
+            self.close_body(then_stmt, "");
+            return;
+        };
+
+        // The `else` would belong to an `if` without `else` in `then_stmt` unless it is braced.
+        let is_braced = has_trailing_if(then_stmt);
+        self.body(&header, then_stmt, is_braced);
+        let prefix = if is_braced || matches!(then_stmt, Statement::Compound(_)) {
+            self.indent -= 1;
+            match self.style.brace_style {
+                BraceStyle::SameLine => "} else",
+                BraceStyle::NextLine => {
+                    self.line("}");
+                    "else"
+                }
+            }
+        } else {
+            "else"
+        };
+
+        if let Statement::If(else_if) = &else_stmt.node {
+            self.if_statement(&format!("{prefix} "), &else_if.node);
+        } else {
+            self.body(prefix, &else_stmt.node, false);
+            self.close_body(&else_stmt.node, "");
+        }
+    }
+
+    /// Writes `header` and the statement `stmt` as its body. A compound statement is left open to
+    /// be closed by [`Self::close_body`], and so is `stmt` if it is `braced`.
+    fn body(&mut self, header: &str, stmt: &Statement, braced: bool) {
+        if let Statement::Compound(items) = stmt {
+            self.open_brace(header);
+            self.block_items(items);
+        } else if braced {
+            self.open_brace(header);
+            self.statement(stmt);
+        } else {
+            self.line(header);
+            self.indent += 1;
+            self.statement(stmt);
+            self.indent -= 1;
+        }
+    }
+
+    /// Closes the body `stmt` written by [`Self::body`] followed by `tail`, e.g., `while (1);` of
+    /// `do`-`while`.
+    fn close_body(&mut self, stmt: &Statement, tail: &str) {
+        if !matches!(stmt, Statement::Compound(_)) {
+            return;
+        }
+
+        self.indent -= 1;
+        match (self.style.brace_style, tail) {
+            (_, "") => self.line("}"),
+            (BraceStyle::SameLine, tail) => self.line(&format!("}} {tail}")),
+            (BraceStyle::NextLine, tail) => {
+                self.line("}");
+                self.line(tail);
+            }
+        }
+    }
+
+    fn asm_statement(&self, asm: &AsmStatement) -> String {
+        match asm {
+            AsmStatement::GnuBasic(template) => {
+                format!("__asm__({})", string_literal(&template.node))
+            }
+            AsmStatement::GnuExtended(asm) => {
+                let qualifier = asm
+                    .qualifier
+                    .as_ref()
+                    .map(|qualifier| format!(" {}", type_qualifier(&qualifier.node)))
+                    .unwrap_or_default();
+                let operands = |operands: &[Node<GnuAsmOperand>]| {
+                    operands
+                        .iter()
+                        .map(|operand| {
+                            let name = operand
+                                .node
+                                .symbolic_name
+                                .as_ref()
+                                .map(|name| format!("[{}] ", name.node.name))
+                                .unwrap_or_default();
+                            format!(
+                                "{name}{} ({})",
+                                string_literal(&operand.node.constraints.node),
+                                self.expression(&operand.node.variable_name, COMMA)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                let clobbers = asm
+                    .clobbers
+                    .iter()
+                    .map(|clobber| string_literal(&clobber.node))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "__asm__{qualifier}({} : {} : {} : {clobbers})",
+                    string_literal(&asm.template.node),
+                    operands(&asm.outputs),
+                    operands(&asm.inputs),
+                )
+            }
+        }
+    }
+
+    fn static_assert(&self, static_assert: &StaticAssert) -> String {
+        format!(
+            "_Static_assert({}, {})",
+            self.expression(&static_assert.expression, CONDITIONAL),
+            string_literal(&static_assert.message.node)
+        )
+    }
+
+    /// Returns the declaration `decl` without the semicolon.
+    fn declaration(&self, decl: &Declaration) -> String {
+        let specifiers = self.declaration_specifiers(&decl.specifiers);
+        if decl.declarators.is_empty() {
+            return specifiers;
+        }
+
+        let declarators = decl
+            .declarators
+            .iter()
+            .map(|init_decl| {
+                let declarator = self.declarator(&init_decl.node.declarator.node);
+                match &init_decl.node.initializer {
+                    Some(initializer) => {
+                        format!("{declarator} = {}", self.initializer(&initializer.node))
+                    }
+                    None => declarator,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{specifiers} {declarators}")
+    }
+
+    fn declaration_with_specifiers(
+        &self,
+        specifiers: &[Node<DeclarationSpecifier>],
+        declarator: &Declarator,
+    ) -> String {
+        join_type(
+            self.declaration_specifiers(specifiers),
+            self.declarator(declarator),
+        )
+    }
+
+    fn declaration_specifiers(&self, specifiers: &[Node<DeclarationSpecifier>]) -> String {
+        specifiers
+            .iter()
+            .map(|spec| match &spec.node {
+                DeclarationSpecifier::StorageClass(storage_class) => {
+                    storage_class_specifier(&storage_class.node).to_string()
+                }
+                DeclarationSpecifier::TypeSpecifier(type_specifier) => {
+                    self.type_specifier(&type_specifier.node)
+                }
+                DeclarationSpecifier::TypeQualifier(qualifier) => {
+                    type_qualifier(&qualifier.node).to_string()
+                }
+                DeclarationSpecifier::Function(function_specifier) => {
+                    match function_specifier.node {
+                        FunctionSpecifier::Inline => "inline".to_string(),
+                        FunctionSpecifier::Noreturn => "_Noreturn".to_string(),
+                    }
+                }
+                DeclarationSpecifier::Alignment(alignment) => match &alignment.node {
+                    AlignmentSpecifier::Type(type_name) => {
+                        format!("_Alignas({})", self.type_name(&type_name.node))
+                    }
+                    AlignmentSpecifier::Constant(expr) => {
+                        format!("_Alignas({})", self.expression(expr, CONDITIONAL))
+                    }
+                },
+                DeclarationSpecifier::Extension(extensions) => self.extensions(extensions),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn specifier_qualifiers(&self, specifiers: &[Node<SpecifierQualifier>]) -> String {
+        specifiers
+            .iter()
+            .map(|spec| match &spec.node {
+                SpecifierQualifier::TypeSpecifier(type_specifier) => {
+                    self.type_specifier(&type_specifier.node)
+                }
+                SpecifierQualifier::TypeQualifier(qualifier) => {
+                    type_qualifier(&qualifier.node).to_string()
+                }
+                SpecifierQualifier::Extension(extensions) => self.extensions(extensions),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn type_specifier(&self, type_specifier: &TypeSpecifier) -> String {
+        match type_specifier {
+            TypeSpecifier::Void => "void".to_string(),
+            TypeSpecifier::Char => "char".to_string(),
+            TypeSpecifier::Short => "short".to_string(),
+            TypeSpecifier::Int => "int".to_string(),
+            TypeSpecifier::Long => "long".to_string(),
+            TypeSpecifier::Float => "float".to_string(),
+            TypeSpecifier::Double => "double".to_string(),
+            TypeSpecifier::Signed => "signed".to_string(),
+            TypeSpecifier::Unsigned => "unsigned".to_string(),
+            TypeSpecifier::Bool => "_Bool".to_string(),
+            TypeSpecifier::Complex => "_Complex".to_string(),
+            TypeSpecifier::Atomic(type_name) => {
+                format!("_Atomic({})", self.type_name(&type_name.node))
+            }
+            TypeSpecifier::Struct(struct_type) => self.struct_type(&struct_type.node),
+            TypeSpecifier::Enum(enum_type) => self.enum_type(&enum_type.node),
+            TypeSpecifier::TypedefName(identifier) => identifier.node.name.clone(),
+            TypeSpecifier::TypeOf(type_of) => match &type_of.node {
+                TypeOf::Expression(expr) => format!("typeof({})", self.expression(expr, COMMA)),
+                TypeOf::Type(type_name) => format!("typeof({})", self.type_name(&type_name.node)),
+            },
+            TypeSpecifier::TS18661Float(float_type) => {
+                let (prefix, suffix) = ts18661_float_format(&float_type.format);
+                format!("_{prefix}{}{suffix}", float_type.width)
+            }
+        }
+    }
+
+    fn struct_type(&self, struct_type: &StructType) -> String {
+        let keyword = match struct_type.kind.node {
+            StructKind::Struct => "struct",
+            StructKind::Union => "union",
+        };
+        let header = match &struct_type.identifier {
+            Some(identifier) => format!("{keyword} {}", identifier.node.name),
+            None => keyword.to_string(),
+        };
+        let Some(declarations) = &struct_type.declarations else {
+            return header;
+        };
+
+        let mut printer = Printer::new(self.style, self.indent + 1);
+        for decl in declarations {
+            match &decl.node {
+                StructDeclaration::Field(field) => {
+                    let specifiers = printer.specifier_qualifiers(&field.node.specifiers);
+                    let declarators = field
+                        .node
+                        .declarators
+                        .iter()
+                        .map(|declarator| {
+                            let bit_width = declarator.node.bit_width.as_ref().map(|width| {
+                                format!(" : {}", printer.expression(width, CONDITIONAL))
+                            });
+                            let declarator = declarator
+                                .node
+                                .declarator
+                                .as_ref()
+                                .map(|declarator| printer.declarator(&declarator.node))
+                                .unwrap_or_default();
+                            match bit_width {
+                                Some(bit_width) => {
+                                    format!("{declarator}{bit_width}").trim_start().to_string()
+                                }
+                                None => declarator,
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    printer.line(&format!("{};", join_type(specifiers, declarators)));
+                }
+                StructDeclaration::StaticAssert(static_assert) => {
+                    let static_assert = printer.static_assert(&static_assert.node);
+                    printer.line(&format!("{static_assert};"));
+                }
+            }
+        }
+        format!(
+            "{}{}{}}}",
+            self.brace_after(&header),
+            printer.out,
+            self.indentation(self.indent)
+        )
+    }
+
+    fn enum_type(&self, enum_type: &EnumType) -> String {
+        let header = match &enum_type.identifier {
+            Some(identifier) => format!("enum {}", identifier.node.name),
+            None => "enum".to_string(),
+        };
+        if enum_type.enumerators.is_empty() {
+            return header;
+        }
+
+        let indentation = self.indentation(self.indent + 1);
+        let enumerators = enum_type
+            .enumerators
+            .iter()
+            .map(|enumerator| {
+                let enumerator = &enumerator.node;
+                let extensions = if enumerator.extensions.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {}", self.extensions(&enumerator.extensions))
+                };
+                let value = enumerator
+                    .expression
+                    .as_ref()
+                    .map(|expr| format!(" = {}", self.expression(expr, CONDITIONAL)))
+                    .unwrap_or_default();
+                format!(
+                    "{indentation}{}{extensions}{value}",
+                    enumerator.identifier.node.name
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+        format!(
+            "{}{enumerators}\n{}}}",
+            self.brace_after(&header),
+            self.indentation(self.indent)
+        )
+    }
+
+    fn declarator(&self, declarator: &Declarator) -> String {
+        // Pointers precede the declarator, and arrays and functions follow it in `derived`.
+        let mut prefix = String::new();
+        let mut suffix = String::new();
+        for derived in &declarator.derived {
+            match &derived.node {
+                DerivedDeclarator::Pointer(qualifiers) => {
+                    prefix.push('*');
+                    prefix.push_str(&self.pointer_qualifiers(qualifiers));
+                }
+                DerivedDeclarator::Block(qualifiers) => {
+                    prefix.push('^');
+                    prefix.push_str(&self.pointer_qualifiers(qualifiers));
+                }
+                DerivedDeclarator::Array(array) => {
+                    let mut qualifiers = array
+                        .node
+                        .qualifiers
+                        .iter()
+                        .map(|qualifier| type_qualifier(&qualifier.node).to_string())
+                        .collect::<Vec<_>>();
+                    match &array.node.size {
+                        ArraySize::Unknown => (),
+                        ArraySize::VariableUnknown => qualifiers.push("*".to_string()),
+                        ArraySize::VariableExpression(expr) => {
+                            qualifiers.push(self.expression(expr, ASSIGNMENT))
+                        }
+                        ArraySize::StaticExpression(expr) => {
+                            qualifiers.push(format!("static {}", self.expression(expr, ASSIGNMENT)))
+                        }
+                    }
+                    suffix.push_str(&format!("[{}]", qualifiers.join(" ")));
+                }
+                DerivedDeclarator::Function(func) => {
+                    let mut params = func
+                        .node
+                        .parameters
+                        .iter()
+                        .map(|param| self.parameter_declaration(&param.node))
+                        .collect::<Vec<_>>();
+                    if func.node.ellipsis == Ellipsis::Some {
+                        params.push("...".to_string());
+                    }
+                    suffix.push_str(&format!("({})", params.join(", ")));
+                }
+                DerivedDeclarator::KRFunction(identifiers) => {
+                    let identifiers = identifiers
+                        .iter()
+                        .map(|identifier| identifier.node.name.as_str())
+                        .collect::<Vec<_>>();
+                    suffix.push_str(&format!("({})", identifiers.join(", ")));
+                }
+            }
+        }
+
+        let kind = match &declarator.kind.node {
+            DeclaratorKind::Abstract => String::new(),
+            DeclaratorKind::Identifier(identifier) => identifier.node.name.clone(),
+            DeclaratorKind::Declarator(declarator) => {
+                format!("({})", self.declarator(&declarator.node))
+            }
+        };
+        let declarator_string = format!("{prefix}{kind}{suffix}");
+        let declarator_string = declarator_string.trim_end();
+        if declarator.extensions.is_empty() {
+            declarator_string.to_string()
+        } else {
+            format!(
+                "{declarator_string} {}",
+                self.extensions(&declarator.extensions)
+            )
+        }
+    }
+
+    /// Returns the qualifiers of a pointer, each followed by a space, e.g., `const ` of
+    /// `*const p`.
+    fn pointer_qualifiers(&self, qualifiers: &[Node<PointerQualifier>]) -> String {
+        qualifiers
+            .iter()
+            .map(|qualifier| match &qualifier.node {
+                PointerQualifier::TypeQualifier(qualifier) => {
+                    format!("{} ", type_qualifier(&qualifier.node))
+                }
+                PointerQualifier::Extension(extensions) => {
+                    format!("{} ", self.extensions(extensions))
+                }
+            })
+            .collect()
+    }
+
+    fn parameter_declaration(&self, param: &ParameterDeclaration) -> String {
+        let specifiers = self.declaration_specifiers(&param.specifiers);
+        let declarator = param
+            .declarator
+            .as_ref()
+            .map(|declarator| self.declarator(&declarator.node))
+            .unwrap_or_default();
+        let param_string = join_type(specifiers, declarator);
+        if param.extensions.is_empty() {
+            param_string
+        } else {
+            format!("{param_string} {}", self.extensions(&param.extensions))
+        }
+    }
+
+    fn type_name(&self, type_name: &TypeName) -> String {
+        let specifiers = self.specifier_qualifiers(&type_name.specifiers);
+        let declarator = type_name
+            .declarator
+            .as_ref()
+            .map(|declarator| self.declarator(&declarator.node))
+            .unwrap_or_default();
+        join_type(specifiers, declarator)
+    }
+
+    fn extensions(&self, extensions: &[Node<Extension>]) -> String {
+        let mut attributes = Vec::new();
+        let mut strings = Vec::new();
+        for extension in extensions {
+            match &extension.node {
+                Extension::Attribute(attribute) => {
+                    if attribute.arguments.is_empty() {
+                        attributes.push(attribute.name.node.clone());
+                    } else {
+                        let arguments = attribute
+                            .arguments
+                            .iter()
+                            .map(|arg| self.expression(arg, ASSIGNMENT))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        attributes.push(format!("{}({arguments})", attribute.name.node));
+                    }
+                }
+                Extension::AsmLabel(label) => {
+                    strings.push(format!("__asm__({})", string_literal(&label.node)))
+                }
+                Extension::AvailabilityAttribute(availability) => {
+                    let mut clauses = vec![availability.node.platform.node.name.clone()];
+                    for clause in &availability.node.clauses {
+                        clauses.push(match &clause.node {
+                            AvailabilityClause::Introduced(version) => {
+                                format!("introduced={}", availability_version(&version.node))
+                            }
+                            AvailabilityClause::Deprecated(version) => {
+                                format!("deprecated={}", availability_version(&version.node))
+                            }
+                            AvailabilityClause::Obsoleted(version) => {
+                                format!("obsoleted={}", availability_version(&version.node))
+                            }
+                            AvailabilityClause::Unavailable => "unavailable".to_string(),
+                            AvailabilityClause::Message(message) => {
+                                format!("message={}", string_literal(&message.node))
+                            }
+                            AvailabilityClause::Replacement(replacement) => {
+                                format!("replacement={}", string_literal(&replacement.node))
+                            }
+                        });
+                    }
+                    attributes.push(format!("availability({})", clauses.join(", ")));
+                }
+            }
+        }
+
+        if !attributes.is_empty() {
+            strings.push(format!("__attribute__(({}))", attributes.join(", ")));
+        }
+        strings.join(" ")
+    }
+
+    fn initializer(&self, initializer: &Initializer) -> String {
+        match initializer {
+            Initializer::Expression(expr) => self.expression(expr, ASSIGNMENT),
+            Initializer::List(items) => self.initializer_list(items),
+        }
+    }
+
+    fn initializer_list(&self, items: &[Node<InitializerListItem>]) -> String {
+        let items = items
+            .iter()
+            .map(|item| {
+                let initializer = self.initializer(&item.node.initializer.node);
+                if item.node.designation.is_empty() {
+                    return initializer;
+                }
+
+                let designation = item
+                    .node
+                    .designation
+                    .iter()
+                    .map(|designator| match &designator.node {
+                        Designator::Index(expr) => {
+                            format!("[{}]", self.expression(expr, CONDITIONAL))
+                        }
+                        Designator::Member(identifier) => format!(".{}", identifier.node.name),
+                        Designator::Range(range) => format!(
+                            "[{} ... {}]",
+                            self.expression(&range.node.from, CONDITIONAL),
+                            self.expression(&range.node.to, CONDITIONAL)
+                        ),
+                    })
+                    .collect::<String>();
+                format!("{designation} = {initializer}")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{{{items}}}")
+    }
+
+    /// Returns the expression `expr` as an operand requiring `precedence`, parenthesized if it
+    /// binds looser than that.
+    fn expression(&self, expr: &Node<Expression>, precedence: Precedence) -> String {
+        let (expr_string, expr_precedence) = self.expression_with_precedence(&expr.node);
+        if expr_precedence < precedence {
+            format!("({expr_string})")
+        } else {
+            expr_string
+        }
+    }
+
+    fn expression_with_precedence(&self, expr: &Expression) -> (String, Precedence) {
+        match expr {
+            Expression::Identifier(identifier) => (identifier.node.name.clone(), PRIMARY),
+            Expression::Constant(constant) => (self::constant(&constant.node), PRIMARY),
+            Expression::StringLiteral(literal) => (string_literal(&literal.node), PRIMARY),
+            Expression::GenericSelection(generic) => {
+                let mut operands = vec![self.expression(&generic.node.expression, ASSIGNMENT)];
+                for association in &generic.node.associations {
+                    operands.push(match &association.node {
+                        GenericAssociation::Type(association) => format!(
+                            "{}: {}",
+                            self.type_name(&association.node.type_name.node),
+                            self.expression(&association.node.expression, ASSIGNMENT)
+                        ),
+                        GenericAssociation::Default(expr) => {
+                            format!("default: {}", self.expression(expr, ASSIGNMENT))
+                        }
+                    });
+                }
+                (format!("_Generic({})", operands.join(", ")), PRIMARY)
+            }
+            Expression::Member(member) => {
+                let operator = match member.node.operator.node {
+                    MemberOperator::Direct => ".",
+                    MemberOperator::Indirect => "->",
+                };
+                let expr = format!(
+                    "{}{operator}{}",
+                    self.expression(&member.node.expression, POSTFIX),
+                    member.node.identifier.node.name
+                );
+                (expr, POSTFIX)
+            }
+            Expression::Call(call) => {
+                let arguments = call
+                    .node
+                    .arguments
+                    .iter()
+                    .map(|arg| self.expression(arg, ASSIGNMENT))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let expr = format!(
+                    "{}({arguments})",
+                    self.expression(&call.node.callee, POSTFIX)
+                );
+                (expr, POSTFIX)
+            }
+            Expression::CompoundLiteral(literal) => {
+                let expr = format!(
+                    "({}){}",
+                    self.type_name(&literal.node.type_name.node),
+                    self.initializer_list(&literal.node.initializer_list)
+                );
+                (expr, POSTFIX)
+            }
+            Expression::SizeOfTy(size_of) => (
+                format!("sizeof({})", self.type_name(&size_of.node.0.node)),
+                UNARY,
+            ),
+            Expression::SizeOfVal(size_of) => (
+                format!("sizeof({})", self.expression(&size_of.node.0, COMMA)),
+                UNARY,
+            ),
+            Expression::AlignOf(align_of) => (
+                format!("_Alignof({})", self.type_name(&align_of.node.0.node)),
+                UNARY,
+            ),
+            Expression::UnaryOperator(unary) => {
+                let operand = &unary.node.operand;
+                let (operator, operand_precedence) = match unary.node.operator.node {
+                    UnaryOperator::PostIncrement => {
+                        return (format!("{}++", self.expression(operand, POSTFIX)), POSTFIX);
+                    }
+                    UnaryOperator::PostDecrement => {
+                        return (format!("{}--", self.expression(operand, POSTFIX)), POSTFIX);
+                    }
+                    UnaryOperator::PreIncrement => ("++", UNARY),
+                    UnaryOperator::PreDecrement => ("--", UNARY),
+                    UnaryOperator::Address => ("&", CAST),
+                    UnaryOperator::Indirection => ("*", CAST),
+                    UnaryOperator::Plus => ("+", CAST),
+                    UnaryOperator::Minus => ("-", CAST),
+                    UnaryOperator::Complement => ("~", CAST),
+                    UnaryOperator::Negate => ("!", CAST),
+                };
+                let operand = self.expression(operand, operand_precedence);
+                // Separates the tokens that would be merged, e.g., `- -x` from `--x`.
+                let separator = match operator.chars().last() {
+                    Some(last @ ('+' | '-' | '&')) if operand.starts_with(last) => " ",
+                    _ => "",
+                };
+                (format!("{operator}{separator}{operand}"), UNARY)
+            }
+            Expression::Cast(cast) => {
+                let expr = format!(
+                    "({}){}",
+                    self.type_name(&cast.node.type_name.node),
+                    self.expression(&cast.node.expression, CAST)
+                );
+                (expr, CAST)
+            }
+            Expression::BinaryOperator(binary) => {
+                let lhs = &binary.node.lhs;
+                let rhs = &binary.node.rhs;
+                if binary.node.operator.node == BinaryOperator::Index {
+                    let expr = format!(
+                        "{}[{}]",
+                        self.expression(lhs, POSTFIX),
+                        self.expression(rhs, COMMA)
+                    );
+                    return (expr, POSTFIX);
+                }
+
+                let (precedence, operator) = binary_operator(&binary.node.operator.node);
+                // Assignments are right-associative, and the others are left-associative.
+                let (lhs_precedence, rhs_precedence) = if precedence == ASSIGNMENT {
+                    (UNARY, ASSIGNMENT)
+                } else {
+                    (precedence, precedence + 1)
+                };
+                let expr = format!(
+                    "{} {operator} {}",
+                    self.expression(lhs, lhs_precedence),
+                    self.expression(rhs, rhs_precedence)
+                );
+                (expr, precedence)
+            }
+            Expression::Conditional(conditional) => {
+                let expr = format!(
+                    "{} ? {} : {}",
+                    self.expression(&conditional.node.condition, CONDITIONAL + 1),
+                    self.expression(&conditional.node.then_expression, COMMA),
+                    self.expression(&conditional.node.else_expression, CONDITIONAL)
+                );
+                (expr, CONDITIONAL)
+            }
+            Expression::Comma(exprs) => {
+                let expr = exprs
+                    .iter()
+                    .map(|expr| self.expression(expr, ASSIGNMENT))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                (expr, COMMA)
+            }
+            Expression::OffsetOf(offset_of) => {
+                let designator = &offset_of.node.designator.node;
+                let mut member = designator.base.node.name.clone();
+                for offset_member in &designator.members {
+                    match &offset_member.node {
+                        OffsetMember::Member(identifier) => {
+                            member.push_str(&format!(".{}", identifier.node.name))
+                        }
+                        OffsetMember::IndirectMember(identifier) => {
+                            member.push_str(&format!("->{}", identifier.node.name))
+                        }
+                        OffsetMember::Index(expr) => {
+                            member.push_str(&format!("[{}]", self.expression(expr, COMMA)))
+                        }
+                    }
+                }
+                let expr = format!(
+                    "__builtin_offsetof({}, {member})",
+                    self.type_name(&offset_of.node.type_name.node)
+                );
+                (expr, PRIMARY)
+            }
+            Expression::VaArg(va_arg) => {
+                let expr = format!(
+                    "__builtin_va_arg({}, {})",
+                    self.expression(&va_arg.node.va_list, ASSIGNMENT),
+                    self.type_name(&va_arg.node.type_name.node)
+                );
+                (expr, PRIMARY)
+            }
+            Expression::Statement(stmt) => {
+                let mut printer = Printer::new(self.style, self.indent);
+                printer.statement(&stmt.node);
+                let stmt = printer.out.trim();
+                (format!("({stmt})"), PRIMARY)
+            }
+        }
+    }
+}
+
+/// Checks if `stmt` ends with an `if` statement without `else`, to which a following `else` would
+/// belong.
+fn has_trailing_if(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::If(if_stmt) => match &if_stmt.node.else_statement {
+            Some(else_stmt) => has_trailing_if(&else_stmt.node),
+            None => true,
+        },
+        Statement::Labeled(labeled) => has_trailing_if(&labeled.node.statement.node),
+        Statement::Switch(switch) => has_trailing_if(&switch.node.statement.node),
+        Statement::While(while_stmt) => has_trailing_if(&while_stmt.node.statement.node),
+        Statement::For(for_stmt) => has_trailing_if(&for_stmt.node.statement.node),
+        _ => false,
+    }
+}
+
+/// Joins the specifiers and the declarator of a type, e.g., `int` and `*p` to `int *p`.
+fn join_type(specifiers: String, declarator: String) -> String {
+    if declarator.is_empty() {
+        specifiers
+    } else if specifiers.is_empty() {
+        declarator
+    } else if declarator.starts_with('[') {
+        format!("{specifiers}{declarator}")
+    } else {
+        format!("{specifiers} {declarator}")
+    }
+}
+
+fn constant(constant: &Constant) -> String {
+    match constant {
+        Constant::Integer(integer) => {
+            let prefix = match integer.base {
+                IntegerBase::Decimal => "",
+                IntegerBase::Octal => "0",
+                IntegerBase::Hexadecimal => "0x",
+                IntegerBase::Binary => "0b",
+            };
+            let unsigned = if integer.suffix.unsigned { "u" } else { "" };
+            let size = match integer.suffix.size {
+                IntegerSize::Int => "",
+                IntegerSize::Long => "l",
+                IntegerSize::LongLong => "ll",
+            };
+            let imaginary = if integer.suffix.imaginary { "i" } else { "" };
+            format!("{prefix}{}{unsigned}{size}{imaginary}", integer.number)
+        }
+        Constant::Float(float) => {
+            let prefix = match float.base {
+                FloatBase::Decimal => "",
+                FloatBase::Hexadecimal => "0x",
+            };
+            let format = match &float.suffix.format {
+                FloatFormat::Float => "f".to_string(),
+                FloatFormat::Double => String::new(),
+                FloatFormat::LongDouble => "l".to_string(),
+                FloatFormat::TS18661Format(float_type) => {
+                    let (prefix, suffix) = ts18661_float_format(&float_type.format);
+                    let prefix = if prefix == "Float" { "f" } else { "d" };
+                    format!("{prefix}{}{suffix}", float_type.width)
+                }
+            };
+            let imaginary = if float.suffix.imaginary { "i" } else { "" };
+            format!("{prefix}{}{format}{imaginary}", float.number)
+        }
+        Constant::Character(literal) => literal.clone(),
+    }
+}
+
+/// Returns the name and the suffix of a TS 18661 floating type, e.g., `Float` and `x` of
+/// `_Float64x`.
+fn ts18661_float_format(format: &TS18661FloatFormat) -> (&'static str, &'static str) {
+    match format {
+        TS18661FloatFormat::BinaryInterchange => ("Float", ""),
+        TS18661FloatFormat::BinaryExtended => ("Float", "x"),
+        TS18661FloatFormat::DecimalInterchange => ("Decimal", ""),
+        TS18661FloatFormat::DecimalExtended => ("Decimal", "x"),
+    }
+}
+
+fn string_literal(literal: &StringLiteral) -> String {
+    literal.join(" ")
+}
+
+fn storage_class_specifier(storage_class: &StorageClassSpecifier) -> &'static str {
+    match storage_class {
+        StorageClassSpecifier::Typedef => "typedef",
+        StorageClassSpecifier::Extern => "extern",
+        StorageClassSpecifier::Static => "static",
+        StorageClassSpecifier::ThreadLocal => "_Thread_local",
+        StorageClassSpecifier::Auto => "auto",
+        StorageClassSpecifier::Register => "register",
+    }
+}
+
+fn type_qualifier(qualifier: &TypeQualifier) -> &'static str {
+    match qualifier {
+        TypeQualifier::Const => "const",
+        TypeQualifier::Restrict => "restrict",
+        TypeQualifier::Volatile => "volatile",
+        TypeQualifier::Nonnull => "_Nonnull",
+        TypeQualifier::NullUnspecified => "_Null_unspecified",
+        TypeQualifier::Nullable => "_Nullable",
+        TypeQualifier::Atomic => "_Atomic",
+    }
+}
+
+fn availability_version(version: &AvailabilityVersion) -> String {
+    let mut result = version.major.clone();
+    for part in [&version.minor, &version.subminor].into_iter().flatten() {
+        result.push('.');
+        result.push_str(part);
     }
+    result
 }
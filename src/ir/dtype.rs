@@ -34,9 +34,15 @@ struct BaseDtype {
     typedef_name: Option<String>,
     struct_type: Option<ast::StructType>,
     is_const: bool,
-    is_typedef: bool,
+    storage_class: Option<ast::StorageClassSpecifier>,
 }
 
+/// The bit offset of a bit-field in its storage unit, and the width of the bit-field.
+pub type BitField = (usize, usize);
+
+/// A field of a struct, and its width in bits if it is a bit-field.
+pub type StructField = (Named<Dtype>, Option<usize>);
+
 /// TODO(document)
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum Dtype {
@@ -93,6 +99,13 @@ pub enum Dtype {
         /// TODO(document)
         is_const: bool,
 
+        /// Whether the fields overlap each other, i.e., the type is a `union`.
+        is_union: bool,
+
+        /// The width in bits of each field that is a bit-field, in the same order as `fields`.
+        /// Empty if the type has no bit-fields.
+        bit_widths: Vec<Option<usize>>,
+
         /// TODO(document)
         size_align_offsets: Option<(usize, usize, Vec<usize>)>,
     },
@@ -103,6 +116,10 @@ pub enum Dtype {
 
         /// TODO(document)
         params: Vec<Dtype>,
+
+        /// Whether the function takes variable arguments after `params`, i.e., its parameter list
+        /// ends with `...`.
+        is_variadic: bool,
     },
     /// TODO(document)
     Typedef {
@@ -130,9 +147,20 @@ impl BaseDtype {
         storage_class: &ast::StorageClassSpecifier,
     ) -> Result<(), DtypeError> {
         match storage_class {
-            ast::StorageClassSpecifier::Typedef => {
-                // duplicate `typedef` is allowed
-                self.is_typedef = true;
+            ast::StorageClassSpecifier::Typedef
+            | ast::StorageClassSpecifier::Static
+            | ast::StorageClassSpecifier::Extern => {
+                // duplicate storage class specifier is allowed
+                if self
+                    .storage_class
+                    .as_ref()
+                    .is_some_and(|prev| prev != storage_class)
+                {
+                    return Err(DtypeError::Misc {
+                        message: "multiple storage classes in declaration specifiers".to_string(),
+                    });
+                }
+                self.storage_class = Some(storage_class.clone());
                 Ok(())
             }
             scs => Err(DtypeError::Misc {
@@ -196,6 +224,15 @@ impl BaseDtype {
                 }
                 self.struct_type = Some(struct_type.node.clone());
             }
+            // Enumerators are constants of type `int`, and so is an enum type.
+            ast::TypeSpecifier::Enum(_) => {
+                if self.scalar.is_some() {
+                    return Err(DtypeError::Misc {
+                        message: "two or more scalar types in declaration specifiers".to_string(),
+                    });
+                }
+                self.scalar = Some(ast::TypeSpecifier::Int);
+            }
             _ => todo!("apply_type_specifier: support {:?}", type_specifier),
         }
 
@@ -265,6 +302,9 @@ impl BaseDtype {
             ast::DeclarationSpecifier::TypeQualifier(type_qualifier) => {
                 self.apply_type_qualifier(&type_qualifier.node)?
             }
+            // `_Alignas` specifies the alignment of the declared object, not of its type, so it
+            // is applied to the object by irgen.
+            ast::DeclarationSpecifier::Alignment(_) => (),
             ds => {
                 return Err(DtypeError::Misc {
                     message: format!("unsupported declaration qualifier: {ds:#?}"),
@@ -358,9 +398,14 @@ impl TryFrom<BaseDtype> for Dtype {
                 });
             }
 
-            let dtype = Self::typedef(name).set_const(spec.is_const);
+            // `__builtin_va_list`, to which `va_list` of `<stdarg.h>` is defined, is predefined.
+            let dtype = if name == Self::BUILTIN_VA_LIST {
+                Self::va_list()
+            } else {
+                Self::typedef(name)
+            };
 
-            return Ok(dtype);
+            return Ok(dtype.set_const(spec.is_const));
         }
 
         if let Some(struct_type) = spec.struct_type {
@@ -375,18 +420,20 @@ impl TryFrom<BaseDtype> for Dtype {
             }
 
             assert!(struct_type.identifier.is_some() || struct_type.declarations.is_some());
-            assert_eq!(struct_type.kind.node, ast::StructKind::Struct);
+            let is_union = struct_type.kind.node == ast::StructKind::Union;
             let struct_name = struct_type.identifier.map(|i| i.node.name);
-            let fields = if let Some(declarations) = struct_type.declarations {
-                let fields = declarations
+            let (fields, bit_widths) = if let Some(declarations) = struct_type.declarations {
+                let (fields, bit_widths): (Vec<_>, _) = declarations
                     .iter()
                     .map(|d| Self::try_from_ast_struct_declaration(&d.node))
                     .collect::<Result<Vec<_>, _>>()?
-                    .concat();
+                    .concat()
+                    .into_iter()
+                    .unzip();
 
-                Some(fields)
+                (Some(fields), bit_widths)
             } else {
-                None
+                (None, Vec::new())
             };
 
             if let Some(fields) = &fields {
@@ -398,13 +445,14 @@ impl TryFrom<BaseDtype> for Dtype {
                 }
             }
 
-            let dtype = Self::structure(struct_name, fields).set_const(spec.is_const);
+            let dtype = Self::structure_or_union(is_union, struct_name, fields, bit_widths)
+                .set_const(spec.is_const);
 
             return Ok(dtype);
         }
 
         // Creates `dtype` from the scalar.
-        let mut dtype = if let Some(t) = spec.scalar {
+        let mut dtype = if let Some(t) = &spec.scalar {
             match t {
                 ast::TypeSpecifier::Void => Self::unit(),
                 ast::TypeSpecifier::Bool => Self::BOOL,
@@ -418,30 +466,23 @@ impl TryFrom<BaseDtype> for Dtype {
             Self::default()
         };
 
-        let number_of_modifier = spec.size_modifiers.len();
-        dtype = match number_of_modifier {
-            0 => dtype,
-            1 => match spec.size_modifiers[0] {
-                ast::TypeSpecifier::Short => Self::SHORT,
-                ast::TypeSpecifier::Long => Self::LONG,
-                _ => panic!(
-                    "Dtype::try_from::<BaseDtype>: {:?} is not a size modifier",
-                    spec.size_modifiers
-                ),
-            },
-            2 => {
-                if spec.size_modifiers[0] != ast::TypeSpecifier::Long
-                    || spec.size_modifiers[1] != ast::TypeSpecifier::Long
-                {
-                    return Err(DtypeError::Misc {
-                        message: "two or more size modifiers in declaration specifiers".to_string(),
-                    });
-                }
-                Self::LONGLONG
+        dtype = match (&spec.scalar, spec.size_modifiers.as_slice()) {
+            (_, []) => dtype,
+            (None | Some(ast::TypeSpecifier::Int), [ast::TypeSpecifier::Short]) => Self::SHORT,
+            (None | Some(ast::TypeSpecifier::Int), [ast::TypeSpecifier::Long]) => Self::LONG,
+            (
+                None | Some(ast::TypeSpecifier::Int),
+                [ast::TypeSpecifier::Long, ast::TypeSpecifier::Long],
+            ) => Self::LONGLONG,
+            (Some(ast::TypeSpecifier::Double), [ast::TypeSpecifier::Long]) => Self::LONG_DOUBLE,
+            (None | Some(ast::TypeSpecifier::Int), _) => {
+                return Err(DtypeError::Misc {
+                    message: "two or more size modifiers in declaration specifiers".to_string(),
+                });
             }
             _ => {
                 return Err(DtypeError::Misc {
-                    message: "two or more size modifiers in declaration specifiers".to_string(),
+                    message: "size modifiers cannot be applied to the type specifier".to_string(),
                 });
             }
         };
@@ -544,6 +585,9 @@ impl Dtype {
     /// TODO(document)
     pub const SIZE_OF_DOUBLE: usize = 8;
 
+    /// The size of `long double`, which is the IEEE 754 quadruple precision format in LP64.
+    pub const SIZE_OF_LONG_DOUBLE: usize = 16;
+
     /// TODO(document)
     /// A boolean value cannot be signed.
     pub const BOOL: Self = Self::Int {
@@ -573,6 +617,12 @@ impl Dtype {
     /// TODO(document)
     pub const DOUBLE: Self = Self::float(Self::SIZE_OF_DOUBLE * Self::BITS_OF_BYTE);
 
+    /// TODO(document)
+    pub const LONG_DOUBLE: Self = Self::float(Self::SIZE_OF_LONG_DOUBLE * Self::BITS_OF_BYTE);
+
+    /// The name of the predefined type of `va_list`.
+    pub const BUILTIN_VA_LIST: &str = "__builtin_va_list";
+
     /// TODO(document)
     #[inline]
     pub const fn unit() -> Self {
@@ -607,6 +657,15 @@ impl Dtype {
         }
     }
 
+    /// The type of `va_list`, which points to the next variable argument.
+    ///
+    /// As in RISC-V LP64D, the variable arguments are laid out in 8-byte slots in memory, so a
+    /// `va_list` is a plain pointer into them.
+    #[inline]
+    pub fn va_list() -> Self {
+        Self::pointer(Self::CHAR)
+    }
+
     /// TODO(document)
     ///
     /// # Examples
@@ -642,10 +701,78 @@ impl Dtype {
             name,
             fields,
             is_const: false,
+            is_union: false,
+            bit_widths: Vec::new(),
+            size_align_offsets: None,
+        }
+    }
+
+    /// Creates a `union` type, whose fields all start at offset 0.
+    #[inline]
+    pub fn union(name: Option<String>, fields: Option<Vec<Named<Self>>>) -> Self {
+        Self::Struct {
+            name,
+            fields,
+            is_const: false,
+            is_union: true,
+            bit_widths: Vec::new(),
             size_align_offsets: None,
         }
     }
 
+    #[inline]
+    fn structure_or_union(
+        is_union: bool,
+        name: Option<String>,
+        fields: Option<Vec<Named<Self>>>,
+        bit_widths: Vec<Option<usize>>,
+    ) -> Self {
+        let dtype = if is_union {
+            Self::union(name, fields)
+        } else {
+            Self::structure(name, fields)
+        };
+        dtype.set_bit_widths(bit_widths)
+    }
+
+    /// Sets the width in bits of each field that is a bit-field.
+    ///
+    /// `bit_widths` is either empty or as long as the fields of `self`.
+    #[inline]
+    #[must_use]
+    pub fn set_bit_widths(self, bit_widths: Vec<Option<usize>>) -> Self {
+        if let Self::Struct {
+            name,
+            fields,
+            is_const,
+            is_union,
+            size_align_offsets,
+            ..
+        } = self
+        {
+            let bit_widths = if bit_widths.iter().all(Option::is_none) {
+                Vec::new()
+            } else {
+                bit_widths
+            };
+            assert!(
+                bit_widths.is_empty()
+                    || fields.as_ref().is_some_and(|f| f.len() == bit_widths.len())
+            );
+
+            Self::Struct {
+                name,
+                fields,
+                is_const,
+                is_union,
+                bit_widths,
+                size_align_offsets,
+            }
+        } else {
+            panic!("bit-fields only be applied to `Dtype::Struct`")
+        }
+    }
+
     pub fn fill_size_align_offsets_of_struct(
         self,
         structs: &HashMap<String, Option<Dtype>>,
@@ -654,6 +781,8 @@ impl Dtype {
             name,
             fields,
             is_const,
+            is_union,
+            bit_widths,
             size_align_offsets,
         } = self
         {
@@ -662,47 +791,16 @@ impl Dtype {
             );
 
             let fields = fields.unwrap();
-            if fields.is_empty() {
-                return Ok(Self::Struct {
-                    name,
-                    fields: Some(fields),
-                    is_const,
-                    size_align_offsets: Some((0, 1, Vec::new())),
-                });
-            }
-
-            let align_of = fields
-                .iter()
-                .map(|f| f.size_align_of(structs))
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .map(|(_, a)| a)
-                .max()
-                .unwrap_or(0);
-
-            let mut offsets = Vec::new();
-            let mut offset = 0;
-            for field in &fields {
-                let (size_of_dtype, align_of_dtype) = field.size_align_of(structs)?;
-
-                let pad = if (offset % align_of_dtype) != 0 {
-                    align_of_dtype - (offset % align_of_dtype)
-                } else {
-                    0
-                };
-
-                offset += pad;
-                offsets.push(offset);
-
-                offset += size_of_dtype;
-            }
-
-            let size_of = ((offset - 1) / align_of + 1) * align_of;
+            let (size_of, align_of, layout) =
+                layout_of_fields(&fields, &bit_widths, is_union, structs)?;
+            let offsets = layout.into_iter().map(|(offset, _)| offset).collect();
 
             Ok(Self::Struct {
                 name,
                 fields: Some(fields),
                 is_const,
+                is_union,
+                bit_widths,
                 size_align_offsets: Some((size_of, align_of, offsets)),
             })
         } else {
@@ -717,6 +815,22 @@ impl Dtype {
         Self::Function {
             ret: Box::new(ret),
             params,
+            is_variadic: false,
+        }
+    }
+
+    /// Sets whether the function type takes variable arguments.
+    #[inline]
+    #[must_use]
+    pub fn set_variadic(self, is_variadic: bool) -> Self {
+        if let Self::Function { ret, params, .. } = self {
+            Self::Function {
+                ret,
+                params,
+                is_variadic,
+            }
+        } else {
+            panic!("variadic parameters only be applied to `Dtype::Function`")
         }
     }
 
@@ -773,6 +887,11 @@ impl Dtype {
         }
     }
 
+    #[inline]
+    pub fn is_union(&self) -> bool {
+        matches!(self, Self::Struct { is_union: true, .. })
+    }
+
     #[inline]
     pub fn get_struct_fields(&self) -> Option<&Option<Vec<Named<Self>>>> {
         if let Self::Struct { fields, .. } = self {
@@ -782,6 +901,40 @@ impl Dtype {
         }
     }
 
+    #[inline]
+    pub fn get_struct_bit_widths(&self) -> Option<&Vec<Option<usize>>> {
+        if let Self::Struct { bit_widths, .. } = self {
+            Some(bit_widths)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the bit offset in its storage unit and the width of each field that is a
+    /// bit-field, in the same order as the fields.
+    ///
+    /// The storage unit of a bit-field starts at the offset of the field, and is as large as the
+    /// declared type of the field. `self` must be a struct type with its definition.
+    pub fn get_struct_bit_fields(
+        &self,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<Vec<Option<BitField>>, DtypeError> {
+        let Self::Struct {
+            fields: Some(fields),
+            is_union,
+            bit_widths,
+            ..
+        } = self
+        else {
+            return Err(DtypeError::Misc {
+                message: "struct type with its definition is needed".to_string(),
+            });
+        };
+
+        let (_, _, layout) = layout_of_fields(fields, bit_widths, *is_union, structs)?;
+        Ok(layout.into_iter().map(|(_, bit_field)| bit_field).collect())
+    }
+
     #[inline]
     pub fn get_struct_size_align_offsets(&self) -> Option<&Option<(usize, usize, Vec<usize>)>> {
         if let Self::Struct {
@@ -796,13 +949,25 @@ impl Dtype {
 
     #[inline]
     pub fn get_function_inner(&self) -> Option<(&Self, &Vec<Self>)> {
-        if let Self::Function { ret, params } = self {
+        if let Self::Function { ret, params, .. } = self {
             Some((ret.deref(), params))
         } else {
             None
         }
     }
 
+    /// Returns whether the type is a function type taking variable arguments.
+    #[inline]
+    pub fn is_variadic(&self) -> bool {
+        matches!(
+            self,
+            Self::Function {
+                is_variadic: true,
+                ..
+            }
+        )
+    }
+
     #[inline]
     pub fn is_scalar(&self) -> bool {
         match self {
@@ -832,6 +997,31 @@ impl Dtype {
         }
     }
 
+    /// Checks if `self` is a struct type whose definition is not given in `structs`.
+    #[inline]
+    pub fn is_invalid_structure(&self, structs: &HashMap<String, Option<Dtype>>) -> bool {
+        // When `self` is `Dtype::Struct`, `structs` has real definition of `self`
+        if let Self::Struct { name, fields, .. } = self {
+            assert!(name.is_some() && fields.is_none());
+            let name = name.as_ref().unwrap();
+            structs.get(name).is_none_or(Option::is_none)
+        } else {
+            false
+        }
+    }
+
+    /// Applies the default argument promotions to an argument of `self`, e.g., `int` for `char`
+    /// and `double` for `float`.
+    pub fn promote_argument(&self) -> Self {
+        match self {
+            Self::Int { width, .. } if *width < Self::SIZE_OF_INT * Self::BITS_OF_BYTE => Self::INT,
+            Self::Float { width, .. } if *width < Self::SIZE_OF_DOUBLE * Self::BITS_OF_BYTE => {
+                Self::DOUBLE
+            }
+            _ => self.clone(),
+        }
+    }
+
     #[inline]
     /// Check if `Dtype` is constant. if it is constant, the variable of `Dtype` is not assignable.
     pub fn is_immutable(&self, structs: &HashMap<String, Option<Dtype>>) -> bool {
@@ -900,12 +1090,16 @@ impl Dtype {
             Self::Struct {
                 name,
                 fields,
+                is_union,
+                bit_widths,
                 size_align_offsets,
                 ..
             } => Self::Struct {
                 name,
                 fields,
                 is_const,
+                is_union,
+                bit_widths,
                 size_align_offsets,
             },
             Self::Function { .. } => self,
@@ -963,6 +1157,20 @@ impl Dtype {
         field_name: &str,
         structs: &HashMap<String, Option<Dtype>>,
     ) -> Option<(usize, Self)> {
+        self.get_offset_bit_field_struct_field(field_name, structs)
+            .map(|(offset, dtype, _)| (offset, dtype))
+    }
+
+    /// Like [`Dtype::get_offset_struct_field`], but also returns the bit offset in its storage
+    /// unit and the width of the field if it is a bit-field.
+    ///
+    /// The storage unit of a bit-field starts at the returned offset, and its type is the returned
+    /// dtype.
+    pub fn get_offset_bit_field_struct_field(
+        &self,
+        field_name: &str,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Option<(usize, Self, Option<BitField>)> {
         if let Self::Struct { name, .. } = self {
             let struct_name = name.as_ref().expect("`self` must have its name");
             let struct_type = structs
@@ -980,21 +1188,24 @@ impl Dtype {
                 .expect("`struct_type` must be struct type")
                 .as_ref()
                 .expect("`offsets` must be `Some`");
+            let bit_fields = struct_type
+                .get_struct_bit_fields(structs)
+                .expect("`struct_type` must have a valid layout");
 
             assert_eq!(fields.len(), offsets.len());
-            for (field, &offset) in fields.iter().zip(offsets) {
+            for ((field, &offset), bit_field) in fields.iter().zip(offsets).zip(bit_fields) {
                 if let Some(name) = field.name() {
                     if name == field_name {
-                        return Some((offset, field.deref().clone()));
+                        return Some((offset, field.deref().clone(), bit_field));
                     }
-                } else {
+                } else if bit_field.is_none() {
                     let field_dtype = field.deref();
-                    let Some((offset_inner, dtype)) =
-                        field_dtype.get_offset_struct_field(field_name, structs)
+                    let Some((offset_inner, dtype, bit_field)) =
+                        field_dtype.get_offset_bit_field_struct_field(field_name, structs)
                     else {
                         continue;
                     };
-                    return Some((offset + offset_inner, dtype));
+                    return Some((offset + offset_inner, dtype, bit_field));
                 }
             }
 
@@ -1024,16 +1235,17 @@ impl Dtype {
     ) -> Result<(Self, bool), DtypeError> {
         let mut spec = BaseDtype::default();
         BaseDtype::apply_declaration_specifiers(&mut spec, specifiers)?;
-        let is_typedef = spec.is_typedef;
+        let is_typedef = spec.storage_class == Some(ast::StorageClassSpecifier::Typedef);
         let dtype = Self::try_from(spec)?;
 
         Ok((dtype, is_typedef))
     }
 
-    /// Derive a data type and its name from the struct declaration.
+    /// Derive a data type and its name from the struct declaration, along with its width in bits if
+    /// it is a bit-field.
     pub fn try_from_ast_struct_declaration(
         declaration: &ast::StructDeclaration,
-    ) -> Result<Vec<Named<Self>>, DtypeError> {
+    ) -> Result<Vec<StructField>, DtypeError> {
         let field_decl = if let ast::StructDeclaration::Field(field_decl) = declaration {
             &field_decl.node
         } else {
@@ -1050,9 +1262,24 @@ impl Dtype {
             .declarators
             .iter()
             .map(|d| {
-                dtype
-                    .clone()
-                    .with_ast_declarator(&d.node.declarator.as_ref().unwrap().node)
+                let field = if let Some(declarator) = &d.node.declarator {
+                    dtype.clone().with_ast_declarator(&declarator.node)?
+                } else {
+                    Named::new(None, dtype.clone())
+                };
+                let bit_width = d
+                    .node
+                    .bit_width
+                    .as_ref()
+                    .map(|w| Self::bit_width_of_field(&field, &w.node))
+                    .transpose()?;
+                if bit_width.is_none() && field.name().is_none() {
+                    return Err(DtypeError::Misc {
+                        message: "declaration does not declare anything".to_string(),
+                    });
+                }
+
+                Ok((field, bit_width))
             })
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -1065,7 +1292,7 @@ impl Dtype {
             if let Self::Struct { name, .. } = &dtype {
                 if name.is_none() {
                     // Note that `const` qualifier has no effect in this time.
-                    return Ok(vec![Named::new(None, dtype.set_const(false))]);
+                    return Ok(vec![(Named::new(None, dtype.set_const(false)), None)]);
                 }
             }
 
@@ -1077,6 +1304,41 @@ impl Dtype {
         }
     }
 
+    /// Evaluates the width of the bit-field `field`.
+    fn bit_width_of_field(
+        field: &Named<Self>,
+        expr: &ast::Expression,
+    ) -> Result<usize, DtypeError> {
+        let name = field.name().map_or("<anonymous>", String::as_str);
+        let Self::Int { width, .. } = field.deref() else {
+            return Err(DtypeError::Misc {
+                message: format!(
+                    "bit-field `{name}` has non-integral type `{}`",
+                    field.deref()
+                ),
+            });
+        };
+
+        let (value, _, is_signed) = Constant::try_from(expr)
+            .ok()
+            .and_then(|c| c.get_int())
+            .ok_or_else(|| DtypeError::Misc {
+                message: format!("width of bit-field `{name}` is not an integer constant"),
+            })?;
+        if (is_signed && (value as i128) < 0) || value > *width as u128 {
+            return Err(DtypeError::Misc {
+                message: format!("width of bit-field `{name}` is out of range"),
+            });
+        }
+        if value == 0 && field.name().is_some() {
+            return Err(DtypeError::Misc {
+                message: format!("named bit-field `{name}` has zero width"),
+            });
+        }
+
+        Ok(value as usize)
+    }
+
     /// Generate `Dtype` based on declarator and `self` which has a scalar type.
     ///
     /// Let's say declaration is `const int * const * const a;`. In general `self` start with `const
@@ -1116,11 +1378,12 @@ impl Dtype {
                         let _unused = params.pop();
                     }
 
-                    Self::function(self, params)
+                    let is_variadic = func_decl.node.ellipsis == ast::Ellipsis::Some;
+                    Self::function(self, params).set_variadic(is_variadic)
                 }
-                ast::DerivedDeclarator::KRFunction(kr_func_decl) => {
-                    // K&R function is allowed only when it has no parameter
-                    assert!(kr_func_decl.is_empty());
+                ast::DerivedDeclarator::KRFunction(_) => {
+                    // The types of the parameters of K&R function are given by the declaration list
+                    // of its definition, which is handled by irgen.
                     Self::function(self, Vec::new())
                 }
                 ast::DerivedDeclarator::Block(_) => panic!(),
@@ -1157,8 +1420,9 @@ impl Dtype {
             });
         };
 
-        let constant = Constant::try_from(expr)
-            .expect("expression of `ArraySize::VariableExpression` must be constant value");
+        let constant = Constant::try_from(expr).map_err(|_| DtypeError::Misc {
+            message: "array size is not an integer literal".to_string(),
+        })?;
 
         let (value, _, is_signed) = constant.get_int().ok_or_else(|| DtypeError::Misc {
             message: "expression is not an integer constant expression".to_string(),
@@ -1191,6 +1455,8 @@ impl Dtype {
                 name,
                 fields,
                 is_const,
+                is_union,
+                bit_widths,
                 ..
             } => {
                 let (name, fields) = if let Some(fields) = fields {
@@ -1198,25 +1464,29 @@ impl Dtype {
                         .into_iter()
                         .map(|f| {
                             let (d, name) = f.destruct();
-                            let d = d.resolve_typedefs(typedefs).unwrap();
-                            Named::new(name, d)
+                            let d = d.resolve_typedefs(typedefs)?;
+                            Ok(Named::new(name, d))
                         })
-                        .collect::<Vec<_>>();
+                        .collect::<Result<Vec<_>, _>>()?;
                     (name, Some(fields))
                 } else {
                     assert!(name.is_some());
                     (name, fields)
                 };
-                Self::structure(name, fields).set_const(is_const)
+                Self::structure_or_union(is_union, name, fields, bit_widths).set_const(is_const)
             }
-            Self::Function { ret, params } => {
+            Self::Function {
+                ret,
+                params,
+                is_variadic,
+            } => {
                 let ret = ret.resolve_typedefs(typedefs)?;
                 let params = params
                     .into_iter()
                     .map(|p| p.resolve_typedefs(typedefs))
                     .collect::<Result<Vec<_>, _>>()?;
 
-                Self::function(ret, params)
+                Self::function(ret, params).set_variadic(is_variadic)
             }
             Self::Typedef { name, is_const } => {
                 let dtype = typedefs
@@ -1236,6 +1506,60 @@ impl Dtype {
 
     /// If the struct type has a definition, it is saved to the struct table
     /// and transformed to a struct type with no definition.
+    /// Renames the tags of the struct types in `self` with `rename`, which is given each tag and
+    /// whether the struct type is defined there, i.e., it has fields. A struct type is renamed
+    /// before its fields, since its tag is visible in the fields, e.g., `struct A { struct A *p; }`.
+    pub fn rename_structs<F>(self, rename: &mut F) -> Self
+    where
+        F: FnMut(&str, bool) -> String,
+    {
+        match self {
+            Self::Unit { .. } | Self::Int { .. } | Self::Float { .. } | Self::Typedef { .. } => {
+                self
+            }
+            Self::Pointer { inner, is_const } => {
+                Self::pointer(inner.rename_structs(rename)).set_const(is_const)
+            }
+            Self::Array { inner, size } => Self::Array {
+                inner: Box::new(inner.rename_structs(rename)),
+                size,
+            },
+            Self::Struct {
+                name,
+                fields,
+                is_const,
+                is_union,
+                bit_widths,
+                ..
+            } => {
+                let name = name.map(|name| rename(&name, fields.is_some()));
+                let fields = fields.map(|fields| {
+                    fields
+                        .into_iter()
+                        .map(|f| {
+                            let (d, name) = f.destruct();
+                            Named::new(name, d.rename_structs(rename))
+                        })
+                        .collect::<Vec<_>>()
+                });
+                Self::structure_or_union(is_union, name, fields, bit_widths).set_const(is_const)
+            }
+            Self::Function {
+                ret,
+                params,
+                is_variadic,
+            } => {
+                let ret = ret.rename_structs(rename);
+                let params = params
+                    .into_iter()
+                    .map(|p| p.rename_structs(rename))
+                    .collect::<Vec<_>>();
+
+                Self::function(ret, params).set_variadic(is_variadic)
+            }
+        }
+    }
+
     pub fn resolve_structs(
         self,
         structs: &mut HashMap<String, Option<Dtype>>,
@@ -1270,6 +1594,8 @@ impl Dtype {
                 name,
                 fields,
                 is_const,
+                is_union,
+                bit_widths,
                 ..
             } => {
                 let (name, fields) = if let Some(fields) = fields {
@@ -1277,10 +1603,10 @@ impl Dtype {
                         .into_iter()
                         .map(|f| {
                             let (d, name) = f.destruct();
-                            let d = d.resolve_structs(structs, tempid_counter).unwrap();
-                            Named::new(name, d)
+                            let d = d.resolve_structs(structs, tempid_counter)?;
+                            Ok(Named::new(name, d))
                         })
-                        .collect::<Vec<_>>();
+                        .collect::<Result<Vec<_>, _>>()?;
 
                     let name = if let Some(name) = name {
                         name
@@ -1289,7 +1615,12 @@ impl Dtype {
                         *tempid_counter += 1;
                         format!("%t{tempid}")
                     };
-                    let resolved_struct = Self::structure(Some(name.clone()), Some(fields));
+                    let resolved_struct = Self::structure_or_union(
+                        is_union,
+                        Some(name.clone()),
+                        Some(fields),
+                        bit_widths,
+                    );
                     let filled_struct =
                         resolved_struct.fill_size_align_offsets_of_struct(structs)?;
 
@@ -1309,22 +1640,30 @@ impl Dtype {
                     })?;
                     if struct_type.is_none() {
                         return Err(DtypeError::Misc {
-                            message: format!("variable has incomplete type 'struct {name}'"),
+                            message: format!(
+                                "variable has incomplete type '{} {name}'",
+                                if is_union { "union" } else { "struct" }
+                            ),
                         });
                     }
 
                     (name, fields)
                 };
-                Self::structure(Some(name), fields).set_const(is_const)
+                Self::structure_or_union(is_union, Some(name), fields, Vec::new())
+                    .set_const(is_const)
             }
-            Self::Function { ret, params } => {
+            Self::Function {
+                ret,
+                params,
+                is_variadic,
+            } => {
                 let ret = ret.resolve_structs(structs, tempid_counter)?;
                 let params = params
                     .into_iter()
                     .map(|p| p.resolve_structs(structs, tempid_counter))
                     .collect::<Result<Vec<_>, _>>()?;
 
-                Self::function(ret, params)
+                Self::function(ret, params).set_variadic(is_variadic)
             }
             Self::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
         };
@@ -1359,34 +1698,53 @@ impl fmt::Display for Dtype {
                 name,
                 fields,
                 is_const,
+                is_union,
+                bit_widths,
                 ..
             } => {
                 let fields = if let Some(fields) = fields {
-                    let fields = fields.iter().format_with(", ", |field, f| {
-                        f(&format_args!(
-                            "{}:{}",
-                            if let Some(name) = field.name() {
-                                name
-                            } else {
-                                "%anon"
-                            },
-                            field.deref()
-                        ))
-                    });
+                    let fields = fields
+                        .iter()
+                        .enumerate()
+                        .format_with(", ", |(i, field), f| {
+                            f(&format_args!(
+                                "{}:{}{}",
+                                if let Some(name) = field.name() {
+                                    name
+                                } else {
+                                    "%anon"
+                                },
+                                field.deref(),
+                                if let Some(Some(width)) = bit_widths.get(i) {
+                                    format!(":{width}")
+                                } else {
+                                    "".to_string()
+                                }
+                            ))
+                        });
                     format!(":<{fields}>")
                 } else {
                     "".to_string()
                 };
                 write!(
                     f,
-                    "{}struct {}{}",
+                    "{}{} {}{}",
                     if *is_const { "const " } else { "" },
+                    if *is_union { "union" } else { "struct" },
                     if let Some(name) = name { name } else { "%anon" },
                     fields
                 )
             }
-            Self::Function { ret, params } => {
-                write!(f, "[ret:{} params:({})]", ret, params.iter().format(", "))
+            Self::Function {
+                ret,
+                params,
+                is_variadic,
+            } => {
+                write!(f, "[ret:{} params:({}", ret, params.iter().format(", "))?;
+                if *is_variadic {
+                    write!(f, "{}...", if params.is_empty() { "" } else { ", " })?;
+                }
+                write!(f, ")]")
             }
             Self::Typedef { name, is_const } => {
                 write!(f, "{}{}", if *is_const { "const " } else { "" }, name)
@@ -1409,13 +1767,7 @@ fn check_no_duplicate_field(fields: &[Named<Dtype>], field_names: &mut HashSet<S
             if !field_names.insert(name.clone()) {
                 return false;
             }
-        } else {
-            let field_dtype = field.deref();
-            let fields = field_dtype
-                .get_struct_fields()
-                .expect("`field_dtype` must be a struct type")
-                .as_ref()
-                .expect("struct type must have its definition");
+        } else if let Some(Some(fields)) = field.deref().get_struct_fields() {
             if !check_no_duplicate_field(fields, field_names) {
                 return false;
             }
@@ -1424,3 +1776,71 @@ fn check_no_duplicate_field(fields: &[Named<Dtype>], field_names: &mut HashSet<S
 
     true
 }
+
+/// The size and alignment of a struct, and the offset and bit-field layout of each field.
+type LayoutOfFields = (usize, usize, Vec<(usize, Option<BitField>)>);
+
+/// Lays out `fields` following the RISC-V LP64 ABI.
+///
+/// Returns the size and alignment of the struct, and for each field its offset and, if it is a
+/// bit-field, its bit offset in the storage unit starting at the offset and its width. A bit-field
+/// is packed right after the previous field unless it would straddle a boundary of its declared
+/// type, and a zero-width bit-field pads to such a boundary. Unnamed bit-fields do not affect the
+/// alignment of the struct.
+fn layout_of_fields(
+    fields: &[Named<Dtype>],
+    bit_widths: &[Option<usize>],
+    is_union: bool,
+    structs: &HashMap<String, Option<Dtype>>,
+) -> Result<LayoutOfFields, DtypeError> {
+    let mut align_of = 1;
+    let mut size_in_bits = 0;
+    let mut bit_pos: usize = 0;
+    let mut layout = Vec::new();
+
+    for (i, field) in fields.iter().enumerate() {
+        let (size_of_dtype, align_of_dtype) = field.size_align_of(structs)?;
+        let unit = align_of_dtype * Dtype::BITS_OF_BYTE;
+
+        // All fields of a union start at offset 0.
+        if is_union {
+            bit_pos = 0;
+        }
+
+        match bit_widths.get(i).copied().flatten() {
+            None => {
+                let offset = bit_pos.div_ceil(unit) * align_of_dtype;
+                layout.push((offset, None));
+                bit_pos = (offset + size_of_dtype) * Dtype::BITS_OF_BYTE;
+                align_of = std::cmp::max(align_of, align_of_dtype);
+            }
+            Some(0) => {
+                let offset = bit_pos.div_ceil(unit) * align_of_dtype;
+                layout.push((offset, Some((0, 0))));
+                bit_pos = offset * Dtype::BITS_OF_BYTE;
+            }
+            Some(width) => {
+                if bit_pos / unit != (bit_pos + width - 1) / unit {
+                    bit_pos = bit_pos.div_ceil(unit) * unit;
+                }
+                let offset = bit_pos / unit * align_of_dtype;
+                layout.push((
+                    offset,
+                    Some((bit_pos - offset * Dtype::BITS_OF_BYTE, width)),
+                ));
+                bit_pos += width;
+                if field.name().is_some() {
+                    align_of = std::cmp::max(align_of, align_of_dtype);
+                }
+            }
+        }
+
+        size_in_bits = std::cmp::max(size_in_bits, bit_pos);
+    }
+
+    let size_of = size_in_bits
+        .div_ceil(Dtype::BITS_OF_BYTE)
+        .div_ceil(align_of)
+        * align_of;
+    Ok((size_of, align_of, layout))
+}
@@ -33,13 +33,26 @@ impl IsEquiv for Declaration {
     fn is_equiv(&self, other: &Self) -> bool {
         match (self, other) {
             (
-                Self::Variable { dtype, initializer },
+                Self::Variable {
+                    dtype,
+                    initializer,
+                    linkage,
+                    is_definition,
+                    alignment,
+                },
                 Self::Variable {
                     dtype: dtype_other,
                     initializer: initializer_other,
+                    linkage: linkage_other,
+                    is_definition: is_definition_other,
+                    alignment: alignment_other,
                 },
             ) => {
-                if dtype != dtype_other {
+                if dtype != dtype_other
+                    || linkage != linkage_other
+                    || is_definition != is_definition_other
+                    || alignment != alignment_other
+                {
                     return false;
                 }
 
@@ -49,13 +62,15 @@ impl IsEquiv for Declaration {
                 Self::Function {
                     signature,
                     definition,
+                    linkage,
                 },
                 Self::Function {
                     signature: signature_other,
                     definition: definition_other,
+                    linkage: linkage_other,
                 },
             ) => {
-                if signature != signature_other {
+                if signature != signature_other || linkage != linkage_other {
                     return false;
                 }
 
@@ -207,6 +222,17 @@ fn is_equiv_instruction(
                 && is_equiv_operand(offset, offset_other, map)
                 && dtype == dtype_other
         }
+        (Instruction::VaStart { ptr }, Instruction::VaStart { ptr: ptr_other })
+        | (Instruction::VaEnd { ptr }, Instruction::VaEnd { ptr: ptr_other }) => {
+            is_equiv_operand(ptr, ptr_other, map)
+        }
+        (
+            Instruction::VaArg { ptr, dtype },
+            Instruction::VaArg {
+                ptr: ptr_other,
+                dtype: dtype_other,
+            },
+        ) => is_equiv_operand(ptr, ptr_other, map) && dtype == dtype_other,
         _ => false,
     }
 }
@@ -329,7 +355,9 @@ fn is_equiv_arg(lhs: &JumpArg, rhs: &JumpArg, map: &HashMap<BlockId, BlockId>) -
 
 impl IsEquiv for FunctionDefinition {
     fn is_equiv(&self, other: &Self) -> bool {
-        if self.allocations != other.allocations {
+        if self.allocations != other.allocations
+            || self.allocation_alignments != other.allocation_alignments
+        {
             return false;
         }
 
@@ -0,0 +1,459 @@
+//! Initializers of global variables.
+//!
+//! The initializer of a global variable is kept as a C initializer in the IR. Its expressions are
+//! either arithmetic constants or address constants, i.e., the address of a global variable
+//! displaced by a constant number of bytes, and its list items may have designators. A character
+//! array may also be initialized by a string literal, e.g., `char buf[] = "abc";`.
+
+use core::convert::TryFrom;
+use core::ops::Deref;
+use std::collections::HashMap;
+
+use lang_c::ast;
+use lang_c::span::{Node, Span};
+
+use crate::ir::*;
+
+/// The value of an expression in the initializer of a global variable.
+#[derive(Debug, PartialEq, Clone)]
+pub enum InitialValue {
+    /// An arithmetic constant.
+    Constant(Constant),
+    /// The address of the global variable `name` plus `offset` bytes, pointing to `inner`.
+    Address {
+        name: String,
+        offset: isize,
+        inner: Dtype,
+    },
+}
+
+impl InitialValue {
+    /// Evaluates `expr` in the initializer of a global variable.
+    ///
+    /// `globals` looks up the dtype of a global variable or function by its name.
+    #[allow(clippy::result_unit_err)]
+    pub fn try_from_expression(
+        expr: &ast::Expression,
+        globals: &dyn Fn(&str) -> Option<Dtype>,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<Self, ()> {
+        if let Ok(constant) = Constant::try_from(expr) {
+            return Ok(Self::Constant(constant));
+        }
+
+        match expr {
+            // Arrays and functions decay to pointers.
+            ast::Expression::Identifier(identifier) => {
+                let name = &identifier.node.name;
+                let inner = match globals(name).ok_or(())? {
+                    Dtype::Array { inner, .. } => inner.deref().clone(),
+                    dtype @ Dtype::Function { .. } => dtype,
+                    _ => return Err(()),
+                };
+
+                Ok(Self::Address {
+                    name: name.clone(),
+                    offset: 0,
+                    inner,
+                })
+            }
+            ast::Expression::UnaryOperator(unary)
+                if unary.node.operator.node == ast::UnaryOperator::Address =>
+            {
+                let (name, offset, inner) =
+                    Self::try_from_lvalue(&unary.node.operand.node, globals, structs)?;
+                Ok(Self::Address {
+                    name,
+                    offset,
+                    inner,
+                })
+            }
+            ast::Expression::BinaryOperator(binary) => {
+                let lhs = Self::try_from_expression(&binary.node.lhs.node, globals, structs)?;
+                let rhs = Self::try_from_expression(&binary.node.rhs.node, globals, structs)?;
+                match (&binary.node.operator.node, lhs, rhs) {
+                    (
+                        ast::BinaryOperator::Plus,
+                        address @ Self::Address { .. },
+                        Self::Constant(c),
+                    )
+                    | (
+                        ast::BinaryOperator::Plus,
+                        Self::Constant(c),
+                        address @ Self::Address { .. },
+                    ) => address.displace(&c, false, structs),
+                    (
+                        ast::BinaryOperator::Minus,
+                        address @ Self::Address { .. },
+                        Self::Constant(c),
+                    ) => address.displace(&c, true, structs),
+                    _ => Err(()),
+                }
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// Evaluates the address of the object designated by `expr`, returning the global variable
+    /// containing it, its offset in bytes and its dtype.
+    fn try_from_lvalue(
+        expr: &ast::Expression,
+        globals: &dyn Fn(&str) -> Option<Dtype>,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<(String, isize, Dtype), ()> {
+        match expr {
+            ast::Expression::Identifier(identifier) => {
+                let name = &identifier.node.name;
+                let dtype = globals(name).ok_or(())?;
+                Ok((name.clone(), 0, dtype))
+            }
+            ast::Expression::Member(member) => {
+                let (name, offset, dtype) = match member.node.operator.node {
+                    ast::MemberOperator::Direct => {
+                        Self::try_from_lvalue(&member.node.expression.node, globals, structs)?
+                    }
+                    ast::MemberOperator::Indirect => {
+                        Self::try_from_expression(&member.node.expression.node, globals, structs)?
+                            .into_address()?
+                    }
+                };
+                if dtype.get_struct_name().is_none() {
+                    return Err(());
+                }
+
+                let (field_offset, field_dtype, bit_field) = dtype
+                    .get_offset_bit_field_struct_field(&member.node.identifier.node.name, structs)
+                    .ok_or(())?;
+                // The address of a bit-field cannot be taken.
+                if bit_field.is_some() {
+                    return Err(());
+                }
+
+                Ok((name, offset + field_offset as isize, field_dtype))
+            }
+            ast::Expression::BinaryOperator(binary)
+                if binary.node.operator.node == ast::BinaryOperator::Index =>
+            {
+                let lhs = Self::try_from_expression(&binary.node.lhs.node, globals, structs)?;
+                let rhs = Self::try_from_expression(&binary.node.rhs.node, globals, structs)?;
+                let address = match (lhs, rhs) {
+                    (address @ Self::Address { .. }, Self::Constant(c))
+                    | (Self::Constant(c), address @ Self::Address { .. }) => {
+                        address.displace(&c, false, structs)?
+                    }
+                    _ => return Err(()),
+                };
+
+                address.into_address()
+            }
+            ast::Expression::UnaryOperator(unary)
+                if unary.node.operator.node == ast::UnaryOperator::Indirection =>
+            {
+                Self::try_from_expression(&unary.node.operand.node, globals, structs)?
+                    .into_address()
+            }
+            _ => Err(()),
+        }
+    }
+
+    fn into_address(self) -> Result<(String, isize, Dtype), ()> {
+        if let Self::Address {
+            name,
+            offset,
+            inner,
+        } = self
+        {
+            Ok((name, offset, inner))
+        } else {
+            Err(())
+        }
+    }
+
+    /// Displaces the address `self` by `count` elements, following the pointer arithmetic of C.
+    fn displace(
+        self,
+        count: &Constant,
+        is_negative: bool,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<Self, ()> {
+        let (name, offset, inner) = self.into_address()?;
+        let (value, width, is_signed) = count.get_int().ok_or(())?;
+        let value = if is_signed {
+            sign_extension(value, width as u128) as i128
+        } else {
+            value as i128
+        };
+        let value = if is_negative { -value } else { value };
+
+        let (size, align) = inner.size_align_of(structs).map_err(|_| ())?;
+        let stride = std::cmp::max(size, align) as i128;
+
+        Ok(Self::Address {
+            name,
+            offset: isize::try_from(offset as i128 + value * stride).map_err(|_| ())?,
+            inner,
+        })
+    }
+}
+
+/// An item of an initializer list, resolved to the member of the aggregate it initializes.
+#[derive(Debug, Clone)]
+pub struct InitializerMember<'a> {
+    /// The index of the member, i.e., the index of an array element or of a struct field.
+    pub index: usize,
+    /// The dtype of the member.
+    pub dtype: Dtype,
+    /// The designators to be applied to the member, e.g., `.y` of `.p.y = 1`.
+    pub designators: Vec<Node<ast::Designator>>,
+    /// The initializer of the member, or of its part designated by `designators`.
+    pub initializer: &'a ast::Initializer,
+}
+
+/// Resolves the items of an initializer list for `dtype` to the members they initialize.
+///
+/// An item without designators initializes the member next to the previously initialized one.
+/// Unnamed bit-fields are skipped, and only one member of a union can be initialized. The index of
+/// an array designator must be an integer literal.
+#[allow(clippy::result_unit_err)]
+pub fn resolve_initializer_list<'a>(
+    items: &'a [Node<ast::InitializerListItem>],
+    dtype: &Dtype,
+    structs: &HashMap<String, Option<Dtype>>,
+) -> Result<Vec<InitializerMember<'a>>, ()> {
+    let mut members = Vec::new();
+    let mut next = 0;
+
+    match dtype {
+        Dtype::Array { inner, size } => {
+            for item in items {
+                let (index, designators) = match item.node.designation.split_first() {
+                    Some((designator, rest)) => {
+                        let ast::Designator::Index(expr) = &designator.node else {
+                            return Err(());
+                        };
+                        (index_of_designator(&expr.node)?, rest.to_vec())
+                    }
+                    None => (next, Vec::new()),
+                };
+                if index >= *size {
+                    return Err(());
+                }
+
+                members.push(InitializerMember {
+                    index,
+                    dtype: inner.deref().clone(),
+                    designators,
+                    initializer: &item.node.initializer.node,
+                });
+                next = index + 1;
+            }
+        }
+        Dtype::Struct { name, is_union, .. } => {
+            let name = name.as_ref().ok_or(())?;
+            let struct_type = structs.get(name).ok_or(())?.as_ref().ok_or(())?;
+            let fields = struct_type
+                .get_struct_fields()
+                .ok_or(())?
+                .as_ref()
+                .ok_or(())?;
+            let bit_widths = struct_type.get_struct_bit_widths().ok_or(())?;
+            let is_unnamed_bit_field =
+                |i: usize| fields[i].name().is_none() && matches!(bit_widths.get(i), Some(Some(_)));
+
+            for item in items {
+                let (index, designators) = match item.node.designation.split_first() {
+                    Some((designator, rest)) => {
+                        let ast::Designator::Member(member) = &designator.node else {
+                            return Err(());
+                        };
+                        let (index, mut designators) =
+                            designate_field(fields, &member.node.name, structs).ok_or(())?;
+                        designators.extend(rest.iter().cloned());
+                        (index, designators)
+                    }
+                    None => {
+                        let index = (next..fields.len())
+                            .find(|i| !is_unnamed_bit_field(*i))
+                            .ok_or(())?;
+                        (index, Vec::new())
+                    }
+                };
+                if *is_union && !members.is_empty() {
+                    return Err(());
+                }
+
+                members.push(InitializerMember {
+                    index,
+                    dtype: fields[index].deref().clone(),
+                    designators,
+                    initializer: &item.node.initializer.node,
+                });
+                next = index + 1;
+            }
+        }
+        _ => return Err(()),
+    }
+
+    Ok(members)
+}
+
+/// Returns the index of an array designator, which must be a non-negative integer literal.
+fn index_of_designator(expr: &ast::Expression) -> Result<usize, ()> {
+    let index = Constant::try_from(expr)?;
+    let (index, width, is_signed) = index.get_int().ok_or(())?;
+    if is_signed && (sign_extension(index, width as u128) as i128) < 0 {
+        return Err(());
+    }
+    Ok(index as usize)
+}
+
+/// Finds the field `field_name` in `fields`, returning its index and the designators to be applied
+/// to it. The field may be a member of an anonymous struct or union field, in which case it is
+/// designated in the anonymous field.
+fn designate_field(
+    fields: &[Named<Dtype>],
+    field_name: &str,
+    structs: &HashMap<String, Option<Dtype>>,
+) -> Option<(usize, Vec<Node<ast::Designator>>)> {
+    if let Some(index) = fields
+        .iter()
+        .position(|f| f.name().is_some_and(|n| n == field_name))
+    {
+        return Some((index, Vec::new()));
+    }
+
+    fields.iter().enumerate().find_map(|(index, f)| {
+        if f.name().is_some() {
+            return None;
+        }
+        let name = f.deref().get_struct_name()?.as_ref()?;
+        let inner_fields = structs.get(name)?.as_ref()?.get_struct_fields()?.as_ref()?;
+        let (_, mut designators) = designate_field(inner_fields, field_name, structs)?;
+
+        let identifier = ast::Identifier {
+            name: field_name.to_string(),
+        };
+        let designator = ast::Designator::Member(Node::new(identifier, Span::none()));
+        designators.insert(0, Node::new(designator, Span::none()));
+        Some((index, designators))
+    })
+}
+
+/// Decodes the string literal `literal` to its bytes, without the terminating null character.
+///
+/// The pieces of `literal` are concatenated, e.g., `"ab" "c"` is decoded to `abc`. Wide string
+/// literals with an encoding prefix, e.g., `L"abc"`, are unsupported.
+#[allow(clippy::result_unit_err)]
+pub fn string_literal_bytes(literal: &[String]) -> Result<Vec<u8>, ()> {
+    let mut bytes = Vec::new();
+
+    for piece in literal {
+        let piece = piece
+            .strip_prefix('"')
+            .and_then(|piece| piece.strip_suffix('"'))
+            .ok_or(())?;
+        unescape(piece, &mut bytes)?;
+    }
+
+    Ok(bytes)
+}
+
+/// Decodes the character constant `literal`, e.g., `'a'` or `'\n'`, to its value.
+///
+/// Multi-character constants and wide character constants with an encoding prefix, e.g., `L'a'`,
+/// are unsupported.
+pub(crate) fn character_constant_byte(literal: &str) -> Result<u8, ()> {
+    let piece = literal
+        .strip_prefix('\'')
+        .and_then(|piece| piece.strip_suffix('\''))
+        .ok_or(())?;
+    let mut bytes = Vec::new();
+    unescape(piece, &mut bytes)?;
+    match bytes.as_slice() {
+        [byte] => Ok(*byte),
+        _ => Err(()),
+    }
+}
+
+/// Decodes the characters `piece` of a string literal or a character constant to `bytes`.
+fn unescape(piece: &str, bytes: &mut Vec<u8>) -> Result<(), ()> {
+    let mut chars = piece.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+
+        let byte = match chars.next().ok_or(())? {
+            'n' => b'\n',
+            't' => b'\t',
+            'r' => b'\r',
+            'a' => 0x07,
+            'b' => 0x08,
+            'f' => 0x0c,
+            'v' => 0x0b,
+            c @ ('\\' | '\'' | '"' | '?') => c as u8,
+            c @ '0'..='7' => {
+                let mut value = c.to_digit(8).unwrap();
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            value = value * 8 + digit;
+                            let _ = chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                u8::try_from(value).map_err(|_| ())?
+            }
+            'x' => {
+                let mut value = 0u32;
+                let mut is_empty = true;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(16)) {
+                    value = value.checked_mul(16).ok_or(())? + digit;
+                    is_empty = false;
+                    let _ = chars.next();
+                }
+                if is_empty {
+                    return Err(());
+                }
+                u8::try_from(value).map_err(|_| ())?
+            }
+            _ => return Err(()),
+        };
+        bytes.push(byte);
+    }
+
+    Ok(())
+}
+
+/// Returns the size of an array of unknown size completed by `initializer`, e.g., `4` for
+/// `int a[] = {1, [3] = 2};` and for `char s[] = "abc";`.
+///
+/// The index of an array designator must be an integer literal.
+#[allow(clippy::result_unit_err)]
+pub fn array_size_of_initializer(initializer: &ast::Initializer) -> Result<usize, ()> {
+    match initializer {
+        ast::Initializer::Expression(expr) => match &expr.node {
+            ast::Expression::StringLiteral(literal) => {
+                Ok(string_literal_bytes(&literal.node)?.len() + 1)
+            }
+            _ => Err(()),
+        },
+        ast::Initializer::List(items) => {
+            let mut size = 0;
+            let mut next = 0;
+            for item in items {
+                let index = match item.node.designation.first().map(|d| &d.node) {
+                    Some(ast::Designator::Index(expr)) => index_of_designator(&expr.node)?,
+                    Some(_) => return Err(()),
+                    None => next,
+                };
+                next = index + 1;
+                size = std::cmp::max(size, next);
+            }
+            Ok(size)
+        }
+    }
+}
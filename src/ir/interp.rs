@@ -1,11 +1,16 @@
 use core::{fmt, iter, mem};
 use std::collections::HashMap;
 
-use ordered_float::OrderedFloat;
+use lang_c::span::{Node, Span};
+
 use thiserror::Error;
 
 use crate::ir::*;
 
+/// The size of a slot holding a variable argument, which is that of an integer register in RISC-V
+/// LP64D.
+const VARIADIC_ARG_SLOT_SIZE: usize = 8;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Value {
     Undef {
@@ -18,13 +23,13 @@ pub enum Value {
         is_signed: bool,
     },
     Float {
-        /// `value` may be `f32`, but it is possible to consider it as `f64`.
+        /// `value` may be `f32` or `f64`, but it is possible to consider it as `Quad`.
         ///
-        /// * Casting from an f32 to an f64 is perfect and lossless (f32 -> f64)
-        /// * Casting from an f64 to an f32 will produce the closest possible value (f64 -> f32)
+        /// * Casting from an f32 or an f64 to a `Quad` is perfect and lossless
+        /// * Casting from a `Quad` to an f32 or an f64 will produce the closest possible value
         ///
         /// For more details: <https://doc.rust-lang.org/stable/reference/expressions/operator-expr.html#type-cast-expressions>
-        value: OrderedFloat<f64>,
+        value: Quad,
         width: usize,
     },
     Pointer {
@@ -40,6 +45,11 @@ pub enum Value {
         name: String,
         fields: Vec<Named<Value>>,
     },
+    /// A `union` value. Its fields overlap, so it is kept as the raw bytes of its storage.
+    Union {
+        name: String,
+        bytes: Vec<Byte>,
+    },
 }
 
 impl TryFrom<Constant> for Value {
@@ -66,6 +76,28 @@ impl TryFrom<Constant> for Value {
     }
 }
 
+impl TryFrom<Value> for Constant {
+    type Error = ();
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Undef { dtype } => Ok(Self::Undef { dtype }),
+            Value::Unit => Ok(Self::Unit),
+            Value::Int {
+                value,
+                width,
+                is_signed,
+            } => Ok(Self::Int {
+                value,
+                width,
+                is_signed,
+            }),
+            Value::Float { value, width } => Ok(Self::Float { value, width }),
+            _ => Err(()),
+        }
+    }
+}
+
 impl HasDtype for Value {
     fn dtype(&self) -> Dtype {
         match self {
@@ -87,6 +119,7 @@ impl HasDtype for Value {
                     .collect();
                 Dtype::structure(Some(name.clone()), Some(fields))
             }
+            Self::Union { name, .. } => Dtype::union(Some(name.clone()), None),
         }
     }
 }
@@ -112,9 +145,9 @@ impl Value {
     }
 
     #[inline]
-    fn float(value: f64, width: usize) -> Self {
+    fn float<T: Into<Quad>>(value: T, width: usize) -> Self {
         Self::Float {
-            value: value.into(),
+            value: value.into().round_to_width(width),
             width,
         }
     }
@@ -137,6 +170,11 @@ impl Value {
         Self::Struct { name, fields }
     }
 
+    #[inline]
+    fn union(name: String, bytes: Vec<Byte>) -> Self {
+        Self::Union { name, bytes }
+    }
+
     #[inline]
     pub fn get_int(&self) -> Option<(u128, usize, bool)> {
         if let Value::Int {
@@ -179,13 +217,22 @@ impl Value {
             Dtype::Int {
                 width, is_signed, ..
             } => Self::int(u128::default(), *width, *is_signed),
-            Dtype::Float { width, .. } => Self::float(f64::default(), *width),
+            Dtype::Float { width, .. } => Self::float(Quad::default(), *width),
             Dtype::Pointer { inner, .. } => Self::nullptr(inner.deref().clone()),
             Dtype::Array { inner, size } => {
                 let values = iter::repeat_n(Self::default_from_dtype(inner, structs), *size)
                     .collect::<Result<Vec<_>, _>>()?;
                 Self::array(inner.deref().clone(), values)
             }
+            Dtype::Struct {
+                name,
+                is_union: true,
+                ..
+            } => {
+                let name = name.as_ref().expect("union should have its name");
+                let size = dtype.size_align_of(structs).map_err(|_| ())?.0;
+                Self::union(name.clone(), vec![Byte::concrete(0); size])
+            }
             Dtype::Struct { name, .. } => {
                 let name = name.as_ref().expect("struct should have its name");
                 let struct_type = structs
@@ -216,78 +263,137 @@ impl Value {
         Ok(value)
     }
 
+    /// Creates the value of a global variable of `dtype` from its `initializer`.
+    ///
+    /// `globals` looks up the memory box id and the dtype of a global variable by its name, which
+    /// are used for address constants.
     #[allow(clippy::result_unit_err)]
     pub fn try_from_initializer(
         initializer: &ast::Initializer,
         dtype: &Dtype,
         structs: &HashMap<String, Option<Dtype>>,
+        globals: &dyn Fn(&str) -> Option<(usize, Dtype)>,
     ) -> Result<Self, ()> {
         match initializer {
             ast::Initializer::Expression(expr) => match dtype {
                 Dtype::Int { .. } | Dtype::Float { .. } | Dtype::Pointer { .. } => {
-                    let constant = Constant::try_from(&expr.node)?;
-                    let value = Self::try_from(constant)?;
-
-                    calculator::calculate_typecast(value, dtype.clone())
-                }
-                _ => Err(()),
-            },
-            ast::Initializer::List(items) => match dtype {
-                Dtype::Array { inner, size } => {
-                    let inner_dtype = inner.deref().clone();
-                    let num_of_items = items.len();
-                    let values = (0..*size)
-                        .map(|i| {
-                            if i < num_of_items {
-                                Self::try_from_initializer(
-                                    &items[i].node.initializer.node,
-                                    &inner_dtype,
-                                    structs,
-                                )
-                            } else {
-                                Self::default_from_dtype(&inner_dtype, structs)
-                            }
-                        })
-                        .collect::<Result<Vec<_>, _>>()?;
-
-                    Ok(Self::array(inner_dtype, values))
+                    let dtype_of = |name: &str| globals(name).map(|(_, dtype)| dtype);
+                    match InitialValue::try_from_expression(&expr.node, &dtype_of, structs)? {
+                        InitialValue::Constant(constant) => {
+                            let value = Self::try_from(constant)?;
+                            calculator::calculate_typecast(value, dtype.clone())
+                        }
+                        InitialValue::Address { name, offset, .. } => {
+                            let inner = dtype.get_pointer_inner().ok_or(())?;
+                            let (bid, _) = globals(&name).ok_or(())?;
+                            Ok(Self::pointer(Some(bid), offset, inner.clone()))
+                        }
+                    }
                 }
-                Dtype::Struct { name, .. } => {
-                    let name = name.as_ref().expect("struct should have its name");
-                    let struct_type = structs
-                        .get(name)
-                        .expect("struct type matched with `name` must exist")
-                        .as_ref()
-                        .expect("`struct_type` must have its definition");
-                    let fields = struct_type
-                        .get_struct_fields()
-                        .expect("`struct_type` must be struct type")
-                        .as_ref()
-                        .expect("`fields` must be `Some`");
+                // e.g., `char buf[4] = "abc";`. The bytes not given by the string literal are
+                // zero-initialized.
+                Dtype::Array { inner, size }
+                    if inner.get_int_width() == Some(Dtype::BITS_OF_BYTE) =>
+                {
+                    let ast::Expression::StringLiteral(literal) = &expr.node else {
+                        return Err(());
+                    };
+                    let bytes = string_literal_bytes(&literal.node)?;
+                    if bytes.len() > *size {
+                        return Err(());
+                    }
 
-                    let fields = fields
-                        .iter()
-                        .enumerate()
-                        .map(|(i, f)| {
-                            let value = if let Some(item) = items.get(i) {
-                                Self::try_from_initializer(
-                                    &item.node.initializer.node,
-                                    f.deref(),
-                                    structs,
-                                )?
-                            } else {
-                                Self::default_from_dtype(f.deref(), structs)?
-                            };
-
-                            Ok(Named::new(f.name().cloned(), value))
+                    let values = bytes
+                        .into_iter()
+                        .chain(iter::repeat(0))
+                        .take(*size)
+                        .map(|byte| {
+                            let value = Self::int(byte as u128, Dtype::BITS_OF_BYTE, false);
+                            calculator::calculate_typecast(value, inner.deref().clone())
                         })
                         .collect::<Result<Vec<_>, _>>()?;
-
-                    Ok(Self::structure(name.clone(), fields))
+                    Ok(Self::array(inner.deref().clone(), values))
                 }
                 _ => Err(()),
             },
+            ast::Initializer::List(items) => Self::default_from_dtype(dtype, structs)?
+                .initialize_members(items, dtype, structs, globals),
+        }
+    }
+
+    /// Initializes the members of `self` of `dtype` with the initializer list `items`.
+    ///
+    /// The members not designated by `items` are kept.
+    fn initialize_members(
+        mut self,
+        items: &[Node<ast::InitializerListItem>],
+        dtype: &Dtype,
+        structs: &HashMap<String, Option<Dtype>>,
+        globals: &dyn Fn(&str) -> Option<(usize, Dtype)>,
+    ) -> Result<Self, ()> {
+        for member in resolve_initializer_list(items, dtype, structs)? {
+            let value = if member.designators.is_empty() {
+                Self::try_from_initializer(member.initializer, &member.dtype, structs, globals)?
+            } else {
+                // e.g., `.p.y = 1` initializes `y` of the member `p`, keeping its other fields.
+                let item = ast::InitializerListItem {
+                    designation: member.designators,
+                    initializer: Box::new(Node::new(member.initializer.clone(), Span::none())),
+                };
+                self.get_member(member.index, &member.dtype, structs)?
+                    .initialize_members(
+                        &[Node::new(item, Span::none())],
+                        &member.dtype,
+                        structs,
+                        globals,
+                    )?
+            };
+            self.set_member(member.index, value, structs)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the `index`-th member of the aggregate `self`, whose dtype is `dtype`.
+    fn get_member(
+        &self,
+        index: usize,
+        dtype: &Dtype,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<Self, ()> {
+        match self {
+            Self::Array { values, .. } => values.get(index).cloned().ok_or(()),
+            Self::Struct { fields, .. } => fields.get(index).map(|f| f.deref().clone()).ok_or(()),
+            Self::Union { bytes, .. } => {
+                Byte::bytes_to_value(&mut bytes.iter(), dtype, structs).map_err(|_| ())
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// Sets the `index`-th member of the aggregate `self` to `value`.
+    fn set_member(
+        &mut self,
+        index: usize,
+        value: Self,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<(), ()> {
+        match self {
+            Self::Array { values, .. } => *values.get_mut(index).ok_or(())? = value,
+            Self::Struct { fields, .. } => {
+                let field = fields.get_mut(index).ok_or(())?;
+                *field = Named::new(field.name().cloned(), value);
+            }
+            // The rest of the storage of a union is zero.
+            Self::Union { bytes, .. } => {
+                let size = bytes.len();
+                *bytes = Byte::value_to_bytes(&value, structs);
+                bytes.resize(size, Byte::concrete(0));
+            }
+            _ => return Err(()),
         }
+
+        Ok(())
     }
 }
 
@@ -391,6 +497,9 @@ struct StackFrame<'i> {
     pub registers: RegisterMap,
     pub func_name: String,
     pub func_def: &'i FunctionDefinition,
+    /// The memory block holding the variable arguments with its dtype, if the function is
+    /// variadic.
+    pub variadic_args: Option<(usize, Dtype)>,
 }
 
 impl<'i> StackFrame<'i> {
@@ -400,11 +509,12 @@ impl<'i> StackFrame<'i> {
             registers: Default::default(),
             func_name,
             func_def,
+            variadic_args: None,
         }
     }
 }
 
-mod calculator {
+pub(crate) mod calculator {
     use std::cmp::Ordering;
 
     use lang_c::ast;
@@ -538,26 +648,27 @@ mod calculator {
 
     fn calculate_float_binary_operator_expression(
         op: &ast::BinaryOperator,
-        lhs: OrderedFloat<f64>,
-        rhs: OrderedFloat<f64>,
+        lhs: Quad,
+        rhs: Quad,
         width: usize,
     ) -> Result<Value, ()> {
-        let (lhs, rhs) = if width == Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
-            (
-                lhs.into_inner() as f32 as f64,
-                rhs.into_inner() as f32 as f64,
-            )
-        } else {
-            (lhs.into_inner(), rhs.into_inner())
+        // `f32` and `f64` are calculated in `f64`, which is as precise as calculating in their own
+        // type and rounding the result. `f128` is calculated by the software implementation.
+        let arithmetic = |native: fn(f64, f64) -> f64, quad: fn(Quad, Quad) -> Quad| {
+            if width == Dtype::SIZE_OF_LONG_DOUBLE * Dtype::BITS_OF_BYTE {
+                quad(lhs, rhs)
+            } else {
+                Quad::from_f64(native(lhs.to_f64(), rhs.to_f64()))
+            }
         };
 
         let result = match op {
-            ast::BinaryOperator::Plus => lhs + rhs,
-            ast::BinaryOperator::Minus => lhs - rhs,
-            ast::BinaryOperator::Multiply => lhs * rhs,
+            ast::BinaryOperator::Plus => arithmetic(|lhs, rhs| lhs + rhs, Quad::add),
+            ast::BinaryOperator::Minus => arithmetic(|lhs, rhs| lhs - rhs, Quad::sub),
+            ast::BinaryOperator::Multiply => arithmetic(|lhs, rhs| lhs * rhs, Quad::mul),
             ast::BinaryOperator::Divide => {
-                assert!(rhs != 0.0);
-                lhs / rhs
+                assert!(!rhs.is_zero());
+                arithmetic(|lhs, rhs| lhs / rhs, Quad::div)
             }
             ast::BinaryOperator::Equals => {
                 let order = lhs
@@ -708,8 +819,8 @@ mod calculator {
                 }
             }
             Value::Float { value, width } => match op {
-                ast::UnaryOperator::Plus => Ok(Value::float(value.into_inner(), width)),
-                ast::UnaryOperator::Minus => Ok(Value::float(-value.into_inner(), width)),
+                ast::UnaryOperator::Plus => Ok(Value::float(value, width)),
+                ast::UnaryOperator::Minus => Ok(Value::float(value.neg(), width)),
                 _ => todo!(
                     "calculate_unary_operator_expression: not supported case for {:?} {:?}",
                     op,
@@ -739,7 +850,10 @@ mod calculator {
                     ..
                 },
             ) => {
-                let result = if target_signed {
+                let result = if target_width == 1 {
+                    // Converting to `_Bool` yields whether the value is nonzero.
+                    (trim_unnecessary_bits(value, width as u128) != 0).into()
+                } else if target_signed {
                     if width >= target_width {
                         // TODO: explain the logic in the future
                         let value = trim_unnecessary_bits(value, target_width as u128);
@@ -759,16 +873,10 @@ mod calculator {
                 },
                 Dtype::Float { width, .. },
             ) => {
-                let size = (width - 1) / Dtype::BITS_OF_BYTE + 1;
-                let casted_value = match (is_signed, size) {
-                    (true, Dtype::SIZE_OF_FLOAT) => value as i128 as f32 as f64,
-                    (true, Dtype::SIZE_OF_DOUBLE) => value as i128 as f64,
-                    (false, Dtype::SIZE_OF_FLOAT) => value as f32 as f64,
-                    (false, Dtype::SIZE_OF_DOUBLE) => value as f64,
-                    _ => panic!(
-                        "calculate_typecast: not supported case \
-                            typecast int to float when `width` is {width}"
-                    ),
+                let casted_value = if is_signed {
+                    Quad::from_i128(value as i128)
+                } else {
+                    Quad::from_u128(value)
                 };
                 Ok(Value::float(casted_value, width))
             }
@@ -788,23 +896,30 @@ mod calculator {
                     width, is_signed, ..
                 },
             ) => {
-                let casted_value = if is_signed {
-                    value.into_inner() as i128 as u128
+                let casted_value = if width == 1 {
+                    // Converting to `_Bool` yields whether the value is nonzero.
+                    (!value.is_zero()).into()
+                } else if is_signed {
+                    value.to_i128() as u128
                 } else {
-                    value.into_inner() as u128
+                    value.to_u128()
                 };
                 Ok(Value::int(casted_value, width, is_signed))
             }
             (Value::Float { value, .. }, Dtype::Float { width, .. }) => {
-                Ok(Value::float(value.into_inner(), width))
+                Ok(Value::float(value, width))
+            }
+            (Value::Pointer { bid, offset, .. }, Dtype::Pointer { inner, .. }) => {
+                Ok(Value::pointer(bid, offset, *inner))
             }
             (value, dtype) => todo!("calculate_typecast ({:?}) {:?}", value, dtype),
         }
     }
 }
 
+/// A byte of the interpreter's memory.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum Byte {
+pub enum Byte {
     Undef,
     Concrete(u8),
     Pointer {
@@ -848,7 +963,129 @@ impl Byte {
 
     fn block_from_dtype(dtype: &Dtype, structs: &HashMap<String, Option<Dtype>>) -> Vec<Self> {
         let size = dtype.size_align_of(structs).unwrap().0;
-        iter::repeat_n(Self::Undef, size).collect()
+        let mut block = iter::repeat_n(Self::Undef, size).collect::<Vec<_>>();
+        Self::fill_bit_field_units(&mut block, dtype, structs);
+        block
+    }
+
+    /// Zero-initializes the storage units of the bit-fields in `block` of `dtype`.
+    ///
+    /// A bit-field is written by a read-modify-write of its whole storage unit, so the unit must not
+    /// be undefined even if the other fields sharing it are not initialized yet.
+    fn fill_bit_field_units(
+        block: &mut [Self],
+        dtype: &Dtype,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) {
+        match dtype {
+            Dtype::Array { inner, size } => {
+                let (inner_size, inner_align) = inner.size_align_of(structs).unwrap();
+                let stride = std::cmp::max(inner_size, inner_align);
+                for i in 0..*size {
+                    Self::fill_bit_field_units(&mut block[i * stride..], inner, structs);
+                }
+            }
+            Dtype::Struct {
+                name,
+                is_union: false,
+                ..
+            } => {
+                let name = name.as_ref().expect("struct should have its name");
+                let struct_type = structs
+                    .get(name)
+                    .expect("struct type matched with `name` must exist")
+                    .as_ref()
+                    .expect("`struct_type` must have its definition");
+                let fields = struct_type
+                    .get_struct_fields()
+                    .expect("`struct_type` must be struct type")
+                    .as_ref()
+                    .expect("`fields` must be `Some`");
+                let (size, _, offsets) = struct_type
+                    .get_struct_size_align_offsets()
+                    .expect("`struct_type` must be struct type")
+                    .as_ref()
+                    .expect("`offsets` must be `Some`");
+                let bit_fields = struct_type.get_struct_bit_fields(structs).unwrap();
+
+                for ((f, o), bit_field) in fields.iter().zip(offsets).zip(bit_fields) {
+                    if bit_field.is_some() {
+                        if f.name().is_some() {
+                            let unit_size = f.deref().size_align_of(structs).unwrap().0;
+                            let end = std::cmp::min(*o + unit_size, *size);
+                            block[*o..end].fill(Self::concrete(0));
+                        }
+                    } else {
+                        Self::fill_bit_field_units(&mut block[*o..], f.deref(), structs);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Reads the bit-field of `dtype` at `bit_offset` of `width` bits in its storage unit `unit`.
+    fn bytes_to_bit_field(unit: &[Self], dtype: &Dtype, (bit_offset, width): BitField) -> Value {
+        let Dtype::Int {
+            width: dtype_width,
+            is_signed,
+            ..
+        } = dtype
+        else {
+            panic!("bit-field must have an integer type")
+        };
+        if width == 0 {
+            return Value::int(0, *dtype_width, *is_signed);
+        }
+
+        let first = bit_offset / Dtype::BITS_OF_BYTE;
+        let last = (bit_offset + width).div_ceil(Dtype::BITS_OF_BYTE);
+        let Some(bytes) = unit[first..last]
+            .iter()
+            .map(Byte::get_concrete)
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Value::undef(dtype.clone());
+        };
+
+        let value = Self::bytes_to_u128(&bytes, false) >> (bit_offset % Dtype::BITS_OF_BYTE);
+        let value = trim_unnecessary_bits(value, width as u128);
+        let value = if *is_signed {
+            sign_extension(value, width as u128)
+        } else {
+            value
+        };
+        Value::int(value, *dtype_width, *is_signed)
+    }
+
+    /// Writes `value` to the bit-field at `bit_offset` of `width` bits in its storage unit `unit`,
+    /// keeping the other bits of the unit.
+    fn bit_field_to_bytes(unit: &mut [Self], value: &Value, (bit_offset, width): BitField) {
+        let Value::Int { value, .. } = value else {
+            return;
+        };
+        if width == 0 {
+            return;
+        }
+
+        let first = bit_offset / Dtype::BITS_OF_BYTE;
+        let last = (bit_offset + width).div_ceil(Dtype::BITS_OF_BYTE);
+        let bytes = unit[first..last]
+            .iter()
+            .map(|b| b.get_concrete().unwrap_or(0))
+            .collect::<Vec<_>>();
+
+        let shift = bit_offset % Dtype::BITS_OF_BYTE;
+        let mask = trim_unnecessary_bits(u128::MAX, width as u128) << shift;
+        let old = Self::bytes_to_u128(&bytes, false);
+        let new = (old & !mask) | ((*value << shift) & mask);
+
+        for (byte, new) in unit[first..last]
+            .iter_mut()
+            .zip(Self::u128_to_bytes(new, last - first))
+        {
+            *byte = Self::concrete(new);
+        }
     }
 
     fn u128_to_bytes(mut value: u128, size: usize) -> Vec<u8> {
@@ -906,10 +1143,10 @@ impl Byte {
                     return Ok(Value::undef(dtype.clone()));
                 };
                 let value = Self::bytes_to_u128(&value, false);
-                let value = if size == Dtype::SIZE_OF_FLOAT {
-                    f32::from_bits(value as u32) as f64
-                } else {
-                    f64::from_bits(value as u64)
+                let value = match size {
+                    Dtype::SIZE_OF_FLOAT => Quad::from_f32(f32::from_bits(value as u32)),
+                    Dtype::SIZE_OF_DOUBLE => Quad::from_f64(f64::from_bits(value as u64)),
+                    _ => Quad::from_bits(value),
                 };
 
                 Ok(Value::float(value, *width))
@@ -954,6 +1191,16 @@ impl Byte {
                     values,
                 })
             }
+            Dtype::Struct {
+                name,
+                is_union: true,
+                ..
+            } => {
+                let name = name.as_ref().expect("union should have its name");
+                let size = dtype.size_align_of(structs).unwrap().0;
+                let bytes = bytes.by_ref().take(size).cloned().collect();
+                Ok(Value::union(name.clone(), bytes))
+            }
             Dtype::Struct { name, .. } => {
                 let name = name.as_ref().expect("struct should have its name");
                 let struct_type = structs
@@ -971,15 +1218,21 @@ impl Byte {
                     .expect("`struct_type` must be struct type")
                     .as_ref()
                     .expect("`offsets` must be `Some`");
+                let bit_fields = struct_type.get_struct_bit_fields(structs).unwrap();
                 let bytes = bytes.by_ref().take(*size).cloned().collect::<Vec<_>>();
 
                 assert_eq!(fields.len(), offsets.len());
                 let fields = fields
                     .iter()
                     .zip(offsets)
-                    .map(|(f, o)| {
-                        let mut sub_bytes = bytes[*o..].iter();
-                        let value = Self::bytes_to_value(&mut sub_bytes, f.deref(), structs)?;
+                    .zip(bit_fields)
+                    .map(|((f, o), bit_field)| {
+                        let value = if let Some(bit_field) = bit_field {
+                            Self::bytes_to_bit_field(&bytes[*o..], f.deref(), bit_field)
+                        } else {
+                            let mut sub_bytes = bytes[*o..].iter();
+                            Self::bytes_to_value(&mut sub_bytes, f.deref(), structs)?
+                        };
                         Ok(Named::new(f.name().cloned(), value))
                     })
                     .collect::<Result<Vec<_>, _>>()?;
@@ -994,7 +1247,10 @@ impl Byte {
         }
     }
 
-    fn value_to_bytes(value: &Value, structs: &HashMap<String, Option<Dtype>>) -> Vec<Self> {
+    pub(crate) fn value_to_bytes(
+        value: &Value,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Vec<Self> {
         match value {
             Value::Undef { dtype } => Self::block_from_dtype(dtype, structs),
             Value::Unit => Vec::new(),
@@ -1012,8 +1268,9 @@ impl Byte {
             } => {
                 let size = value.dtype().size_align_of(structs).unwrap().0;
                 let value_bits: u128 = match size {
-                    Dtype::SIZE_OF_FLOAT => (float_value.into_inner() as f32).to_bits() as u128,
-                    Dtype::SIZE_OF_DOUBLE => (float_value.into_inner()).to_bits() as u128,
+                    Dtype::SIZE_OF_FLOAT => float_value.to_f32().to_bits() as u128,
+                    Dtype::SIZE_OF_DOUBLE => float_value.to_f64().to_bits() as u128,
+                    Dtype::SIZE_OF_LONG_DOUBLE => float_value.to_bits(),
                     _ => panic!("value_to_bytes: {size} is not a valid float size"),
                 };
 
@@ -1051,17 +1308,28 @@ impl Byte {
                     .expect("`struct_type` must be struct type")
                     .as_ref()
                     .expect("`offsets` must be `Some`");
+                let bit_fields = struct_type.get_struct_bit_fields(structs).unwrap();
                 let mut values = iter::repeat_n(Byte::Undef, *size_of).collect::<Vec<_>>();
+                Self::fill_bit_field_units(&mut values, struct_type, structs);
 
                 assert_eq!(fields.len(), offsets.len());
-                fields.iter().zip(offsets).for_each(|(f, o)| {
-                    let result = Self::value_to_bytes(f.deref(), structs);
-                    let size_of_data = f.deref().dtype().size_align_of(structs).unwrap().0;
-                    let _unused = values.splice(*o..(*o + size_of_data), result);
-                });
+                fields
+                    .iter()
+                    .zip(offsets)
+                    .zip(bit_fields)
+                    .for_each(|((f, o), bit_field)| {
+                        if let Some(bit_field) = bit_field {
+                            Self::bit_field_to_bytes(&mut values[*o..], f.deref(), bit_field);
+                            return;
+                        }
+                        let result = Self::value_to_bytes(f.deref(), structs);
+                        let size_of_data = f.deref().dtype().size_align_of(structs).unwrap().0;
+                        let _unused = values.splice(*o..(*o + size_of_data), result);
+                    });
 
                 values
             }
+            Value::Union { bytes, .. } => bytes.clone(),
         }
     }
 }
@@ -1183,24 +1451,40 @@ impl<'i> State<'i> {
     }
 
     fn alloc_global_variables(&mut self) -> Result<(), InterpreterError> {
+        // Memory allocation. All global variables are allocated before initialization, since an
+        // initializer may refer to the address of any global variable.
         for (name, decl) in &self.ir.decls {
-            // Memory allocation
             let bid = self.memory.alloc(&decl.dtype(), &self.ir.structs)?;
             self.global_map.insert(name.clone(), bid)?;
+        }
+
+        for (name, decl) in &self.ir.decls {
+            let bid = self
+                .global_map
+                .get_bid(name)
+                .expect("global variable must be allocated");
 
             // Initialize allocated memory space
             match decl {
-                Declaration::Variable { dtype, initializer } => {
+                Declaration::Variable {
+                    dtype, initializer, ..
+                } => {
+                    let globals = |name: &str| {
+                        let bid = self.global_map.get_bid(name)?;
+                        let decl = self.ir.decls.get(name)?;
+                        Some((bid, decl.dtype()))
+                    };
+                    // A variable declared by `extern` is allocated as well, in case its definition
+                    // is not linked.
                     let value = if let Some(initializer) = initializer {
-                        Value::try_from_initializer(initializer, dtype, &self.ir.structs).map_err(
-                            |_| InterpreterError::Misc {
+                        Value::try_from_initializer(initializer, dtype, &self.ir.structs, &globals)
+                            .map_err(|_| InterpreterError::Misc {
                                 func_name: self.stack_frame.func_name.clone(),
                                 pc: self.stack_frame.pc,
                                 msg: format!(
                                     "fail to translate `Initializer` and `{dtype}` to `Value`"
                                 ),
-                            },
-                        )?
+                            })?
                     } else {
                         Value::default_from_dtype(dtype, &self.ir.structs)
                             .expect("default value must be derived from `dtype`")
@@ -1238,6 +1522,38 @@ impl<'i> State<'i> {
         Ok(())
     }
 
+    /// Allocates the memory block holding the variable arguments `values` of a call.
+    ///
+    /// As in RISC-V LP64D, each argument occupies 8-byte slots, and an argument aligned to 16
+    /// bytes starts at an even slot.
+    fn alloc_variadic_args(
+        &mut self,
+        values: &[Value],
+    ) -> Result<(usize, Dtype), InterpreterError> {
+        let mut offsets = Vec::new();
+        let mut size: usize = 0;
+        for value in values {
+            let (value_size, value_align) = value.dtype().size_align_of(&self.ir.structs).unwrap();
+            let offset = size.next_multiple_of(value_align.max(VARIADIC_ARG_SLOT_SIZE));
+            offsets.push(offset);
+            size = offset + value_size.next_multiple_of(VARIADIC_ARG_SLOT_SIZE);
+        }
+
+        let dtype = Dtype::array(Dtype::CHAR, size);
+        let bid = self.memory.alloc(&dtype, &self.ir.structs)?;
+        for (value, offset) in values.iter().zip(offsets) {
+            self.memory
+                .store(bid, offset as isize, value, &self.ir.structs)
+                .map_err(|_| InterpreterError::Misc {
+                    func_name: self.stack_frame.func_name.clone(),
+                    pc: self.stack_frame.pc,
+                    msg: format!("fail to store variable argument {value:?}"),
+                })?;
+        }
+
+        Ok((bid, dtype))
+    }
+
     fn write_args(&mut self, bid_init: BlockId, args: Vec<Value>) -> Result<(), InterpreterError> {
         for (i, value) in args.iter().enumerate() {
             self.stack_frame
@@ -1281,6 +1597,9 @@ impl<'i> State<'i> {
             self.memory
                 .dealloc(bid.unwrap(), *offset, dtype, &self.ir.structs)?;
         }
+        if let Some((bid, dtype)) = &self.stack_frame.variadic_args {
+            self.memory.dealloc(*bid, 0, dtype, &self.ir.structs)?;
+        }
 
         // restore previous state
         let Some(prev_stack_frame) = self.stack.pop() else {
@@ -1308,8 +1627,10 @@ impl<'i> State<'i> {
         signature: &FunctionSignature,
         args: &[Operand],
     ) -> Result<Vec<Value>, InterpreterError> {
-        // Check that the dtype of each args matches the expected
-        if !(args.len() == signature.params.len()
+        // Check that the dtype of each args matches the expected. A variadic function takes any
+        // number of arguments after the parameters.
+        if !((args.len() == signature.params.len()
+            || (signature.is_variadic && args.len() > signature.params.len()))
             && args
                 .iter()
                 .zip(&signature.params)
@@ -1468,6 +1789,9 @@ impl<'i> State<'i> {
                     .get(&func_def.bid_init)
                     .expect("init block must exists");
 
+                let mut args = self.interp_args(func_signature, args)?;
+                let variadic_args = args.split_off(func_signature.params.len());
+
                 if !(args.len() == block_init.phinodes.len()
                     && args.iter().zip(&block_init.phinodes).all(|(a, d)| {
                         a.dtype().set_const(false) == d.deref().clone().set_const(false)
@@ -1476,9 +1800,14 @@ impl<'i> State<'i> {
                     panic!("dtype of args and phinodes of init block must be compatible");
                 }
 
-                let args = self.interp_args(func_signature, args)?;
+                let variadic_args = if func_signature.is_variadic {
+                    Some(self.alloc_variadic_args(&variadic_args)?)
+                } else {
+                    None
+                };
 
-                let stack_frame = StackFrame::new(func_def.bid_init, callee_name, func_def);
+                let mut stack_frame = StackFrame::new(func_def.bid_init, callee_name, func_def);
+                stack_frame.variadic_args = variadic_args;
                 let prev_stack_frame = mem::replace(&mut self.stack_frame, stack_frame);
                 self.stack.push(prev_stack_frame);
 
@@ -1522,6 +1851,61 @@ impl<'i> State<'i> {
 
                 Value::pointer(*bid, offset, inner_dtype.clone())
             }
+            Instruction::VaStart { ptr } => {
+                let (bid, _) = self.stack_frame.variadic_args.as_ref().ok_or_else(|| {
+                    InterpreterError::Misc {
+                        func_name: self.stack_frame.func_name.clone(),
+                        pc: self.stack_frame.pc,
+                        msg: "`va_start` is used in a function with fixed arguments".into(),
+                    }
+                })?;
+                let va_list = Value::pointer(Some(*bid), 0, Dtype::CHAR);
+
+                let ptr = self.interp_operand(ptr)?;
+                let (bid, offset, _) = self.interp_ptr(&ptr)?;
+                self.memory
+                    .store(bid, offset, &va_list, &self.ir.structs)
+                    .map_err(|_| InterpreterError::Misc {
+                        func_name: self.stack_frame.func_name.clone(),
+                        pc: self.stack_frame.pc,
+                        msg: format!(
+                            "fail to initialize `va_list` at bid: {bid}, offset: {offset}"
+                        ),
+                    })?;
+                Value::Unit
+            }
+            Instruction::VaArg { ptr, dtype } => {
+                let ptr = self.interp_operand(ptr)?;
+                let (ptr_bid, ptr_offset, va_list_dtype) = self.interp_ptr(&ptr)?;
+                let va_list =
+                    self.memory
+                        .load(ptr_bid, ptr_offset, &va_list_dtype, &self.ir.structs)?;
+                let (bid, offset, _) = self.interp_ptr(&va_list)?;
+
+                // The argument is read from its slots, as laid out by `alloc_variadic_args`.
+                let (size, align) = dtype.size_align_of(&self.ir.structs).unwrap();
+                let offset = (offset as usize).next_multiple_of(align.max(VARIADIC_ARG_SLOT_SIZE));
+                let value = self
+                    .memory
+                    .load(bid, offset as isize, dtype, &self.ir.structs)?;
+
+                let next = offset + size.next_multiple_of(VARIADIC_ARG_SLOT_SIZE);
+                let va_list = Value::pointer(Some(bid), next as isize, Dtype::CHAR);
+                self.memory
+                    .store(ptr_bid, ptr_offset, &va_list, &self.ir.structs)
+                    .map_err(|_| InterpreterError::Misc {
+                        func_name: self.stack_frame.func_name.clone(),
+                        pc: self.stack_frame.pc,
+                        msg: format!(
+                            "fail to advance `va_list` at bid: {ptr_bid}, offset: {ptr_offset}"
+                        ),
+                    })?;
+                value
+            }
+            Instruction::VaEnd { ptr } => {
+                let _unused = self.interp_operand(ptr)?;
+                Value::Unit
+            }
         };
 
         let register = RegisterId::temp(self.stack_frame.pc.bid, self.stack_frame.pc.iid);
@@ -0,0 +1,359 @@
+//! Link IR.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use lang_c::ast;
+use thiserror::Error;
+
+use crate::Translate;
+use crate::ir::*;
+
+/// An error in linking translation units.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum LinkError {
+    /// A variable is initialized, or a function is defined, in more than one translation unit.
+    #[error("multiple definitions of `{name}`")]
+    MultipleDefinitions { name: String },
+    /// A variable or a function is declared with incompatible types in translation units.
+    #[error("conflicting types for `{name}`")]
+    ConflictingTypes { name: String },
+}
+
+/// Links translation units into one, e.g., the IR generated from each C file of a program.
+///
+/// A function declared without a definition is resolved against its definition in another
+/// translation unit, and so is a variable declared without an initializer. The variables and the
+/// functions with the internal linkage, and the struct types defined differently in translation
+/// units, are renamed if their names clash.
+#[derive(Default, Debug)]
+pub struct Link;
+
+impl Translate<Vec<TranslationUnit>> for Link {
+    type Target = TranslationUnit;
+    type Error = LinkError;
+
+    fn translate(&mut self, source: &Vec<TranslationUnit>) -> Result<Self::Target, Self::Error> {
+        let mut linked = TranslationUnit {
+            decls: BTreeMap::new(),
+            structs: HashMap::new(),
+        };
+
+        // The names in use, which the renamed ones must avoid.
+        let mut symbols = source
+            .iter()
+            .flat_map(|unit| unit.decls.keys().cloned())
+            .collect::<HashSet<_>>();
+        let mut struct_names = source
+            .iter()
+            .flat_map(|unit| unit.structs.keys().cloned())
+            .collect::<HashSet<_>>();
+
+        for (index, unit) in source.iter().enumerate() {
+            let mut symbol_renames = HashMap::new();
+            for (name, decl) in &unit.decls {
+                let is_clashing = source
+                    .iter()
+                    .enumerate()
+                    .any(|(other, unit)| other != index && unit.decls.contains_key(name));
+                if decl.linkage() == Linkage::Internal && is_clashing {
+                    let fresh = fresh_name(name, &mut symbols);
+                    let _unused = symbol_renames.insert(name.clone(), fresh);
+                }
+            }
+            let struct_renames = struct_renames(unit, &linked.structs, &mut struct_names);
+
+            let mut unit = unit.clone();
+            if !symbol_renames.is_empty() || !struct_renames.is_empty() {
+                rename_unit(&mut unit, &symbol_renames, &struct_renames);
+            }
+
+            for (name, dtype) in unit.structs {
+                match linked.structs.entry(name) {
+                    Entry::Vacant(entry) => {
+                        let _unused = entry.insert(dtype);
+                    }
+                    Entry::Occupied(mut entry) => {
+                        if entry.get().is_none() {
+                            let _unused = entry.insert(dtype);
+                        }
+                    }
+                }
+            }
+
+            for (name, decl) in unit.decls {
+                match linked.decls.get_mut(&name) {
+                    Some(old) => merge_declaration(&name, old, decl)?,
+                    None => {
+                        let _unused = linked.decls.insert(name, decl);
+                    }
+                }
+            }
+        }
+
+        Ok(linked)
+    }
+}
+
+/// Merges `decl` into `old`, the declarations of `name` in different translation units.
+fn merge_declaration(
+    name: &str,
+    old: &mut Declaration,
+    decl: Declaration,
+) -> Result<(), LinkError> {
+    let conflicting_types = || LinkError::ConflictingTypes {
+        name: name.to_string(),
+    };
+    let multiple_definitions = || LinkError::MultipleDefinitions {
+        name: name.to_string(),
+    };
+
+    match (old, decl) {
+        (
+            Declaration::Variable {
+                dtype,
+                initializer,
+                is_definition,
+                alignment,
+                ..
+            },
+            Declaration::Variable {
+                dtype: other,
+                initializer: other_initializer,
+                is_definition: other_is_definition,
+                alignment: other_alignment,
+                ..
+            },
+        ) => {
+            if *dtype != other {
+                return Err(conflicting_types());
+            }
+            // An `extern` declaration refers to the definition in the other translation unit.
+            *is_definition |= other_is_definition;
+            *alignment = (*alignment).max(other_alignment);
+            if let Some(other_initializer) = other_initializer {
+                if initializer.is_some() {
+                    return Err(multiple_definitions());
+                }
+                *initializer = Some(other_initializer);
+            }
+        }
+        (
+            Declaration::Function {
+                signature,
+                definition,
+                ..
+            },
+            Declaration::Function {
+                signature: other,
+                definition: other_definition,
+                ..
+            },
+        ) => {
+            // A declaration without a prototype is replaced by the prototype.
+            if signature.is_unprototyped_of(&other) {
+                *signature = other;
+            } else if *signature != other && !other.is_unprototyped_of(signature) {
+                return Err(conflicting_types());
+            }
+            if let Some(other_definition) = other_definition {
+                if definition.is_some() {
+                    return Err(multiple_definitions());
+                }
+                *definition = Some(other_definition);
+            }
+        }
+        _ => return Err(conflicting_types()),
+    }
+    Ok(())
+}
+
+/// Returns the new names of the struct types of `unit` defined differently in `linked`.
+///
+/// A struct type whose fields refer to a renamed one is also defined differently, so the renames
+/// are repeated until none is added.
+fn struct_renames(
+    unit: &TranslationUnit,
+    linked: &HashMap<String, Option<Dtype>>,
+    struct_names: &mut HashSet<String>,
+) -> HashMap<String, String> {
+    let mut renames = HashMap::<String, String>::new();
+    loop {
+        let mut is_changed = false;
+        for (name, dtype) in &unit.structs {
+            let (Some(dtype), Some(Some(other))) = (dtype, linked.get(name)) else {
+                continue;
+            };
+            if renames.contains_key(name) {
+                continue;
+            }
+            let dtype = rename_dtype(dtype.clone(), &renames);
+            if dtype != *other {
+                let fresh = fresh_name(name, struct_names);
+                let _unused = renames.insert(name.clone(), fresh);
+                is_changed = true;
+            }
+        }
+        if !is_changed {
+            return renames;
+        }
+    }
+}
+
+/// Returns a name derived from `name` not in `names`, and adds it to `names`, e.g., `x.1` for `x`
+/// or `%t1` for the anonymous struct type `%t0`.
+fn fresh_name(name: &str, names: &mut HashSet<String>) -> String {
+    let base = name.split('.').next().unwrap_or(name);
+    let fresh = (0..)
+        .map(|index| {
+            if base.starts_with("%t") {
+                format!("%t{index}")
+            } else {
+                format!("{base}.{index}")
+            }
+        })
+        .find(|fresh| !names.contains(fresh))
+        .expect("infinite names");
+    let _unused = names.insert(fresh.clone());
+    fresh
+}
+
+/// Renames the variables and the functions in `unit` with `symbols`, and the struct types with
+/// `structs`.
+fn rename_unit(
+    unit: &mut TranslationUnit,
+    symbols: &HashMap<String, String>,
+    structs: &HashMap<String, String>,
+) {
+    let rename_symbol = |name: &String| symbols.get(name).unwrap_or(name).clone();
+
+    unit.structs = unit
+        .structs
+        .drain()
+        .map(|(name, dtype)| {
+            let name = structs.get(&name).unwrap_or(&name).clone();
+            (name, dtype.map(|dtype| rename_dtype(dtype, structs)))
+        })
+        .collect();
+
+    let decls = core::mem::take(&mut unit.decls);
+    for (name, mut decl) in decls {
+        match &mut decl {
+            Declaration::Variable {
+                dtype, initializer, ..
+            } => {
+                *dtype = rename_dtype(dtype.clone(), structs);
+                if let Some(initializer) = initializer {
+                    rename_initializer(initializer, symbols);
+                }
+            }
+            Declaration::Function {
+                signature,
+                definition,
+                ..
+            } => {
+                *signature = FunctionSignature::new(rename_dtype(signature.dtype(), structs));
+                if let Some(definition) = definition {
+                    rename_function(definition, symbols, structs);
+                }
+            }
+        }
+        let _unused = unit.decls.insert(rename_symbol(&name), decl);
+    }
+}
+
+fn rename_function(
+    definition: &mut FunctionDefinition,
+    symbols: &HashMap<String, String>,
+    structs: &HashMap<String, String>,
+) {
+    let rename_named = |named: &mut Named<Dtype>| {
+        let (dtype, name) = named.clone().destruct();
+        *named = Named::new(name, rename_dtype(dtype, structs));
+    };
+    let rename_operand = |operand: &mut Operand| match operand {
+        Operand::Register { dtype, .. } | Operand::Constant(Constant::Undef { dtype }) => {
+            *dtype = rename_dtype(dtype.clone(), structs);
+        }
+        Operand::Constant(Constant::GlobalVariable { name, dtype }) => {
+            *name = symbols.get(name).unwrap_or(name).clone();
+            *dtype = rename_dtype(dtype.clone(), structs);
+        }
+        Operand::Constant(_) => {}
+    };
+
+    definition.allocations.iter_mut().for_each(rename_named);
+    for block in definition.blocks.values_mut() {
+        block.phinodes.iter_mut().for_each(rename_named);
+        for instruction in &mut block.instructions {
+            let instruction = &mut **instruction;
+            instruction.walk_operands(rename_operand);
+            match instruction {
+                Instruction::BinOp { dtype, .. }
+                | Instruction::UnaryOp { dtype, .. }
+                | Instruction::Call {
+                    return_type: dtype, ..
+                }
+                | Instruction::TypeCast {
+                    target_dtype: dtype,
+                    ..
+                }
+                | Instruction::GetElementPtr { dtype, .. }
+                | Instruction::VaArg { dtype, .. } => {
+                    *dtype = rename_dtype(dtype.clone(), structs);
+                }
+                _ => {}
+            }
+        }
+        block.exit.walk_operands(rename_operand);
+    }
+}
+
+fn rename_dtype(dtype: Dtype, structs: &HashMap<String, String>) -> Dtype {
+    dtype.rename_structs(&mut |name, _| structs.get(name).map_or(name, String::as_str).to_string())
+}
+
+/// Renames the variables referred to by the address constants in `initializer`, e.g., `x` of
+/// `&x`.
+fn rename_initializer(initializer: &mut ast::Initializer, symbols: &HashMap<String, String>) {
+    match initializer {
+        ast::Initializer::Expression(expr) => rename_expression(&mut expr.node, symbols),
+        ast::Initializer::List(items) => {
+            for item in items {
+                rename_initializer(&mut item.node.initializer.node, symbols);
+            }
+        }
+    }
+}
+
+fn rename_expression(expr: &mut ast::Expression, symbols: &HashMap<String, String>) {
+    match expr {
+        ast::Expression::Identifier(identifier) => {
+            if let Some(name) = symbols.get(&identifier.node.name) {
+                identifier.node.name = name.clone();
+            }
+        }
+        ast::Expression::Member(member) => {
+            rename_expression(&mut member.node.expression.node, symbols)
+        }
+        ast::Expression::UnaryOperator(unary) => {
+            rename_expression(&mut unary.node.operand.node, symbols)
+        }
+        ast::Expression::BinaryOperator(binary) => {
+            rename_expression(&mut binary.node.lhs.node, symbols);
+            rename_expression(&mut binary.node.rhs.node, symbols);
+        }
+        ast::Expression::Cast(cast) => rename_expression(&mut cast.node.expression.node, symbols),
+        ast::Expression::Conditional(conditional) => {
+            rename_expression(&mut conditional.node.condition.node, symbols);
+            rename_expression(&mut conditional.node.then_expression.node, symbols);
+            rename_expression(&mut conditional.node.else_expression.node, symbols);
+        }
+        ast::Expression::Comma(exprs) => {
+            for expr in exprs.iter_mut() {
+                rename_expression(&mut expr.node, symbols);
+            }
+        }
+        _ => {}
+    }
+}
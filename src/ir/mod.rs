@@ -2,8 +2,12 @@
 
 mod dtype;
 mod equiv;
+mod initializer;
 mod interp;
+mod link;
 mod parse;
+mod quad;
+mod verify;
 mod visualize;
 mod write_ir;
 
@@ -13,13 +17,20 @@ use core::ops::{Deref, DerefMut};
 use std::collections::{BTreeMap, HashMap};
 use std::hash::{Hash, Hasher};
 
-pub use dtype::{Dtype, DtypeError, HasDtype};
+pub use dtype::{BitField, Dtype, DtypeError, HasDtype, StructField};
 use hexf_parse::{parse_hexf32, parse_hexf64};
-pub use interp::{Value, interp};
+pub use initializer::{
+    InitialValue, InitializerMember, array_size_of_initializer, resolve_initializer_list,
+    string_literal_bytes,
+};
+pub(crate) use interp::calculator;
+pub use interp::{Byte, Value, interp};
 use itertools::Itertools;
 use lang_c::ast;
-use ordered_float::OrderedFloat;
+pub use link::{Link, LinkError};
 pub use parse::Parse;
+pub use quad::Quad;
+pub use verify::{Location, VerifyError, verify};
 pub use visualize::Visualizer;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -33,13 +44,43 @@ pub enum Declaration {
     Variable {
         dtype: Dtype,
         initializer: Option<ast::Initializer>,
+        linkage: Linkage,
+        /// Whether the variable is defined, i.e., its storage is allocated in this translation
+        /// unit. A variable defined without an initializer is initialized to zero. It is not
+        /// defined if it is only declared by `extern` without an initializer, e.g., `extern int
+        /// g;`, referring to the variable defined in another translation unit.
+        is_definition: bool,
+        /// The alignment in bytes given by `_Alignas`, or `None` if it is not given.
+        alignment: Option<usize>,
     },
     Function {
         signature: FunctionSignature,
         definition: Option<FunctionDefinition>,
+        linkage: Linkage,
     },
 }
 
+/// The linkage of a global variable or a function, i.e., whether it can be referred to by the
+/// other translation units.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Linkage {
+    /// Visible to the other translation units, e.g., `int g;` or `extern int g;` in C.
+    #[default]
+    External,
+    /// Visible only in its own translation unit, e.g., `static int g;` in C, including `static`
+    /// local variables hoisted to global variables.
+    Internal,
+}
+
+impl fmt::Display for Linkage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::External => write!(f, "external"),
+            Self::Internal => write!(f, "internal"),
+        }
+    }
+}
+
 impl TryFrom<Dtype> for Declaration {
     type Error = DtypeError;
 
@@ -67,10 +108,14 @@ impl TryFrom<Dtype> for Declaration {
             | Dtype::Struct { .. } => Ok(Declaration::Variable {
                 dtype,
                 initializer: None,
+                linkage: Linkage::default(),
+                is_definition: true,
+                alignment: None,
             }),
             Dtype::Function { .. } => Ok(Declaration::Function {
                 signature: FunctionSignature::new(dtype),
                 definition: None,
+                linkage: Linkage::default(),
             }),
             Dtype::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
         }
@@ -79,7 +124,10 @@ impl TryFrom<Dtype> for Declaration {
 
 impl Declaration {
     pub fn get_variable(&self) -> Option<(&Dtype, &Option<ast::Initializer>)> {
-        if let Self::Variable { dtype, initializer } = self {
+        if let Self::Variable {
+            dtype, initializer, ..
+        } = self
+        {
             Some((dtype, initializer))
         } else {
             None
@@ -90,6 +138,7 @@ impl Declaration {
         if let Self::Function {
             signature,
             definition,
+            ..
         } = self
         {
             Some((signature, definition))
@@ -104,6 +153,7 @@ impl Declaration {
         if let Self::Function {
             signature,
             definition,
+            ..
         } = self
         {
             Some((signature, definition))
@@ -112,6 +162,20 @@ impl Declaration {
         }
     }
 
+    pub fn linkage(&self) -> Linkage {
+        match self {
+            Self::Variable { linkage, .. } | Self::Function { linkage, .. } => *linkage,
+        }
+    }
+
+    #[must_use]
+    pub fn set_linkage(mut self, linkage: Linkage) -> Self {
+        match &mut self {
+            Self::Variable { linkage: l, .. } | Self::Function { linkage: l, .. } => *l = linkage,
+        }
+        self
+    }
+
     /// Check if type is conflicting for pre-declared one
     pub fn is_compatible(&self, other: &Declaration) -> bool {
         match (self, other) {
@@ -140,6 +204,8 @@ impl HasDtype for Declaration {
 pub struct FunctionSignature {
     pub ret: Dtype,
     pub params: Vec<Dtype>,
+    /// Whether the function takes variable arguments after `params`.
+    pub is_variadic: bool,
 }
 
 impl FunctionSignature {
@@ -150,13 +216,28 @@ impl FunctionSignature {
         Self {
             ret: ret.clone(),
             params: params.clone(),
+            is_variadic: dtype.is_variadic(),
         }
     }
+
+    /// Checks if `self`, the signature of a function declared without a prototype, can be that of
+    /// the function with the signature `signature`, i.e., they return the same type and the
+    /// parameters of `signature` are not changed by the default argument promotions.
+    pub fn is_unprototyped_of(&self, signature: &Self) -> bool {
+        self.params.is_empty()
+            && !self.is_variadic
+            && self.ret == signature.ret
+            && !signature.is_variadic
+            && signature
+                .params
+                .iter()
+                .all(|param| &param.promote_argument() == param)
+    }
 }
 
 impl HasDtype for FunctionSignature {
     fn dtype(&self) -> Dtype {
-        Dtype::function(self.ret.clone(), self.params.clone())
+        Dtype::function(self.ret.clone(), self.params.clone()).set_variadic(self.is_variadic)
     }
 }
 
@@ -166,6 +247,10 @@ pub struct FunctionDefinition {
     /// function invocation.
     pub allocations: Vec<Named<Dtype>>,
 
+    /// The alignment in bytes given by `_Alignas` to each allocation, in the same order as
+    /// `allocations`. Empty if no allocation has `_Alignas`.
+    pub allocation_alignments: Vec<Option<usize>>,
+
     /// Basic blocks.
     pub blocks: BTreeMap<BlockId, Block>,
 
@@ -173,6 +258,20 @@ pub struct FunctionDefinition {
     pub bid_init: BlockId,
 }
 
+impl FunctionDefinition {
+    /// Returns the alignment in bytes of the allocation `aid`, which is the alignment of its type
+    /// unless a stricter one is given by `_Alignas`.
+    pub fn allocation_align_of(
+        &self,
+        aid: usize,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<usize, DtypeError> {
+        let (_, align_of) = self.allocations[aid].size_align_of(structs)?;
+        let alignment = self.allocation_alignments.get(aid).copied().flatten();
+        Ok(alignment.map_or(align_of, |alignment| alignment.max(align_of)))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct BlockId(pub usize);
 
@@ -235,12 +334,29 @@ pub enum Instruction {
         offset: Operand,
         dtype: Dtype,
     },
+    /// Initializes the `va_list` pointed to by `ptr` to the first variable argument of the current
+    /// function, which must be variadic (`va_start` of C).
+    VaStart {
+        ptr: Operand,
+    },
+    /// Reads the next variable argument of `dtype` from the `va_list` pointed to by `ptr`, and
+    /// advances the `va_list` to the argument after it (`va_arg` of C).
+    VaArg {
+        ptr: Operand,
+        dtype: Dtype,
+    },
+    /// Finishes the use of the `va_list` pointed to by `ptr` (`va_end` of C).
+    VaEnd {
+        ptr: Operand,
+    },
 }
 
 impl HasDtype for Instruction {
     fn dtype(&self) -> Dtype {
         match self {
-            Self::Nop | Self::Store { .. } => Dtype::unit(),
+            Self::Nop | Self::Store { .. } | Self::VaStart { .. } | Self::VaEnd { .. } => {
+                Dtype::unit()
+            }
             Self::Value { value } => value.dtype(),
             Self::BinOp { dtype, .. }
             | Self::UnaryOp { dtype, .. }
@@ -251,7 +367,8 @@ impl HasDtype for Instruction {
                 target_dtype: dtype,
                 ..
             }
-            | Self::GetElementPtr { dtype, .. } => dtype.clone(),
+            | Self::GetElementPtr { dtype, .. }
+            | Self::VaArg { dtype, .. } => dtype.clone(),
             Self::Load { ptr } => ptr
                 .dtype()
                 .get_pointer_inner()
@@ -264,7 +381,63 @@ impl HasDtype for Instruction {
 
 impl Instruction {
     pub fn has_no_side_effects(&self) -> bool {
-        !matches!(self, Self::Store { .. } | Self::Call { .. })
+        !matches!(
+            self,
+            Self::Store { .. }
+                | Self::Call { .. }
+                | Self::VaStart { .. }
+                | Self::VaArg { .. }
+                | Self::VaEnd { .. }
+        )
+    }
+
+    /// Returns the operands of the instruction.
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Self::Nop => Vec::new(),
+            Self::Value { value } | Self::TypeCast { value, .. } => vec![value],
+            Self::BinOp { lhs, rhs, .. } => vec![lhs, rhs],
+            Self::UnaryOp { operand, .. } => vec![operand],
+            Self::Store { ptr, value } => vec![ptr, value],
+            Self::Load { ptr }
+            | Self::VaStart { ptr }
+            | Self::VaArg { ptr, .. }
+            | Self::VaEnd { ptr } => vec![ptr],
+            Self::Call { callee, args, .. } => Some(callee).into_iter().chain(args).collect(),
+            Self::GetElementPtr { ptr, offset, .. } => vec![ptr, offset],
+        }
+    }
+
+    /// Applies `f` to each operand of the instruction.
+    pub fn walk_operands<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Operand),
+    {
+        match self {
+            Self::Nop => {}
+            Self::Value { value } | Self::TypeCast { value, .. } => f(value),
+            Self::BinOp { lhs, rhs, .. } => {
+                f(lhs);
+                f(rhs);
+            }
+            Self::UnaryOp { operand, .. } => f(operand),
+            Self::Store { ptr, value } => {
+                f(ptr);
+                f(value);
+            }
+            Self::Load { ptr }
+            | Self::VaStart { ptr }
+            | Self::VaArg { ptr, .. }
+            | Self::VaEnd { ptr } => f(ptr),
+            Self::Call { callee, args, .. } => {
+                f(callee);
+                args.iter_mut().for_each(f);
+            }
+            Self::GetElementPtr { ptr, offset, .. } => {
+                f(ptr);
+                f(offset);
+            }
+        }
     }
 }
 
@@ -350,6 +523,9 @@ impl fmt::Display for Instruction {
             Instruction::GetElementPtr { ptr, offset, .. } => {
                 write!(f, "getelementptr {ptr} offset {offset}")
             }
+            Instruction::VaStart { ptr } => write!(f, "va_start {ptr}"),
+            Instruction::VaArg { ptr, .. } => write!(f, "va_arg {ptr}"),
+            Instruction::VaEnd { ptr } => write!(f, "va_end {ptr}"),
         }
     }
 }
@@ -397,6 +573,47 @@ impl BlockExit {
             Self::Return { .. } | Self::Unreachable => {}
         }
     }
+
+    /// Returns the jumps of the block exit.
+    pub fn jump_args(&self) -> Vec<&JumpArg> {
+        match self {
+            Self::Jump { arg } => vec![arg],
+            Self::ConditionalJump {
+                arg_then, arg_else, ..
+            } => vec![arg_then, arg_else],
+            Self::Switch { default, cases, .. } => Some(default)
+                .into_iter()
+                .chain(cases.iter().map(|(_, arg)| arg))
+                .collect(),
+            Self::Return { .. } | Self::Unreachable => Vec::new(),
+        }
+    }
+
+    /// Returns the operands of the block exit, including the arguments of its jumps.
+    pub fn operands(&self) -> Vec<&Operand> {
+        let operand = match self {
+            Self::ConditionalJump { condition, .. } => Some(condition),
+            Self::Switch { value, .. } | Self::Return { value } => Some(value),
+            Self::Jump { .. } | Self::Unreachable => None,
+        };
+        operand
+            .into_iter()
+            .chain(self.jump_args().into_iter().flat_map(|arg| &arg.args))
+            .collect()
+    }
+
+    /// Applies `f` to each operand of the block exit, including the arguments of its jumps.
+    pub fn walk_operands<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Operand),
+    {
+        match self {
+            Self::ConditionalJump { condition, .. } => f(condition),
+            Self::Switch { value, .. } | Self::Return { value } => f(value),
+            Self::Jump { .. } | Self::Unreachable => {}
+        }
+        self.walk_jump_args(|arg| arg.args.iter_mut().for_each(&mut f));
+    }
 }
 
 impl fmt::Display for BlockExit {
@@ -624,13 +841,13 @@ pub enum Constant {
         is_signed: bool,
     },
     Float {
-        /// `value` may be `f32`, but it is possible to consider it as `f64`.
+        /// `value` may be `f32` or `f64`, but it is possible to consider it as `Quad`.
         ///
-        /// * Casting from an f32 to an f64 is perfect and lossless (f32 -> f64)
-        /// * Casting from an f64 to an f32 will produce the closest possible value (f64 -> f32)
+        /// * Casting from an f32 or an f64 to a `Quad` is perfect and lossless
+        /// * Casting from a `Quad` to an f32 or an f64 will produce the closest possible value
         ///
         /// For more details: <https://doc.rust-lang.org/stable/reference/expressions/operator-expr.html#type-cast-expressions>
-        value: OrderedFloat<f64>,
+        value: Quad,
         width: usize,
     },
     GlobalVariable {
@@ -716,7 +933,15 @@ impl TryFrom<&ast::Constant> for Constant {
                         (Dtype::DOUBLE, value)
                     }
                     ast::FloatFormat::LongDouble => {
-                        panic!("`FloatFormat::LongDouble` is_unsupported")
+                        let value = match pat {
+                            Self::DECIMAL => Quad::from_decimal_str(&float.number).unwrap(),
+                            Self::HEXADECIMAL => Quad::from_hex_str(&float.number).unwrap(),
+                            _ => panic!(
+                                "Constant::try_from::<&ast::Constant>: \
+                                 {pat:?} is not a pattern of `pat`"
+                            ),
+                        };
+                        return Ok(Self::float(value, Dtype::LONG_DOUBLE));
                     }
                     ast::FloatFormat::TS18661Format(_) => {
                         panic!("`FloatFormat::TS18661Format` is_unsupported")
@@ -727,9 +952,9 @@ impl TryFrom<&ast::Constant> for Constant {
             }
             ast::Constant::Character(character) => {
                 let dtype = Dtype::CHAR;
-                let value = character.parse::<char>().unwrap() as u128;
+                let value = initializer::character_constant_byte(character)?;
 
-                Ok(Self::int(value, dtype))
+                Ok(Self::int(value as u128, dtype))
             }
         }
     }
@@ -794,14 +1019,15 @@ impl Constant {
         }
     }
 
+    /// Creates a floating-point constant, rounding `value` to the nearest value of `dtype`.
     #[inline]
-    pub fn float(value: f64, dtype: Dtype) -> Self {
+    pub fn float<T: Into<Quad>>(value: T, dtype: Dtype) -> Self {
         let width = dtype
             .get_float_width()
             .expect("`dtype` must be `Dtype::Float`");
 
         Self::Float {
-            value: value.into(),
+            value: value.into().round_to_width(width),
             width,
         }
     }
@@ -850,10 +1076,10 @@ impl Constant {
                     is_signed,
                 }
             }
-            Self::Float { mut value, width } => {
-                *value.as_mut() *= -1.0f64;
-                Self::Float { value, width }
-            }
+            Self::Float { value, width } => Self::Float {
+                value: value.neg(),
+                width,
+            },
             _ => panic!(
                 "constant value generated by `Constant::from_ast_expression` \
                  must be `Constant(Int, Float)`"
@@ -880,7 +1106,9 @@ impl Constant {
                     ..
                 },
             ) => {
-                let result = if *target_signed {
+                let result = if *target_width == 1 {
+                    u128::from(trim_unnecessary_bits(*value, *width as u128) != 0)
+                } else if *target_signed {
                     if *width >= *target_width {
                         let value = trim_unnecessary_bits(*value, *target_width as u128);
                         sign_extension(value, *target_width as u128)
@@ -900,24 +1128,31 @@ impl Constant {
                 Dtype::Float { .. },
             ) => {
                 let casted_value = if *is_signed {
-                    *value as i128 as f64
+                    Quad::from_i128(*value as i128)
                 } else {
-                    *value as f64
+                    Quad::from_u128(*value)
                 };
 
                 Constant::float(casted_value, target_dtype)
             }
-            (Constant::Float { value, .. }, Dtype::Int { is_signed, .. }) => {
-                let casted_value = if *is_signed {
-                    value.into_inner() as i128 as u128
+            (
+                Constant::Float { value, .. },
+                Dtype::Int {
+                    width, is_signed, ..
+                },
+            ) => {
+                let casted_value = if *width == 1 {
+                    u128::from(!value.is_zero())
+                } else if *is_signed {
+                    value.to_i128() as u128
                 } else {
-                    value.into_inner() as u128
+                    value.to_u128()
                 };
 
                 Constant::int(casted_value, target_dtype)
             }
             (Constant::Float { value, .. }, Dtype::Float { .. }) => {
-                Constant::float(value.into_inner(), target_dtype)
+                Constant::float(*value, target_dtype)
             }
             _ => todo!("typecast ({:?}) {:?}", self, target_dtype),
         }
@@ -957,8 +1192,15 @@ impl fmt::Display for Constant {
                     value.to_string()
                 }
             ),
+            // `f128` values are written in the hexadecimal format because they cannot be
+            // represented in `f64`.
+            Self::Float { value, width }
+                if *width > Dtype::SIZE_OF_DOUBLE * Dtype::BITS_OF_BYTE =>
+            {
+                write!(f, "{value}")
+            }
             Self::Float { value, .. } => {
-                let mut val_str = value.to_string();
+                let mut val_str = value.to_f64().to_string();
                 // For floats with integral values, ".0" is added.
                 if !val_str.contains('.') {
                     val_str += ".0"
@@ -4,6 +4,7 @@ use std::path::Path;
 use lang_c::*;
 
 use crate::Translate;
+use crate::c::Diagnostics;
 use crate::ir::*;
 use crate::utils::AssertSupported;
 
@@ -46,49 +47,98 @@ peg::parser! {
             }
         /
             "struct" __ id:id() _ ":" _ "{" _ fields:(struct_field() ** (_ "," _)) _ "}"  {
-                let struct_type = Dtype::structure(Some(id.clone()), Some(fields));
+                let (fields, bit_widths) = fields.into_iter().unzip();
+                let struct_type =
+                    Dtype::structure(Some(id.clone()), Some(fields)).set_bit_widths(bit_widths);
                 Named::new(Some(id), Some(struct_type))
             }
+        /
+            "union" __ id:id() _ ":" _ "opaque" {
+                Named::new(Some(id), None)
+            }
+        /
+            "union" __ id:id() _ ":" _ "{" _ fields:(struct_field() ** (_ "," _)) _ "}"  {
+                let (fields, bit_widths) = fields.into_iter().unzip();
+                let union_type =
+                    Dtype::union(Some(id.clone()), Some(fields)).set_bit_widths(bit_widths);
+                Named::new(Some(id), Some(union_type))
+            }
         /
             "<named_struct>" {
                 todo!()
             }
 
-        rule struct_field() -> Named<Dtype> =
-            "%anon" _ ":" _ dtype:dtype() {
-                Named::new(None, dtype)
+        rule struct_field() -> (Named<Dtype>, Option<usize>) =
+            "%anon" _ ":" _ dtype:dtype() bit_width:bit_width()? {
+                (Named::new(None, dtype), bit_width)
             }
         /
-            id:id() _ ":" _ dtype:dtype() {
-                Named::new(Some(id), dtype)
+            id:id() _ ":" _ dtype:dtype() bit_width:bit_width()? {
+                (Named::new(Some(id), dtype), bit_width)
             }
         /
             "<struct_field>" {
                 todo!()
             }
 
+        rule bit_width() -> usize =
+            _ ":" _ width:number() {
+                width
+            }
+
         rule named_decl() -> Named<Declaration> =
-            "var" __ dtype:dtype() __ var:global_variable() _ "=" _ initializer:initializer() {
+            "var" __ linkage:linkage() dtype:dtype() __ var:global_variable() alignment:alignment()? _ "=" _ initializer:initializer() {
                 Named::new(Some(var), Declaration::Variable {
                     dtype,
                     initializer,
+                    linkage,
+                    is_definition: true,
+                    alignment,
                 })
             }
         /
-            "fun" __ dtype:dtype() __ var:global_variable() _ "(" params:(dtype() ** (_ "," _)) _ ")" _ "{" _ fun_body:fun_body() _ "}" {
+            "var" __ linkage:linkage() dtype:dtype() __ var:global_variable() alignment:alignment()? {
+                Named::new(Some(var), Declaration::Variable {
+                    dtype,
+                    initializer: None,
+                    linkage,
+                    is_definition: false,
+                    alignment,
+                })
+            }
+        /
+            "fun" __ linkage:linkage() dtype:dtype() __ var:global_variable() _ "(" params:params() _ ")" _ "{" _ fun_body:fun_body() _ "}" {
+                let (params, is_variadic) = params;
                 Named::new(Some(var), Declaration::Function {
-                    signature: FunctionSignature::new(Dtype::function(dtype, params)),
+                    signature: FunctionSignature::new(
+                        Dtype::function(dtype, params).set_variadic(is_variadic),
+                    ),
                     definition: Some(fun_body),
+                    linkage,
                 })
             }
         /
-            "fun" __ dtype:dtype() __ var:global_variable() _ "(" params:(dtype() ** (_ "," _)) _ ")" {
+            "fun" __ linkage:linkage() dtype:dtype() __ var:global_variable() _ "(" params:params() _ ")" {
+                let (params, is_variadic) = params;
                 Named::new(Some(var), Declaration::Function {
-                    signature: FunctionSignature::new(Dtype::function(dtype, params)),
+                    signature: FunctionSignature::new(
+                        Dtype::function(dtype, params).set_variadic(is_variadic),
+                    ),
                     definition: None,
+                    linkage,
                 })
             }
 
+        // The external linkage is the default, and only the internal linkage is written.
+        rule linkage() -> Linkage =
+            "internal" __ {
+                Linkage::Internal
+            }
+        /
+            "" {
+                Linkage::External
+            }
+
         rule dtype() -> Dtype =
             inner:dtype_inner() is_consts:(is_const_of_pointer() ** _) {
                 let mut inner = inner;
@@ -111,24 +161,39 @@ peg::parser! {
                 Dtype::Array { inner: Box::new(inner), size: n }
             }
         /
-            "[ret:" _ ret:dtype() __ "params:(" params:(dtype() ** (_ "," _)) _ ")]" {
-                Dtype::Function { ret: Box::new(ret), params }
+            "[ret:" _ ret:dtype() __ "params:(" params:params() _ ")]" {
+                let (params, is_variadic) = params;
+                Dtype::function(ret, params).set_variadic(is_variadic)
             }
         /
             "struct" __ id:id() {
                 Dtype::structure(Some(id), None)
             }
+        /
+            "union" __ id:id() {
+                Dtype::union(Some(id), None)
+            }
         /
             "const" __ dtype:dtype_inner() { dtype.set_const(true) }
         /
             expected!("dtype")
 
+        /// Parameter types, followed by `...` if the function is variadic.
+        rule params() -> (Vec<Dtype>, bool) =
+            params:(dtype() ** (_ "," _)) ellipsis:(_ "," _ "..." {} / "..." {})? {
+                (params, ellipsis.is_some())
+            }
+
         rule is_const_of_pointer() -> bool =
             _ "*" _ "const" { true }
         /
             _ "*" { false }
 
         rule id() -> String =
+            static_id:$(['_' | 'a'..='z' | 'A'..='Z']['_' | 'a'..='z' | 'A'..='Z' | '0'..='9']* "." ['0'..='9']+) {
+                String::from(static_id)
+            }
+        /
             n:$(['_' | 'a'..='z' | 'A'..='Z']['_' | 'a'..='z' | 'A'..='Z' | '0'..='9']*) {
                 String::from(n)
             }
@@ -136,6 +201,14 @@ peg::parser! {
             temp_id:$(['%']['t']['0'..='9']+) {
                 String::from(temp_id)
             }
+        /
+            string_id:$(".str" ['0'..='9']+) {
+                String::from(string_id)
+            }
+        /
+            compound_id:$(".compound" ['0'..='9']+) {
+                String::from(compound_id)
+            }
         /
             expected!("id")
 
@@ -152,16 +225,29 @@ peg::parser! {
 
         rule fun_body() -> FunctionDefinition
             = "init:" __ "bid:" _ bid_init:bid() _ "allocations:" _ allocations:(allocation() ** __) _ blocks:(block() ** __) {
+                let (allocations, allocation_alignments): (Vec<_>, Vec<_>) =
+                    allocations.into_iter().map(|a| (a.1, a.2)).unzip();
+                let allocation_alignments = if allocation_alignments.iter().all(Option::is_none) {
+                    Vec::new()
+                } else {
+                    allocation_alignments
+                };
                 FunctionDefinition {
-                    allocations: allocations.into_iter().map(|a| a.1).collect(),
+                    allocations,
+                    allocation_alignments,
                     blocks: blocks.into_iter().collect(),
                     bid_init,
                 }
             }
 
-        rule allocation() -> (usize, Named<Dtype>)
-            = "%l" number:number() ":" dtype:dtype() ":" name:id() {
-                (number, Named::new(Some(name), dtype))
+        rule allocation() -> (usize, Named<Dtype>, Option<usize>)
+            = "%l" number:number() ":" dtype:dtype() ":" name:id() alignment:alignment()? {
+                (number, Named::new(Some(name), dtype), alignment)
+            }
+
+        rule alignment() -> usize =
+            __ "align" __ alignment:number() {
+                alignment
             }
 
         rule block() -> (BlockId, Block)
@@ -188,9 +274,13 @@ peg::parser! {
             }
         / expected!("number")
 
-        rule float_number() -> f64
-            = f:$(['0'..='9']+['.']['0'..='9']+) {
-                f.parse().unwrap()
+        rule float_number() -> Quad
+            = f:$("0x" ['0'..='9' | 'a'..='f']+ ("." ['0'..='9' | 'a'..='f']+)? "p" ['+' | '-'] ['0'..='9']+) {
+                Quad::from_hex_str(f).unwrap()
+            }
+        /
+            f:$(['0'..='9']+['.']['0'..='9']+) {
+                Quad::from_f64(f.parse().unwrap())
             }
         / expected!("float_number")
 
@@ -212,10 +302,14 @@ peg::parser! {
                 // has type `*[5 x i32]`, after applying `GetElementPtr`, result' dtype can currently
                 // be `*i32` or `*[5 x i32]`. Thus, we need to check the dtype of the result to confirm
                 // the dtype of `GetElementPtr` instruction when parsing IR.
-                let instruction = if let Instruction::GetElementPtr { ptr, offset, .. } = instruction {
-                    Instruction::GetElementPtr { ptr, offset, dtype }
-                } else {
-                    instruction
+                //
+                // Likewise, the dtype of `VaArg` instruction is given only by the dtype of the result.
+                let instruction = match instruction {
+                    Instruction::GetElementPtr { ptr, offset, .. } => {
+                        Instruction::GetElementPtr { ptr, offset, dtype }
+                    }
+                    Instruction::VaArg { ptr, .. } => Instruction::VaArg { ptr, dtype },
+                    instruction => instruction,
                 };
 
                 (bid, number, Named::new(name, instruction))
@@ -251,6 +345,18 @@ peg::parser! {
                     return_type,
                 }
             }
+        /
+            "va_start" __ ptr:operand() {
+                Instruction::VaStart { ptr }
+            }
+        /
+            "va_arg" __ ptr:operand() {
+                Instruction::VaArg { ptr, dtype: Dtype::unit() }
+            }
+        /
+            "va_end" __ ptr:operand() {
+                Instruction::VaEnd { ptr }
+            }
         /
             "typecast" __ value:operand() __ "to" __ target_dtype:dtype() {
                 Instruction::TypeCast { value, target_dtype }
@@ -391,11 +497,11 @@ peg::parser! {
 
         rule constant() -> Constant =
             f:float_number() {
-                Constant::float(f, Dtype::float(64)) // TODO: the right dtype
+                Constant::float(f, Dtype::LONG_DOUBLE) // TODO: the right dtype
             }
         /
             "-" f:float_number() {
-                Constant::minus(Constant::float(f, Dtype::float(64))) // TODO: the right dtype
+                Constant::minus(Constant::float(f, Dtype::LONG_DOUBLE)) // TODO: the right dtype
             }
         /
             n:number() {
@@ -451,7 +557,7 @@ peg::parser! {
                     }
                     (Constant::Float { value, .. }, Dtype::Float { width, .. }) => {
                         Constant::Float {
-                            value: *value,
+                            value: value.round_to_width(*width),
                             width: *width,
                         }
                     }
@@ -487,9 +593,14 @@ peg::parser! {
                 None
             }
         /
-            init:ast_initializer() {
-                init.assert_supported();
-                Some(init)
+            init:ast_initializer() {?
+                let mut diags = Diagnostics::default();
+                init.assert_supported(&mut diags);
+                if diags.is_empty() {
+                    Ok(Some(init))
+                } else {
+                    Err("supported initializer")
+                }
             }
         /
             "<initializer>" {
@@ -502,26 +613,78 @@ peg::parser! {
                 ast::Initializer::Expression(expr)
             }
         /
-            "{" _ exprs:(ast_initializer() ** (_ "," _)) _ "}" {
-                let list = exprs
-                    .iter()
-                    .map(|e| {
-                        let initializer = Box::new(span::Node::new(e.clone(), span::Span::none()));
-                        let item = ast::InitializerListItem{
-                            designation: Vec::new(),
-                            initializer,
-                        };
-                        span::Node::new(item, span::Span::none())
-                    })
-                    .collect();
-                ast::Initializer::List(list)
+            "{" _ items:(ast_initializer_list_item() ** (_ "," _)) _ "}" {
+                ast::Initializer::List(items)
             }
         /
             "<ast_initializer>" {
                 todo!()
             }
 
-        rule ast_expression() -> ast::Expression =
+        rule ast_initializer_list_item() -> span::Node<ast::InitializerListItem> =
+            designation:(ast_designator() ** _) _ "=" _ initializer:ast_initializer() {
+                let item = ast::InitializerListItem {
+                    designation,
+                    initializer: Box::new(node(initializer)),
+                };
+                node(item)
+            }
+        /
+            initializer:ast_initializer() {
+                let item = ast::InitializerListItem {
+                    designation: Vec::new(),
+                    initializer: Box::new(node(initializer)),
+                };
+                node(item)
+            }
+
+        rule ast_designator() -> span::Node<ast::Designator> =
+            "[" _ expr:ast_expression() _ "]" {
+                node(ast::Designator::Index(node(expr)))
+            }
+        /
+            "." _ id:id() {
+                node(ast::Designator::Member(node(ast::Identifier { name: id })))
+            }
+
+        // Address constants, e.g., `&g.f`, `&a[1]` and `a + 2`.
+        rule ast_expression() -> ast::Expression = precedence!{
+            lhs:(@) _ "+" _ rhs:@ {
+                binary_expression(ast::BinaryOperator::Plus, lhs, rhs)
+            }
+            lhs:(@) _ "-" _ rhs:@ {
+                binary_expression(ast::BinaryOperator::Minus, lhs, rhs)
+            }
+            --
+            "&" _ operand:@ {
+                unary_expression(ast::UnaryOperator::Address, operand)
+            }
+            "*" _ operand:@ {
+                unary_expression(ast::UnaryOperator::Indirection, operand)
+            }
+            --
+            lhs:@ _ "[" _ rhs:ast_expression() _ "]" {
+                binary_expression(ast::BinaryOperator::Index, lhs, rhs)
+            }
+            expression:@ _ "." _ id:id() {
+                member_expression(ast::MemberOperator::Direct, expression, id)
+            }
+            expression:@ _ "->" _ id:id() {
+                member_expression(ast::MemberOperator::Indirect, expression, id)
+            }
+            --
+            id:id() {
+                ast::Expression::Identifier(Box::new(node(ast::Identifier { name: id })))
+            }
+            expr:ast_primary_expression() {
+                expr
+            }
+            "(" _ expr:ast_expression() _ ")" {
+                expr
+            }
+        }
+
+        rule ast_primary_expression() -> ast::Expression =
             constant:ast_constant() {
                 let constant = Box::new(span::Node::new(constant, span::Span::none()));
                 ast::Expression::Constant(constant)
@@ -540,11 +703,34 @@ peg::parser! {
 
                 ast::Expression::UnaryOperator(unary_expr)
             }
+        /
+            operator:ast_unaryop() _ constant:ast_constant() {
+                let constant = Box::new(span::Node::new(constant, span::Span::none()));
+                let expr = ast::Expression::Constant(constant);
+                let operand = Box::new(span::Node::new(expr, span::Span::none()));
+
+                let unary_expr = ast::UnaryOperatorExpression{
+                    operator: span::Node::new(operator, span::Span::none()),
+                    operand,
+                };
+                let unary_expr = Box::new(span::Node::new(unary_expr, span::Span::none()));
+
+                ast::Expression::UnaryOperator(unary_expr)
+            }
+        /
+            literal:ast_string_literal() {
+                ast::Expression::StringLiteral(Box::new(node(literal)))
+            }
         /
             "<ast_expression>" {
                 todo!()
             }
 
+        rule ast_string_literal() -> ast::StringLiteral =
+            pieces:($("\"" (("\\" [_]) / [^ '"' | '\\' | '\n'])* "\"") ++ _) {
+                pieces.into_iter().map(String::from).collect()
+            }
+
         rule ast_unaryop() -> ast::UnaryOperator =
             "+" {
                 ast::UnaryOperator::Plus
@@ -614,6 +800,22 @@ peg::parser! {
             }
 
         rule ast_integer_suffix() -> ast::IntegerSuffix =
+            (['u' | 'U'] ['l' | 'L'] / ['l' | 'L'] ['u' | 'U']) {
+                ast::IntegerSuffix {
+                    size: ast::IntegerSize::Long,
+                    unsigned: true,
+                    imaginary: false,
+                }
+            }
+        /
+            ['u' | 'U'] {
+                ast::IntegerSuffix {
+                    size: ast::IntegerSize::Int,
+                    unsigned: true,
+                    imaginary: false,
+                }
+            }
+        /
             ['l' | 'L'] {
                 ast::IntegerSuffix {
                     size: ast::IntegerSize::Long,
@@ -638,6 +840,14 @@ peg::parser! {
                     suffix,
                 }
             }
+        /
+            ['0']['x' | 'X'] number:$(['0'..='9' | 'a'..='f' | 'A'..='F']+ ("." ['0'..='9' | 'a'..='f' | 'A'..='F']*)? ['p' | 'P'] ['+' | '-']? ['0'..='9']+) suffix:ast_float_suffix() {
+                ast::Float {
+                    base: ast::FloatBase::Hexadecimal,
+                    number: Box::from(number),
+                    suffix,
+                }
+            }
         /
             "<ast_float>" {
                 todo!()
@@ -650,6 +860,13 @@ peg::parser! {
                     imaginary: false,
                 }
             }
+        /
+            ['l' | 'L'] {
+                ast::FloatSuffix {
+                    format: ast::FloatFormat::LongDouble,
+                    imaginary: false,
+                }
+            }
         /
             "" {
                 ast::FloatSuffix {
@@ -721,3 +938,42 @@ fn resolve_structs(struct_type: Dtype, structs: &mut HashMap<String, Option<Dtyp
     let result = structs.insert(name, Some(filled_struct));
     assert!(result.is_some());
 }
+
+#[inline]
+fn node<T>(node: T) -> span::Node<T> {
+    span::Node::new(node, span::Span::none())
+}
+
+fn unary_expression(operator: ast::UnaryOperator, operand: ast::Expression) -> ast::Expression {
+    let unary = ast::UnaryOperatorExpression {
+        operator: node(operator),
+        operand: Box::new(node(operand)),
+    };
+    ast::Expression::UnaryOperator(Box::new(node(unary)))
+}
+
+fn binary_expression(
+    operator: ast::BinaryOperator,
+    lhs: ast::Expression,
+    rhs: ast::Expression,
+) -> ast::Expression {
+    let binary = ast::BinaryOperatorExpression {
+        operator: node(operator),
+        lhs: Box::new(node(lhs)),
+        rhs: Box::new(node(rhs)),
+    };
+    ast::Expression::BinaryOperator(Box::new(node(binary)))
+}
+
+fn member_expression(
+    operator: ast::MemberOperator,
+    expression: ast::Expression,
+    identifier: String,
+) -> ast::Expression {
+    let member = ast::MemberExpression {
+        operator: node(operator),
+        expression: Box::new(node(expression)),
+        identifier: node(ast::Identifier { name: identifier }),
+    };
+    ast::Expression::Member(Box::new(node(member)))
+}
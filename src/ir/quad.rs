@@ -0,0 +1,889 @@
+//! IEEE 754 quadruple precision (binary128) floating-point numbers, i.e., `long double` of LP64.
+//!
+//! Rust has no stable 128-bit floating-point type, so the arithmetic is implemented in software
+//! with round-to-nearest-even, as done by the soft-float routines of the RISC-V toolchain (e.g.,
+//! `__addtf3`).
+
+use core::cmp::Ordering;
+use core::fmt;
+
+/// A binary floating-point format, given by the number of bits of its fraction and exponent.
+#[derive(Debug, Clone, Copy)]
+struct Format {
+    fraction_bits: u32,
+    exponent_bits: u32,
+}
+
+impl Format {
+    const SINGLE: Self = Self::new(23, 8);
+    const DOUBLE: Self = Self::new(52, 11);
+    const QUAD: Self = Self::new(112, 15);
+
+    const fn new(fraction_bits: u32, exponent_bits: u32) -> Self {
+        Self {
+            fraction_bits,
+            exponent_bits,
+        }
+    }
+
+    const fn bias(self) -> i32 {
+        (1 << (self.exponent_bits - 1)) - 1
+    }
+
+    const fn max_biased_exponent(self) -> u128 {
+        (1 << self.exponent_bits) - 1
+    }
+
+    const fn sign_bit(self) -> u128 {
+        1 << (self.fraction_bits + self.exponent_bits)
+    }
+
+    const fn quiet_nan(self) -> u128 {
+        (self.max_biased_exponent() << self.fraction_bits) | (1 << (self.fraction_bits - 1))
+    }
+
+    /// The exponent of the least significant bit of the subnormal numbers.
+    const fn min_quantum_exponent(self) -> i32 {
+        1 - self.bias() - self.fraction_bits as i32
+    }
+
+    /// Decodes `bits` to a finite number `(-1)^sign * significand * 2^exponent`, or returns
+    /// `None` if `bits` is an infinity or a NaN.
+    fn decode(self, bits: u128) -> Option<(bool, i32, u128)> {
+        let sign = bits & self.sign_bit() != 0;
+        let biased = (bits >> self.fraction_bits) & self.max_biased_exponent();
+        let fraction = bits & ((1 << self.fraction_bits) - 1);
+
+        if biased == self.max_biased_exponent() {
+            None
+        } else if biased == 0 {
+            Some((sign, self.min_quantum_exponent(), fraction))
+        } else {
+            let exponent = biased as i32 - self.bias() - self.fraction_bits as i32;
+            Some((sign, exponent, fraction | (1 << self.fraction_bits)))
+        }
+    }
+
+    /// Encodes `(-1)^sign * (significand + e) * 2^exponent` rounded to nearest even, where `e` is
+    /// a number in `(0, 1)` if `sticky` and `0` otherwise.
+    ///
+    /// If `sticky`, `significand` must have more bits than the format so that the bit right below
+    /// the rounded significand, i.e., the round bit, is in `significand`.
+    fn encode(self, sign: bool, exponent: i32, significand: u128, sticky: bool) -> u128 {
+        let sign_bit = if sign { self.sign_bit() } else { 0 };
+        if significand == 0 && !sticky {
+            return sign_bit;
+        }
+
+        // The exponent of the least significant bit of the rounded significand.
+        let length = (u128::BITS - significand.leading_zeros()) as i32;
+        let quantum =
+            (exponent + length - 1 - self.fraction_bits as i32).max(self.min_quantum_exponent());
+        let shift = quantum - exponent;
+
+        let mut significand = if shift <= 0 {
+            debug_assert!(!sticky || shift < 0, "round bit must be given");
+            significand << -shift
+        } else {
+            shift_right_round(significand, shift as u32, sticky)
+        };
+        let mut quantum = quantum;
+        if significand >> (self.fraction_bits + 1) != 0 {
+            significand >>= 1;
+            quantum += 1;
+        }
+
+        if significand >> self.fraction_bits == 0 {
+            // Subnormal numbers, including zero rounded from a tiny number.
+            return sign_bit | significand;
+        }
+
+        let biased = (quantum - self.min_quantum_exponent() + 1) as u128;
+        if biased >= self.max_biased_exponent() {
+            return sign_bit | (self.max_biased_exponent() << self.fraction_bits);
+        }
+        let fraction = significand & ((1 << self.fraction_bits) - 1);
+        sign_bit | (biased << self.fraction_bits) | fraction
+    }
+
+    /// Converts `bits` of `from` format to this format.
+    fn convert(self, from: Self, bits: u128) -> u128 {
+        match from.decode(bits) {
+            Some((sign, exponent, significand)) => self.encode(sign, exponent, significand, false),
+            None => {
+                let sign = if bits & from.sign_bit() != 0 {
+                    self.sign_bit()
+                } else {
+                    0
+                };
+                let fraction = bits & ((1 << from.fraction_bits) - 1);
+                if fraction == 0 {
+                    sign | (self.max_biased_exponent() << self.fraction_bits)
+                } else {
+                    sign | self.quiet_nan()
+                }
+            }
+        }
+    }
+}
+
+/// Shifts `value` right by `shift` bits, rounding to nearest even. The bits below `value` are
+/// nonzero if `sticky`.
+fn shift_right_round(value: u128, shift: u32, sticky: bool) -> u128 {
+    if shift > u128::BITS {
+        return 0;
+    }
+    let (truncated, half, rest) = if shift == u128::BITS {
+        (0, value >> (u128::BITS - 1) != 0, value << 1 != 0)
+    } else {
+        let dropped = value & ((1u128 << shift) - 1);
+        let half = 1u128 << (shift - 1);
+        (
+            value >> shift,
+            dropped & half != 0,
+            dropped & (half - 1) != 0,
+        )
+    };
+
+    let rest = rest || sticky;
+    if half && (rest || truncated & 1 != 0) {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
+
+/// A 128-bit floating-point number in the IEEE 754 binary128 format.
+///
+/// A `f32` or `f64` value is represented exactly, so it can be considered as a `Quad` value.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Quad(u128);
+
+impl Quad {
+    pub const ZERO: Self = Self(0);
+
+    #[inline]
+    pub fn from_bits(bits: u128) -> Self {
+        Self(bits)
+    }
+
+    #[inline]
+    pub fn to_bits(self) -> u128 {
+        self.0
+    }
+
+    #[inline]
+    pub fn from_f64(value: f64) -> Self {
+        Self(Format::QUAD.convert(Format::DOUBLE, value.to_bits() as u128))
+    }
+
+    #[inline]
+    pub fn from_f32(value: f32) -> Self {
+        Self(Format::QUAD.convert(Format::SINGLE, value.to_bits() as u128))
+    }
+
+    /// Converts to `f64`, rounding to nearest even.
+    #[inline]
+    pub fn to_f64(self) -> f64 {
+        f64::from_bits(Format::DOUBLE.convert(Format::QUAD, self.0) as u64)
+    }
+
+    /// Converts to `f32`, rounding to nearest even.
+    #[inline]
+    pub fn to_f32(self) -> f32 {
+        f32::from_bits(Format::SINGLE.convert(Format::QUAD, self.0) as u32)
+    }
+
+    /// Rounds to the nearest value of the floating-point type of `width` bits.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `width` is not one of 32, 64 and 128.
+    pub fn round_to_width(self, width: usize) -> Self {
+        match width {
+            32 => Self::from_f32(self.to_f32()),
+            64 => Self::from_f64(self.to_f64()),
+            128 => self,
+            _ => panic!("floating-point type of width {width} is not supported"),
+        }
+    }
+
+    #[inline]
+    pub fn from_u128(value: u128) -> Self {
+        Self(Format::QUAD.encode(false, 0, value, false))
+    }
+
+    #[inline]
+    pub fn from_i128(value: i128) -> Self {
+        Self(Format::QUAD.encode(value < 0, 0, value.unsigned_abs(), false))
+    }
+
+    /// Converts to `u128`, truncating toward zero. As with `as` casts of Rust, the result is
+    /// saturated and NaN is converted to zero.
+    pub fn to_u128(self) -> u128 {
+        if self.is_nan() {
+            return 0;
+        }
+        if self.is_sign_negative() {
+            return 0;
+        }
+        self.magnitude_to_u128().unwrap_or(u128::MAX)
+    }
+
+    /// Converts to `i128`, truncating toward zero. As with `as` casts of Rust, the result is
+    /// saturated and NaN is converted to zero.
+    pub fn to_i128(self) -> i128 {
+        if self.is_nan() {
+            return 0;
+        }
+        let magnitude = self.magnitude_to_u128();
+        if self.is_sign_negative() {
+            match magnitude {
+                Some(magnitude) if magnitude <= i128::MIN.unsigned_abs() => {
+                    (magnitude as i128).wrapping_neg()
+                }
+                _ => i128::MIN,
+            }
+        } else {
+            match magnitude {
+                Some(magnitude) if magnitude <= i128::MAX as u128 => magnitude as i128,
+                _ => i128::MAX,
+            }
+        }
+    }
+
+    /// Returns the integral part of the absolute value, or `None` if it does not fit in `u128`.
+    fn magnitude_to_u128(self) -> Option<u128> {
+        let (_, exponent, significand) = Format::QUAD.decode(self.0)?;
+        if exponent >= 0 {
+            let length = u128::BITS - significand.leading_zeros();
+            if length + exponent as u32 > u128::BITS {
+                return None;
+            }
+            Some(significand << exponent)
+        } else if -exponent >= u128::BITS as i32 {
+            Some(0)
+        } else {
+            Some(significand >> -exponent)
+        }
+    }
+
+    #[inline]
+    pub fn is_nan(self) -> bool {
+        Format::QUAD.decode(self.0).is_none() && self.fraction() != 0
+    }
+
+    #[inline]
+    pub fn is_infinite(self) -> bool {
+        Format::QUAD.decode(self.0).is_none() && self.fraction() == 0
+    }
+
+    #[inline]
+    pub fn is_zero(self) -> bool {
+        self.0 & !Format::QUAD.sign_bit() == 0
+    }
+
+    #[inline]
+    pub fn is_sign_negative(self) -> bool {
+        self.0 & Format::QUAD.sign_bit() != 0
+    }
+
+    #[inline]
+    fn fraction(self) -> u128 {
+        self.0 & ((1 << Format::QUAD.fraction_bits) - 1)
+    }
+
+    #[inline]
+    fn nan() -> Self {
+        Self(Format::QUAD.quiet_nan())
+    }
+
+    #[inline]
+    fn infinity(sign: bool) -> Self {
+        let sign = if sign { Format::QUAD.sign_bit() } else { 0 };
+        Self(sign | (Format::QUAD.max_biased_exponent() << Format::QUAD.fraction_bits))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn neg(self) -> Self {
+        Self(self.0 ^ Format::QUAD.sign_bit())
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn add(self, other: Self) -> Self {
+        if self.is_nan() || other.is_nan() {
+            return Self::nan();
+        }
+        match (self.is_infinite(), other.is_infinite()) {
+            (true, true) if self.is_sign_negative() != other.is_sign_negative() => {
+                return Self::nan();
+            }
+            (true, _) => return self,
+            (_, true) => return other,
+            _ => (),
+        }
+        if self.is_zero() && other.is_zero() {
+            return Self(self.0 & other.0);
+        }
+
+        let (lhs_sign, lhs_exponent, lhs) = Format::QUAD.decode(self.0).unwrap();
+        let (rhs_sign, rhs_exponent, rhs) = Format::QUAD.decode(other.0).unwrap();
+
+        // Keeps three more bits for rounding.
+        let (lhs, rhs) = (lhs << 3, rhs << 3);
+        let (lhs_exponent, rhs_exponent) = (lhs_exponent - 3, rhs_exponent - 3);
+        let ((big_sign, big_exponent, big), (small_sign, small_exponent, small)) =
+            if lhs_exponent >= rhs_exponent {
+                ((lhs_sign, lhs_exponent, lhs), (rhs_sign, rhs_exponent, rhs))
+            } else {
+                ((rhs_sign, rhs_exponent, rhs), (lhs_sign, lhs_exponent, lhs))
+            };
+
+        let shift = (big_exponent - small_exponent) as u32;
+        let (small, sticky) = if shift >= u128::BITS {
+            (0, small != 0)
+        } else {
+            (small >> shift, small & ((1 << shift) - 1) != 0)
+        };
+
+        if big_sign == small_sign {
+            return Self(Format::QUAD.encode(big_sign, big_exponent, big + small, sticky));
+        }
+
+        match big.cmp(&small) {
+            Ordering::Equal => Self::ZERO,
+            Ordering::Greater => {
+                // The exact difference is `big - small - e` for some `e` in `(0, 1)` if `sticky`.
+                let difference = big - small - u128::from(sticky);
+                Self(Format::QUAD.encode(big_sign, big_exponent, difference, sticky))
+            }
+            Ordering::Less => {
+                debug_assert!(!sticky);
+                Self(Format::QUAD.encode(small_sign, big_exponent, small - big, false))
+            }
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn sub(self, other: Self) -> Self {
+        self.add(other.neg())
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn mul(self, other: Self) -> Self {
+        let sign = self.is_sign_negative() != other.is_sign_negative();
+        if self.is_nan() || other.is_nan() {
+            return Self::nan();
+        }
+        if self.is_infinite() || other.is_infinite() {
+            if self.is_zero() || other.is_zero() {
+                return Self::nan();
+            }
+            return Self::infinity(sign);
+        }
+
+        let (_, lhs_exponent, lhs) = Format::QUAD.decode(self.0).unwrap();
+        let (_, rhs_exponent, rhs) = Format::QUAD.decode(other.0).unwrap();
+        let exponent = lhs_exponent + rhs_exponent;
+
+        let (high, low) = widening_mul(lhs, rhs);
+        if high == 0 {
+            return Self(Format::QUAD.encode(sign, exponent, low, false));
+        }
+
+        let shift = u128::BITS - high.leading_zeros();
+        let significand = (high << (u128::BITS - shift)) | (low >> shift);
+        let sticky = low & ((1 << shift) - 1) != 0;
+        Self(Format::QUAD.encode(sign, exponent + shift as i32, significand, sticky))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn div(self, other: Self) -> Self {
+        let sign = self.is_sign_negative() != other.is_sign_negative();
+        if self.is_nan() || other.is_nan() {
+            return Self::nan();
+        }
+        match (self.is_infinite(), other.is_infinite()) {
+            (true, true) => return Self::nan(),
+            (true, false) => return Self::infinity(sign),
+            (false, true) => return Self(if sign { Format::QUAD.sign_bit() } else { 0 }),
+            (false, false) => (),
+        }
+        match (self.is_zero(), other.is_zero()) {
+            (true, true) => return Self::nan(),
+            (false, true) => return Self::infinity(sign),
+            (true, false) => return Self(if sign { Format::QUAD.sign_bit() } else { 0 }),
+            (false, false) => (),
+        }
+
+        let (_, lhs_exponent, lhs) = normalize(Format::QUAD.decode(self.0).unwrap());
+        let (_, rhs_exponent, rhs) = normalize(Format::QUAD.decode(other.0).unwrap());
+
+        // Long division computing `floor(lhs * 2^QUOTIENT_BITS / rhs)`, which has at least
+        // `QUOTIENT_BITS` bits since both `lhs` and `rhs` have the same number of bits.
+        const QUOTIENT_BITS: i32 = 116;
+        let mut remainder = lhs;
+        let mut quotient = 0u128;
+        for _ in 0..=QUOTIENT_BITS {
+            quotient <<= 1;
+            if remainder >= rhs {
+                remainder -= rhs;
+                quotient |= 1;
+            }
+            remainder <<= 1;
+        }
+
+        let exponent = lhs_exponent - rhs_exponent - QUOTIENT_BITS;
+        Self(Format::QUAD.encode(sign, exponent, quotient, remainder != 0))
+    }
+
+    /// Parses a decimal floating-point number, e.g., `1.5`, `.5e-3` and `12`, without sign and
+    /// suffix. The result is rounded to nearest even.
+    pub fn from_decimal_str(s: &str) -> Option<Self> {
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(index) => (&s[..index], s[index + 1..].parse::<i64>().ok()?),
+            None => (s, 0),
+        };
+        let (integral, fractional) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if integral.is_empty() && fractional.is_empty() {
+            return None;
+        }
+
+        let mut digits = BigUint::default();
+        for c in integral.chars().chain(fractional.chars()) {
+            digits.mul_add_small(10, c.to_digit(10)?);
+        }
+        if digits.is_zero() {
+            return Some(Self::ZERO);
+        }
+        let exponent = exponent - fractional.len() as i64;
+
+        // The numbers beyond these bounds are rounded to infinity or zero.
+        if exponent > 5000 {
+            return Some(Self::infinity(false));
+        }
+        if 3 * exponent + i64::from(digits.bit_length()) < -16600 {
+            return Some(Self::ZERO);
+        }
+
+        if exponent >= 0 {
+            for _ in 0..exponent {
+                digits.mul_add_small(10, 0);
+            }
+            let (significand, shift, sticky) = digits.top_bits(120);
+            return Some(Self(Format::QUAD.encode(
+                false,
+                shift as i32,
+                significand,
+                sticky,
+            )));
+        }
+
+        let mut denominator = BigUint::from_u128(1);
+        for _ in 0..-exponent {
+            denominator.mul_add_small(10, 0);
+        }
+
+        // Computes `floor(digits * 2^shift / denominator)`, which has 118 or 119 bits.
+        let shift = i64::from(denominator.bit_length()) + 118 - i64::from(digits.bit_length());
+        let mut numerator = digits;
+        if shift >= 0 {
+            numerator.shl(shift as u32);
+        } else {
+            denominator.shl(-shift as u32);
+        }
+        let top = numerator
+            .bit_length()
+            .saturating_sub(denominator.bit_length());
+        denominator.shl(top);
+        let mut quotient = 0u128;
+        for i in (0..=top).rev() {
+            if numerator >= denominator {
+                numerator.sub(&denominator);
+                quotient |= 1 << i;
+            }
+            denominator.shr1();
+        }
+
+        Some(Self(Format::QUAD.encode(
+            false,
+            -(shift as i32),
+            quotient,
+            !numerator.is_zero(),
+        )))
+    }
+
+    /// Parses a hexadecimal floating-point number, e.g., `1.8p3` and `0x1.8p+3`, without sign and
+    /// suffix. The result is rounded to nearest even.
+    pub fn from_hex_str(s: &str) -> Option<Self> {
+        let s = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        let (mantissa, exponent) = match s.find(['p', 'P']) {
+            Some(index) => (&s[..index], s[index + 1..].parse::<i64>().ok()?),
+            None => (s, 0),
+        };
+        let (integral, fractional) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if integral.is_empty() && fractional.is_empty() {
+            return None;
+        }
+
+        let mut significand = 0u128;
+        let mut exponent = exponent - 4 * fractional.len() as i64;
+        let mut sticky = false;
+        for c in integral.chars().chain(fractional.chars()) {
+            let digit = c.to_digit(16)?;
+            if significand >> (u128::BITS - 4) == 0 {
+                significand = (significand << 4) | u128::from(digit);
+            } else {
+                sticky |= digit != 0;
+                exponent += 4;
+            }
+        }
+
+        let exponent = exponent.clamp(-20000, 20000) as i32;
+        Some(Self(Format::QUAD.encode(
+            false,
+            exponent,
+            significand,
+            sticky,
+        )))
+    }
+}
+
+impl PartialOrd for Quad {
+    /// Compares the numbers, where `-0.0` and `0.0` are equal and NaN is unordered.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.is_nan() || other.is_nan() {
+            return None;
+        }
+        if self.is_zero() && other.is_zero() {
+            return Some(Ordering::Equal);
+        }
+
+        let magnitude = |value: &Self| value.0 & !Format::QUAD.sign_bit();
+        let ordering = match (self.is_sign_negative(), other.is_sign_negative()) {
+            (false, false) => magnitude(self).cmp(&magnitude(other)),
+            (true, true) => magnitude(other).cmp(&magnitude(self)),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        };
+        Some(ordering)
+    }
+}
+
+impl fmt::Debug for Quad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// Writes the number in the hexadecimal floating-point format, e.g., `0x1.8p+3`, which represents
+/// the number exactly.
+impl fmt::Display for Quad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.is_sign_negative() { "-" } else { "" };
+        if self.is_nan() {
+            return write!(f, "nan");
+        }
+        if self.is_infinite() {
+            return write!(f, "{sign}inf");
+        }
+
+        let biased = (self.0 >> Format::QUAD.fraction_bits) & Format::QUAD.max_biased_exponent();
+        let (leading, exponent) = if biased == 0 {
+            (0, 1 - Format::QUAD.bias())
+        } else {
+            (1, biased as i32 - Format::QUAD.bias())
+        };
+        let exponent = if self.is_zero() { 0 } else { exponent };
+
+        let fraction = format!("{:028x}", self.fraction());
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{sign}0x{leading}p{exponent:+}")
+        } else {
+            write!(f, "{sign}0x{leading}.{fraction}p{exponent:+}")
+        }
+    }
+}
+
+/// Normalizes a finite nonzero number given by `Format::decode` so that the most significant bit
+/// of the significand is the implicit bit of the normal numbers.
+fn normalize((sign, exponent, significand): (bool, i32, u128)) -> (bool, i32, u128) {
+    let shift = significand.leading_zeros() - (u128::BITS - 1 - Format::QUAD.fraction_bits);
+    (sign, exponent - shift as i32, significand << shift)
+}
+
+/// Returns the high and low 128 bits of the product of `lhs` and `rhs`.
+fn widening_mul(lhs: u128, rhs: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (lhs_high, lhs_low) = (lhs >> 64, lhs & MASK);
+    let (rhs_high, rhs_low) = (rhs >> 64, rhs & MASK);
+
+    let low_low = lhs_low * rhs_low;
+    let low_high = lhs_low * rhs_high;
+    let high_low = lhs_high * rhs_low;
+    let high_high = lhs_high * rhs_high;
+
+    let middle = (low_low >> 64) + (low_high & MASK) + (high_low & MASK);
+    let low = (middle << 64) | (low_low & MASK);
+    let high = high_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// An unsigned integer of arbitrary precision, used to parse decimal numbers.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct BigUint {
+    /// The 32-bit digits in little endian, without leading zeros.
+    digits: Vec<u32>,
+}
+
+impl BigUint {
+    fn from_u128(mut value: u128) -> Self {
+        let mut digits = Vec::new();
+        while value != 0 {
+            digits.push(value as u32);
+            value >>= 32;
+        }
+        Self { digits }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    fn bit_length(&self) -> u32 {
+        self.digits.last().map_or(0, |last| {
+            32 * (self.digits.len() as u32 - 1) + (u32::BITS - last.leading_zeros())
+        })
+    }
+
+    fn trim(&mut self) {
+        while self.digits.last() == Some(&0) {
+            let _unused = self.digits.pop();
+        }
+    }
+
+    /// `self = self * multiplier + addend`
+    fn mul_add_small(&mut self, multiplier: u32, addend: u32) {
+        let mut carry = u64::from(addend);
+        for digit in &mut self.digits {
+            let value = u64::from(*digit) * u64::from(multiplier) + carry;
+            *digit = value as u32;
+            carry = value >> 32;
+        }
+        if carry != 0 {
+            self.digits.push(carry as u32);
+        }
+    }
+
+    fn shl(&mut self, shift: u32) {
+        if self.is_zero() {
+            return;
+        }
+        let (words, bits) = ((shift / 32) as usize, shift % 32);
+        let mut digits = vec![0; words];
+        let mut carry = 0u32;
+        for digit in &self.digits {
+            digits.push((digit << bits) | carry);
+            carry = if bits == 0 { 0 } else { digit >> (32 - bits) };
+        }
+        digits.push(carry);
+        self.digits = digits;
+        self.trim();
+    }
+
+    fn shr1(&mut self) {
+        let mut carry = 0u32;
+        for digit in self.digits.iter_mut().rev() {
+            let next_carry = *digit << 31;
+            *digit = (*digit >> 1) | carry;
+            carry = next_carry;
+        }
+        self.trim();
+    }
+
+    /// `self = self - other`, where `self` must be greater than or equal to `other`.
+    fn sub(&mut self, other: &Self) {
+        let mut borrow = 0i64;
+        for (i, digit) in self.digits.iter_mut().enumerate() {
+            let value =
+                i64::from(*digit) - i64::from(other.digits.get(i).copied().unwrap_or(0)) - borrow;
+            borrow = i64::from(value < 0);
+            *digit = value.rem_euclid(1 << 32) as u32;
+        }
+        debug_assert_eq!(borrow, 0);
+        self.trim();
+    }
+
+    /// Returns the most significant `bits` bits, the number of the bits below them, and whether
+    /// the bits below them are nonzero.
+    fn top_bits(&self, bits: u32) -> (u128, u32, bool) {
+        let shift = self.bit_length().saturating_sub(bits);
+        let mut top = 0u128;
+        let mut sticky = false;
+        for (i, digit) in self.digits.iter().enumerate().rev() {
+            let position = 32 * i as u32;
+            if position + 32 <= shift {
+                sticky |= *digit != 0;
+            } else if position >= shift {
+                top |= u128::from(*digit) << (position - shift);
+            } else {
+                let below = shift - position;
+                top |= u128::from(*digit >> below);
+                sticky |= digit & ((1 << below) - 1) != 0;
+            }
+        }
+        (top, shift, sticky)
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.digits
+            .len()
+            .cmp(&other.digits.len())
+            .then_with(|| self.digits.iter().rev().cmp(other.digits.iter().rev()))
+    }
+}
+
+impl From<f64> for Quad {
+    fn from(value: f64) -> Self {
+        Self::from_f64(value)
+    }
+}
+
+impl From<f32> for Quad {
+    fn from(value: f32) -> Self {
+        Self::from_f32(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 0x3fff << 112;
+    const MAX: u128 = 0x7ffe_ffff_ffff_ffff_ffff_ffff_ffff_ffff;
+    const INFINITY: u128 = 0x7fff << 112;
+
+    fn hex(s: &str) -> Quad {
+        Quad::from_hex_str(s).unwrap()
+    }
+
+    fn decimal(s: &str) -> Quad {
+        Quad::from_decimal_str(s).unwrap()
+    }
+
+    #[test]
+    fn exact_values() {
+        assert_eq!(decimal("1").to_bits(), ONE);
+        assert_eq!(
+            decimal("0.1").to_bits(),
+            0x3ffb_9999_9999_9999_9999_9999_9999_999a
+        );
+        let third = Quad::from_i128(1).div(Quad::from_i128(3));
+        assert_eq!(third.to_bits(), 0x3ffd_5555_5555_5555_5555_5555_5555_5555);
+        assert_eq!(third.mul(Quad::from_i128(3)).to_bits(), ONE);
+        assert_eq!(decimal("1.5e1"), hex("0x1.ep+3"));
+        assert_eq!(hex("0x1.8p+3").to_string(), "0x1.8p+3");
+
+        // `f64` values are represented exactly, but `0.1` of `f64` is not `0.1L`.
+        assert_eq!(Quad::from_f64(0.1).to_f64(), 0.1);
+        assert_ne!(Quad::from_f64(0.1), decimal("0.1"));
+        assert_eq!(decimal("0.1").to_f64(), 0.1);
+    }
+
+    #[test]
+    fn rounding_ties_to_even() {
+        // The significand has 113 bits, so `2^113 + 1` is halfway between two numbers.
+        let base = 1u128 << 113;
+        assert_eq!(Quad::from_u128(base + 1).to_u128(), base);
+        assert_eq!(Quad::from_u128(base + 3).to_u128(), base + 4);
+        assert_eq!(Quad::from_u128(base + 5).to_u128(), base + 4);
+        assert_eq!(Quad::from_u128(base + 7).to_u128(), base + 8);
+
+        let one = Quad::from_bits(ONE);
+        let half_ulp = hex("1p-113");
+        assert_eq!(one.add(half_ulp).to_bits(), ONE);
+        assert_eq!(one.add(half_ulp.mul(hex("3"))).to_bits(), ONE + 2);
+        assert_eq!(one.sub(half_ulp.mul(hex("0.5"))).to_bits(), ONE);
+
+        // Rounding to `f64` ties to even as well.
+        assert_eq!(one.add(hex("1p-53")).to_f64(), 1.0);
+        assert_eq!(one.add(hex("3p-53")).to_f64(), 1.0 + 2f64.powi(-51));
+    }
+
+    #[test]
+    fn subnormals() {
+        assert_eq!(hex("1p-16494").to_bits(), 1);
+        assert_eq!(hex("1p-16495").to_bits(), 0);
+        assert_eq!(hex("1.8p-16495").to_bits(), 1);
+        assert_eq!(hex("1p-16382").to_bits(), 1 << 112);
+        assert_eq!(
+            hex("1p-16382").sub(hex("1p-16494")).to_bits(),
+            (1 << 112) - 1
+        );
+        assert_eq!(Quad::from_bits(1).mul(hex("2")).to_bits(), 2);
+        assert_eq!(Quad::from_bits(3).div(hex("2")).to_bits(), 2);
+        assert_eq!(decimal("1e-5000").to_bits(), 0);
+
+        // A subnormal `f64` is a normal `long double`.
+        let min = f64::from_bits(1);
+        assert_eq!(Quad::from_f64(min).to_bits(), (16383 - 1074) << 112);
+        assert_eq!(Quad::from_f64(min).to_f64(), min);
+        assert_eq!(Quad::from_bits(1).to_f64(), 0.0);
+    }
+
+    #[test]
+    fn overflow_to_infinity() {
+        let max = Quad::from_bits(MAX);
+        assert_eq!(hex("1.ffffffffffffffffffffffffffffp16383").to_bits(), MAX);
+        assert_eq!(max.add(max).to_bits(), INFINITY);
+        assert_eq!(max.mul(hex("2").neg()).to_bits(), INFINITY | (1 << 127));
+        assert_eq!(max.add(hex("1p16270")).to_bits(), INFINITY);
+        assert_eq!(max.add(hex("1p16269")).to_bits(), MAX);
+        assert_eq!(hex("1p16384").to_bits(), INFINITY);
+        assert_eq!(decimal("1e5000").to_bits(), INFINITY);
+        assert!(hex("1").div(Quad::ZERO).is_infinite());
+        assert_eq!(decimal("1e400").to_f64(), f64::INFINITY);
+        assert_eq!(Quad::from_bits(INFINITY).to_f64(), f64::INFINITY);
+    }
+
+    #[test]
+    fn integer_conversions() {
+        let nan = Quad::ZERO.div(Quad::ZERO);
+        assert!(nan.is_nan());
+        assert_eq!(nan.to_i128(), 0);
+        assert_eq!(nan.to_u128(), 0);
+        assert_ne!(nan.partial_cmp(&nan), Some(Ordering::Equal));
+
+        let infinity = Quad::from_bits(INFINITY);
+        assert_eq!(infinity.to_i128(), i128::MAX);
+        assert_eq!(infinity.neg().to_i128(), i128::MIN);
+        assert_eq!(infinity.to_u128(), u128::MAX);
+        assert_eq!(hex("1p127").to_i128(), i128::MAX);
+        assert_eq!(hex("1p127").neg().to_i128(), i128::MIN);
+        assert_eq!(hex("1p128").to_u128(), u128::MAX);
+        assert_eq!(Quad::from_i128(i128::MIN).to_i128(), i128::MIN);
+
+        assert_eq!(decimal("7.9").to_i128(), 7);
+        assert_eq!(decimal("7.9").neg().to_i128(), -7);
+        assert_eq!(decimal("7.9").neg().to_u128(), 0);
+        assert_eq!(decimal("0.5").to_u128(), 0);
+        assert_eq!(Quad::from_i128(i64::MAX.into()).to_i128(), i64::MAX.into());
+    }
+}
@@ -0,0 +1,372 @@
+//! Verify IR.
+
+use core::fmt;
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::ir::*;
+use crate::opt::opt_utils::{Cfg, DomTree};
+
+/// An error in the well-formedness of IR, located in a function.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[error("{func_name}:{location} / {msg}")]
+pub struct VerifyError {
+    pub func_name: String,
+    pub location: Location,
+    pub msg: String,
+}
+
+/// A location in a function definition.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Location {
+    /// The initial block id and the allocations.
+    Init,
+    Phinode {
+        bid: BlockId,
+        aid: usize,
+    },
+    Instruction {
+        bid: BlockId,
+        iid: usize,
+    },
+    Exit {
+        bid: BlockId,
+    },
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Init => write!(f, "init"),
+            Self::Phinode { bid, aid } => write!(f, "{bid}:p{aid}"),
+            Self::Instruction { bid, iid } => write!(f, "{bid}:i{iid}"),
+            Self::Exit { bid } => write!(f, "{bid}:exit"),
+        }
+    }
+}
+
+/// Verifies the well-formedness of the function definitions in `unit`:
+///
+/// - The initial block exists, and its phinodes are the parameters of the function.
+/// - Each register refers to an existing allocation, phinode or instruction, and each use of a
+///   phinode or an instruction in a reachable block is dominated by its definition.
+/// - The dtype of each operand agrees with that of its definition, and the operands of the
+///   instructions have the dtypes they require, e.g., `load` takes a pointer. The arguments of a
+///   call are checked only in number, since the interpreter converts them to the parameters.
+/// - The arguments of each jump agree in number and dtypes with the phinodes of its target.
+/// - The cases of each `switch` are unique.
+pub fn verify(unit: &TranslationUnit) -> Result<(), VerifyError> {
+    for (name, decl) in &unit.decls {
+        let Some((signature, Some(definition))) = decl.get_function() else {
+            continue;
+        };
+        Verifier::new(unit, name, signature, definition).verify()?;
+    }
+    Ok(())
+}
+
+struct Verifier<'a> {
+    unit: &'a TranslationUnit,
+    func_name: &'a str,
+    signature: &'a FunctionSignature,
+    definition: &'a FunctionDefinition,
+}
+
+impl<'a> Verifier<'a> {
+    fn new(
+        unit: &'a TranslationUnit,
+        func_name: &'a str,
+        signature: &'a FunctionSignature,
+        definition: &'a FunctionDefinition,
+    ) -> Self {
+        Self {
+            unit,
+            func_name,
+            signature,
+            definition,
+        }
+    }
+
+    fn error(&self, location: Location, msg: String) -> VerifyError {
+        VerifyError {
+            func_name: self.func_name.to_string(),
+            location,
+            msg,
+        }
+    }
+
+    fn verify(&self) -> Result<(), VerifyError> {
+        let bid_init = self.definition.bid_init;
+        let Some(block_init) = self.definition.blocks.get(&bid_init) else {
+            return Err(self.error(
+                Location::Init,
+                format!("initial block {bid_init} does not exist"),
+            ));
+        };
+        if block_init.phinodes.len() != self.signature.params.len() {
+            return Err(self.error(
+                Location::Init,
+                format!(
+                    "initial block has {} phinodes for {} parameters",
+                    block_init.phinodes.len(),
+                    self.signature.params.len()
+                ),
+            ));
+        }
+        for (aid, (phinode, param)) in block_init
+            .phinodes
+            .iter()
+            .zip(&self.signature.params)
+            .enumerate()
+        {
+            if &**phinode != param {
+                return Err(self.error(
+                    Location::Phinode { bid: bid_init, aid },
+                    format!(
+                        "phinode of dtype {} for parameter of dtype {param}",
+                        **phinode
+                    ),
+                ));
+            }
+        }
+
+        let cfg = Cfg::new(self.definition);
+        let dominators = DomTree::new(self.definition.bid_init, &cfg);
+        for (bid, block) in &self.definition.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                let location = Location::Instruction { bid: *bid, iid };
+                for operand in instruction.operands() {
+                    self.verify_operand(operand, location, &dominators)?;
+                }
+                self.verify_instruction(instruction, location)?;
+            }
+
+            let location = Location::Exit { bid: *bid };
+            for operand in block.exit.operands() {
+                self.verify_operand(operand, location, &dominators)?;
+            }
+            self.verify_exit(&block.exit, location)?;
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that `operand` used at `location` agrees with its definition.
+    fn verify_operand(
+        &self,
+        operand: &Operand,
+        location: Location,
+        dominators: &DomTree,
+    ) -> Result<(), VerifyError> {
+        let (use_bid, use_iid) = match location {
+            Location::Instruction { bid, iid } => (bid, Some(iid)),
+            Location::Exit { bid } => (bid, None),
+            _ => panic!("operands are used only in instructions and block exits"),
+        };
+
+        let (dtype, def_dtype) = match operand {
+            Operand::Constant(Constant::GlobalVariable { name, dtype }) => {
+                let Some(decl) = self.unit.decls.get(name) else {
+                    return Err(self.error(location, format!("undeclared global variable @{name}")));
+                };
+                (dtype, decl.dtype())
+            }
+            Operand::Constant(_) => return Ok(()),
+            Operand::Register { rid, dtype } => {
+                let def_dtype = match rid {
+                    RegisterId::Local { aid } => {
+                        let Some(allocation) = self.definition.allocations.get(*aid) else {
+                            return Err(self.error(location, format!("undefined register {rid}")));
+                        };
+                        Dtype::pointer((**allocation).clone())
+                    }
+                    RegisterId::Arg { bid, aid } => {
+                        let Some(phinode) = self
+                            .definition
+                            .blocks
+                            .get(bid)
+                            .and_then(|block| block.phinodes.get(*aid))
+                        else {
+                            return Err(self.error(location, format!("undefined register {rid}")));
+                        };
+                        if dominators.is_reachable(use_bid) && !dominators.dominates(*bid, use_bid)
+                        {
+                            return Err(self.error(
+                                location,
+                                format!("register {rid} is not dominated by its definition"),
+                            ));
+                        }
+                        (**phinode).clone()
+                    }
+                    RegisterId::Temp { bid, iid } => {
+                        let Some(instruction) = self
+                            .definition
+                            .blocks
+                            .get(bid)
+                            .and_then(|block| block.instructions.get(*iid))
+                        else {
+                            return Err(self.error(location, format!("undefined register {rid}")));
+                        };
+                        let is_dominated = if *bid == use_bid {
+                            use_iid.is_none_or(|use_iid| *iid < use_iid)
+                        } else {
+                            dominators.dominates(*bid, use_bid)
+                        };
+                        if dominators.is_reachable(use_bid) && !is_dominated {
+                            return Err(self.error(
+                                location,
+                                format!("register {rid} is not dominated by its definition"),
+                            ));
+                        }
+                        // The dtype of an ill-formed `load` is reported at the `load`.
+                        let Some(dtype) = instruction_dtype(instruction) else {
+                            return Ok(());
+                        };
+                        dtype
+                    }
+                };
+                (dtype, def_dtype)
+            }
+        };
+
+        // A function defined with a prototype may be referred to without it, e.g., in a call of a
+        // K&R-style function.
+        let is_unprototyped_use = dtype.get_function_inner().is_some()
+            && def_dtype.get_function_inner().is_some()
+            && FunctionSignature::new(dtype.clone())
+                .is_unprototyped_of(&FunctionSignature::new(def_dtype.clone()));
+        if *dtype != def_dtype && !is_unprototyped_use {
+            return Err(self.error(
+                location,
+                format!("operand {operand} is used as dtype {dtype}, but defined as {def_dtype}"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Verifies that the operands of `instruction` have the dtypes it requires.
+    fn verify_instruction(
+        &self,
+        instruction: &Instruction,
+        location: Location,
+    ) -> Result<(), VerifyError> {
+        let pointer_inner = |ptr: &Operand| {
+            ptr.dtype()
+                .get_pointer_inner()
+                .cloned()
+                .ok_or_else(|| self.error(location, format!("operand {ptr} is not a pointer")))
+        };
+
+        match instruction {
+            Instruction::Load { ptr }
+            | Instruction::VaStart { ptr }
+            | Instruction::VaArg { ptr, .. }
+            | Instruction::VaEnd { ptr } => {
+                let _unused = pointer_inner(ptr)?;
+            }
+            Instruction::Store { ptr, value } => {
+                let inner = pointer_inner(ptr)?;
+                if inner.set_const(false) != value.dtype().set_const(false) {
+                    return Err(self.error(
+                        location,
+                        format!("value {value} is stored to pointer {ptr}"),
+                    ));
+                }
+            }
+            Instruction::GetElementPtr { ptr, dtype, .. } => {
+                let _unused = pointer_inner(ptr)?;
+                if dtype.get_pointer_inner().is_none() {
+                    return Err(self.error(location, format!("dtype {dtype} is not a pointer")));
+                }
+            }
+            Instruction::Call {
+                callee,
+                args,
+                return_type,
+            } => {
+                let callee_dtype = pointer_inner(callee)?;
+                let Some((ret, params)) = callee_dtype.get_function_inner() else {
+                    return Err(self.error(
+                        location,
+                        format!("callee {callee} is not a pointer to a function"),
+                    ));
+                };
+                if ret != return_type {
+                    return Err(self.error(
+                        location,
+                        format!("return type {return_type} of function returning {ret}"),
+                    ));
+                }
+                let is_arity_matched = if callee_dtype.is_variadic() {
+                    args.len() >= params.len()
+                } else {
+                    args.len() == params.len()
+                };
+                if !is_arity_matched {
+                    return Err(self.error(
+                        location,
+                        format!("{} arguments for {} parameters", args.len(), params.len()),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Verifies the jumps of `exit`, and that the cases of a `switch` are unique.
+    fn verify_exit(&self, exit: &BlockExit, location: Location) -> Result<(), VerifyError> {
+        for arg in exit.jump_args() {
+            let Some(block) = self.definition.blocks.get(&arg.bid) else {
+                return Err(self.error(location, format!("jump to undefined block {}", arg.bid)));
+            };
+            if arg.args.len() != block.phinodes.len() {
+                return Err(self.error(
+                    location,
+                    format!(
+                        "jump to {} with {} arguments for {} phinodes",
+                        arg.bid,
+                        arg.args.len(),
+                        block.phinodes.len()
+                    ),
+                ));
+            }
+            for (operand, phinode) in arg.args.iter().zip(&block.phinodes) {
+                if operand.dtype() != **phinode {
+                    return Err(self.error(
+                        location,
+                        format!(
+                            "jump to {} with argument {operand} for phinode of dtype {}",
+                            arg.bid, **phinode
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let BlockExit::Switch { value, cases, .. } = exit {
+            let mut constants = HashSet::new();
+            for (constant, _) in cases {
+                if constant.dtype() != value.dtype() {
+                    return Err(self.error(
+                        location,
+                        format!("case {constant} of dtype {} for {value}", constant.dtype()),
+                    ));
+                }
+                if !constants.insert(constant) {
+                    return Err(self.error(location, format!("duplicate case {constant}")));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the dtype of `instruction`, or `None` if it is a `load` from a non-pointer.
+fn instruction_dtype(instruction: &Instruction) -> Option<Dtype> {
+    if let Instruction::Load { ptr } = instruction {
+        let _unused = ptr.dtype().get_pointer_inner()?;
+    }
+    Some(instruction.dtype())
+}
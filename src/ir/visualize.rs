@@ -26,6 +26,7 @@ impl Translate<TranslationUnit> for Visualizer {
             if let Declaration::Function {
                 signature,
                 definition: Some(definition),
+                ..
             } = decl
             {
                 let subgraph = self.translate_function(name, signature, definition)?;
@@ -9,6 +9,11 @@ impl WriteLine for TranslationUnit {
         let mut structs = self.structs.iter().collect::<Vec<_>>();
         structs.sort_unstable_by_key(|&(name, _)| name);
         for (name, struct_type) in structs {
+            let keyword = if struct_type.as_ref().is_some_and(Dtype::is_union) {
+                "union"
+            } else {
+                "struct"
+            };
             let definition = if let Some(struct_type) = struct_type {
                 let fields = struct_type
                     .get_struct_fields()
@@ -16,24 +21,36 @@ impl WriteLine for TranslationUnit {
                     .as_ref()
                     .expect("`fields` must be `Some`");
 
-                let fields = fields.iter().format_with(", ", |field, f| {
-                    f(&format_args!(
-                        "{}:{}",
-                        if let Some(name) = field.name() {
-                            name
-                        } else {
-                            "%anon"
-                        },
-                        field.deref()
-                    ))
-                });
+                let bit_widths = struct_type
+                    .get_struct_bit_widths()
+                    .expect("`struct_type` must be struct type");
+
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .format_with(", ", |(i, field), f| {
+                        f(&format_args!(
+                            "{}:{}{}",
+                            if let Some(name) = field.name() {
+                                name
+                            } else {
+                                "%anon"
+                            },
+                            field.deref(),
+                            if let Some(Some(width)) = bit_widths.get(i) {
+                                format!(":{width}")
+                            } else {
+                                "".to_string()
+                            }
+                        ))
+                    });
 
                 format!("{{ {fields} }}")
             } else {
                 "opaque".to_string()
             };
 
-            writeln!(write, "struct {name} : {definition}")?;
+            writeln!(write, "{keyword} {name} : {definition}")?;
         }
 
         for (name, decl) in &self.decls {
@@ -59,28 +76,52 @@ impl WriteLine for (&String, &Declaration) {
         let decl = self.1;
 
         match decl {
-            Declaration::Variable { dtype, initializer } => {
-                writeln!(
-                    write,
-                    "var {} @{} = {}",
-                    dtype,
-                    name,
-                    if let Some(init) = initializer {
-                        init.write_string()
-                    } else {
-                        "default".to_string()
-                    }
-                )?;
+            Declaration::Variable {
+                dtype,
+                initializer,
+                linkage,
+                is_definition,
+                alignment,
+            } => {
+                write!(write, "var {}{} @{}", linkage_prefix(*linkage), dtype, name)?;
+                if let Some(alignment) = alignment {
+                    write!(write, " align {alignment}")?;
+                }
+                if *is_definition {
+                    write!(
+                        write,
+                        " = {}",
+                        if let Some(init) = initializer {
+                            init.write_string()
+                        } else {
+                            "default".to_string()
+                        }
+                    )?;
+                }
+                writeln!(write)?;
             }
             Declaration::Function {
                 signature,
                 definition,
+                linkage,
             } => {
-                let params = signature.params.iter().format(", ");
+                let params = signature
+                    .params
+                    .iter()
+                    .map(ToString::to_string)
+                    .chain(signature.is_variadic.then(|| "...".to_string()))
+                    .format(", ");
 
                 if let Some(definition) = definition.as_ref() {
                     // print function definition
-                    writeln!(write, "fun {} @{} ({}) {{", signature.ret, name, params)?;
+                    writeln!(
+                        write,
+                        "fun {}{} @{} ({}) {{",
+                        linkage_prefix(*linkage),
+                        signature.ret,
+                        name,
+                        params
+                    )?;
                     // print meta data for function
                     writeln!(
                         write,
@@ -91,13 +132,20 @@ impl WriteLine for (&String, &Declaration) {
                             .iter()
                             .enumerate()
                             .format_with("\n", |(i, a), f| f(&format_args!(
-                                "    %l{}:{}{}",
+                                "    %l{}:{}{}{}",
                                 i,
                                 a.deref(),
                                 if let Some(name) = a.name() {
                                     format!(":{name}")
                                 } else {
                                     "".into()
+                                },
+                                if let Some(Some(alignment)) =
+                                    definition.allocation_alignments.get(i)
+                                {
+                                    format!(" align {alignment}")
+                                } else {
+                                    "".into()
                                 }
                             )))
                     )?;
@@ -110,7 +158,14 @@ impl WriteLine for (&String, &Declaration) {
                     writeln!(write, "}}")?;
                 } else {
                     // print declaration line only
-                    writeln!(write, "fun {} @{} ({})", signature.ret, name, params)?;
+                    writeln!(
+                        write,
+                        "fun {}{} @{} ({})",
+                        linkage_prefix(*linkage),
+                        signature.ret,
+                        name,
+                        params
+                    )?;
                     writeln!(write)?;
                 }
             }
@@ -159,3 +214,12 @@ impl WriteLine for (&BlockId, &Block) {
         Ok(())
     }
 }
+
+/// Returns the keyword of `linkage` followed by a space. The external linkage is the default, so it
+/// is written as the empty string.
+fn linkage_prefix(linkage: Linkage) -> String {
+    match linkage {
+        Linkage::External => String::new(),
+        Linkage::Internal => format!("{linkage} "),
+    }
+}
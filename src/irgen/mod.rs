@@ -37,15 +37,20 @@
 use core::cmp::Ordering;
 use core::convert::TryFrom;
 use core::{fmt, mem};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::Deref;
 
 use itertools::izip;
 use lang_c::ast::*;
 use lang_c::driver::Parse;
-use lang_c::span::Node;
+use lang_c::span::{Node, Span};
 use thiserror::Error;
 
+use crate::c::ast_utils::{
+    complete_array_size, compound_literal_declaration, function_derived_declarator,
+    name_of_declarator, rename_declarator, storage_class_of_specifiers,
+};
+use crate::c::const_eval::{self, ConstEval, ConstEvalError};
 use crate::ir::{DtypeError, HasDtype, Named};
 use crate::write_base::WriteString;
 use crate::*;
@@ -91,6 +96,15 @@ pub enum IrgenErrorMessage {
     RequireLvalue { message: String },
 }
 
+impl From<ConstEvalError> for IrgenErrorMessage {
+    fn from(error: ConstEvalError) -> Self {
+        match error {
+            ConstEvalError::Misc { message } => Self::Misc { message },
+            ConstEvalError::InvalidDtype { dtype_error } => Self::InvalidDtype { dtype_error },
+        }
+    }
+}
+
 /// A C file going through IR generation.
 #[derive(Default, Debug)]
 pub struct Irgen {
@@ -103,6 +117,18 @@ pub struct Irgen {
     structs: HashMap<String, Option<ir::Dtype>>,
     /// Temporary counter for anonymous structs. One should not need to use this any more.
     struct_tempid_counter: usize,
+    /// Enumerators declared in the C file with their values (e.g, `RED` of `enum { RED };`)
+    enumerators: HashMap<String, ir::Constant>,
+    /// Counter for the anonymous global variables holding string literals (e.g, `.str0`)
+    string_literal_counter: usize,
+    /// Counter for the global variables hoisted from `static` local variables (e.g, `x.0` for `x`)
+    static_local_counter: usize,
+    /// Counter for the unique names of the structs defined in functions (e.g, `A.0` for `A`)
+    local_struct_counter: usize,
+    /// Counter for the anonymous variables holding compound literals (e.g, `.compound0`)
+    compound_literal_counter: usize,
+    /// Functions declared only without a prototype (e.g, `f` of `int f();`)
+    unprototyped_functions: HashSet<String>,
 }
 
 impl Translate<Parse> for Irgen {
@@ -124,9 +150,8 @@ impl Translate<TranslationUnit> for Irgen {
                 ExternalDeclaration::Declaration(var) => {
                     self.add_declaration(&var.node)?;
                 }
-                ExternalDeclaration::StaticAssert(_) => {
-                    panic!("ExternalDeclaration::StaticAssert is unsupported")
-                }
+                // Static assertions are checked by the semantic analysis.
+                ExternalDeclaration::StaticAssert(_) => (),
                 ExternalDeclaration::FunctionDefinition(func) => {
                     self.add_function_definition(&func.node)?;
                 }
@@ -147,6 +172,13 @@ impl Irgen {
 
     /// Add a declaration. It can be either a struct, typedef, or a variable.
     fn add_declaration(&mut self, source: &Declaration) -> Result<(), IrgenError> {
+        for spec in &source.specifiers {
+            if let DeclarationSpecifier::TypeSpecifier(type_specifier) = &spec.node {
+                self.add_enumerators_of_type_specifier(&type_specifier.node)?;
+            }
+        }
+
+        let storage_class = storage_class_of_specifiers(&source.specifiers);
         let (base_dtype, is_typedef) =
             ir::Dtype::try_from_ast_declaration_specifiers(&source.specifiers).map_err(|e| {
                 IrgenError::new(
@@ -185,9 +217,11 @@ impl Irgen {
         for init_decl in &source.declarators {
             let declarator = &init_decl.node.declarator.node;
             let name = name_of_declarator(declarator);
+            let declarator = complete_array_size(declarator, init_decl.node.initializer.as_ref())
+                .map_err(|e| IrgenError::new(format!("{source:#?}"), e.into()))?;
             let dtype = base_dtype
                 .clone()
-                .with_ast_declarator(declarator)
+                .with_ast_declarator(&declarator)
                 .map_err(|e| {
                     IrgenError::new(
                         format!("{source:#?}"),
@@ -202,7 +236,7 @@ impl Irgen {
                     IrgenErrorMessage::InvalidDtype { dtype_error: e },
                 )
             })?;
-            if !is_typedef && is_invalid_structure(&dtype, &self.structs) {
+            if !is_typedef && dtype.is_invalid_structure(&self.structs) {
                 return Err(IrgenError::new(
                     format!("{source:#?}"),
                     IrgenErrorMessage::Misc {
@@ -211,6 +245,21 @@ impl Irgen {
                 ));
             }
 
+            // An alignment can only be specified for an object.
+            if (is_typedef || dtype.get_function_inner().is_some())
+                && source
+                    .specifiers
+                    .iter()
+                    .any(|spec| matches!(spec.node, DeclarationSpecifier::Alignment(_)))
+            {
+                return Err(IrgenError::new(
+                    format!("{source:#?}"),
+                    IrgenErrorMessage::Misc {
+                        message: "`_Alignas` of a typedef or function".to_string(),
+                    },
+                ));
+            }
+
             if is_typedef {
                 // Add new typedef if nothing has been declared before
                 let prev_dtype = self
@@ -232,23 +281,56 @@ impl Irgen {
             }
 
             // Creates a new declaration based on the dtype.
-            let mut decl = ir::Declaration::try_from(dtype.clone()).map_err(|e| {
+            let decl = ir::Declaration::try_from(dtype.clone()).map_err(|e| {
                 IrgenError::new(
                     format!("{source:#?}"),
                     IrgenErrorMessage::InvalidDtype { dtype_error: e },
                 )
             })?;
 
+            if let (Some(DerivedDeclarator::KRFunction(_)), Some((signature, _))) = (
+                function_derived_declarator(&declarator),
+                decl.get_function(),
+            ) {
+                // A declaration without a prototype does not replace the pre-declared prototype.
+                match self
+                    .decls
+                    .get(&name)
+                    .and_then(ir::Declaration::get_function)
+                {
+                    Some((old_signature, _)) if signature.is_unprototyped_of(old_signature) => {
+                        continue;
+                    }
+                    Some(_) => (),
+                    None => {
+                        let _unused = self.unprototyped_functions.insert(name.clone());
+                    }
+                }
+            }
+            let linkage = self
+                .linkage_of(&name, storage_class, decl.get_function().is_some())
+                .map_err(|e| IrgenError::new(format!("{source:#?}"), e))?;
+            let mut decl = decl.set_linkage(linkage);
+
+            // `extern int g;` only declares `g` defined elsewhere, unlike `int g;`.
+            if let ir::Declaration::Variable {
+                is_definition,
+                alignment,
+                ..
+            } = &mut decl
+            {
+                *is_definition = storage_class != Some(&StorageClassSpecifier::Extern)
+                    || init_decl.node.initializer.is_some();
+                *alignment = self
+                    .alignment_of(&source.specifiers, &dtype)
+                    .map_err(|e| IrgenError::new(format!("{source:#?}"), e))?;
+            }
+
             // If `initializer` exists, convert initializer to a constant value
             if let Some(initializer) = init_decl.node.initializer.as_ref() {
-                if !is_valid_initializer(&initializer.node, &dtype, &self.structs) {
-                    return Err(IrgenError::new(
-                        format!("{source:#?}"),
-                        IrgenErrorMessage::Misc {
-                            message: "initializer is not valid".to_string(),
-                        },
-                    ));
-                }
+                let initializer = self
+                    .translate_global_initializer(&name, &initializer.node, &dtype)
+                    .map_err(|e| IrgenError::new(format!("{source:#?}"), e))?;
 
                 match &mut decl {
                     ir::Declaration::Variable {
@@ -261,7 +343,7 @@ impl Irgen {
                                 IrgenErrorMessage::Redefinition { name },
                             ));
                         }
-                        *var_initializer = Some(initializer.node.clone());
+                        *var_initializer = Some(initializer);
                     }
                     ir::Declaration::Function { .. } => {
                         return Err(IrgenError::new(
@@ -291,6 +373,13 @@ impl Irgen {
         let name_of_params = name_of_params_from_function_declarator(declarator)
             .expect("declarator is not from function definition");
 
+        for spec in specifiers {
+            if let DeclarationSpecifier::TypeSpecifier(type_specifier) = &spec.node {
+                self.add_enumerators_of_type_specifier(&type_specifier.node)?;
+            }
+        }
+
+        let storage_class = storage_class_of_specifiers(specifiers);
         let (base_dtype, is_typedef) = ir::Dtype::try_from_ast_declaration_specifiers(specifiers)
             .map_err(|e| {
             IrgenError::new(
@@ -325,13 +414,68 @@ impl Irgen {
             )
         })?;
 
+        // The arguments of a K&R-style definition are passed after the default argument promotions,
+        // e.g., `double` for `float b` of `int f(a, b) float b; { ... }`.
+        let (dtype, param_dtypes) = match function_derived_declarator(declarator) {
+            Some(DerivedDeclarator::KRFunction(identifiers)) => {
+                let param_dtypes = self
+                    .translate_kr_parameters(identifiers, &source.declarations)
+                    .map_err(|e| {
+                        IrgenError::new(format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"), e)
+                    })?;
+                let (ret, _) = dtype
+                    .get_function_inner()
+                    .expect("`dtype` must be function type");
+                let params = param_dtypes
+                    .iter()
+                    .map(ir::Dtype::promote_argument)
+                    .collect();
+                (ir::Dtype::function(ret.clone(), params), param_dtypes)
+            }
+            _ => {
+                if !source.declarations.is_empty() {
+                    return Err(IrgenError::new(
+                        format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"),
+                        IrgenErrorMessage::Misc {
+                            message: "parameter declarations given for function with prototype"
+                                .to_string(),
+                        },
+                    ));
+                }
+                let (_, params) = dtype
+                    .get_function_inner()
+                    .expect("`dtype` must be function type");
+                let param_dtypes = params.clone();
+                (dtype, param_dtypes)
+            }
+        };
         let signature = ir::FunctionSignature::new(dtype.clone());
 
         // Adds new declaration if nothing has been declared before
-        let decl = ir::Declaration::try_from(dtype).unwrap();
+        let linkage = self.linkage_of(&name, storage_class, true).map_err(|e| {
+            IrgenError::new(format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"), e)
+        })?;
+        let decl = ir::Declaration::try_from(dtype)
+            .unwrap()
+            .set_linkage(linkage);
         self.add_decl(&name, decl)?;
 
-        // Prepare scope for global variable
+        // Hoists the `static` and `extern` local variables to global variables
+        let mut block_scope_globals = HashMap::new();
+        let params_scope = LocalScope {
+            variables: name_of_params
+                .iter()
+                .map(|name| (name.clone(), None))
+                .collect(),
+            ..LocalScope::default()
+        };
+        self.hoist_block_scope_globals(
+            &source.statement.node,
+            &mut vec![params_scope],
+            &mut block_scope_globals,
+        )?;
+
+        // Prepare scope for global variable and enumerators
         let global_scope: HashMap<_, _> = self
             .decls
             .iter()
@@ -341,6 +485,11 @@ impl Irgen {
                 let operand = ir::Operand::constant(pointer);
                 (name.clone(), operand)
             })
+            .chain(
+                self.enumerators
+                    .iter()
+                    .map(|(name, value)| (name.clone(), ir::Operand::constant(value.clone()))),
+            )
             .collect();
 
         // Prepares for irgen pass.
@@ -348,14 +497,25 @@ impl Irgen {
             return_type: signature.ret.clone(),
             bid_init: Irgen::BID_INIT,
             phinodes_init: Vec::new(),
+            param_dtypes,
             allocations: Vec::new(),
+            allocation_alignments: Vec::new(),
             blocks: BTreeMap::new(),
             bid_counter: Irgen::BID_COUNTER_INIT,
             tempid_counter: Irgen::TEMPID_COUNTER_INIT,
             typedefs: &self.typedefs,
-            structs: &self.structs,
+            structs: &mut self.structs,
+            struct_tempid_counter: self.struct_tempid_counter,
+            local_struct_counter: self.local_struct_counter,
             // Initial symbol table has scope for global variable already
             symbol_table: vec![global_scope],
+            // Global type names are in `typedefs` and `structs`
+            type_scopes: vec![TypeScope::default()],
+            labels: HashMap::new(),
+            string_literals: Vec::new(),
+            string_literal_counter: self.string_literal_counter,
+            compound_literal_counter: self.compound_literal_counter,
+            block_scope_globals,
         };
         let mut context = Context::new(irgen.bid_init);
 
@@ -393,12 +553,30 @@ impl Irgen {
         // Exit variable scope created above
         irgen.exit_scope();
 
+        let allocation_alignments = if irgen.allocation_alignments.iter().all(Option::is_none) {
+            Vec::new()
+        } else {
+            irgen.allocation_alignments
+        };
         let func_def = ir::FunctionDefinition {
             allocations: irgen.allocations,
+            allocation_alignments,
             blocks: irgen.blocks,
             bid_init: irgen.bid_init,
         };
 
+        // Adds the string literals used in the function
+        self.string_literal_counter = irgen.string_literal_counter;
+        self.compound_literal_counter = irgen.compound_literal_counter;
+        self.struct_tempid_counter = irgen.struct_tempid_counter;
+        self.local_struct_counter = irgen.local_struct_counter;
+        for string_literal in irgen.string_literals {
+            let (decl, name) = string_literal.destruct();
+            let _unused = self
+                .decls
+                .insert(name.expect("string literal must be named"), decl);
+        }
+
         let decl = self
             .decls
             .get_mut(&name)
@@ -422,16 +600,75 @@ impl Irgen {
         Ok(())
     }
 
+    /// Translates the types of the parameters `identifiers` of a K&R-style function definition,
+    /// which are declared by `declarations`, e.g., `char a; double b;` of
+    /// `int f(a, b) char a; double b; { ... }`. A parameter not declared is of type `int`.
+    fn translate_kr_parameters(
+        &self,
+        identifiers: &[Node<Identifier>],
+        declarations: &[Node<Declaration>],
+    ) -> Result<Vec<ir::Dtype>, IrgenErrorMessage> {
+        let mut param_dtypes = HashMap::new();
+        for declaration in declarations {
+            let (base_dtype, _) =
+                ir::Dtype::try_from_ast_declaration_specifiers(&declaration.node.specifiers)
+                    .map_err(|e| IrgenErrorMessage::InvalidDtype { dtype_error: e })?;
+            for init_decl in &declaration.node.declarators {
+                let declarator = &init_decl.node.declarator.node;
+                let dtype = base_dtype
+                    .clone()
+                    .with_ast_declarator(declarator)
+                    .and_then(|dtype| dtype.into_inner().resolve_typedefs(&self.typedefs))
+                    .map_err(|e| IrgenErrorMessage::InvalidDtype { dtype_error: e })?;
+                // An array parameter is adjusted to a pointer as in a prototype.
+                let dtype = match dtype.get_array_inner() {
+                    Some(inner) => ir::Dtype::pointer(inner.clone()),
+                    None => dtype,
+                };
+                let _unused = param_dtypes.insert(name_of_declarator(declarator), dtype);
+            }
+        }
+
+        Ok(identifiers
+            .iter()
+            .map(|identifier| {
+                param_dtypes
+                    .remove(&identifier.node.name)
+                    .unwrap_or(ir::Dtype::INT)
+            })
+            .collect())
+    }
+
     /// Adds a possibly existing declaration.
     ///
     /// Returns error if the previous declearation is incompatible with `decl`.
     fn add_decl(&mut self, name: &str, decl: ir::Declaration) -> Result<(), IrgenError> {
-        let Some(old_decl) = self.decls.insert(name.to_string(), decl.clone()) else {
+        if self.enumerators.contains_key(name) {
+            return Err(IrgenError::new(
+                name.to_string(),
+                IrgenErrorMessage::Redefinition {
+                    name: name.to_string(),
+                },
+            ));
+        }
+
+        let Some(old_decl) = self.decls.get(name) else {
+            let _unused = self.decls.insert(name.to_string(), decl);
             return Ok(());
         };
 
-        // Check if type is conflicting for pre-declared one
-        if !old_decl.is_compatible(&decl) {
+        // Check if type is conflicting for pre-declared one. A function declared without a prototype
+        // is compatible with a prototype whose parameters are not changed by the default argument
+        // promotions, e.g., `int f();` with `int f(int, double);` but not `int f(char);`.
+        let is_compatible = old_decl.is_compatible(&decl)
+            || match (old_decl.get_function(), decl.get_function()) {
+                (Some((old_signature, _)), Some((signature, _))) => {
+                    self.unprototyped_functions.contains(name)
+                        && old_signature.is_unprototyped_of(signature)
+                }
+                _ => false,
+            };
+        if !is_compatible {
             return Err(IrgenError::new(
                 name.to_string(),
                 IrgenErrorMessage::ConflictingDtype {
@@ -441,6 +678,618 @@ impl Irgen {
             ));
         }
 
+        // Keeps the initializer of the pre-declared one, e.g., `int g = 1;` followed by
+        // `extern int g;`, and the strictest alignment of them.
+        let mut decl = decl;
+        if let (
+            ir::Declaration::Variable {
+                initializer,
+                is_definition,
+                alignment,
+                ..
+            },
+            ir::Declaration::Variable {
+                initializer: old_initializer,
+                is_definition: old_is_definition,
+                alignment: old_alignment,
+                ..
+            },
+        ) = (&mut decl, old_decl)
+        {
+            if initializer.is_some() && old_initializer.is_some() {
+                return Err(IrgenError::new(
+                    name.to_string(),
+                    IrgenErrorMessage::Redefinition {
+                        name: name.to_string(),
+                    },
+                ));
+            }
+            if initializer.is_none() {
+                initializer.clone_from(old_initializer);
+            }
+            *is_definition |= *old_is_definition;
+            *alignment = (*alignment).max(*old_alignment);
+        }
+
+        // Keeps the definition of the pre-declared one, which is checked by
+        // `add_function_definition`
+        if let (
+            ir::Declaration::Function { definition, .. },
+            ir::Declaration::Function {
+                definition: old_definition,
+                ..
+            },
+        ) = (&mut decl, old_decl)
+        {
+            definition.clone_from(old_definition);
+        }
+
+        let _unused = self.unprototyped_functions.remove(name);
+        let _unused = self.decls.insert(name.to_string(), decl);
+        Ok(())
+    }
+
+    /// Translates the `_Alignas` specifiers in `specifiers` of a global variable of `dtype` to its
+    /// alignment, as `alignment_of_specifiers` does.
+    fn alignment_of(
+        &self,
+        specifiers: &[Node<DeclarationSpecifier>],
+        dtype: &ir::Dtype,
+    ) -> Result<Option<usize>, IrgenErrorMessage> {
+        let lookup = |name: &str| self.enumerators.get(name).cloned();
+        let const_eval = ConstEval::new(&lookup, &self.typedefs, &self.structs);
+        alignment_of_specifiers(specifiers, dtype, &self.structs, |expr| {
+            const_eval.eval(expr).map_err(IrgenErrorMessage::from)
+        })
+    }
+
+    /// Returns the linkage of `name` declared at file scope with `storage_class`, or declared at
+    /// block scope with `extern`.
+    ///
+    /// A declaration without `static` follows the linkage of the pre-declared one if it is a
+    /// function or declared with `extern`, e.g., `static int f();` followed by `int f() {}`.
+    fn linkage_of(
+        &self,
+        name: &str,
+        storage_class: Option<&StorageClassSpecifier>,
+        is_function: bool,
+    ) -> Result<ir::Linkage, IrgenErrorMessage> {
+        let old_linkage = self.decls.get(name).map(ir::Declaration::linkage);
+        match storage_class {
+            Some(StorageClassSpecifier::Static) => {
+                if old_linkage == Some(ir::Linkage::External) {
+                    return Err(IrgenErrorMessage::Misc {
+                        message: format!("static declaration of `{name}` follows non-static one"),
+                    });
+                }
+                Ok(ir::Linkage::Internal)
+            }
+            Some(StorageClassSpecifier::Extern) => Ok(old_linkage.unwrap_or_default()),
+            _ if is_function => Ok(old_linkage.unwrap_or_default()),
+            _ => {
+                if old_linkage == Some(ir::Linkage::Internal) {
+                    return Err(IrgenErrorMessage::Misc {
+                        message: format!("non-static declaration of `{name}` follows static one"),
+                    });
+                }
+                Ok(ir::Linkage::External)
+            }
+        }
+    }
+
+    /// Hoists the block-scope declarations with `static` or `extern` storage class in `stmt` to the
+    /// declarations of the C file, since they declare global variables rather than local ones.
+    /// A `static` local variable is renamed to a unique name, e.g., `x.0` for `x`, so that it does
+    /// not conflict with the other variables of the same name.
+    ///
+    /// `scopes` has the local variables visible at `stmt`, each with the name of the global
+    /// variable if it is declared with `static` or `extern`. The hoisted variables are added to
+    /// `globals` so that `IrgenFunc` adds them to the symbol table instead of allocating them.
+    fn hoist_block_scope_globals(
+        &mut self,
+        stmt: &Statement,
+        scopes: &mut Vec<LocalScope>,
+        globals: &mut BlockScopeGlobals,
+    ) -> Result<(), IrgenError> {
+        match stmt {
+            Statement::Labeled(stmt) => {
+                self.hoist_block_scope_globals(&stmt.node.statement.node, scopes, globals)
+            }
+            Statement::Compound(items) => {
+                scopes.push(LocalScope::default());
+                for item in items {
+                    match &item.node {
+                        BlockItem::Declaration(decl) => {
+                            self.hoist_block_scope_declaration(decl, scopes, globals)?
+                        }
+                        BlockItem::StaticAssert(_) => (),
+                        BlockItem::Statement(stmt) => {
+                            self.hoist_block_scope_globals(&stmt.node, scopes, globals)?
+                        }
+                    }
+                }
+                let _unused = scopes.pop();
+                Ok(())
+            }
+            Statement::If(stmt) => {
+                self.hoist_block_scope_globals(&stmt.node.then_statement.node, scopes, globals)?;
+                if let Some(else_stmt) = &stmt.node.else_statement {
+                    self.hoist_block_scope_globals(&else_stmt.node, scopes, globals)?;
+                }
+                Ok(())
+            }
+            Statement::Switch(stmt) => {
+                self.hoist_block_scope_globals(&stmt.node.statement.node, scopes, globals)
+            }
+            Statement::While(stmt) => {
+                self.hoist_block_scope_globals(&stmt.node.statement.node, scopes, globals)
+            }
+            Statement::DoWhile(stmt) => {
+                self.hoist_block_scope_globals(&stmt.node.statement.node, scopes, globals)
+            }
+            Statement::For(stmt) => {
+                scopes.push(LocalScope::default());
+                if let ForInitializer::Declaration(decl) = &stmt.node.initializer.node {
+                    self.hoist_block_scope_declaration(decl, scopes, globals)?;
+                }
+                self.hoist_block_scope_globals(&stmt.node.statement.node, scopes, globals)?;
+                let _unused = scopes.pop();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Hoists the block-scope declaration `decl` if it has `static` or `extern` storage class, and
+    /// adds the variables it declares to the innermost scope of `scopes`.
+    fn hoist_block_scope_declaration(
+        &mut self,
+        decl: &Node<Declaration>,
+        scopes: &mut [LocalScope],
+        globals: &mut BlockScopeGlobals,
+    ) -> Result<(), IrgenError> {
+        let storage_class = storage_class_of_specifiers(&decl.node.specifiers);
+        if matches!(
+            storage_class,
+            Some(StorageClassSpecifier::Static | StorageClassSpecifier::Extern)
+        ) && uses_local_type(&decl.node.specifiers, scopes)
+        {
+            return Err(IrgenError::new(
+                format!("{decl:#?}"),
+                IrgenErrorMessage::Misc {
+                    message: "unsupported `static` or `extern` local variable of a local type"
+                        .to_string(),
+                },
+            ));
+        }
+        for spec in &decl.node.specifiers {
+            if let DeclarationSpecifier::TypeSpecifier(type_specifier) = &spec.node {
+                if let TypeSpecifier::Struct(struct_type) = &type_specifier.node {
+                    if let (Some(identifier), Some(_)) =
+                        (&struct_type.node.identifier, &struct_type.node.declarations)
+                    {
+                        let _unused = scopes
+                            .last_mut()
+                            .expect("block must have its own scope")
+                            .struct_tags
+                            .insert(identifier.node.name.clone());
+                    }
+                }
+            }
+        }
+
+        if storage_class == Some(&StorageClassSpecifier::Extern) {
+            self.add_declaration(&decl.node)?;
+        }
+
+        for init_decl in &decl.node.declarators {
+            let name = name_of_declarator(&init_decl.node.declarator.node);
+            let global = match storage_class {
+                Some(StorageClassSpecifier::Static) => {
+                    let global = format!("{name}.{}", self.static_local_counter);
+                    self.static_local_counter += 1;
+
+                    // The variable is visible in its own initializer, e.g., `static void *p = &p;`
+                    let _unused = scopes
+                        .last_mut()
+                        .expect("block must have its own scope")
+                        .variables
+                        .insert(name, Some(global.clone()));
+                    let initializer = init_decl
+                        .node
+                        .initializer
+                        .as_ref()
+                        .map(|initializer| {
+                            rename_locals_of_initializer(&initializer.node, scopes)
+                                .map(|renamed| Node::new(renamed, initializer.span))
+                        })
+                        .transpose()
+                        .map_err(|e| IrgenError::new(format!("{decl:#?}"), e))?;
+                    let hoisted = Declaration {
+                        specifiers: decl.node.specifiers.clone(),
+                        declarators: vec![Node::new(
+                            InitDeclarator {
+                                declarator: Node::new(
+                                    rename_declarator(&init_decl.node.declarator.node, &global),
+                                    init_decl.node.declarator.span,
+                                ),
+                                initializer,
+                            },
+                            init_decl.span,
+                        )],
+                    };
+                    self.add_declaration(&hoisted)?;
+                    global
+                }
+                Some(StorageClassSpecifier::Extern) => {
+                    let _unused = scopes
+                        .last_mut()
+                        .expect("block must have its own scope")
+                        .variables
+                        .insert(name.clone(), Some(name.clone()));
+                    name
+                }
+                _ => {
+                    let _unused = scopes
+                        .last_mut()
+                        .expect("block must have its own scope")
+                        .variables
+                        .insert(name, None);
+                    continue;
+                }
+            };
+
+            let decl_of_global = self
+                .decls
+                .get(&global)
+                .unwrap_or_else(|| panic!("`{global}` must be declared"));
+            if storage_class == Some(&StorageClassSpecifier::Static)
+                && decl_of_global.get_function().is_some()
+            {
+                return Err(IrgenError::new(
+                    format!("{decl:#?}"),
+                    IrgenErrorMessage::Misc {
+                        message: "invalid storage class for block-scope function".to_string(),
+                    },
+                ));
+            }
+            let pointer = ir::Constant::global_variable(global, decl_of_global.dtype());
+            let _unused = globals.insert(
+                (init_decl.span.start, init_decl.span.end),
+                ir::Operand::constant(pointer),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Adds the enumerators of the enum types defined in `type_specifier`, including those
+    /// defined in the fields of a struct type.
+    fn add_enumerators_of_type_specifier(
+        &mut self,
+        type_specifier: &TypeSpecifier,
+    ) -> Result<(), IrgenError> {
+        match type_specifier {
+            TypeSpecifier::Enum(enum_type) => self.add_enumerators(&enum_type.node),
+            TypeSpecifier::Struct(struct_type) => {
+                for decl in struct_type.node.declarations.iter().flatten() {
+                    let StructDeclaration::Field(field) = &decl.node else {
+                        continue;
+                    };
+                    for spec in &field.node.specifiers {
+                        if let SpecifierQualifier::TypeSpecifier(type_specifier) = &spec.node {
+                            self.add_enumerators_of_type_specifier(&type_specifier.node)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Translates the initializer of the global variable `name` of `dtype` to the form kept in the
+    /// IR.
+    ///
+    /// Arithmetic constant expressions, including the indexes of array designators, are folded to
+    /// literals. The other expressions must be address constants, e.g., `&g.f` or `a + 2`, where
+    /// a string literal is replaced with the anonymous global variable holding it. A character
+    /// array may be initialized by a string literal, which is kept as it is.
+    fn translate_global_initializer(
+        &mut self,
+        name: &str,
+        initializer: &Initializer,
+        dtype: &ir::Dtype,
+    ) -> Result<Initializer, IrgenErrorMessage> {
+        match initializer {
+            Initializer::Expression(expr) => {
+                if let (ir::Dtype::Array { inner, size }, Expression::StringLiteral(literal)) =
+                    (dtype, &expr.node)
+                {
+                    let bytes = ir::string_literal_bytes(&literal.node).map_err(|_| {
+                        IrgenErrorMessage::Misc {
+                            message: "invalid string literal".to_string(),
+                        }
+                    })?;
+                    if inner.get_int_width() != Some(ir::Dtype::BITS_OF_BYTE) {
+                        return Err(IrgenErrorMessage::Misc {
+                            message: format!("invalid initializer for `{dtype}`"),
+                        });
+                    }
+                    if bytes.len() > *size {
+                        return Err(IrgenErrorMessage::Misc {
+                            message: format!("initializer-string for `{dtype}` is too long"),
+                        });
+                    }
+                    return Ok(initializer.clone());
+                }
+
+                if !matches!(
+                    dtype,
+                    ir::Dtype::Int { .. } | ir::Dtype::Float { .. } | ir::Dtype::Pointer { .. }
+                ) {
+                    return Err(IrgenErrorMessage::Misc {
+                        message: format!("invalid initializer for `{dtype}`"),
+                    });
+                }
+
+                let expr = Node::new(self.translate_literal_objects(&expr.node)?, expr.span);
+                let lookup = |name: &str| self.enumerators.get(name).cloned();
+                let const_eval = ConstEval::new(&lookup, &self.typedefs, &self.structs);
+
+                if let Ok(constant) = const_eval.eval(&expr.node) {
+                    let constant = if dtype.get_pointer_inner().is_some() {
+                        if !constant.is_integer_constant() {
+                            return Err(IrgenErrorMessage::Misc {
+                                message: format!("invalid initializer for `{dtype}`"),
+                            });
+                        }
+                        constant
+                    } else {
+                        constant.typecast(dtype.clone().set_const(false))
+                    };
+                    let literal = const_eval::to_literal(&constant).ok_or_else(|| {
+                        IrgenErrorMessage::Misc {
+                            message: format!("initializer `{constant}` is not representable"),
+                        }
+                    })?;
+                    return Ok(Initializer::Expression(Box::new(Node::new(
+                        literal, expr.span,
+                    ))));
+                }
+
+                let address = fold_address_constant(&const_eval, &expr.node);
+                let globals = |global: &str| {
+                    if global == name {
+                        Some(dtype.clone())
+                    } else {
+                        self.decls.get(global).map(HasDtype::dtype)
+                    }
+                };
+                match ir::InitialValue::try_from_expression(&address, &globals, &self.structs) {
+                    Ok(ir::InitialValue::Address { .. }) if dtype.get_pointer_inner().is_some() => {
+                        Ok(Initializer::Expression(Box::new(Node::new(
+                            address, expr.span,
+                        ))))
+                    }
+                    _ => Err(IrgenErrorMessage::Misc {
+                        message: "initializer element is not a compile-time constant".to_string(),
+                    }),
+                }
+            }
+            Initializer::List(items) => {
+                // A scalar may be initialized with braces, e.g., `int x = {1};` and `(int){1}`.
+                if let [item] = items.as_slice() {
+                    if item.node.designation.is_empty()
+                        && matches!(
+                            dtype,
+                            ir::Dtype::Int { .. }
+                                | ir::Dtype::Float { .. }
+                                | ir::Dtype::Pointer { .. }
+                        )
+                    {
+                        return self.translate_global_initializer(
+                            name,
+                            &item.node.initializer.node,
+                            dtype,
+                        );
+                    }
+                }
+
+                let lookup = |name: &str| self.enumerators.get(name).cloned();
+                let const_eval = ConstEval::new(&lookup, &self.typedefs, &self.structs);
+                let items = items
+                    .iter()
+                    .map(|item| {
+                        let designation = item
+                            .node
+                            .designation
+                            .iter()
+                            .map(|designator| fold_designator(&const_eval, designator))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(Node::new(
+                            InitializerListItem {
+                                designation,
+                                initializer: item.node.initializer.clone(),
+                            },
+                            item.span,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, IrgenErrorMessage>>()?;
+
+                let members =
+                    ir::resolve_initializer_list(&items, dtype, &self.structs).map_err(|_| {
+                        IrgenErrorMessage::Misc {
+                            message: format!("invalid initializer list for `{dtype}`"),
+                        }
+                    })?;
+                let initializers = members
+                    .iter()
+                    .map(|member| {
+                        self.translate_designated_initializer(
+                            name,
+                            member.initializer,
+                            &member.designators,
+                            &member.dtype,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let items = izip!(items, initializers)
+                    .map(|(item, initializer)| {
+                        let span = item.span;
+                        let item = InitializerListItem {
+                            designation: item.node.designation,
+                            initializer: Box::new(Node::new(
+                                initializer,
+                                item.node.initializer.span,
+                            )),
+                        };
+                        Node::new(item, span)
+                    })
+                    .collect();
+                Ok(Initializer::List(items))
+            }
+        }
+    }
+
+    /// Translates `initializer` of the part of a member of `dtype` designated by `designators`,
+    /// e.g., `.y` of `.p.y = 1` initializing the member `p`.
+    fn translate_designated_initializer(
+        &mut self,
+        name: &str,
+        initializer: &Initializer,
+        designators: &[Node<Designator>],
+        dtype: &ir::Dtype,
+    ) -> Result<Initializer, IrgenErrorMessage> {
+        if designators.is_empty() {
+            return self.translate_global_initializer(name, initializer, dtype);
+        }
+
+        let item = InitializerListItem {
+            designation: designators.to_vec(),
+            initializer: Box::new(Node::new(initializer.clone(), Span::none())),
+        };
+        let items = [Node::new(item, Span::none())];
+        let member = ir::resolve_initializer_list(&items, dtype, &self.structs)
+            .ok()
+            .and_then(|mut members| members.pop())
+            .ok_or_else(|| IrgenErrorMessage::Misc {
+                message: format!("invalid designator for `{dtype}`"),
+            })?;
+
+        self.translate_designated_initializer(name, initializer, &member.designators, &member.dtype)
+    }
+
+    /// Replaces the string literals and compound literals in the address constant `expr` with the
+    /// anonymous global variables holding them, e.g., `"abc" + 1` to `.str0 + 1` and
+    /// `&(int){1}` to `&.compound0`.
+    fn translate_literal_objects(
+        &mut self,
+        expr: &Expression,
+    ) -> Result<Expression, IrgenErrorMessage> {
+        let mut translate = |expr: &Node<Expression>| {
+            let translated = self.translate_literal_objects(&expr.node)?;
+            Ok::<_, IrgenErrorMessage>(Box::new(Node::new(translated, expr.span)))
+        };
+
+        let expr = match expr {
+            Expression::StringLiteral(literal) => {
+                let (decl, name) =
+                    string_literal_declaration(literal, &mut self.string_literal_counter)?
+                        .destruct();
+                let name = name.expect("string literal must be named");
+                let _unused = self.decls.insert(name.clone(), decl);
+                Expression::Identifier(Box::new(Node::new(Identifier { name }, literal.span)))
+            }
+            Expression::CompoundLiteral(literal) => {
+                // A compound literal outside of functions has static storage duration.
+                let name = format!(".compound{}", self.compound_literal_counter);
+                self.compound_literal_counter += 1;
+                let static_specifier = DeclarationSpecifier::StorageClass(Node::new(
+                    StorageClassSpecifier::Static,
+                    literal.span,
+                ));
+                let mut decl = compound_literal_declaration(&literal.node, &name, literal.span);
+                decl.specifiers
+                    .insert(0, Node::new(static_specifier, literal.span));
+                self.add_declaration(&decl).map_err(|e| e.message)?;
+                Expression::Identifier(Box::new(Node::new(Identifier { name }, literal.span)))
+            }
+            Expression::BinaryOperator(binary) => {
+                let binary_expr = BinaryOperatorExpression {
+                    operator: binary.node.operator.clone(),
+                    lhs: translate(&binary.node.lhs)?,
+                    rhs: translate(&binary.node.rhs)?,
+                };
+                Expression::BinaryOperator(Box::new(Node::new(binary_expr, binary.span)))
+            }
+            Expression::UnaryOperator(unary) => {
+                let unary_expr = UnaryOperatorExpression {
+                    operator: unary.node.operator.clone(),
+                    operand: translate(&unary.node.operand)?,
+                };
+                Expression::UnaryOperator(Box::new(Node::new(unary_expr, unary.span)))
+            }
+            _ => expr.clone(),
+        };
+        Ok(expr)
+    }
+
+    /// Adds the enumerators of `enum_type`.
+    ///
+    /// An enumerator without an explicit value has the value of the previous enumerator plus one,
+    /// and the first enumerator has `0` by default.
+    fn add_enumerators(&mut self, enum_type: &EnumType) -> Result<(), IrgenError> {
+        let mut next = 0i128;
+        for enumerator in &enum_type.enumerators {
+            let name = &enumerator.node.identifier.node.name;
+            let value = if let Some(expr) = &enumerator.node.expression {
+                let lookup = |name: &str| self.enumerators.get(name).cloned();
+                let value = ConstEval::new(&lookup, &self.typedefs, &self.structs)
+                    .eval(&expr.node)
+                    .map_err(|e| IrgenError::new(format!("{enumerator:#?}"), e.into()))?;
+                let Some((value, _, _)) = value.get_int() else {
+                    return Err(IrgenError::new(
+                        format!("{enumerator:#?}"),
+                        IrgenErrorMessage::Misc {
+                            message: "enumerator value is not an integer constant".to_string(),
+                        },
+                    ));
+                };
+                value as i128
+            } else {
+                next
+            };
+
+            if i32::try_from(value).is_err() {
+                return Err(IrgenError::new(
+                    format!("{enumerator:#?}"),
+                    IrgenErrorMessage::Misc {
+                        message: format!(
+                            "enumerator value of `{name}` is not representable in `int`"
+                        ),
+                    },
+                ));
+            }
+            next = value + 1;
+
+            if self.decls.contains_key(name)
+                || self
+                    .enumerators
+                    .insert(
+                        name.clone(),
+                        ir::Constant::int(value as u128, ir::Dtype::INT),
+                    )
+                    .is_some()
+            {
+                return Err(IrgenError::new(
+                    format!("{enumerator:#?}"),
+                    IrgenErrorMessage::Redefinition { name: name.clone() },
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -478,6 +1327,29 @@ impl Context {
     }
 }
 
+/// Local names of a scope declared before the translation of a function.
+#[derive(Debug, Default)]
+struct LocalScope {
+    /// Local variables and typedef names, each with the name of the global variable if it is
+    /// declared with `static` or `extern`.
+    variables: HashMap<String, Option<String>>,
+    /// Struct tags defined in the scope.
+    struct_tags: HashSet<String>,
+}
+
+/// Pointers to the global variables declared at block scope, with the spans of their declarators
+/// as keys.
+type BlockScopeGlobals = HashMap<(usize, usize), ir::Operand>;
+
+/// Type names declared in a scope of a function.
+#[derive(Debug, Default)]
+struct TypeScope {
+    /// Typedef names with their dtypes.
+    typedefs: HashMap<String, ir::Dtype>,
+    /// Struct tags with their unique names in `structs` of the C file, e.g., `A.0` for `A`.
+    structs: HashMap<String, String>,
+}
+
 /// A C function being translated.
 struct IrgenFunc<'i> {
     /// return type of the function.
@@ -487,8 +1359,13 @@ struct IrgenFunc<'i> {
     /// arguments represented as initial phinodes. Order must be the same of that given in the C
     /// function.
     phinodes_init: Vec<Named<ir::Dtype>>,
+    /// The types of the parameters as declared. They are `params` of the signature except for a
+    /// K&R-style definition, whose arguments are of the promoted types.
+    param_dtypes: Vec<ir::Dtype>,
     /// local allocations.
     allocations: Vec<Named<ir::Dtype>>,
+    /// The alignments given by `_Alignas` to `allocations`.
+    allocation_alignments: Vec<Option<usize>>,
     /// Map from block id to basic blocks
     blocks: BTreeMap<ir::BlockId, ir::Block>,
     /// current block id. `blocks` must have an entry for all ids less then this
@@ -497,11 +1374,34 @@ struct IrgenFunc<'i> {
     tempid_counter: usize,
     /// Usable definitions
     typedefs: &'i HashMap<String, ir::Dtype>,
-    /// Usable structs
+    /// Usable structs. The structs defined in the function are added with unique names.
     // TODO: Add examples on how to use properly use this field.
-    structs: &'i HashMap<String, Option<ir::Dtype>>,
+    structs: &'i mut HashMap<String, Option<ir::Dtype>>,
+    /// Counter for anonymous structs. It continues from the counter of the C file.
+    struct_tempid_counter: usize,
+    /// Counter for the unique names of the structs defined in the function. It continues from the
+    /// counter of the C file.
+    local_struct_counter: usize,
     /// Current symbol table. The initial symbol table has the global variables.
     symbol_table: Vec<HashMap<String, ir::Operand>>,
+    /// Type names declared in each scope of `symbol_table`, which shadow `typedefs` and the tags of
+    /// `structs`.
+    type_scopes: Vec<TypeScope>,
+    /// Labels of the function with their blocks. A label is added when it is first referred to,
+    /// possibly by a `goto` before its definition.
+    labels: HashMap<String, ir::BlockId>,
+    /// Anonymous global variables holding the string literals used in the function. They are
+    /// added to the declarations of the C file after the function is translated.
+    string_literals: Vec<Named<ir::Declaration>>,
+    /// Counter for the anonymous global variables holding string literals. It continues from the
+    /// counter of the C file.
+    string_literal_counter: usize,
+    /// Counter for the variables holding compound literals. It continues from the counter of the C
+    /// file.
+    compound_literal_counter: usize,
+    /// Global variables declared in the function with `static` or `extern`, which are hoisted
+    /// before the translation of the function.
+    block_scope_globals: BlockScopeGlobals,
 }
 
 impl IrgenFunc<'_> {
@@ -521,7 +1421,18 @@ impl IrgenFunc<'_> {
 
     /// Create a new allocation with type given by `alloc`.
     fn insert_alloc(&mut self, alloc: Named<ir::Dtype>) -> ir::RegisterId {
+        self.insert_aligned_alloc(alloc, None)
+    }
+
+    /// Create a new allocation with type given by `alloc` and the alignment given by `_Alignas`,
+    /// e.g., the result of `translate_alignment`.
+    fn insert_aligned_alloc(
+        &mut self,
+        alloc: Named<ir::Dtype>,
+        alignment: Option<usize>,
+    ) -> ir::RegisterId {
         self.allocations.push(alloc);
+        self.allocation_alignments.push(alignment);
         let id = self.allocations.len() - 1;
         ir::RegisterId::local(id)
     }
@@ -549,6 +1460,7 @@ impl IrgenFunc<'_> {
     /// Enter a scope and create a new symbol table entry, i.e, we are at a `{` in the function.
     fn enter_scope(&mut self) {
         self.symbol_table.push(HashMap::new());
+        self.type_scopes.push(TypeScope::default());
     }
 
     /// Exit a scope and remove the a oldest symbol table entry. i.e, we are at a `}` in the
@@ -559,6 +1471,7 @@ impl IrgenFunc<'_> {
     /// Panics if there are no scopes to exit, i.e, the function has a unmatched `}`.
     fn exit_scope(&mut self) {
         let _unused = self.symbol_table.pop().unwrap();
+        let _unused = self.type_scopes.pop().unwrap();
         debug_assert!(!self.symbol_table.is_empty())
     }
 
@@ -574,28 +1487,546 @@ impl IrgenFunc<'_> {
             .symbol_table
             .last_mut()
             .expect("symbol table has no valid scope");
-        if cur_scope.insert(var.clone(), value).is_some() {
+        let cur_type_scope = self
+            .type_scopes
+            .last()
+            .expect("symbol table has no valid scope");
+        if cur_type_scope.typedefs.contains_key(&var)
+            || cur_scope.insert(var.clone(), value).is_some()
+        {
             return Err(IrgenErrorMessage::Redefinition { name: var });
         }
 
         Ok(())
     }
 
-    /// Transalte a C statement `stmt` under the current block `context`, with `continue` block
-    /// `bid_continue` and break block `bid_break`.
-    fn translate_stmt(
-        &mut self,
-        stmt: &Statement,
-        context: &mut Context,
-        bid_continue: Option<ir::BlockId>,
-        bid_break: Option<ir::BlockId>,
-    ) -> Result<(), IrgenError> {
-        todo!()
+    /// Inserts the typedef name `name` of `dtype` to the current scope.
+    ///
+    /// Returns Ok() if the current scope has no variable of the same name, and has no typedef of
+    /// the same name unless it has the same dtype.
+    fn insert_typedef(&mut self, name: String, dtype: ir::Dtype) -> Result<(), IrgenErrorMessage> {
+        let cur_scope = self
+            .symbol_table
+            .last()
+            .expect("symbol table has no valid scope");
+        if cur_scope.contains_key(&name) {
+            return Err(IrgenErrorMessage::Redefinition { name });
+        }
+
+        let cur_type_scope = self
+            .type_scopes
+            .last_mut()
+            .expect("symbol table has no valid scope");
+        let prev_dtype = cur_type_scope
+            .typedefs
+            .entry(name)
+            .or_insert_with(|| dtype.clone());
+        if prev_dtype != &dtype {
+            return Err(IrgenErrorMessage::ConflictingDtype {
+                dtype,
+                protorype_dtype: prev_dtype.clone(),
+            });
+        }
+
+        Ok(())
     }
 
-    /// Translate initial parameter declarations of the functions to IR.
-    ///
-    /// For example, given the following C function from [`foo.c`][foo]:
+    /// Returns the typedefs visible in the current scope, where the inner ones shadow the outer
+    /// ones.
+    fn visible_typedefs(&self) -> HashMap<String, ir::Dtype> {
+        let mut typedefs = self.typedefs.clone();
+        for scope in &self.type_scopes {
+            typedefs.extend(scope.typedefs.clone());
+        }
+        typedefs
+    }
+
+    /// Translates the base dtype of the block-scope declaration specifiers `specifiers`, and
+    /// whether they declare typedef names.
+    ///
+    /// The typedef names and struct tags visible in the current scope are resolved. A struct type
+    /// defined in `specifiers` is added to `structs` with a unique name, e.g., `A.0` for
+    /// `struct A { int x; }`, and its tag is added to the current scope.
+    fn translate_base_dtype(
+        &mut self,
+        specifiers: &[Node<DeclarationSpecifier>],
+    ) -> Result<(ir::Dtype, bool), IrgenErrorMessage> {
+        let (base_dtype, is_typedef) =
+            ir::Dtype::try_from_ast_declaration_specifiers(specifiers)
+                .map_err(|e| IrgenErrorMessage::InvalidDtype { dtype_error: e })?;
+
+        let type_scopes = &mut self.type_scopes;
+        let local_struct_counter = &mut self.local_struct_counter;
+        let base_dtype = base_dtype.rename_structs(&mut |tag, is_definition| {
+            if is_definition {
+                let name = format!("{tag}.{local_struct_counter}");
+                *local_struct_counter += 1;
+                let _unused = type_scopes
+                    .last_mut()
+                    .expect("symbol table has no valid scope")
+                    .structs
+                    .insert(tag.to_string(), name.clone());
+                return name;
+            }
+
+            type_scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.structs.get(tag))
+                .cloned()
+                .unwrap_or_else(|| tag.to_string())
+        });
+        let base_dtype = base_dtype
+            .resolve_typedefs(&self.visible_typedefs())
+            .map_err(|e| IrgenErrorMessage::InvalidDtype { dtype_error: e })?;
+
+        let base_dtype = match base_dtype.get_struct_fields() {
+            Some(Some(_)) => base_dtype
+                .resolve_structs(self.structs, &mut self.struct_tempid_counter)
+                .map_err(|e| IrgenErrorMessage::InvalidDtype { dtype_error: e })?,
+            Some(None) => {
+                let name = base_dtype
+                    .get_struct_name()
+                    .and_then(Option::as_ref)
+                    .expect("struct without fields must be named");
+                let _unused = self.structs.entry(name.clone()).or_insert(None);
+                base_dtype
+            }
+            None => base_dtype,
+        };
+
+        Ok((base_dtype, is_typedef))
+    }
+
+    /// Translates the dtype declared by `declarator` in a block-scope declaration whose base
+    /// dtype is `base_dtype`, which is given by `translate_base_dtype`.
+    ///
+    /// Returns error if a variable of an incomplete struct type is declared, unless `is_typedef`.
+    fn translate_declarator_dtype(
+        &self,
+        base_dtype: &ir::Dtype,
+        declarator: &Declarator,
+        is_typedef: bool,
+    ) -> Result<ir::Dtype, IrgenErrorMessage> {
+        let dtype = base_dtype
+            .clone()
+            .with_ast_declarator(declarator)
+            .and_then(|dtype| {
+                dtype
+                    .deref()
+                    .clone()
+                    .resolve_typedefs(&self.visible_typedefs())
+            })
+            .map_err(|e| IrgenErrorMessage::InvalidDtype { dtype_error: e })?;
+
+        if !is_typedef && dtype.is_invalid_structure(self.structs) {
+            return Err(IrgenErrorMessage::Misc {
+                message: "incomplete struct type".to_string(),
+            });
+        }
+
+        Ok(dtype)
+    }
+
+    /// Evaluates a constant expression `expr`, e.g., the expression of a `case` label.
+    ///
+    /// Enumerators in the symbol table can be used in `expr`, unless shadowed by a variable.
+    fn translate_constant_expr(
+        &self,
+        expr: &Expression,
+    ) -> Result<ir::Constant, IrgenErrorMessage> {
+        let lookup = |name: &str| {
+            let operand = self
+                .symbol_table
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(name))?;
+            operand
+                .get_constant()
+                .filter(|constant| constant.is_integer_constant())
+                .cloned()
+        };
+        ConstEval::new(&lookup, &self.visible_typedefs(), self.structs)
+            .eval(expr)
+            .map_err(IrgenErrorMessage::from)
+    }
+
+    /// Translates the string literal `literal` to the address of the anonymous global variable
+    /// holding it.
+    fn translate_string_literal(
+        &mut self,
+        literal: &Node<StringLiteral>,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        let decl = string_literal_declaration(literal, &mut self.string_literal_counter)?;
+        let name = decl.name().expect("string literal must be named").clone();
+        let pointer = ir::Constant::global_variable(name, decl.dtype());
+        self.string_literals.push(decl);
+        Ok(ir::Operand::constant(pointer))
+    }
+
+    /// Translates the `_Alignas` specifiers in `specifiers` of a local variable of `dtype` to the
+    /// alignment of its allocation, as `alignment_of_specifiers` does.
+    fn translate_alignment(
+        &self,
+        specifiers: &[Node<DeclarationSpecifier>],
+        dtype: &ir::Dtype,
+    ) -> Result<Option<usize>, IrgenErrorMessage> {
+        alignment_of_specifiers(specifiers, dtype, self.structs, |expr| {
+            self.translate_constant_expr(expr)
+        })
+    }
+
+    /// Translates the compound literal `literal` in a function, which has automatic storage
+    /// duration, to the declaration of a fresh local variable initialized by it, and the identifier
+    /// of the variable.
+    ///
+    /// `(struct P){1, 2}` is lowered by translating the declaration as `struct P .compound0 = {1,
+    /// 2};` in the current scope, and then translating the identifier, an lvalue, instead of the
+    /// compound literal.
+    fn translate_compound_literal(
+        &mut self,
+        literal: &Node<CompoundLiteral>,
+    ) -> (Declaration, Expression) {
+        let name = format!(".compound{}", self.compound_literal_counter);
+        self.compound_literal_counter += 1;
+        let decl = compound_literal_declaration(&literal.node, &name, literal.span);
+        let identifier =
+            Expression::Identifier(Box::new(Node::new(Identifier { name }, literal.span)));
+        (decl, identifier)
+    }
+
+    /// Translates the declarator `init_decl` of a block-scope declaration with `static` or `extern`
+    /// storage class. The global variable it declares is hoisted before the translation of the
+    /// function, so it is added to the current scope without any allocation.
+    fn translate_block_scope_global(
+        &mut self,
+        init_decl: &Node<InitDeclarator>,
+    ) -> Result<(), IrgenErrorMessage> {
+        let name = name_of_declarator(&init_decl.node.declarator.node);
+        let pointer = self
+            .block_scope_globals
+            .get(&(init_decl.span.start, init_decl.span.end))
+            .unwrap_or_else(|| panic!("`{name}` must be hoisted"))
+            .clone();
+        self.insert_symbol_table_entry(name, pointer)
+    }
+
+    /// Returns the block of label `name`. A new block is allocated if the label is seen for the
+    /// first time, e.g., it is referred to by a `goto` before its definition.
+    fn bid_of_label(&mut self, name: &str) -> ir::BlockId {
+        if let Some(bid) = self.labels.get(name) {
+            return *bid;
+        }
+
+        let bid = self.alloc_bid();
+        let _unused = self.labels.insert(name.to_string(), bid);
+        bid
+    }
+
+    /// Translate a label `name:` under the current block `context`.
+    ///
+    /// The current block jumps to the block of the label, which becomes the current block.
+    fn translate_label(&mut self, name: &str, context: &mut Context) {
+        let bid = self.bid_of_label(name);
+        let context = mem::replace(context, Context::new(bid));
+        self.insert_block(
+            context,
+            ir::BlockExit::Jump {
+                arg: ir::JumpArg::new(bid, Vec::new()),
+            },
+        );
+    }
+
+    /// Translate `goto name;` under the current block `context`.
+    ///
+    /// The current block jumps to the block of the label. The following statements are translated
+    /// in a new block, which is unreachable unless it is labeled.
+    fn translate_goto(&mut self, name: &str, context: &mut Context) {
+        let bid = self.bid_of_label(name);
+        let bid_next = self.alloc_bid();
+        let context = mem::replace(context, Context::new(bid_next));
+        self.insert_block(
+            context,
+            ir::BlockExit::Jump {
+                arg: ir::JumpArg::new(bid, Vec::new()),
+            },
+        );
+    }
+
+    /// Translate a call of the builtin `name` for variable arguments under the current block
+    /// `context`, e.g., `__builtin_va_start` to which `va_start` of `<stdarg.h>` expands.
+    ///
+    /// `ptrs` are the addresses of the `va_list`s given as arguments. The last parameter given to
+    /// `__builtin_va_start` is not needed. The call is translated to the corresponding
+    /// instruction, and `va_copy` is translated to a copy of the `va_list`.
+    ///
+    /// Returns the result of the call, which is `void`.
+    fn translate_va_builtin(
+        &mut self,
+        name: &str,
+        ptrs: &[ir::Operand],
+        context: &mut Context,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        match (name, ptrs) {
+            ("__builtin_va_start", [ptr]) => {
+                context.insert_instruction(ir::Instruction::VaStart { ptr: ptr.clone() })
+            }
+            ("__builtin_va_end", [ptr]) => {
+                context.insert_instruction(ir::Instruction::VaEnd { ptr: ptr.clone() })
+            }
+            ("__builtin_va_copy", [dest, src]) => {
+                let value =
+                    context.insert_instruction(ir::Instruction::Load { ptr: src.clone() })?;
+                context.insert_instruction(ir::Instruction::Store {
+                    ptr: dest.clone(),
+                    value,
+                })
+            }
+            _ => panic!("`{name}` is not a builtin for variable arguments taking `{ptrs:?}`"),
+        }
+    }
+
+    /// Translate `__builtin_va_arg(ap, dtype)`, to which `va_arg` of `<stdarg.h>` expands, under
+    /// the current block `context`. `ptr` is the address of `ap`.
+    ///
+    /// The arguments are passed after the default argument promotions, so `dtype` must be a
+    /// promoted type, e.g., `int` instead of `char` and `double` instead of `float`. A `long double`
+    /// is not promoted.
+    fn translate_va_arg(
+        &mut self,
+        ptr: ir::Operand,
+        dtype: ir::Dtype,
+        context: &mut Context,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        let is_promoted = match &dtype {
+            ir::Dtype::Int { width, .. } => {
+                *width >= ir::Dtype::SIZE_OF_INT * ir::Dtype::BITS_OF_BYTE
+            }
+            ir::Dtype::Float { width, .. } => {
+                *width >= ir::Dtype::SIZE_OF_DOUBLE * ir::Dtype::BITS_OF_BYTE
+            }
+            ir::Dtype::Pointer { .. } | ir::Dtype::Struct { .. } => true,
+            _ => false,
+        };
+        if !is_promoted {
+            return Err(IrgenErrorMessage::Misc {
+                message: format!("`{dtype}` is not a type of variable arguments after promotion"),
+            });
+        }
+
+        context.insert_instruction(ir::Instruction::VaArg { ptr, dtype })
+    }
+
+    /// Translate the `index`-th argument of the function with `signature`, which is in the initial
+    /// phinodes of `bid_init`, to the value of the parameter under the current block `context`.
+    ///
+    /// Returns the declared type of the parameter and its value. The argument of a K&R-style
+    /// definition is converted from its promoted type to the declared type, e.g., from `double` to
+    /// `float`.
+    fn translate_parameter_value(
+        &mut self,
+        signature: &ir::FunctionSignature,
+        bid_init: ir::BlockId,
+        index: usize,
+        context: &mut Context,
+    ) -> Result<(ir::Dtype, ir::Operand), IrgenErrorMessage> {
+        let arg_dtype = signature.params[index].clone();
+        let dtype = self.param_dtypes[index].clone();
+        let value = ir::Operand::register(ir::RegisterId::arg(bid_init, index), arg_dtype.clone());
+        if dtype == arg_dtype {
+            return Ok((dtype, value));
+        }
+
+        let value = context.insert_instruction(ir::Instruction::TypeCast {
+            value,
+            target_dtype: dtype.clone(),
+        })?;
+        Ok((dtype, value))
+    }
+
+    /// Translate the call of `callee` with `args` under the current block `context`, where `callee`
+    /// is a function declared without a prototype, e.g., `f(c, 1.0f)` of `int f();`. A call with
+    /// arguments to a function without parameters is regarded as such a call.
+    ///
+    /// Returns the callee and the arguments to call. The arguments are converted by the default
+    /// argument promotions, and `callee` is converted to the pointer to the function taking them.
+    fn translate_unprototyped_call(
+        &mut self,
+        callee: ir::Operand,
+        args: Vec<ir::Operand>,
+        context: &mut Context,
+    ) -> Result<(ir::Operand, Vec<ir::Operand>), IrgenErrorMessage> {
+        let callee_dtype = callee.dtype();
+        let (ret, _) = callee_dtype
+            .get_pointer_inner()
+            .and_then(ir::Dtype::get_function_inner)
+            .expect("`callee` must be a pointer to function");
+
+        let args = args
+            .into_iter()
+            .map(|arg| {
+                let dtype = arg.dtype();
+                let promoted = dtype.promote_argument();
+                if dtype == promoted {
+                    return Ok(arg);
+                }
+                context.insert_instruction(ir::Instruction::TypeCast {
+                    value: arg,
+                    target_dtype: promoted,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let params = args.iter().map(ir::Operand::dtype).collect();
+        let target_dtype = ir::Dtype::pointer(ir::Dtype::function(ret.clone(), params));
+        let callee = context.insert_instruction(ir::Instruction::TypeCast {
+            value: callee,
+            target_dtype,
+        })?;
+        Ok((callee, args))
+    }
+
+    /// Translate a load of the bit-field at `bit_offset` of `width` bits in the storage unit `ptr`
+    /// under the current block `context`.
+    ///
+    /// The storage unit is loaded as a whole, and the bit-field is extracted with shifts and masks.
+    /// A signed bit-field is sign-extended by an arithmetic right shift.
+    fn translate_bit_field_load(
+        &mut self,
+        ptr: ir::Operand,
+        (bit_offset, width): ir::BitField,
+        context: &mut Context,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        let dtype = ptr
+            .dtype()
+            .get_pointer_inner()
+            .expect("`ptr` must be a pointer")
+            .clone()
+            .set_const(false);
+        let (unit_width, is_signed) = (
+            dtype.get_int_width().expect("bit-field must be an integer"),
+            dtype.is_int_signed(),
+        );
+        let shift =
+            |amount: usize| ir::Operand::constant(ir::Constant::int(amount as u128, dtype.clone()));
+
+        let unit = context.insert_instruction(ir::Instruction::Load { ptr })?;
+        if is_signed {
+            let shifted = context.insert_instruction(ir::Instruction::BinOp {
+                op: BinaryOperator::ShiftLeft,
+                lhs: unit,
+                rhs: shift(unit_width - bit_offset - width),
+                dtype: dtype.clone(),
+            })?;
+            context.insert_instruction(ir::Instruction::BinOp {
+                op: BinaryOperator::ShiftRight,
+                lhs: shifted,
+                rhs: shift(unit_width - width),
+                dtype: dtype.clone(),
+            })
+        } else {
+            let shifted = context.insert_instruction(ir::Instruction::BinOp {
+                op: BinaryOperator::ShiftRight,
+                lhs: unit,
+                rhs: shift(bit_offset),
+                dtype: dtype.clone(),
+            })?;
+            context.insert_instruction(ir::Instruction::BinOp {
+                op: BinaryOperator::BitwiseAnd,
+                lhs: shifted,
+                rhs: ir::Operand::constant(ir::Constant::int(
+                    ir::trim_unnecessary_bits(u128::MAX, width as u128),
+                    dtype.clone(),
+                )),
+                dtype,
+            })
+        }
+    }
+
+    /// Translate a store of `value` to the bit-field at `bit_offset` of `width` bits in the storage
+    /// unit `ptr` under the current block `context`.
+    ///
+    /// The storage unit is read, the bits of the bit-field are replaced, and the unit is written
+    /// back, so that the other bit-fields sharing the unit are kept.
+    fn translate_bit_field_store(
+        &mut self,
+        ptr: ir::Operand,
+        value: ir::Operand,
+        (bit_offset, width): ir::BitField,
+        context: &mut Context,
+    ) -> Result<(), IrgenErrorMessage> {
+        let dtype = ptr
+            .dtype()
+            .get_pointer_inner()
+            .expect("`ptr` must be a pointer")
+            .clone()
+            .set_const(false);
+        let mask = ir::trim_unnecessary_bits(u128::MAX, width as u128) << bit_offset;
+        let int_constant = |value: u128| {
+            let width = dtype.get_int_width().unwrap() as u128;
+            let value = ir::trim_unnecessary_bits(value, width);
+            let value = if dtype.is_int_signed() {
+                ir::sign_extension(value, width)
+            } else {
+                value
+            };
+            ir::Operand::constant(ir::Constant::int(value, dtype.clone()))
+        };
+
+        let value = if value.dtype() == dtype {
+            value
+        } else {
+            context.insert_instruction(ir::Instruction::TypeCast {
+                value,
+                target_dtype: dtype.clone(),
+            })?
+        };
+        let value = context.insert_instruction(ir::Instruction::BinOp {
+            op: BinaryOperator::ShiftLeft,
+            lhs: value,
+            rhs: int_constant(bit_offset as u128),
+            dtype: dtype.clone(),
+        })?;
+        let value = context.insert_instruction(ir::Instruction::BinOp {
+            op: BinaryOperator::BitwiseAnd,
+            lhs: value,
+            rhs: int_constant(mask),
+            dtype: dtype.clone(),
+        })?;
+
+        let unit = context.insert_instruction(ir::Instruction::Load { ptr: ptr.clone() })?;
+        let unit = context.insert_instruction(ir::Instruction::BinOp {
+            op: BinaryOperator::BitwiseAnd,
+            lhs: unit,
+            rhs: int_constant(!mask),
+            dtype: dtype.clone(),
+        })?;
+        let unit = context.insert_instruction(ir::Instruction::BinOp {
+            op: BinaryOperator::BitwiseOr,
+            lhs: unit,
+            rhs: value,
+            dtype,
+        })?;
+
+        let _unused = context.insert_instruction(ir::Instruction::Store { ptr, value: unit })?;
+        Ok(())
+    }
+
+    /// Transalte a C statement `stmt` under the current block `context`, with `continue` block
+    /// `bid_continue` and break block `bid_break`.
+    fn translate_stmt(
+        &mut self,
+        stmt: &Statement,
+        context: &mut Context,
+        bid_continue: Option<ir::BlockId>,
+        bid_break: Option<ir::BlockId>,
+    ) -> Result<(), IrgenError> {
+        todo!()
+    }
+
+    /// Translate initial parameter declarations of the functions to IR.
+    ///
+    /// For example, given the following C function from [`foo.c`][foo]:
     ///
     /// ```C
     /// int foo(int x, int y, int z) {
@@ -655,6 +2086,10 @@ impl IrgenFunc<'_> {
     /// In particular, note that it is added to the local allocation list and store them to the
     /// initial phinodes.
     ///
+    /// The parameters of a K&R-style definition may be of types other than `signature.params`,
+    /// e.g., `float` for the argument of `double`. `translate_parameter_value` gives the value of
+    /// each parameter of its declared type.
+    ///
     /// Note that the resulting IR is **a** solution. If you can think of a better way to
     /// translate parameters, feel free to do so.
     ///
@@ -670,56 +2105,152 @@ impl IrgenFunc<'_> {
     }
 }
 
-#[inline]
-fn name_of_declarator(declarator: &Declarator) -> String {
-    let declarator_kind = &declarator.kind;
-    match &declarator_kind.node {
-        DeclaratorKind::Abstract => panic!("DeclaratorKind::Abstract is unsupported"),
-        DeclaratorKind::Identifier(identifier) => identifier.node.name.clone(),
-        DeclaratorKind::Declarator(declarator) => name_of_declarator(&declarator.node),
-    }
+/// Returns whether `specifiers` use a type declared in a function, i.e., a struct type defined in
+/// `specifiers`, or a struct tag or typedef name declared in `scopes`.
+fn uses_local_type(specifiers: &[Node<DeclarationSpecifier>], scopes: &[LocalScope]) -> bool {
+    specifiers.iter().any(|spec| {
+        let DeclarationSpecifier::TypeSpecifier(type_specifier) = &spec.node else {
+            return false;
+        };
+        match &type_specifier.node {
+            TypeSpecifier::Struct(struct_type) => {
+                struct_type.node.declarations.is_some()
+                    || struct_type
+                        .node
+                        .identifier
+                        .as_ref()
+                        .is_some_and(|identifier| {
+                            scopes
+                                .iter()
+                                .any(|scope| scope.struct_tags.contains(&identifier.node.name))
+                        })
+            }
+            TypeSpecifier::TypedefName(identifier) => scopes
+                .iter()
+                .any(|scope| scope.variables.contains_key(&identifier.node.name)),
+            _ => false,
+        }
+    })
 }
 
-#[inline]
-fn name_of_params_from_function_declarator(declarator: &Declarator) -> Option<Vec<String>> {
-    let declarator_kind = &declarator.kind;
-    match &declarator_kind.node {
-        DeclaratorKind::Abstract => panic!("DeclaratorKind::Abstract is unsupported"),
-        DeclaratorKind::Identifier(_) => {
-            name_of_params_from_derived_declarators(&declarator.derived)
-        }
-        DeclaratorKind::Declarator(next_declarator) => {
-            name_of_params_from_function_declarator(&next_declarator.node)
-                .or_else(|| name_of_params_from_derived_declarators(&declarator.derived))
+/// Renames the `static` and `extern` local variables in `initializer` to the global variables
+/// they refer to, e.g., `&x` to `&x.0`. The other local variables cannot be used in the
+/// initializer of a `static` local variable.
+fn rename_locals_of_initializer(
+    initializer: &Initializer,
+    scopes: &[LocalScope],
+) -> Result<Initializer, IrgenErrorMessage> {
+    match initializer {
+        Initializer::Expression(expr) => Ok(Initializer::Expression(Box::new(Node::new(
+            rename_locals(&expr.node, scopes)?,
+            expr.span,
+        )))),
+        Initializer::List(items) => {
+            let items = items
+                .iter()
+                .map(|item| {
+                    let initializer = &item.node.initializer;
+                    let item_renamed = InitializerListItem {
+                        designation: item.node.designation.clone(),
+                        initializer: Box::new(Node::new(
+                            rename_locals_of_initializer(&initializer.node, scopes)?,
+                            initializer.span,
+                        )),
+                    };
+                    Ok(Node::new(item_renamed, item.span))
+                })
+                .collect::<Result<_, IrgenErrorMessage>>()?;
+            Ok(Initializer::List(items))
         }
     }
 }
 
-#[inline]
-fn name_of_params_from_derived_declarators(
-    derived_decls: &[Node<DerivedDeclarator>],
-) -> Option<Vec<String>> {
-    for derived_decl in derived_decls {
-        match &derived_decl.node {
-            DerivedDeclarator::Function(func_decl) => {
-                let name_of_params = func_decl
-                    .node
-                    .parameters
-                    .iter()
-                    .map(|p| name_of_parameter_declaration(&p.node))
-                    .collect::<Option<Vec<_>>>()
-                    .unwrap_or_default();
-                return Some(name_of_params);
-            }
-            DerivedDeclarator::KRFunction(_kr_func_decl) => {
-                // K&R function is allowed only when it has no parameter
-                return Some(Vec::new());
+/// Renames the `static` and `extern` local variables in the address constant `expr`. See
+/// `rename_locals_of_initializer`.
+fn rename_locals(
+    expr: &Expression,
+    scopes: &[LocalScope],
+) -> Result<Expression, IrgenErrorMessage> {
+    let rename = |expr: &Node<Expression>| {
+        Ok::<_, IrgenErrorMessage>(Box::new(Node::new(
+            rename_locals(&expr.node, scopes)?,
+            expr.span,
+        )))
+    };
+
+    let expr = match expr {
+        Expression::Identifier(identifier) => {
+            let local = scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.variables.get(&identifier.node.name));
+            match local {
+                Some(Some(global)) => Expression::Identifier(Box::new(Node::new(
+                    Identifier {
+                        name: global.clone(),
+                    },
+                    identifier.span,
+                ))),
+                Some(None) => {
+                    return Err(IrgenErrorMessage::Misc {
+                        message: "initializer element is not a compile-time constant".to_string(),
+                    });
+                }
+                None => expr.clone(),
             }
-            _ => (),
-        };
-    }
+        }
+        Expression::BinaryOperator(binary) => {
+            let binary_expr = BinaryOperatorExpression {
+                operator: binary.node.operator.clone(),
+                lhs: rename(&binary.node.lhs)?,
+                rhs: rename(&binary.node.rhs)?,
+            };
+            Expression::BinaryOperator(Box::new(Node::new(binary_expr, binary.span)))
+        }
+        Expression::UnaryOperator(unary) => {
+            let unary_expr = UnaryOperatorExpression {
+                operator: unary.node.operator.clone(),
+                operand: rename(&unary.node.operand)?,
+            };
+            Expression::UnaryOperator(Box::new(Node::new(unary_expr, unary.span)))
+        }
+        Expression::Member(member) => {
+            let member_expr = MemberExpression {
+                operator: member.node.operator.clone(),
+                expression: rename(&member.node.expression)?,
+                identifier: member.node.identifier.clone(),
+            };
+            Expression::Member(Box::new(Node::new(member_expr, member.span)))
+        }
+        Expression::Cast(cast) => {
+            let cast_expr = CastExpression {
+                type_name: cast.node.type_name.clone(),
+                expression: rename(&cast.node.expression)?,
+            };
+            Expression::Cast(Box::new(Node::new(cast_expr, cast.span)))
+        }
+        _ => expr.clone(),
+    };
+    Ok(expr)
+}
 
-    None
+#[inline]
+fn name_of_params_from_function_declarator(declarator: &Declarator) -> Option<Vec<String>> {
+    let name_of_params = match function_derived_declarator(declarator)? {
+        DerivedDeclarator::Function(func_decl) => func_decl
+            .node
+            .parameters
+            .iter()
+            .map(|p| name_of_parameter_declaration(&p.node))
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default(),
+        DerivedDeclarator::KRFunction(identifiers) => identifiers
+            .iter()
+            .map(|identifier| identifier.node.name.clone())
+            .collect(),
+        _ => unreachable!(),
+    };
+    Some(name_of_params)
 }
 
 #[inline]
@@ -728,60 +2259,809 @@ fn name_of_parameter_declaration(parameter_declaration: &ParameterDeclaration) -
     Some(name_of_declarator(&declarator.node))
 }
 
-#[inline]
-fn is_valid_initializer(
-    initializer: &Initializer,
+/// Folds the integer constant operands of the address constant `expr`, e.g., `a + N` to `a + 2`.
+fn fold_address_constant(const_eval: &ConstEval<'_>, expr: &Expression) -> Expression {
+    let fold = |expr: &Node<Expression>| {
+        let folded = const_eval
+            .eval(&expr.node)
+            .ok()
+            .filter(ir::Constant::is_integer_constant)
+            .and_then(|constant| const_eval::to_literal(&constant))
+            .unwrap_or_else(|| fold_address_constant(const_eval, &expr.node));
+        Box::new(Node::new(folded, expr.span))
+    };
+
+    match expr {
+        Expression::BinaryOperator(binary) => {
+            let binary_expr = BinaryOperatorExpression {
+                operator: binary.node.operator.clone(),
+                lhs: fold(&binary.node.lhs),
+                rhs: fold(&binary.node.rhs),
+            };
+            Expression::BinaryOperator(Box::new(Node::new(binary_expr, binary.span)))
+        }
+        Expression::UnaryOperator(unary) => {
+            let unary_expr = UnaryOperatorExpression {
+                operator: unary.node.operator.clone(),
+                operand: fold(&unary.node.operand),
+            };
+            Expression::UnaryOperator(Box::new(Node::new(unary_expr, unary.span)))
+        }
+        Expression::Member(member) => {
+            let member_expr = MemberExpression {
+                operator: member.node.operator.clone(),
+                expression: fold(&member.node.expression),
+                identifier: member.node.identifier.clone(),
+            };
+            Expression::Member(Box::new(Node::new(member_expr, member.span)))
+        }
+        _ => expr.clone(),
+    }
+}
+
+/// Folds the index of an array designator to a literal.
+fn fold_designator(
+    const_eval: &ConstEval<'_>,
+    designator: &Node<Designator>,
+) -> Result<Node<Designator>, IrgenErrorMessage> {
+    let Designator::Index(expr) = &designator.node else {
+        return Ok(designator.clone());
+    };
+
+    let index = const_eval.eval(&expr.node)?;
+    let literal = index
+        .get_int()
+        .and_then(|_| const_eval::to_literal(&index))
+        .ok_or_else(|| IrgenErrorMessage::Misc {
+            message: "array designator index is not an integer constant".to_string(),
+        })?;
+    Ok(Node::new(
+        Designator::Index(Node::new(literal, expr.span)),
+        designator.span,
+    ))
+}
+
+/// Creates the anonymous read-only global variable holding the string literal `literal`, e.g.,
+/// `var [4 x const i8] @.str0 = "abc"`. Its name is allocated with `counter`.
+fn string_literal_declaration(
+    literal: &Node<StringLiteral>,
+    counter: &mut usize,
+) -> Result<Named<ir::Declaration>, IrgenErrorMessage> {
+    let bytes = ir::string_literal_bytes(&literal.node).map_err(|_| IrgenErrorMessage::Misc {
+        message: "invalid string literal".to_string(),
+    })?;
+    let dtype = ir::Dtype::array(ir::Dtype::CHAR.set_const(true), bytes.len() + 1);
+    let initializer = Initializer::Expression(Box::new(Node::new(
+        Expression::StringLiteral(Box::new(literal.clone())),
+        literal.span,
+    )));
+
+    let name = format!(".str{counter}");
+    *counter += 1;
+
+    Ok(Named::new(
+        Some(name),
+        ir::Declaration::Variable {
+            dtype,
+            initializer: Some(initializer),
+            linkage: ir::Linkage::Internal,
+            is_definition: true,
+            alignment: None,
+        },
+    ))
+}
+
+/// Translates the `_Alignas` specifiers in `specifiers` of a variable of `dtype` to its
+/// alignment, or `None` if there is no `_Alignas`. The alignments are evaluated by `eval`.
+///
+/// `_Alignas(T)` is the same as `_Alignas(_Alignof(T))`. The strictest alignment is taken if there
+/// are multiple specifiers, and `_Alignas(0)` has no effect. It is an error to specify an alignment
+/// less strict than that of `dtype`.
+fn alignment_of_specifiers<F>(
+    specifiers: &[Node<DeclarationSpecifier>],
     dtype: &ir::Dtype,
     structs: &HashMap<String, Option<ir::Dtype>>,
-) -> bool {
-    match initializer {
-        Initializer::Expression(expr) => match dtype {
-            ir::Dtype::Int { .. } | ir::Dtype::Float { .. } | ir::Dtype::Pointer { .. } => {
-                match &expr.node {
-                    Expression::Constant(_) => true,
-                    Expression::UnaryOperator(unary) => matches!(
-                        &unary.node.operator.node,
-                        UnaryOperator::Minus | UnaryOperator::Plus
-                    ),
-                    _ => false,
-                }
-            }
-            _ => false,
-        },
-        Initializer::List(items) => match dtype {
-            ir::Dtype::Array { inner, .. } => items
-                .iter()
-                .all(|i| is_valid_initializer(&i.node.initializer.node, inner, structs)),
-            ir::Dtype::Struct { name, .. } => {
-                let name = name.as_ref().expect("struct should have its name");
-                let struct_type = structs
-                    .get(name)
-                    .expect("struct type matched with `name` must exist")
-                    .as_ref()
-                    .expect("`struct_type` must have its definition");
-                let fields = struct_type
-                    .get_struct_fields()
-                    .expect("`struct_type` must be struct type")
-                    .as_ref()
-                    .expect("`fields` must be `Some`");
-
-                izip!(fields, items).all(|(f, i)| {
-                    is_valid_initializer(&i.node.initializer.node, f.deref(), structs)
-                })
+    eval: F,
+) -> Result<Option<usize>, IrgenErrorMessage>
+where
+    F: Fn(&Expression) -> Result<ir::Constant, IrgenErrorMessage>,
+{
+    let mut result = None;
+    for spec in specifiers {
+        let DeclarationSpecifier::Alignment(alignment) = &spec.node else {
+            continue;
+        };
+        let alignment = match &alignment.node {
+            AlignmentSpecifier::Type(type_name) => {
+                let align_of = AlignOf(Box::new(type_name.clone()));
+                eval(&Expression::AlignOf(Box::new(Node::new(
+                    align_of,
+                    type_name.span,
+                ))))?
             }
-            _ => false,
-        },
+            AlignmentSpecifier::Constant(expr) => eval(&expr.node)?,
+        };
+        let Some((alignment, _, _)) = alignment.get_int() else {
+            return Err(IrgenErrorMessage::Misc {
+                message: "requested alignment is not an integer constant".to_string(),
+            });
+        };
+        // A negative alignment is not a power of 2 either since it is sign-extended.
+        if !(alignment == 0 || alignment.is_power_of_two()) {
+            return Err(IrgenErrorMessage::Misc {
+                message: "requested alignment is not a power of 2".to_string(),
+            });
+        }
+        if alignment != 0 {
+            result = result.max(Some(alignment as usize));
+        }
+    }
+
+    if let Some(alignment) = result {
+        let (_, align_of) = dtype
+            .size_align_of(structs)
+            .map_err(|e| IrgenErrorMessage::InvalidDtype { dtype_error: e })?;
+        if alignment < align_of {
+            return Err(IrgenErrorMessage::Misc {
+                message: format!(
+                    "requested alignment {alignment} is less than the alignment of `{dtype}`"
+                ),
+            });
+        }
     }
+    Ok(result)
 }
 
-#[inline]
-fn is_invalid_structure(dtype: &ir::Dtype, structs: &HashMap<String, Option<ir::Dtype>>) -> bool {
-    // When `dtype` is `Dtype::Struct`, `structs` has real definition of `dtype`
-    if let ir::Dtype::Struct { name, fields, .. } = dtype {
-        assert!(name.is_some() && fields.is_none());
-        let name = name.as_ref().unwrap();
-        structs.get(name).is_none_or(Option::is_none)
-    } else {
-        false
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates the translator of a function `int main()` using `typedefs` and `structs`, with the
+    /// context of its initial block.
+    fn irgen_func<'i>(
+        typedefs: &'i HashMap<String, ir::Dtype>,
+        structs: &'i mut HashMap<String, Option<ir::Dtype>>,
+    ) -> (IrgenFunc<'i>, Context) {
+        let irgen = IrgenFunc {
+            return_type: ir::Dtype::INT,
+            bid_init: Irgen::BID_INIT,
+            phinodes_init: Vec::new(),
+            param_dtypes: Vec::new(),
+            allocations: Vec::new(),
+            allocation_alignments: Vec::new(),
+            blocks: BTreeMap::new(),
+            bid_counter: Irgen::BID_COUNTER_INIT,
+            tempid_counter: Irgen::TEMPID_COUNTER_INIT,
+            typedefs,
+            structs,
+            struct_tempid_counter: 0,
+            local_struct_counter: 0,
+            symbol_table: vec![HashMap::new(), HashMap::new()],
+            type_scopes: vec![TypeScope::default(), TypeScope::default()],
+            labels: HashMap::new(),
+            string_literals: Vec::new(),
+            string_literal_counter: 0,
+            compound_literal_counter: 0,
+            block_scope_globals: HashMap::new(),
+        };
+        let context = Context::new(irgen.bid_init);
+        (irgen, context)
+    }
+
+    /// Returns `return value;` under the current block `context`. The following statements are
+    /// translated in a new block.
+    fn translate_return(irgen: &mut IrgenFunc<'_>, value: ir::Operand, context: &mut Context) {
+        let bid_next = irgen.alloc_bid();
+        let context = mem::replace(context, Context::new(bid_next));
+        irgen.insert_block(context, ir::BlockExit::Return { value });
+    }
+
+    /// Completes `irgen` with the last block `context` returning 0, and adds the function to
+    /// `decls` as `name` of `dtype`, with the anonymous global variables used in it.
+    fn add_function(
+        mut irgen: IrgenFunc<'_>,
+        context: Context,
+        name: &str,
+        dtype: ir::Dtype,
+        decls: &mut BTreeMap<String, ir::Declaration>,
+    ) {
+        let zero = ir::Operand::constant(ir::Constant::int(0, ir::Dtype::INT));
+        irgen.insert_block(context, ir::BlockExit::Return { value: zero });
+        let definition = ir::FunctionDefinition {
+            allocations: irgen.allocations,
+            allocation_alignments: irgen.allocation_alignments,
+            blocks: irgen.blocks,
+            bid_init: irgen.bid_init,
+        };
+        let function = ir::Declaration::Function {
+            signature: ir::FunctionSignature::new(dtype),
+            definition: Some(definition),
+            linkage: ir::Linkage::External,
+        };
+        decls.extend(
+            irgen
+                .string_literals
+                .into_iter()
+                .map(|decl| (decl.name().cloned().unwrap(), decl.into_inner())),
+        );
+        let _unused = decls.insert(name.to_string(), function);
+    }
+
+    /// Interprets the translation unit of `decls` and `structs`, returning the result of `main`.
+    fn interp_decls(
+        decls: BTreeMap<String, ir::Declaration>,
+        structs: HashMap<String, Option<ir::Dtype>>,
+    ) -> u128 {
+        let unit = ir::TranslationUnit { decls, structs };
+        ir::verify(&unit).unwrap();
+        let result = ir::interp(&unit, Vec::new()).unwrap();
+        let (value, _, _) = result.get_int().expect("`main` must return an integer");
+        value
+    }
+
+    /// Completes `irgen` with the last block `context` returning 0, and interprets the translation
+    /// unit with the function as `main`, returning its result.
+    fn interp_main(irgen: IrgenFunc<'_>, context: Context) -> u128 {
+        let structs = irgen.structs.clone();
+        let mut decls = BTreeMap::new();
+        let dtype = ir::Dtype::function(ir::Dtype::INT, Vec::new());
+        add_function(irgen, context, "main", dtype, &mut decls);
+        interp_decls(decls, structs)
+    }
+
+    fn int(value: u128) -> ir::Operand {
+        ir::Operand::constant(ir::Constant::int(value, ir::Dtype::INT))
+    }
+
+    #[test]
+    fn goto_forward_and_backward() {
+        let typedefs = HashMap::new();
+        let mut structs = HashMap::new();
+        let (mut irgen, mut context) = irgen_func(&typedefs, &mut structs);
+
+        // goto second; first: return 1; second: goto first;
+        irgen.translate_goto("second", &mut context);
+        irgen.translate_label("first", &mut context);
+        translate_return(&mut irgen, int(1), &mut context);
+        irgen.translate_label("second", &mut context);
+        irgen.translate_goto("first", &mut context);
+
+        assert_eq!(irgen.labels["second"], ir::BlockId(1));
+        assert_eq!(irgen.labels["first"], ir::BlockId(3));
+        assert_eq!(interp_main(irgen, context), 1);
+    }
+
+    /// Interprets `main` returning the result of `f` as `int`, where `f` is given the address of a
+    /// storage unit of `dtype` initialized to zero.
+    fn interp_bit_field<F>(dtype: ir::Dtype, f: F) -> u128
+    where
+        F: FnOnce(&mut IrgenFunc<'_>, ir::Operand, &mut Context) -> ir::Operand,
+    {
+        let typedefs = HashMap::new();
+        let mut structs = HashMap::new();
+        let (mut irgen, mut context) = irgen_func(&typedefs, &mut structs);
+
+        let rid = irgen.insert_alloc(Named::new(Some("unit".to_string()), dtype.clone()));
+        let ptr = ir::Operand::register(rid, ir::Dtype::pointer(dtype.clone()));
+        let zero = ir::Operand::constant(ir::Constant::int(0, dtype));
+        let _unused = context
+            .insert_instruction(ir::Instruction::Store {
+                ptr: ptr.clone(),
+                value: zero,
+            })
+            .unwrap();
+        let value = f(&mut irgen, ptr, &mut context);
+        let value = context
+            .insert_instruction(ir::Instruction::TypeCast {
+                value,
+                target_dtype: ir::Dtype::INT,
+            })
+            .unwrap();
+        translate_return(&mut irgen, value, &mut context);
+        interp_main(irgen, context)
+    }
+
+    #[test]
+    fn bit_field_signed() {
+        // struct { int a : 1; int b : 3; } with `b = -2; a = 1;`
+        let store_both = |irgen: &mut IrgenFunc<'_>, ptr: &ir::Operand, context: &mut Context| {
+            irgen
+                .translate_bit_field_store(ptr.clone(), int(-2i128 as u128), (1, 3), context)
+                .unwrap();
+            irgen
+                .translate_bit_field_store(ptr.clone(), int(1), (0, 1), context)
+                .unwrap();
+        };
+        let b = interp_bit_field(ir::Dtype::INT, |irgen, ptr, context| {
+            store_both(irgen, &ptr, context);
+            irgen
+                .translate_bit_field_load(ptr, (1, 3), context)
+                .unwrap()
+        });
+        assert_eq!(b, -2i128 as u128);
+        let a = interp_bit_field(ir::Dtype::INT, |irgen, ptr, context| {
+            store_both(irgen, &ptr, context);
+            irgen
+                .translate_bit_field_load(ptr, (0, 1), context)
+                .unwrap()
+        });
+        assert_eq!(a, -1i128 as u128);
+
+        // struct { long c : 40; } with `c = -1;`
+        let c = interp_bit_field(ir::Dtype::LONG, |irgen, ptr, context| {
+            irgen
+                .translate_bit_field_store(ptr.clone(), int(-1i128 as u128), (0, 40), context)
+                .unwrap();
+            irgen
+                .translate_bit_field_load(ptr, (0, 40), context)
+                .unwrap()
+        });
+        assert_eq!(c, -1i128 as u128);
+    }
+
+    #[test]
+    fn bit_field_unsigned() {
+        let u8_dtype =
+            ir::Dtype::int(ir::Dtype::SIZE_OF_CHAR * ir::Dtype::BITS_OF_BYTE).set_signed(false);
+
+        // struct { unsigned char lo : 4, hi : 4; } with `hi = 0x1f; lo = 9;`, where `hi` is
+        // truncated to 0xf without changing `lo`.
+        let store_both = |irgen: &mut IrgenFunc<'_>, ptr: &ir::Operand, context: &mut Context| {
+            irgen
+                .translate_bit_field_store(ptr.clone(), int(0x1f), (4, 4), context)
+                .unwrap();
+            irgen
+                .translate_bit_field_store(ptr.clone(), int(9), (0, 4), context)
+                .unwrap();
+        };
+        let hi = interp_bit_field(u8_dtype.clone(), |irgen, ptr, context| {
+            store_both(irgen, &ptr, context);
+            irgen
+                .translate_bit_field_load(ptr, (4, 4), context)
+                .unwrap()
+        });
+        assert_eq!(hi, 0xf);
+        let unit = interp_bit_field(u8_dtype, |irgen, ptr, context| {
+            store_both(irgen, &ptr, context);
+            context
+                .insert_instruction(ir::Instruction::Load { ptr })
+                .unwrap()
+        });
+        assert_eq!(unit, 0xf9);
+    }
+
+    #[test]
+    fn string_literal() {
+        let typedefs = HashMap::new();
+        let mut structs = HashMap::new();
+        let (mut irgen, mut context) = irgen_func(&typedefs, &mut structs);
+        let literal = |pieces: &[&str]| {
+            let pieces = pieces.iter().map(|piece| piece.to_string()).collect();
+            Node::new(pieces, Span::none())
+        };
+
+        // "ke" "c\x63"[3]
+        let _unused = irgen.translate_string_literal(&literal(&["\"\""])).unwrap();
+        let ptr = irgen
+            .translate_string_literal(&literal(&["\"ke\"", "\"c\\x63\""]))
+            .unwrap();
+        let char_dtype = ir::Dtype::CHAR.set_const(true);
+        assert_eq!(
+            ptr,
+            ir::Operand::constant(ir::Constant::global_variable(
+                ".str1".to_string(),
+                ir::Dtype::array(char_dtype.clone(), 5)
+            ))
+        );
+        let offset = ir::Operand::constant(ir::Constant::int(3, ir::Dtype::LONG));
+        let ptr = context
+            .insert_instruction(ir::Instruction::GetElementPtr {
+                ptr,
+                offset,
+                dtype: ir::Dtype::pointer(char_dtype),
+            })
+            .unwrap();
+        let value = context
+            .insert_instruction(ir::Instruction::Load { ptr })
+            .unwrap();
+        let value = context
+            .insert_instruction(ir::Instruction::TypeCast {
+                value,
+                target_dtype: ir::Dtype::INT,
+            })
+            .unwrap();
+        translate_return(&mut irgen, value, &mut context);
+
+        assert_eq!(irgen.string_literal_counter, 2);
+        assert_eq!(interp_main(irgen, context), b'c' as u128);
+    }
+
+    #[test]
+    fn variable_arguments() {
+        let typedefs = HashMap::new();
+        let mut structs = HashMap::new();
+        let mut decls = BTreeMap::new();
+
+        // int sum(int count, ...) {
+        //     va_list ap, copy;
+        //     va_start(ap, count);
+        //     int a = va_arg(ap, int), b = va_arg(ap, int);
+        //     va_copy(copy, ap);
+        //     long c = va_arg(copy, long);
+        //     va_end(copy);
+        //     va_end(ap);
+        //     return a + b + c;
+        // }
+        let sum_dtype =
+            ir::Dtype::function(ir::Dtype::INT, vec![ir::Dtype::INT]).set_variadic(true);
+        let (mut irgen, mut context) = irgen_func(&typedefs, &mut structs);
+        irgen.phinodes_init = vec![Named::new(Some("count".to_string()), ir::Dtype::INT)];
+        let va_list = ir::Dtype::va_list();
+        let mut alloc_va_list = |irgen: &mut IrgenFunc<'_>, name: &str| {
+            let rid = irgen.insert_alloc(Named::new(Some(name.to_string()), va_list.clone()));
+            ir::Operand::register(rid, ir::Dtype::pointer(va_list.clone()))
+        };
+        let ap = alloc_va_list(&mut irgen, "ap");
+        let copy = alloc_va_list(&mut irgen, "copy");
+        let _unused = irgen
+            .translate_va_builtin("__builtin_va_start", &[ap.clone()], &mut context)
+            .unwrap();
+        let a = irgen
+            .translate_va_arg(ap.clone(), ir::Dtype::INT, &mut context)
+            .unwrap();
+        let b = irgen
+            .translate_va_arg(ap.clone(), ir::Dtype::INT, &mut context)
+            .unwrap();
+        let _unused = irgen
+            .translate_va_builtin(
+                "__builtin_va_copy",
+                &[copy.clone(), ap.clone()],
+                &mut context,
+            )
+            .unwrap();
+        let c = irgen
+            .translate_va_arg(copy.clone(), ir::Dtype::LONG, &mut context)
+            .unwrap();
+        for ptr in [copy, ap] {
+            let _unused = irgen
+                .translate_va_builtin("__builtin_va_end", &[ptr], &mut context)
+                .unwrap();
+        }
+        let c = context
+            .insert_instruction(ir::Instruction::TypeCast {
+                value: c,
+                target_dtype: ir::Dtype::INT,
+            })
+            .unwrap();
+        let mut add = |lhs, rhs| {
+            context
+                .insert_instruction(ir::Instruction::BinOp {
+                    op: BinaryOperator::Plus,
+                    lhs,
+                    rhs,
+                    dtype: ir::Dtype::INT,
+                })
+                .unwrap()
+        };
+        let a_b = add(a, b);
+        let value = add(a_b, c);
+        translate_return(&mut irgen, value, &mut context);
+        add_function(irgen, context, "sum", sum_dtype.clone(), &mut decls);
+
+        // int main() { return sum(3, 10, 20, 12L); }
+        let (mut irgen, mut context) = irgen_func(&typedefs, &mut structs);
+        let callee =
+            ir::Operand::constant(ir::Constant::global_variable("sum".to_string(), sum_dtype));
+        let args = vec![
+            int(3),
+            int(10),
+            int(20),
+            ir::Operand::constant(ir::Constant::int(12, ir::Dtype::LONG)),
+        ];
+        let value = context
+            .insert_instruction(ir::Instruction::Call {
+                callee,
+                args,
+                return_type: ir::Dtype::INT,
+            })
+            .unwrap();
+        translate_return(&mut irgen, value, &mut context);
+        let dtype = ir::Dtype::function(ir::Dtype::INT, Vec::new());
+        add_function(irgen, context, "main", dtype, &mut decls);
+
+        assert_eq!(interp_decls(decls, structs), 42);
+    }
+
+    #[test]
+    fn variable_arguments_errors() {
+        let typedefs = HashMap::new();
+        let mut structs = HashMap::new();
+        let (mut irgen, mut context) = irgen_func(&typedefs, &mut structs);
+        let rid = irgen.insert_alloc(Named::new(None, ir::Dtype::va_list()));
+        let ap = ir::Operand::register(rid, ir::Dtype::pointer(ir::Dtype::va_list()));
+
+        assert_eq!(
+            irgen.translate_va_arg(ap, ir::Dtype::CHAR, &mut context),
+            Err(IrgenErrorMessage::Misc {
+                message: "`i8` is not a type of variable arguments after promotion".to_string()
+            })
+        );
+    }
+
+    /// Parses the last declaration of `source`, e.g., `struct A { int x; } a;`. The typedef names
+    /// used in it are declared before, e.g., `typedef int t; t x;`.
+    fn parse_declaration(source: &str) -> Declaration {
+        let config = lang_c::driver::Config::default();
+        let ast = lang_c::driver::parse_preprocessed(&config, source.to_string()).unwrap();
+        let Some(decl) = ast.unit.0.last() else {
+            panic!("`source` must have a declaration");
+        };
+        let ExternalDeclaration::Declaration(decl) = &decl.node else {
+            panic!("`source` must be a declaration");
+        };
+        decl.node.clone()
+    }
+
+    /// Translates the dtype of the first declarator of the last block-scope declaration of `source`.
+    fn translate_local_dtype(irgen: &mut IrgenFunc<'_>, source: &str) -> ir::Dtype {
+        let decl = parse_declaration(source);
+        let (base_dtype, is_typedef) = irgen.translate_base_dtype(&decl.specifiers).unwrap();
+        irgen
+            .translate_declarator_dtype(
+                &base_dtype,
+                &decl.declarators[0].node.declarator.node,
+                is_typedef,
+            )
+            .unwrap()
+    }
+
+    fn struct_name(dtype: &ir::Dtype) -> &str {
+        dtype
+            .get_struct_name()
+            .and_then(Option::as_deref)
+            .expect("`dtype` must be a named struct")
+    }
+
+    #[test]
+    fn local_structs() {
+        let typedefs = HashMap::new();
+        let mut structs = HashMap::new();
+        let _unused = structs.insert(
+            "Pair".to_string(),
+            Some(ir::Dtype::structure(Some("Pair".to_string()), None)),
+        );
+        let (mut irgen, _) = irgen_func(&typedefs, &mut structs);
+
+        // A local definition shadows the global tag with a unique name.
+        let rect = translate_local_dtype(&mut irgen, "struct Pair { long w; long h; } rect;");
+        assert_eq!(struct_name(&rect), "Pair.0");
+        let pointer = translate_local_dtype(&mut irgen, "struct Pair *p;");
+        assert_eq!(pointer, ir::Dtype::pointer(rect.clone()));
+
+        // A struct refers to itself in its fields.
+        irgen.enter_scope();
+        let node = translate_local_dtype(&mut irgen, "struct Node { struct Node *next; } n;");
+        assert_eq!(struct_name(&node), "Node.1");
+        let Some(Some(fields)) = irgen.structs["Node.1"]
+            .as_ref()
+            .and_then(ir::Dtype::get_struct_fields)
+            .cloned()
+        else {
+            panic!("`Node.1` must be defined");
+        };
+        assert_eq!(fields[0].deref(), &ir::Dtype::pointer(node));
+        irgen.exit_scope();
+
+        // The tags of the exited scope are not visible.
+        let node = translate_local_dtype(&mut irgen, "struct Node *p;");
+        assert_eq!(node.get_pointer_inner().map(struct_name), Some("Node"));
+        assert_eq!(
+            translate_local_dtype(&mut irgen, "struct Pair q;"),
+            rect.clone()
+        );
+        assert!(irgen.structs.contains_key("Pair.0"));
+        assert!(irgen.structs.contains_key("Pair"));
+    }
+
+    #[test]
+    fn local_typedefs() {
+        let mut typedefs = HashMap::new();
+        let _unused = typedefs.insert("value_t".to_string(), ir::Dtype::INT);
+        let mut structs = HashMap::new();
+        let (mut irgen, _) = irgen_func(&typedefs, &mut structs);
+
+        assert_eq!(
+            translate_local_dtype(&mut irgen, "typedef int value_t; value_t x;"),
+            ir::Dtype::INT
+        );
+        irgen.enter_scope();
+        irgen
+            .insert_typedef("value_t".to_string(), ir::Dtype::LONG)
+            .unwrap();
+        assert_eq!(
+            translate_local_dtype(&mut irgen, "typedef int value_t; value_t *x;"),
+            ir::Dtype::pointer(ir::Dtype::LONG)
+        );
+
+        // A typedef name may be redeclared with the same type only.
+        irgen
+            .insert_typedef("value_t".to_string(), ir::Dtype::LONG)
+            .unwrap();
+        assert_eq!(
+            irgen.insert_typedef("value_t".to_string(), ir::Dtype::CHAR),
+            Err(IrgenErrorMessage::ConflictingDtype {
+                dtype: ir::Dtype::CHAR,
+                protorype_dtype: ir::Dtype::LONG,
+            })
+        );
+        irgen
+            .insert_symbol_table_entry("x".to_string(), int(0))
+            .unwrap();
+        assert_eq!(
+            irgen.insert_typedef("x".to_string(), ir::Dtype::INT),
+            Err(IrgenErrorMessage::Redefinition {
+                name: "x".to_string()
+            })
+        );
+        irgen.exit_scope();
+
+        assert_eq!(
+            translate_local_dtype(&mut irgen, "typedef int value_t; value_t x;"),
+            ir::Dtype::INT
+        );
+    }
+
+    #[test]
+    fn local_alignments() {
+        let typedefs = HashMap::new();
+        let mut structs = HashMap::new();
+        let (mut irgen, context) = irgen_func(&typedefs, &mut structs);
+
+        let mut alignment_of = |source: &str| {
+            let decl = parse_declaration(source);
+            let dtype = translate_local_dtype(&mut irgen, source);
+            let alignment = irgen.translate_alignment(&decl.specifiers, &dtype)?;
+            let _unused = irgen.insert_aligned_alloc(Named::new(None, dtype), alignment);
+            Ok(alignment)
+        };
+        assert_eq!(alignment_of("_Alignas(16) int a;"), Ok(Some(16)));
+        assert_eq!(alignment_of("_Alignas(double) char b;"), Ok(Some(8)));
+        assert_eq!(
+            alignment_of("_Alignas(4) _Alignas(8) short c;"),
+            Ok(Some(8))
+        );
+        assert_eq!(alignment_of("_Alignas(0) long d;"), Ok(None));
+        assert_eq!(
+            alignment_of("_Alignas(3) int e;"),
+            Err(IrgenErrorMessage::Misc {
+                message: "requested alignment is not a power of 2".to_string()
+            })
+        );
+        assert_eq!(
+            alignment_of("_Alignas(1) int f;"),
+            Err(IrgenErrorMessage::Misc {
+                message: "requested alignment 1 is less than the alignment of `i32`".to_string()
+            })
+        );
+
+        let structs = irgen.structs.clone();
+        let mut decls = BTreeMap::new();
+        let dtype = ir::Dtype::function(ir::Dtype::INT, Vec::new());
+        add_function(irgen, context, "main", dtype, &mut decls);
+        let (_, Some(definition)) = decls["main"].get_function().unwrap() else {
+            panic!("`main` must be defined");
+        };
+        let alignments = (0..4)
+            .map(|aid| definition.allocation_align_of(aid, &structs).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(alignments, [16, 8, 8, 8]);
+        assert_eq!(interp_decls(decls, structs), 0);
+    }
+
+    #[test]
+    fn kr_parameters() {
+        let config = lang_c::driver::Config::default();
+        let source = "typedef short s; int f(a, b, c, d) char a; float b; s d[2]; { return 0; }";
+        let ast = lang_c::driver::parse_preprocessed(&config, source.to_string()).unwrap();
+        let Some(ExternalDeclaration::FunctionDefinition(definition)) =
+            ast.unit.0.last().map(|decl| &decl.node)
+        else {
+            panic!("`source` must end with a function definition");
+        };
+        let Some(DerivedDeclarator::KRFunction(identifiers)) =
+            function_derived_declarator(&definition.node.declarator.node)
+        else {
+            panic!("`f` must be a K&R-style definition");
+        };
+
+        let mut irgen = Irgen::default();
+        let _unused = irgen.typedefs.insert("s".to_string(), ir::Dtype::SHORT);
+        let param_dtypes = irgen
+            .translate_kr_parameters(identifiers, &definition.node.declarations)
+            .unwrap();
+        assert_eq!(
+            param_dtypes,
+            [
+                ir::Dtype::CHAR,
+                ir::Dtype::FLOAT,
+                ir::Dtype::INT,
+                ir::Dtype::pointer(ir::Dtype::SHORT),
+            ]
+        );
+    }
+
+    #[test]
+    fn kr_function_call() {
+        let typedefs = HashMap::new();
+        let mut structs = HashMap::new();
+        let mut decls = BTreeMap::new();
+
+        // int add(a, b) char a; float b; { return a + (int)b; }
+        let add_dtype =
+            ir::Dtype::function(ir::Dtype::INT, vec![ir::Dtype::INT, ir::Dtype::DOUBLE]);
+        let signature = ir::FunctionSignature::new(add_dtype.clone());
+        let (mut irgen, mut context) = irgen_func(&typedefs, &mut structs);
+        irgen.phinodes_init = vec![
+            Named::new(Some("a".to_string()), ir::Dtype::INT),
+            Named::new(Some("b".to_string()), ir::Dtype::DOUBLE),
+        ];
+        irgen.param_dtypes = vec![ir::Dtype::CHAR, ir::Dtype::FLOAT];
+        let mut values = Vec::new();
+        for index in 0..2 {
+            let (dtype, value) = irgen
+                .translate_parameter_value(&signature, irgen.bid_init, index, &mut context)
+                .unwrap();
+            assert_eq!(dtype, irgen.param_dtypes[index]);
+            assert_eq!(value.dtype(), dtype);
+            let value = context
+                .insert_instruction(ir::Instruction::TypeCast {
+                    value,
+                    target_dtype: ir::Dtype::INT,
+                })
+                .unwrap();
+            values.push(value);
+        }
+        let [a, b] = <[_; 2]>::try_from(values).unwrap();
+        let value = context
+            .insert_instruction(ir::Instruction::BinOp {
+                op: BinaryOperator::Plus,
+                lhs: a,
+                rhs: b,
+                dtype: ir::Dtype::INT,
+            })
+            .unwrap();
+        translate_return(&mut irgen, value, &mut context);
+        add_function(irgen, context, "add", add_dtype, &mut decls);
+
+        // int add(); int main() { return add((char)3, 4.5f); }
+        let (mut irgen, mut context) = irgen_func(&typedefs, &mut structs);
+        let unprototyped = ir::Dtype::function(ir::Dtype::INT, Vec::new());
+        let callee = ir::Operand::constant(ir::Constant::global_variable(
+            "add".to_string(),
+            unprototyped,
+        ));
+        let args = vec![
+            ir::Operand::constant(ir::Constant::int(3, ir::Dtype::CHAR)),
+            ir::Operand::constant(ir::Constant::float(4.5f32, ir::Dtype::FLOAT)),
+        ];
+        let (callee, args) = irgen
+            .translate_unprototyped_call(callee, args, &mut context)
+            .unwrap();
+        assert_eq!(
+            args.iter().map(ir::Operand::dtype).collect::<Vec<_>>(),
+            [ir::Dtype::INT, ir::Dtype::DOUBLE]
+        );
+        let value = context
+            .insert_instruction(ir::Instruction::Call {
+                callee,
+                args,
+                return_type: ir::Dtype::INT,
+            })
+            .unwrap();
+        translate_return(&mut irgen, value, &mut context);
+        let dtype = ir::Dtype::function(ir::Dtype::INT, Vec::new());
+        add_function(irgen, context, "main", dtype, &mut decls);
+
+        assert_eq!(interp_decls(decls, structs), 7);
     }
 }
@@ -55,16 +55,27 @@ pub mod ir;
 
 mod asmgen;
 mod irgen;
+mod lint;
 mod opt;
+mod toolchain;
 
 pub use asmgen::Asmgen;
-pub use c::Parse;
-pub use ir::{Parse as IrParse, Visualizer as IrVisualizer};
+pub use c::{
+    BraceStyle, CodeStyle, CommandLineMacro, Diagnostic, Diagnostics, Parse, ParseError,
+    Preprocess, PreprocessError, Severity,
+};
+pub use ir::{
+    Link as IrLink, LinkError as IrLinkError, Parse as IrParse, Visualizer as IrVisualizer,
+};
 pub use irgen::Irgen;
+pub use lint::{Lint, Lints};
 pub use opt::{
-    Deadcode, FunctionPass, Gvn, Mem2reg, O0, O1, Optimize, Repeat, SimplifyCfg,
-    SimplifyCfgConstProp, SimplifyCfgEmpty, SimplifyCfgMerge, SimplifyCfgReach,
+    Analysis, Deadcode, FunctionPass, Gvn, Inline, Mem2reg, O0, O1, O1Verified, Optimize,
+    OptimizeFunction, PassManager, PassStatistics, PassVerifyError, PipelineError, Repeat, Sccp,
+    SimplifyCfg, SimplifyCfgConstProp, SimplifyCfgEmpty, SimplifyCfgMerge, SimplifyCfgReach,
+    Verify, pass_names,
 };
 pub use tests::*;
+pub use toolchain::Toolchain;
 pub use utils::*;
 pub use write_base::write;
@@ -0,0 +1,205 @@
+//! Warnings for suspicious code.
+//!
+//! The lints on C constructs are checked by the semantic analysis of C files (see [`Parse`]), and
+//! the lints on control flow are checked on the generated IR by [`Lints::check_ir`].
+//!
+//! [`Parse`]: crate::Parse
+
+use core::fmt;
+use core::str::FromStr;
+use std::collections::BTreeSet;
+
+use crate::c::{Diagnostic, Diagnostics};
+use crate::ir;
+use crate::opt::opt_utils::{Cfg, DomTree};
+
+/// A kind of warnings, named as in GCC, e.g., `unused-variable` for `-Wunused-variable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Lint {
+    /// A local variable is never used.
+    UnusedVariable,
+    /// A function parameter is never used.
+    UnusedParameter,
+    /// A value is implicitly converted to a type that may not represent it, e.g., `long` to `int`.
+    Conversion,
+    /// A signed integer is compared with an unsigned one, converting the signed one to unsigned.
+    SignCompare,
+    /// The control reaches the end of a non-void function without returning a value.
+    ReturnType,
+    /// Code is never executed.
+    UnreachableCode,
+}
+
+impl Lint {
+    /// All the lints.
+    pub const ALL: [Self; 6] = [
+        Self::UnusedVariable,
+        Self::UnusedParameter,
+        Self::Conversion,
+        Self::SignCompare,
+        Self::ReturnType,
+        Self::UnreachableCode,
+    ];
+
+    /// Returns the name of the lint used in `-W<name>`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UnusedVariable => "unused-variable",
+            Self::UnusedParameter => "unused-parameter",
+            Self::Conversion => "conversion",
+            Self::SignCompare => "sign-compare",
+            Self::ReturnType => "return-type",
+            Self::UnreachableCode => "unreachable-code",
+        }
+    }
+
+    /// Whether the lint is enabled without `-W<name>`. The noisy ones are disabled as in GCC.
+    fn is_enabled_by_default(self) -> bool {
+        matches!(
+            self,
+            Self::UnusedVariable | Self::ReturnType | Self::UnreachableCode
+        )
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Lint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|lint| lint.name() == s)
+            .ok_or_else(|| format!("unknown warning option `-W{s}`"))
+    }
+}
+
+/// The lints to check, and whether their warnings are errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lints {
+    enabled: BTreeSet<Lint>,
+    /// Whether warnings are treated as errors, as by `-Werror`.
+    pub is_error: bool,
+}
+
+impl Default for Lints {
+    fn default() -> Self {
+        Self {
+            enabled: Lint::ALL
+                .into_iter()
+                .filter(|lint| lint.is_enabled_by_default())
+                .collect(),
+            is_error: false,
+        }
+    }
+}
+
+impl Lints {
+    /// Returns whether `lint` is checked.
+    pub fn is_enabled(&self, lint: Lint) -> bool {
+        self.enabled.contains(&lint)
+    }
+
+    /// Enables or disables `lint`.
+    pub fn set(&mut self, lint: Lint, is_enabled: bool) {
+        if is_enabled {
+            let _unused = self.enabled.insert(lint);
+        } else {
+            let _unused = self.enabled.remove(&lint);
+        }
+    }
+
+    /// Applies the warning option `-W<option>`, which is one of:
+    ///
+    /// - `<name>` and `no-<name>` to enable and disable a lint,
+    /// - `all` and `no-all` to enable and disable all the lints, and
+    /// - `error` and `no-error` to treat warnings as errors or not.
+    pub fn apply(&mut self, option: &str) -> Result<(), String> {
+        let (name, is_enabled) = match option.strip_prefix("no-") {
+            Some(name) => (name, false),
+            None => (option, true),
+        };
+        match name {
+            "error" => self.is_error = is_enabled,
+            "all" => Lint::ALL
+                .into_iter()
+                .for_each(|lint| self.set(lint, is_enabled)),
+            _ => self.set(
+                name.parse()
+                    .map_err(|_| format!("unknown warning option `-W{option}`"))?,
+                is_enabled,
+            ),
+        }
+        Ok(())
+    }
+
+    /// Checks the lints on control flow in the IR `unit` generated from a C file.
+    ///
+    /// The IR does not keep source locations, so the warnings refer to functions and blocks.
+    pub fn check_ir(&self, unit: &ir::TranslationUnit) -> Vec<Diagnostic> {
+        let mut diags = Diagnostics::new(self.clone());
+        for (name, decl) in &unit.decls {
+            let Some((signature, Some(definition))) = decl.get_function() else {
+                continue;
+            };
+            check_function(name, signature, definition, &mut diags);
+        }
+        diags.resolve("")
+    }
+}
+
+fn check_function(
+    name: &str,
+    signature: &ir::FunctionSignature,
+    definition: &ir::FunctionDefinition,
+    diags: &mut Diagnostics,
+) {
+    let cfg = Cfg::new(definition);
+    let dom_tree = DomTree::new(definition.bid_init, &cfg);
+
+    // The control reaching the end of a function returns `undef`, `unit` for a void function, or
+    // 0 for `main`.
+    let is_implicit_return = |value: &ir::Operand| {
+        value.get_constant().is_some_and(|constant| {
+            constant.is_undef()
+                || matches!(constant, ir::Constant::Unit)
+                || (name == "main" && constant.get_int().is_some_and(|(value, _, _)| value == 0))
+        })
+    };
+    let returns_undef = definition.blocks.iter().any(|(bid, block)| {
+        dom_tree.is_reachable(*bid)
+            && matches!(
+                &block.exit,
+                ir::BlockExit::Return { value } if value.get_constant().is_some_and(ir::Constant::is_undef)
+            )
+    });
+    if !matches!(signature.ret, ir::Dtype::Unit { .. }) && returns_undef {
+        diags.warn(
+            Lint::ReturnType,
+            format!("non-void function `{name}` does not return a value in all control paths"),
+        );
+    }
+
+    // The blocks generated after a jump, e.g., for the statements following `return`, are empty
+    // unless the C code has such statements.
+    for (bid, block) in &definition.blocks {
+        let is_empty = block.instructions.is_empty()
+            && match &block.exit {
+                ir::BlockExit::Return { value } => is_implicit_return(value),
+                ir::BlockExit::Jump { arg } => arg.args.is_empty(),
+                ir::BlockExit::Unreachable => true,
+                _ => false,
+            };
+        if !dom_tree.is_reachable(*bid) && !is_empty {
+            diags.warn(
+                Lint::UnreachableCode,
+                format!("block `{bid}` of function `{name}` will never be executed"),
+            );
+        }
+    }
+}
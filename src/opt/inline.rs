@@ -0,0 +1,329 @@
+use core::mem;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::ir::*;
+use crate::opt::*;
+
+/// Inlines the calls to small functions defined in the translation unit, i.e., replaces each call
+/// with a copy of the blocks of the callee.
+///
+/// The functions are visited from the callees to the callers, so that the calls inside a callee are
+/// inlined before the callee itself is. The functions calling themselves directly or indirectly and
+/// the variadic functions are never inlined.
+#[derive(Clone, Copy, Debug)]
+pub struct Inline {
+    /// The maximum number of instructions of an inlined function.
+    pub threshold: usize,
+}
+
+impl Default for Inline {
+    fn default() -> Self {
+        Self { threshold: 32 }
+    }
+}
+
+impl Optimize<TranslationUnit> for Inline {
+    fn optimize(&mut self, code: &mut TranslationUnit) -> bool {
+        let call_graph = code
+            .decls
+            .iter()
+            .filter_map(|(name, decl)| {
+                let (_, Some(definition)) = decl.get_function()? else {
+                    return None;
+                };
+                let callees = definition
+                    .blocks
+                    .values()
+                    .flat_map(|block| &block.instructions)
+                    .filter_map(|instruction| callee_name(instruction))
+                    .filter(|callee| code.decls.contains_key(*callee))
+                    .map(String::from)
+                    .collect::<BTreeSet<_>>();
+                Some((name.clone(), callees))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let recursive = recursive_functions(&call_graph);
+
+        let mut result = false;
+        for name in post_order(&call_graph) {
+            let Some((_, Some(mut definition))) = code
+                .decls
+                .get_mut(&name)
+                .and_then(Declaration::get_function_mut)
+                .map(|(signature, definition)| (signature, definition.take()))
+            else {
+                continue;
+            };
+
+            while let Some((bid, iid, callee)) = self.find_call_site(&definition, code, &recursive)
+            {
+                inline_call(&mut definition, bid, iid, callee);
+                result = true;
+            }
+
+            let (_, slot) = code
+                .decls
+                .get_mut(&name)
+                .and_then(Declaration::get_function_mut)
+                .expect("`name` must be a function");
+            *slot = Some(definition);
+        }
+        result
+    }
+}
+
+impl Inline {
+    /// Finds the first call in `caller` to be inlined, returning its block, its index and the
+    /// definition of its callee.
+    fn find_call_site<'a>(
+        &self,
+        caller: &FunctionDefinition,
+        code: &'a TranslationUnit,
+        recursive: &HashSet<String>,
+    ) -> Option<(BlockId, usize, &'a FunctionDefinition)> {
+        for (bid, block) in &caller.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                let Some(callee) = callee_name(instruction) else {
+                    continue;
+                };
+                if recursive.contains(callee) {
+                    continue;
+                }
+                let Some((signature, Some(definition))) =
+                    code.decls.get(callee).and_then(Declaration::get_function)
+                else {
+                    continue;
+                };
+                let Instruction::Call {
+                    args, return_type, ..
+                } = &**instruction
+                else {
+                    unreachable!()
+                };
+
+                // The arguments of an unprototyped call may not match the parameters.
+                let is_compatible = !signature.is_variadic
+                    && return_type.clone().set_const(false)
+                        == signature.ret.clone().set_const(false)
+                    && args.len() == signature.params.len()
+                    && args.iter().zip(&signature.params).all(|(arg, param)| {
+                        arg.dtype().set_const(false) == param.clone().set_const(false)
+                    });
+                let size = definition
+                    .blocks
+                    .values()
+                    .map(|block| block.instructions.len())
+                    .sum::<usize>();
+                if is_compatible && size <= self.threshold {
+                    return Some((*bid, iid, definition));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Returns the name of the callee if `instruction` is a direct call.
+fn callee_name(instruction: &Instruction) -> Option<&str> {
+    if let Instruction::Call {
+        callee: Operand::Constant(Constant::GlobalVariable { name, .. }),
+        ..
+    } = instruction
+    {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Returns the functions calling themselves directly or indirectly.
+fn recursive_functions(call_graph: &BTreeMap<String, BTreeSet<String>>) -> HashSet<String> {
+    call_graph
+        .keys()
+        .filter(|name| {
+            let mut visited = HashSet::new();
+            let mut stack = call_graph[*name].iter().collect::<Vec<_>>();
+            while let Some(callee) = stack.pop() {
+                if callee == *name {
+                    return true;
+                }
+                if visited.insert(callee) {
+                    stack.extend(call_graph.get(callee).into_iter().flatten());
+                }
+            }
+            false
+        })
+        .cloned()
+        .collect()
+}
+
+/// Returns the functions in postorder of the call graph, where each function follows its callees
+/// except along cycles.
+fn post_order(call_graph: &BTreeMap<String, BTreeSet<String>>) -> Vec<String> {
+    let mut postorder = Vec::new();
+    let mut visited = HashSet::new();
+    for root in call_graph.keys() {
+        if !visited.insert(root) {
+            continue;
+        }
+        let mut stack = vec![(root, call_graph[root].iter())];
+        while let Some((name, callees)) = stack.last_mut() {
+            if let Some(callee) = callees.next() {
+                if let Some(callees) = call_graph.get(callee) {
+                    if visited.insert(callee) {
+                        stack.push((callee, callees.iter()));
+                    }
+                }
+            } else {
+                postorder.push((*name).clone());
+                let _unused = stack.pop();
+            }
+        }
+    }
+    postorder
+}
+
+/// Inlines the call `iid` of the block `bid` to `callee`.
+///
+/// The block is split at the call, moving the instructions after it to a continuation block, which
+/// receives the return value as its phinode. The blocks of `callee` are copied with fresh block
+/// ids, its allocations are appended to those of `caller`, and its returns jump to the
+/// continuation block.
+fn inline_call(
+    caller: &mut FunctionDefinition,
+    bid: BlockId,
+    iid: usize,
+    callee: &FunctionDefinition,
+) {
+    let mut next_bid = caller.blocks.keys().next_back().map_or(0, |bid| bid.0 + 1);
+    let mut fresh_bid = || {
+        let bid = BlockId(next_bid);
+        next_bid += 1;
+        bid
+    };
+    let bid_continue = fresh_bid();
+    let bids = callee
+        .blocks
+        .keys()
+        .map(|callee_bid| (*callee_bid, fresh_bid()))
+        .collect::<BTreeMap<_, _>>();
+
+    // Splits the block at the call.
+    let block = caller.blocks.get_mut(&bid).expect("`bid` must be a block");
+    let instructions = block.instructions.split_off(iid + 1);
+    let call = block
+        .instructions
+        .pop()
+        .expect("`iid` must be an instruction");
+    let Instruction::Call {
+        args, return_type, ..
+    } = call.into_inner()
+    else {
+        panic!("`iid` must be a call");
+    };
+    let exit = mem::replace(
+        &mut block.exit,
+        BlockExit::Jump {
+            arg: JumpArg::new(bids[&callee.bid_init], args),
+        },
+    );
+    let is_unit = return_type.clone().set_const(false) == Dtype::unit();
+    let return_value = if is_unit {
+        Operand::constant(Constant::unit())
+    } else {
+        Operand::register(RegisterId::arg(bid_continue, 0), return_type.clone())
+    };
+    let phinodes = if is_unit {
+        Vec::new()
+    } else {
+        vec![Named::new(None, return_type)]
+    };
+
+    // The uses of the instructions after the call are renamed to those in the continuation block.
+    let mut rename = |operand: &mut Operand| {
+        let Operand::Register {
+            rid:
+                RegisterId::Temp {
+                    bid: use_bid,
+                    iid: use_iid,
+                },
+            ..
+        } = operand
+        else {
+            return;
+        };
+        if *use_bid != bid || *use_iid < iid {
+            return;
+        }
+        if *use_iid == iid {
+            *operand = return_value.clone();
+        } else {
+            *use_bid = bid_continue;
+            *use_iid -= iid + 1;
+        }
+    };
+    let _unused = caller.blocks.insert(
+        bid_continue,
+        Block {
+            phinodes,
+            instructions,
+            exit,
+        },
+    );
+    for block in caller.blocks.values_mut() {
+        for instruction in &mut block.instructions {
+            instruction.walk_operands(&mut rename);
+        }
+        block.exit.walk_operands(&mut rename);
+    }
+
+    // Copies the blocks of the callee.
+    let num_allocations = caller.allocations.len();
+    let rename = |operand: &mut Operand| {
+        let Some((rid, _)) = operand.get_register_mut() else {
+            return;
+        };
+        *rid = match *rid {
+            RegisterId::Local { aid } => RegisterId::local(num_allocations + aid),
+            RegisterId::Arg { bid, aid } => RegisterId::arg(bids[&bid], aid),
+            RegisterId::Temp { bid, iid } => RegisterId::temp(bids[&bid], iid),
+        };
+    };
+    for (callee_bid, block) in &callee.blocks {
+        let mut block = block.clone();
+        for instruction in &mut block.instructions {
+            instruction.walk_operands(rename);
+        }
+        block.exit.walk_operands(rename);
+        block.exit.walk_jump_args(|arg| arg.bid = bids[&arg.bid]);
+        if let BlockExit::Return { value } = &block.exit {
+            let args = if is_unit {
+                Vec::new()
+            } else {
+                vec![value.clone()]
+            };
+            block.exit = BlockExit::Jump {
+                arg: JumpArg::new(bid_continue, args),
+            };
+        }
+        let _unused = caller.blocks.insert(bids[callee_bid], block);
+    }
+
+    // Appends the allocations of the callee, keeping the alignments empty if none is given.
+    if !callee.allocation_alignments.is_empty() {
+        caller.allocation_alignments.resize(num_allocations, None);
+        caller
+            .allocation_alignments
+            .extend(&callee.allocation_alignments);
+        caller
+            .allocation_alignments
+            .resize(num_allocations + callee.allocations.len(), None);
+    } else if !caller.allocation_alignments.is_empty() {
+        caller
+            .allocation_alignments
+            .resize(num_allocations + callee.allocations.len(), None);
+    }
+    caller
+        .allocations
+        .extend(callee.allocations.iter().cloned());
+}
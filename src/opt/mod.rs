@@ -1,24 +1,53 @@
 mod deadcode;
 mod gvn;
+mod inline;
 mod mem2reg;
 pub(crate) mod opt_utils;
+mod pass_manager;
+mod sccp;
 mod simplify_cfg;
 
 pub use deadcode::Deadcode;
 pub use gvn::Gvn;
+pub use inline::Inline;
 pub use mem2reg::Mem2reg;
+pub use opt_utils::Analysis;
+pub use pass_manager::{PassManager, PassStatistics, PassVerifyError, PipelineError, pass_names};
+pub use sccp::Sccp;
 pub use simplify_cfg::{
     SimplifyCfg, SimplifyCfgConstProp, SimplifyCfgEmpty, SimplifyCfgMerge, SimplifyCfgReach,
 };
 
+use std::collections::HashMap;
+
 use crate::ir;
 
 pub trait Optimize<T> {
     fn optimize(&mut self, code: &mut T) -> bool;
+
+    /// Discards the results cached from the code of the previous runs, e.g., the [`Analysis`] of
+    /// its functions, since another optimization has changed it.
+    fn invalidate(&mut self) {}
+}
+
+/// An optimization of a function definition using the cached [`Analysis`] of the function, which
+/// [`FunctionPass`] keeps across its runs.
+///
+/// The optimizations of a function definition implementing [`Optimize`] ignore the analysis.
+pub trait OptimizeFunction {
+    /// Optimizes `code` with `analysis`, returning whether `code` is changed. The analysis is
+    /// invalidated if and only if `true` is returned, so a change must not be missed.
+    fn optimize_function(&mut self, code: &mut ir::FunctionDefinition, analysis: &Analysis)
+    -> bool;
 }
 
 pub type O0 = Null;
 pub type O1 = Repeat<(SimplifyCfg, (Mem2reg, (Gvn, Deadcode)))>;
+/// [`O1`] verifying IR after each pass.
+pub type O1Verified = Repeat<(
+    Verify<SimplifyCfg>,
+    (Verify<Mem2reg>, (Verify<Gvn>, Verify<Deadcode>)),
+)>;
 
 #[derive(Default, Clone, Copy, Debug)]
 pub struct Null;
@@ -28,9 +57,22 @@ pub struct Repeat<O> {
     inner: O,
 }
 
+/// Verifies IR after the optimization `O` with [`ir::verify`], panicking with the name of `O` if it
+/// is ill-formed.
+#[derive(Default, Debug)]
+pub struct Verify<O> {
+    inner: O,
+}
+
+/// Runs the optimization `T` on each function definition, caching the [`Analysis`] of each
+/// function until `T` or another optimization changes it.
+///
+/// The cache is keyed by the names of the functions, so a `FunctionPass` is meant to optimize one
+/// translation unit. It should be [invalidated](Optimize::invalidate) before optimizing another one.
 #[derive(Default, Debug)]
-pub struct FunctionPass<T: Optimize<ir::FunctionDefinition>> {
+pub struct FunctionPass<T: OptimizeFunction> {
     inner: T,
+    analyses: HashMap<String, Analysis>,
 }
 
 impl Optimize<ir::TranslationUnit> for Null {
@@ -41,7 +83,20 @@ impl Optimize<ir::TranslationUnit> for Null {
 
 impl<T, O1: Optimize<T>, O2: Optimize<T>> Optimize<T> for (O1, O2) {
     fn optimize(&mut self, code: &mut T) -> bool {
-        self.0.optimize(code) | self.1.optimize(code)
+        let result0 = self.0.optimize(code);
+        if result0 {
+            self.1.invalidate();
+        }
+        let result1 = self.1.optimize(code);
+        if result1 {
+            self.0.invalidate();
+        }
+        result0 | result1
+    }
+
+    fn invalidate(&mut self) {
+        self.0.invalidate();
+        self.1.invalidate();
     }
 }
 
@@ -54,27 +109,219 @@ impl<T, O: Optimize<T>> Optimize<T> for Repeat<O> {
         while self.inner.optimize(code) {}
         true
     }
+
+    fn invalidate(&mut self) {
+        self.inner.invalidate();
+    }
+}
+
+impl<O: Optimize<ir::TranslationUnit>> Optimize<ir::TranslationUnit> for Verify<O> {
+    fn optimize(&mut self, code: &mut ir::TranslationUnit) -> bool {
+        let result = self.inner.optimize(code);
+        if let Err(e) = ir::verify(code) {
+            panic!(
+                "{} produced ill-formed IR: {e}",
+                core::any::type_name::<O>()
+            );
+        }
+        result
+    }
+
+    fn invalidate(&mut self) {
+        self.inner.invalidate();
+    }
+}
+
+impl<T: Optimize<ir::FunctionDefinition>> OptimizeFunction for T {
+    fn optimize_function(
+        &mut self,
+        code: &mut ir::FunctionDefinition,
+        _analysis: &Analysis,
+    ) -> bool {
+        self.optimize(code)
+    }
 }
 
 impl<T> Optimize<ir::TranslationUnit> for FunctionPass<T>
 where
-    T: Optimize<ir::FunctionDefinition>,
+    T: OptimizeFunction,
 {
     fn optimize(&mut self, code: &mut ir::TranslationUnit) -> bool {
-        code.decls
-            .values_mut()
-            .fold(false, |b, decl| b | self.optimize(decl))
+        let mut result = false;
+        for (name, decl) in &mut code.decls {
+            let Some((_, Some(fdef))) = decl.get_function_mut() else {
+                continue;
+            };
+            let analysis = self.analyses.entry(name.clone()).or_default();
+            result |= analysis.optimize(fdef, |code, analysis| {
+                self.inner.optimize_function(code, analysis)
+            });
+        }
+        result
+    }
+
+    fn invalidate(&mut self) {
+        self.analyses.clear();
     }
 }
 
 impl<T> Optimize<ir::Declaration> for FunctionPass<T>
 where
-    T: Optimize<ir::FunctionDefinition>,
+    T: OptimizeFunction,
 {
+    /// Optimizes the function definition `code` without caching its analysis, which is not known
+    /// by name.
     fn optimize(&mut self, code: &mut ir::Declaration) -> bool {
         let Some((_, Some(fdef))) = code.get_function_mut() else {
             return false;
         };
-        self.inner.optimize(fdef)
+        self.inner.optimize_function(fdef, &Analysis::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::ir::{Block, BlockExit, BlockId, Constant, JumpArg, Operand};
+
+    /// Records the successors of the initial block in the cached CFG, and then makes it jump to
+    /// block 1 if it jumps to block 2 and vice versa, reporting the change if `reports_change`.
+    #[derive(Default)]
+    struct Flip {
+        reports_change: bool,
+        successors: Vec<Vec<BlockId>>,
+    }
+
+    impl OptimizeFunction for Flip {
+        fn optimize_function(
+            &mut self,
+            code: &mut ir::FunctionDefinition,
+            analysis: &Analysis,
+        ) -> bool {
+            let successors = analysis.cfg(code).successors(code.bid_init).to_vec();
+            let target = if successors == [BlockId(1)] { 2 } else { 1 };
+            self.successors.push(successors);
+            code.blocks.get_mut(&code.bid_init).unwrap().exit = BlockExit::Jump {
+                arg: JumpArg::new(BlockId(target), Vec::new()),
+            };
+            self.reports_change
+        }
+    }
+
+    /// Records the successors of the initial block in the cached CFG without changing anything.
+    #[derive(Default)]
+    struct Record {
+        successors: Vec<Vec<BlockId>>,
+    }
+
+    impl OptimizeFunction for Record {
+        fn optimize_function(
+            &mut self,
+            code: &mut ir::FunctionDefinition,
+            analysis: &Analysis,
+        ) -> bool {
+            self.successors
+                .push(analysis.cfg(code).successors(code.bid_init).to_vec());
+            false
+        }
+    }
+
+    /// Creates a translation unit with the function `f`, whose initial block jumps to block 1, and
+    /// blocks 1 and 2 return.
+    pub(super) fn translation_unit() -> ir::TranslationUnit {
+        let block = |exit| Block {
+            phinodes: Vec::new(),
+            instructions: Vec::new(),
+            exit,
+        };
+        let ret = || BlockExit::Return {
+            value: Operand::constant(Constant::unit()),
+        };
+        let blocks = [
+            (
+                BlockId(0),
+                block(BlockExit::Jump {
+                    arg: JumpArg::new(BlockId(1), Vec::new()),
+                }),
+            ),
+            (BlockId(1), block(ret())),
+            (BlockId(2), block(ret())),
+        ]
+        .into_iter()
+        .collect();
+        let definition = ir::FunctionDefinition {
+            allocations: Vec::new(),
+            allocation_alignments: Vec::new(),
+            blocks,
+            bid_init: BlockId(0),
+        };
+        let decl = ir::Declaration::Function {
+            signature: ir::FunctionSignature {
+                ret: ir::Dtype::unit(),
+                params: Vec::new(),
+                is_variadic: false,
+            },
+            definition: Some(definition),
+            linkage: ir::Linkage::External,
+        };
+        ir::TranslationUnit {
+            decls: BTreeMap::from([("f".to_string(), decl)]),
+            structs: HashMap::new(),
+        }
+    }
+
+    fn bids(bids: &[usize]) -> Vec<Vec<BlockId>> {
+        bids.iter().map(|bid| vec![BlockId(*bid)]).collect()
+    }
+
+    #[test]
+    fn function_pass_invalidates_analysis_on_change() {
+        let mut code = translation_unit();
+        let mut pass = FunctionPass {
+            inner: Flip {
+                reports_change: true,
+                ..Flip::default()
+            },
+            analyses: HashMap::new(),
+        };
+        for _ in 0..3 {
+            assert!(pass.optimize(&mut code));
+        }
+        assert_eq!(pass.inner.successors, bids(&[1, 2, 1]));
+    }
+
+    #[test]
+    fn function_pass_keeps_analysis_without_change() {
+        let mut code = translation_unit();
+        let mut pass = FunctionPass::<Flip>::default();
+        for _ in 0..2 {
+            assert!(!pass.optimize(&mut code));
+        }
+        // The misreported change leaves the CFG in the cache stale until it is invalidated.
+        Optimize::<ir::TranslationUnit>::invalidate(&mut pass);
+        assert!(!pass.optimize(&mut code));
+        assert_eq!(pass.inner.successors, bids(&[1, 1, 2]));
+    }
+
+    #[test]
+    fn sequence_invalidates_other_analyses_on_change() {
+        let mut code = translation_unit();
+        let mut passes = (
+            FunctionPass {
+                inner: Flip {
+                    reports_change: true,
+                    ..Flip::default()
+                },
+                analyses: HashMap::new(),
+            },
+            FunctionPass::<Record>::default(),
+        );
+        for _ in 0..3 {
+            assert!(passes.optimize(&mut code));
+        }
+        assert_eq!(passes.0.inner.successors, bids(&[1, 2, 1]));
+        assert_eq!(passes.1.inner.successors, bids(&[2, 1, 2]));
     }
 }
@@ -2,4 +2,631 @@
 //!
 //! You can freely add utilities commonly used in the implementation of multiple optimizations here.
 
-#![allow(dead_code)]
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::ir::*;
+
+/// The analyses of a function definition, each computed when it is first requested and cached
+/// until the function is changed.
+///
+/// The cached results do not follow the changes of the function, so they must be invalidated by
+/// [`Analysis::invalidate`] whenever it is changed, e.g., by running each step of a pass with
+/// [`Analysis::optimize`]. [`FunctionPass`](super::FunctionPass) does so for the passes
+/// implementing [`OptimizeFunction`](super::OptimizeFunction).
+#[derive(Debug, Default)]
+pub struct Analysis {
+    cfg: OnceCell<Cfg>,
+    dom_tree: OnceCell<DomTree>,
+    loops: OnceCell<Loops>,
+    liveness: OnceCell<Liveness>,
+}
+
+impl Analysis {
+    pub(crate) fn cfg(&self, code: &FunctionDefinition) -> &Cfg {
+        self.cfg.get_or_init(|| Cfg::new(code))
+    }
+
+    pub(crate) fn dom_tree(&self, code: &FunctionDefinition) -> &DomTree {
+        self.dom_tree
+            .get_or_init(|| DomTree::new(code.bid_init, self.cfg(code)))
+    }
+
+    pub(crate) fn loops(&self, code: &FunctionDefinition) -> &Loops {
+        self.loops
+            .get_or_init(|| Loops::new(self.cfg(code), self.dom_tree(code)))
+    }
+
+    pub(crate) fn liveness(&self, code: &FunctionDefinition) -> &Liveness {
+        self.liveness
+            .get_or_init(|| Liveness::new(code, self.cfg(code)))
+    }
+
+    /// Discards the cached results.
+    pub fn invalidate(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Runs `optimize` on `code` with the analyses, and invalidates them if it reports a change of
+    /// `code` by returning `true`.
+    pub(crate) fn optimize<F>(&mut self, code: &mut FunctionDefinition, optimize: F) -> bool
+    where
+        F: FnOnce(&mut FunctionDefinition, &Self) -> bool,
+    {
+        let result = optimize(code, self);
+        if result {
+            self.invalidate();
+        }
+        result
+    }
+}
+
+/// The control flow graph of a function, i.e., the successors and the predecessors of each block
+/// by the jumps of its exit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Cfg {
+    successors: BTreeMap<BlockId, Vec<BlockId>>,
+    predecessors: BTreeMap<BlockId, Vec<BlockId>>,
+}
+
+impl Cfg {
+    pub(crate) fn new(code: &FunctionDefinition) -> Self {
+        let mut successors = BTreeMap::new();
+        let mut predecessors = code
+            .blocks
+            .keys()
+            .map(|bid| (*bid, Vec::new()))
+            .collect::<BTreeMap<_, Vec<_>>>();
+        for (bid, block) in &code.blocks {
+            let mut succs = Vec::new();
+            for arg in block.exit.jump_args() {
+                if !succs.contains(&arg.bid) {
+                    succs.push(arg.bid);
+                    predecessors.entry(arg.bid).or_default().push(*bid);
+                }
+            }
+            let _unused = successors.insert(*bid, succs);
+        }
+        Self {
+            successors,
+            predecessors,
+        }
+    }
+
+    /// Returns the blocks jumped to from `bid`, without duplicates.
+    pub(crate) fn successors(&self, bid: BlockId) -> &[BlockId] {
+        self.successors.get(&bid).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the blocks jumping to `bid`, without duplicates.
+    pub(crate) fn predecessors(&self, bid: BlockId) -> &[BlockId] {
+        self.predecessors.get(&bid).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the blocks reachable from `bid_init` in reverse postorder, where each block precedes
+    /// its successors except along back edges.
+    pub(crate) fn reverse_post_order(&self, bid_init: BlockId) -> Vec<BlockId> {
+        // An explicit stack not to overflow on long chains of blocks.
+        let mut postorder = Vec::new();
+        let mut visited = HashSet::from([bid_init]);
+        let mut stack = vec![(bid_init, 0)];
+        while let Some((bid, index)) = stack.last_mut() {
+            if let Some(succ) = self.successors(*bid).get(*index).copied() {
+                *index += 1;
+                if visited.insert(succ) {
+                    stack.push((succ, 0));
+                }
+            } else {
+                postorder.push(*bid);
+                let _unused = stack.pop();
+            }
+        }
+        postorder.reverse();
+        postorder
+    }
+}
+
+/// The dominator tree of the blocks reachable from the initial block, and their dominance
+/// frontiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DomTree {
+    bid_init: BlockId,
+    /// The reachable blocks in reverse postorder.
+    reverse_post_order: Vec<BlockId>,
+    /// The immediate dominator of each reachable block except the initial block.
+    idoms: HashMap<BlockId, BlockId>,
+    children: HashMap<BlockId, Vec<BlockId>>,
+    frontiers: HashMap<BlockId, Vec<BlockId>>,
+}
+
+impl DomTree {
+    /// Computes the dominator tree with the algorithm of Cooper, Harvey and Kennedy, i.e., iterating
+    /// over the blocks in reverse postorder until the immediate dominators do not change.
+    pub(crate) fn new(bid_init: BlockId, cfg: &Cfg) -> Self {
+        let reverse_post_order = cfg.reverse_post_order(bid_init);
+        let order = reverse_post_order
+            .iter()
+            .enumerate()
+            .map(|(index, bid)| (*bid, index))
+            .collect::<HashMap<_, _>>();
+
+        // The immediate dominator of the initial block is itself while computing.
+        let mut idoms = HashMap::from([(bid_init, bid_init)]);
+        let intersect = |idoms: &HashMap<BlockId, BlockId>, mut lhs: BlockId, mut rhs: BlockId| {
+            while lhs != rhs {
+                while order[&lhs] > order[&rhs] {
+                    lhs = idoms[&lhs];
+                }
+                while order[&rhs] > order[&lhs] {
+                    rhs = idoms[&rhs];
+                }
+            }
+            lhs
+        };
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            for bid in reverse_post_order.iter().skip(1) {
+                let idom = cfg
+                    .predecessors(*bid)
+                    .iter()
+                    .filter(|pred| idoms.contains_key(pred))
+                    .copied()
+                    .reduce(|lhs, rhs| intersect(&idoms, lhs, rhs))
+                    .expect("a reachable block has a processed predecessor");
+                if idoms.insert(*bid, idom) != Some(idom) {
+                    is_changed = true;
+                }
+            }
+        }
+        let _unused = idoms.remove(&bid_init);
+
+        let mut children = HashMap::<_, Vec<_>>::new();
+        for bid in &reverse_post_order {
+            if let Some(idom) = idoms.get(bid) {
+                children.entry(*idom).or_default().push(*bid);
+            }
+        }
+
+        // A block is in the frontiers of the blocks from its predecessors up to, but not including,
+        // its immediate dominator. It is a join block unless it is the initial block.
+        let mut frontiers = HashMap::<_, Vec<_>>::new();
+        for bid in &reverse_post_order {
+            let preds = cfg.predecessors(*bid);
+            for pred in preds.iter().filter(|pred| order.contains_key(pred)) {
+                let mut runner = *pred;
+                while Some(&runner) != idoms.get(bid) {
+                    let frontier = frontiers.entry(runner).or_default();
+                    if !frontier.contains(bid) {
+                        frontier.push(*bid);
+                    }
+                    let Some(idom) = idoms.get(&runner) else {
+                        break;
+                    };
+                    runner = *idom;
+                }
+            }
+        }
+
+        Self {
+            bid_init,
+            reverse_post_order,
+            idoms,
+            children,
+            frontiers,
+        }
+    }
+
+    /// Returns the reachable blocks in reverse postorder.
+    pub(crate) fn reverse_post_order(&self) -> &[BlockId] {
+        &self.reverse_post_order
+    }
+
+    pub(crate) fn is_reachable(&self, bid: BlockId) -> bool {
+        bid == self.bid_init || self.idoms.contains_key(&bid)
+    }
+
+    /// Returns the immediate dominator of `bid`, or `None` if it is the initial block or
+    /// unreachable.
+    pub(crate) fn idom(&self, bid: BlockId) -> Option<BlockId> {
+        self.idoms.get(&bid).copied()
+    }
+
+    /// Returns the blocks immediately dominated by `bid`.
+    pub(crate) fn children(&self, bid: BlockId) -> &[BlockId] {
+        self.children.get(&bid).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns whether `lhs` dominates `rhs`, which includes the case that they are the same. An
+    /// unreachable block neither dominates nor is dominated by any block except itself.
+    pub(crate) fn dominates(&self, lhs: BlockId, mut rhs: BlockId) -> bool {
+        loop {
+            if lhs == rhs {
+                return true;
+            }
+            let Some(idom) = self.idom(rhs) else {
+                return false;
+            };
+            rhs = idom;
+        }
+    }
+
+    /// Returns the dominance frontier of `bid`, i.e., the blocks not strictly dominated by `bid`
+    /// with a predecessor dominated by `bid`.
+    pub(crate) fn frontier(&self, bid: BlockId) -> &[BlockId] {
+        self.frontiers.get(&bid).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// A natural loop, i.e., the blocks from which the back edges to `header` are reachable without
+/// passing `header`, where `header` dominates all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Loop {
+    pub(crate) header: BlockId,
+    pub(crate) blocks: BTreeSet<BlockId>,
+    /// The index of the innermost loop containing this loop.
+    pub(crate) parent: Option<usize>,
+    /// The number of loops containing this loop including itself, i.e., 1 for an outermost loop.
+    pub(crate) depth: usize,
+}
+
+/// The natural loops of a function, where the loops with the same header are merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Loops {
+    /// The loops, where each loop precedes the loops nested in it.
+    loops: Vec<Loop>,
+    /// The index of the innermost loop containing each block.
+    innermost: HashMap<BlockId, usize>,
+}
+
+impl Loops {
+    pub(crate) fn new(cfg: &Cfg, dom_tree: &DomTree) -> Self {
+        let mut bodies = BTreeMap::<BlockId, BTreeSet<BlockId>>::new();
+        for bid in dom_tree.reverse_post_order() {
+            for succ in cfg.successors(*bid) {
+                if !dom_tree.dominates(*succ, *bid) {
+                    continue;
+                }
+                // `bid -> succ` is a back edge.
+                let body = bodies
+                    .entry(*succ)
+                    .or_insert_with(|| BTreeSet::from([*succ]));
+                let mut worklist = vec![*bid];
+                while let Some(bid) = worklist.pop() {
+                    if body.insert(bid) {
+                        worklist.extend(
+                            cfg.predecessors(bid)
+                                .iter()
+                                .filter(|pred| dom_tree.is_reachable(**pred)),
+                        );
+                    }
+                }
+            }
+        }
+
+        // A loop contains the loops nested in it, which are smaller.
+        let mut loops = bodies
+            .into_iter()
+            .map(|(header, blocks)| Loop {
+                header,
+                blocks,
+                parent: None,
+                depth: 1,
+            })
+            .collect::<Vec<_>>();
+        loops.sort_by_key(|lp| core::cmp::Reverse(lp.blocks.len()));
+        for index in 0..loops.len() {
+            let parent = (0..index)
+                .rev()
+                .find(|parent| loops[*parent].blocks.contains(&loops[index].header));
+            loops[index].parent = parent;
+            loops[index].depth = parent.map_or(1, |parent| loops[parent].depth + 1);
+        }
+
+        let mut innermost = HashMap::new();
+        for (index, lp) in loops.iter().enumerate() {
+            for bid in &lp.blocks {
+                let _unused = innermost.insert(*bid, index);
+            }
+        }
+
+        Self { loops, innermost }
+    }
+
+    pub(crate) fn loops(&self) -> &[Loop] {
+        &self.loops
+    }
+
+    /// Returns the innermost loop containing `bid`.
+    pub(crate) fn innermost(&self, bid: BlockId) -> Option<&Loop> {
+        self.innermost.get(&bid).map(|index| &self.loops[*index])
+    }
+
+    /// Returns the number of loops containing `bid`, i.e., 0 if it is in no loop.
+    pub(crate) fn depth(&self, bid: BlockId) -> usize {
+        self.innermost(bid).map_or(0, |lp| lp.depth)
+    }
+}
+
+/// The registers live at the entry and the exit of each block, i.e., those which may be used after
+/// there. The registers of the allocations are not included, as they are constant.
+///
+/// The phinodes of a block are defined at its entry, so they are not live at the entry, and the
+/// arguments of a jump are used at the exit of the block jumping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Liveness {
+    live_in: HashMap<BlockId, HashSet<RegisterId>>,
+    live_out: HashMap<BlockId, HashSet<RegisterId>>,
+}
+
+impl Liveness {
+    pub(crate) fn new(code: &FunctionDefinition, cfg: &Cfg) -> Self {
+        // The registers used in each block but defined in other blocks.
+        let uses = code
+            .blocks
+            .iter()
+            .map(|(bid, block)| {
+                let uses = block
+                    .instructions
+                    .iter()
+                    .flat_map(|instruction| instruction.operands())
+                    .chain(block.exit.operands())
+                    .filter_map(|operand| operand.get_register().map(|(rid, _)| *rid))
+                    .filter(|rid| is_defined_outside(rid, *bid))
+                    .collect::<HashSet<_>>();
+                (*bid, uses)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut live_in = uses.clone();
+        let mut live_out = code
+            .blocks
+            .keys()
+            .map(|bid| (*bid, HashSet::new()))
+            .collect::<HashMap<_, _>>();
+
+        // The blocks are visited in postorder for faster convergence.
+        let mut postorder = cfg.reverse_post_order(code.bid_init);
+        postorder.reverse();
+        let unreachable = code
+            .blocks
+            .keys()
+            .filter(|bid| !postorder.contains(bid))
+            .copied()
+            .collect::<Vec<_>>();
+        postorder.extend(unreachable);
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            for bid in &postorder {
+                let out = cfg
+                    .successors(*bid)
+                    .iter()
+                    .flat_map(|succ| live_in.get(succ).into_iter().flatten())
+                    .copied()
+                    .collect::<HashSet<_>>();
+                let ins = out
+                    .iter()
+                    .filter(|rid| is_defined_outside(rid, *bid))
+                    .copied()
+                    .chain(uses[bid].iter().copied())
+                    .collect::<HashSet<_>>();
+                if live_in[bid] != ins {
+                    let _unused = live_in.insert(*bid, ins);
+                    is_changed = true;
+                }
+                let _unused = live_out.insert(*bid, out);
+            }
+        }
+
+        Self { live_in, live_out }
+    }
+
+    /// Returns the registers live at the entry of `bid`.
+    pub(crate) fn live_in(&self, bid: BlockId) -> &HashSet<RegisterId> {
+        &self.live_in[&bid]
+    }
+
+    /// Returns the registers live at the exit of `bid`.
+    pub(crate) fn live_out(&self, bid: BlockId) -> &HashSet<RegisterId> {
+        &self.live_out[&bid]
+    }
+}
+
+/// Returns whether `rid` is a register defined in a block other than `bid`, which is live at the
+/// entry of `bid` if used in it.
+pub(crate) fn is_defined_outside(rid: &RegisterId, bid: BlockId) -> bool {
+    match rid {
+        RegisterId::Local { .. } => false,
+        RegisterId::Arg { bid: def, .. } | RegisterId::Temp { bid: def, .. } => *def != bid,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lang_c::ast;
+
+    use super::*;
+
+    /// Creates a function whose block `bid` jumps to `succs` for each `(bid, succs)` in `edges`.
+    /// A block returns if it has no successor, and branches on a constant otherwise.
+    fn function(edges: &[(usize, &[usize])]) -> FunctionDefinition {
+        let blocks = edges
+            .iter()
+            .map(|(bid, succs)| {
+                let jump = |bid: &usize| JumpArg::new(BlockId(*bid), Vec::new());
+                let exit = match succs {
+                    [] => BlockExit::Return {
+                        value: Operand::constant(Constant::unit()),
+                    },
+                    [succ] => BlockExit::Jump { arg: jump(succ) },
+                    [then, els] => BlockExit::ConditionalJump {
+                        condition: Operand::constant(Constant::int(1, Dtype::BOOL)),
+                        arg_then: jump(then),
+                        arg_else: jump(els),
+                    },
+                    _ => panic!("a block has at most two successors"),
+                };
+                let block = Block {
+                    phinodes: Vec::new(),
+                    instructions: Vec::new(),
+                    exit,
+                };
+                (BlockId(*bid), block)
+            })
+            .collect();
+        FunctionDefinition {
+            allocations: Vec::new(),
+            allocation_alignments: Vec::new(),
+            blocks,
+            bid_init: BlockId(0),
+        }
+    }
+
+    fn bids(bids: &[usize]) -> BTreeSet<BlockId> {
+        bids.iter().map(|bid| BlockId(*bid)).collect()
+    }
+
+    fn frontier(dom_tree: &DomTree, bid: usize) -> BTreeSet<BlockId> {
+        dom_tree.frontier(BlockId(bid)).iter().copied().collect()
+    }
+
+    #[test]
+    fn diamond() {
+        let code = function(&[(0, &[1, 2]), (1, &[3]), (2, &[3]), (3, &[])]);
+        let cfg = Cfg::new(&code);
+        let dom_tree = DomTree::new(code.bid_init, &cfg);
+
+        assert_eq!(cfg.predecessors(BlockId(3)), [BlockId(1), BlockId(2)]);
+        let order = dom_tree.reverse_post_order();
+        assert_eq!((order[0], order[3]), (BlockId(0), BlockId(3)));
+        for bid in 1..=3 {
+            assert_eq!(dom_tree.idom(BlockId(bid)), Some(BlockId(0)));
+        }
+        assert_eq!(dom_tree.idom(BlockId(0)), None);
+        assert!(dom_tree.dominates(BlockId(0), BlockId(3)));
+        assert!(!dom_tree.dominates(BlockId(1), BlockId(3)));
+        assert_eq!(frontier(&dom_tree, 0), bids(&[]));
+        assert_eq!(frontier(&dom_tree, 1), bids(&[3]));
+        assert_eq!(frontier(&dom_tree, 2), bids(&[3]));
+        assert!(Loops::new(&cfg, &dom_tree).loops().is_empty());
+    }
+
+    #[test]
+    fn nested_loops() {
+        // The outer loop is headed by 1, and the inner loop by 2.
+        let code = function(&[
+            (0, &[1]),
+            (1, &[2, 5]),
+            (2, &[3, 4]),
+            (3, &[2]),
+            (4, &[1]),
+            (5, &[]),
+        ]);
+        let cfg = Cfg::new(&code);
+        let dom_tree = DomTree::new(code.bid_init, &cfg);
+        let loops = Loops::new(&cfg, &dom_tree);
+
+        assert_eq!(dom_tree.idom(BlockId(4)), Some(BlockId(2)));
+        assert_eq!(dom_tree.idom(BlockId(5)), Some(BlockId(1)));
+        assert_eq!(frontier(&dom_tree, 3), bids(&[2]));
+        assert_eq!(frontier(&dom_tree, 4), bids(&[1]));
+        assert_eq!(frontier(&dom_tree, 2), bids(&[1, 2]));
+
+        let [outer, inner] = loops.loops() else {
+            panic!("two loops are expected: {loops:?}");
+        };
+        assert_eq!(
+            (outer.header, &outer.blocks),
+            (BlockId(1), &bids(&[1, 2, 3, 4]))
+        );
+        assert_eq!((outer.parent, outer.depth), (None, 1));
+        assert_eq!((inner.header, &inner.blocks), (BlockId(2), &bids(&[2, 3])));
+        assert_eq!((inner.parent, inner.depth), (Some(0), 2));
+        assert_eq!(loops.innermost(BlockId(3)), Some(inner));
+        assert_eq!(loops.innermost(BlockId(4)), Some(outer));
+        assert_eq!(loops.depth(BlockId(3)), 2);
+        assert_eq!(loops.depth(BlockId(5)), 0);
+    }
+
+    #[test]
+    fn irreducible() {
+        // 1 and 2 jump to each other, but neither dominates the other. 4 is unreachable.
+        let code = function(&[
+            (0, &[1, 2]),
+            (1, &[2, 3]),
+            (2, &[1, 3]),
+            (3, &[]),
+            (4, &[3]),
+        ]);
+        let cfg = Cfg::new(&code);
+        let dom_tree = DomTree::new(code.bid_init, &cfg);
+
+        for bid in 1..=3 {
+            assert_eq!(dom_tree.idom(BlockId(bid)), Some(BlockId(0)));
+        }
+        assert_eq!(frontier(&dom_tree, 1), bids(&[2, 3]));
+        assert_eq!(frontier(&dom_tree, 2), bids(&[1, 3]));
+        assert!(Loops::new(&cfg, &dom_tree).loops().is_empty());
+
+        assert_eq!(dom_tree.reverse_post_order().len(), 4);
+        assert!(!dom_tree.is_reachable(BlockId(4)));
+        assert_eq!(dom_tree.idom(BlockId(4)), None);
+        assert!(!dom_tree.dominates(BlockId(0), BlockId(4)));
+    }
+
+    #[test]
+    fn liveness_across_back_edge() {
+        // block b0:
+        //   %b0:i0 = add 1 2
+        //   j b1()
+        // block b1:
+        //   %b1:i0 = add %b0:i0 1
+        //   br 1, b2(), b3()
+        // block b2:
+        //   %b2:i0 = add %b1:i0 %b0:i0
+        //   j b1()
+        // block b3:
+        //   ret %b0:i0
+        let mut code = function(&[(0, &[1]), (1, &[2, 3]), (2, &[1]), (3, &[])]);
+        let int = |value| Operand::constant(Constant::int(value, Dtype::INT));
+        let temp = |bid, iid| Operand::register(RegisterId::temp(BlockId(bid), iid), Dtype::INT);
+        let add = |lhs, rhs| {
+            Named::new(
+                None,
+                Instruction::BinOp {
+                    op: ast::BinaryOperator::Plus,
+                    lhs,
+                    rhs,
+                    dtype: Dtype::INT,
+                },
+            )
+        };
+        for (bid, instruction) in [
+            (0, add(int(1), int(2))),
+            (1, add(temp(0, 0), int(1))),
+            (2, add(temp(1, 0), temp(0, 0))),
+        ] {
+            let block = code.blocks.get_mut(&BlockId(bid)).unwrap();
+            block.instructions.push(instruction);
+        }
+        code.blocks.get_mut(&BlockId(3)).unwrap().exit = BlockExit::Return { value: temp(0, 0) };
+
+        let cfg = Cfg::new(&code);
+        let liveness = Liveness::new(&code, &cfg);
+        let registers = |rids: &[(usize, usize)]| {
+            rids.iter()
+                .map(|(bid, iid)| RegisterId::temp(BlockId(*bid), *iid))
+                .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(liveness.live_in(BlockId(0)), &registers(&[]));
+        assert_eq!(liveness.live_out(BlockId(0)), &registers(&[(0, 0)]));
+        assert_eq!(liveness.live_in(BlockId(1)), &registers(&[(0, 0)]));
+        assert_eq!(liveness.live_out(BlockId(1)), &registers(&[(0, 0), (1, 0)]));
+        assert_eq!(liveness.live_in(BlockId(2)), &registers(&[(0, 0), (1, 0)]));
+        // `%b0:i0` is live around the loop, as it is used after the loop.
+        assert_eq!(liveness.live_out(BlockId(2)), &registers(&[(0, 0)]));
+        assert_eq!(liveness.live_in(BlockId(3)), &registers(&[(0, 0)]));
+    }
+}
@@ -0,0 +1,401 @@
+//! Compose optimizations by name at runtime.
+
+use core::fmt;
+use core::str::FromStr;
+use std::collections::BTreeSet;
+use std::io;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::ir;
+use crate::opt::*;
+use crate::write;
+
+type Pass = Box<dyn Optimize<ir::TranslationUnit>>;
+type NewPass = fn() -> Pass;
+
+/// The optimizations available in pipelines, by name.
+const PASSES: &[(&str, NewPass)] = &[
+    ("simplify-cfg", boxed::<SimplifyCfg>),
+    (
+        "simplify-cfg-const-prop",
+        boxed::<FunctionPass<SimplifyCfgConstProp>>,
+    ),
+    (
+        "simplify-cfg-reach",
+        boxed::<FunctionPass<SimplifyCfgReach>>,
+    ),
+    (
+        "simplify-cfg-merge",
+        boxed::<FunctionPass<SimplifyCfgMerge>>,
+    ),
+    (
+        "simplify-cfg-empty",
+        boxed::<FunctionPass<SimplifyCfgEmpty>>,
+    ),
+    ("mem2reg", boxed::<Mem2reg>),
+    ("gvn", boxed::<Gvn>),
+    ("sccp", boxed::<Sccp>),
+    ("inline", boxed::<Inline>),
+    ("deadcode", boxed::<Deadcode>),
+];
+
+fn boxed<O: Optimize<ir::TranslationUnit> + Default + 'static>() -> Pass {
+    Box::<O>::default()
+}
+
+/// Returns the optimization named `name`, e.g., `simplify-cfg`.
+fn pass(name: &str) -> Option<Pass> {
+    PASSES
+        .iter()
+        .find(|(pass_name, _)| *pass_name == name)
+        .map(|(_, pass)| pass())
+}
+
+/// Returns the names of the optimizations available in pipelines.
+pub fn pass_names() -> impl Iterator<Item = &'static str> {
+    PASSES.iter().map(|(name, _)| *name)
+}
+
+/// An error in the description of a pipeline.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum PipelineError {
+    /// No optimization has the name.
+    #[error("unknown pass `{name}`")]
+    UnknownPass { name: String },
+    /// The description is malformed, where `column` starts from 1.
+    #[error("expected {expected} at column {column} of the pipeline")]
+    Syntax {
+        expected: &'static str,
+        column: usize,
+    },
+}
+
+/// An optimization in a pipeline producing ill-formed IR, found by [`PassManager::verify_each`].
+#[derive(Debug, PartialEq, Eq, Error)]
+#[error("{pass} produced ill-formed IR: {error}")]
+pub struct PassVerifyError {
+    pub pass: String,
+    #[source]
+    pub error: ir::VerifyError,
+}
+
+/// The statistics of the runs of an optimization in a pipeline.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PassStatistics {
+    pub name: String,
+    /// The number of runs.
+    pub runs: usize,
+    /// The number of runs changing IR.
+    pub changes: usize,
+    /// The total time of the runs.
+    pub time: Duration,
+}
+
+impl fmt::Display for PassStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<24} {:>6} runs {:>6} changes {:>12.3?}",
+            self.name, self.runs, self.changes, self.time
+        )
+    }
+}
+
+enum Step {
+    /// The optimization with its statistics at the index.
+    Pass { pass: Pass, index: usize },
+    /// Repeats the steps until they do not change IR.
+    Repeat(Vec<Step>),
+}
+
+impl Step {
+    fn invalidate(&mut self) {
+        match self {
+            Self::Pass { pass, .. } => pass.invalidate(),
+            Self::Repeat(steps) => steps.iter_mut().for_each(Self::invalidate),
+        }
+    }
+}
+
+/// A pipeline of optimizations parsed from its description, e.g.,
+/// `simplify-cfg,repeat(mem2reg,gvn,deadcode)`.
+///
+/// A description is a comma-separated list of the names of optimizations (see [`pass_names`]) and
+/// `repeat(...)`, which repeats the list inside until it does not change IR, as [`Repeat`] does.
+/// The pipeline records the [`PassStatistics`] of each optimization, summed over its occurrences.
+#[derive(Default)]
+pub struct PassManager {
+    steps: Vec<Step>,
+    statistics: Vec<PassStatistics>,
+    /// The optimizations after which IR is printed to the standard error.
+    pub print_after: BTreeSet<String>,
+    /// Whether IR is verified with [`ir::verify`] after each optimization, stopping the pipeline
+    /// at the first one producing ill-formed IR (see [`PassManager::run`]).
+    pub verify_each: bool,
+    verify_error: Option<PassVerifyError>,
+}
+
+impl fmt::Debug for PassManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PassManager")
+            .field("statistics", &self.statistics)
+            .field("print_after", &self.print_after)
+            .field("verify_each", &self.verify_each)
+            .field("verify_error", &self.verify_error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PassManager {
+    /// Returns the statistics of the optimizations, in the order of their first occurrences.
+    pub fn statistics(&self) -> &[PassStatistics] {
+        &self.statistics
+    }
+
+    /// Runs the pipeline on `code`, returning whether it changes `code`, or the error of the first
+    /// optimization producing ill-formed IR if [`PassManager::verify_each`] is set.
+    pub fn run(&mut self, code: &mut ir::TranslationUnit) -> Result<bool, PassVerifyError> {
+        Self::run_steps(
+            &mut self.steps,
+            &mut self.statistics,
+            &self.print_after,
+            self.verify_each,
+            code,
+        )
+    }
+
+    /// Returns the error recorded by the last run as [`Optimize`], which cannot return it.
+    pub fn verify_error(&self) -> Option<&PassVerifyError> {
+        self.verify_error.as_ref()
+    }
+
+    fn run_steps(
+        steps: &mut [Step],
+        statistics: &mut [PassStatistics],
+        print_after: &BTreeSet<String>,
+        verify_each: bool,
+        code: &mut ir::TranslationUnit,
+    ) -> Result<bool, PassVerifyError> {
+        let mut result = false;
+        for index in 0..steps.len() {
+            let changed = match &mut steps[index] {
+                Step::Pass { pass, index } => {
+                    let statistics = &mut statistics[*index];
+                    let start = Instant::now();
+                    let result = pass.optimize(code);
+                    statistics.time += start.elapsed();
+                    statistics.runs += 1;
+                    if result {
+                        statistics.changes += 1;
+                    }
+
+                    if verify_each {
+                        ir::verify(code).map_err(|error| PassVerifyError {
+                            pass: statistics.name.clone(),
+                            error,
+                        })?;
+                    }
+                    if print_after.contains(&statistics.name) {
+                        eprintln!("*** IR after {} ***", statistics.name);
+                        write(code, &mut io::stderr()).unwrap();
+                    }
+                    result
+                }
+                Step::Repeat(steps) => {
+                    let mut result = false;
+                    while Self::run_steps(steps, statistics, print_after, verify_each, code)? {
+                        result = true;
+                    }
+                    result
+                }
+            };
+
+            // The other steps may have cached the results of analyzing IR before the change.
+            if changed {
+                for (other, step) in steps.iter_mut().enumerate() {
+                    if other != index {
+                        step.invalidate();
+                    }
+                }
+            }
+            result |= changed;
+        }
+        Ok(result)
+    }
+}
+
+impl Optimize<ir::TranslationUnit> for PassManager {
+    /// Runs the pipeline as [`PassManager::run`] does, recording the error of verification to be
+    /// returned by [`PassManager::verify_error`]. IR is assumed to be changed in that case.
+    fn optimize(&mut self, code: &mut ir::TranslationUnit) -> bool {
+        match self.run(code) {
+            Ok(result) => {
+                self.verify_error = None;
+                result
+            }
+            Err(e) => {
+                self.verify_error = Some(e);
+                true
+            }
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.steps.iter_mut().for_each(Step::invalidate);
+    }
+}
+
+impl FromStr for PassManager {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = PipelineParser {
+            source: s,
+            position: 0,
+            statistics: Vec::new(),
+        };
+        let steps = parser.steps()?;
+        parser.skip_whitespace();
+        if parser.position != s.len() {
+            return Err(parser.error("`,`"));
+        }
+        Ok(Self {
+            steps,
+            statistics: parser.statistics,
+            ..Self::default()
+        })
+    }
+}
+
+/// A recursive descent parser of pipeline descriptions.
+struct PipelineParser<'a> {
+    source: &'a str,
+    position: usize,
+    statistics: Vec<PassStatistics>,
+}
+
+impl PipelineParser<'_> {
+    fn steps(&mut self) -> Result<Vec<Step>, PipelineError> {
+        let mut steps = vec![self.step()?];
+        while self.eat(',') {
+            steps.push(self.step()?);
+        }
+        Ok(steps)
+    }
+
+    fn step(&mut self) -> Result<Step, PipelineError> {
+        self.skip_whitespace();
+        let rest = &self.source[self.position..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("a pass name"));
+        }
+        let name = &rest[..len];
+        self.position += len;
+
+        if name == "repeat" {
+            if !self.eat('(') {
+                return Err(self.error("`(`"));
+            }
+            let steps = self.steps()?;
+            if !self.eat(')') {
+                return Err(self.error("`,` or `)`"));
+            }
+            return Ok(Step::Repeat(steps));
+        }
+
+        let pass = pass(name).ok_or_else(|| PipelineError::UnknownPass {
+            name: name.to_string(),
+        })?;
+        let index = self
+            .statistics
+            .iter()
+            .position(|statistics| statistics.name == name)
+            .unwrap_or_else(|| {
+                self.statistics.push(PassStatistics {
+                    name: name.to_string(),
+                    ..PassStatistics::default()
+                });
+                self.statistics.len() - 1
+            });
+        Ok(Step::Pass { pass, index })
+    }
+
+    /// Skips whitespace and `c` if it follows, returning whether it does.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.source[self.position..].starts_with(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, expected: &'static str) -> PipelineError {
+        PipelineError::Syntax {
+            expected,
+            column: self.source[..self.position].chars().count() + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::BlockId;
+    use crate::opt::tests::translation_unit;
+
+    /// Removes block 1 of the function `f`, to which the initial block jumps.
+    struct RemoveBlock;
+
+    impl Optimize<ir::TranslationUnit> for RemoveBlock {
+        fn optimize(&mut self, code: &mut ir::TranslationUnit) -> bool {
+            let (_, Some(definition)) =
+                code.decls.get_mut("f").unwrap().get_function_mut().unwrap()
+            else {
+                panic!("`f` must be defined");
+            };
+            definition.blocks.remove(&BlockId(1)).is_some()
+        }
+    }
+
+    fn pass_manager(verify_each: bool) -> PassManager {
+        PassManager {
+            steps: vec![Step::Pass {
+                pass: Box::new(RemoveBlock),
+                index: 0,
+            }],
+            statistics: vec![PassStatistics {
+                name: "remove-block".to_string(),
+                ..PassStatistics::default()
+            }],
+            verify_each,
+            ..PassManager::default()
+        }
+    }
+
+    #[test]
+    fn verify_each() {
+        let error = pass_manager(true).run(&mut translation_unit()).unwrap_err();
+        assert_eq!(error.pass, "remove-block");
+        assert_eq!(error.error.func_name, "f");
+        assert_eq!(pass_manager(false).run(&mut translation_unit()), Ok(true));
+
+        let mut pass_manager = pass_manager(true);
+        assert!(pass_manager.optimize(&mut translation_unit()));
+        assert_eq!(
+            pass_manager.verify_error().map(|e| e.pass.as_str()),
+            Some("remove-block")
+        );
+    }
+}
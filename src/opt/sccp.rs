@@ -0,0 +1,386 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use lang_c::ast;
+
+use crate::ir::*;
+use crate::opt::opt_utils::*;
+use crate::opt::*;
+
+pub type Sccp = FunctionPass<SccpInner>;
+
+/// Sparse conditional constant propagation by Wegman and Zadeck, which finds the registers of
+/// constant values along with the blocks executed, assuming that a block is not executed unless it
+/// is jumped to from an executed block with a condition not known to be false.
+///
+/// The uses of the constant registers are replaced with the constants, the branches on constants
+/// are replaced with jumps, and the blocks never executed are removed. The constants are calculated
+/// as in [`interp`], so they are bit-exact with the results of executing the instructions.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SccpInner {}
+
+/// The value of a register in the lattice of constant propagation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Lattice {
+    /// Not known to be defined yet, e.g., a phinode of a block not known to be executed.
+    Top,
+    Constant(Constant),
+    /// Not a constant, or may have different values.
+    Bottom,
+}
+
+impl Lattice {
+    fn meet(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Top, other) | (other, Self::Top) => other,
+            (Self::Constant(lhs), Self::Constant(rhs)) if lhs == rhs => Self::Constant(lhs),
+            _ => Self::Bottom,
+        }
+    }
+}
+
+impl Optimize<FunctionDefinition> for SccpInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        let (values, executable) = Solver::new(code).solve();
+
+        let mut result = false;
+        let num_blocks = code.blocks.len();
+        code.blocks.retain(|bid, _| executable.contains(bid));
+        result |= code.blocks.len() != num_blocks;
+
+        let mut replace = |operand: &mut Operand| {
+            let Some((rid, _)) = operand.get_register() else {
+                return;
+            };
+            if let Some(Lattice::Constant(constant)) = values.get(rid) {
+                *operand = Operand::constant(constant.clone());
+                result = true;
+            }
+        };
+        for block in code.blocks.values_mut() {
+            for instruction in &mut block.instructions {
+                instruction.walk_operands(&mut replace);
+            }
+            block.exit.walk_operands(&mut replace);
+        }
+
+        for block in code.blocks.values_mut() {
+            if let Some(arg) = taken_jump_arg(&block.exit) {
+                block.exit = BlockExit::Jump { arg };
+                result = true;
+            }
+        }
+
+        result
+    }
+}
+
+/// Returns the jump taken by a branch on a constant.
+fn taken_jump_arg(exit: &BlockExit) -> Option<JumpArg> {
+    match exit {
+        BlockExit::ConditionalJump {
+            condition: Operand::Constant(Constant::Int { value, .. }),
+            arg_then,
+            arg_else,
+        } => Some(if *value == 1 { arg_then } else { arg_else }.clone()),
+        BlockExit::Switch {
+            value: Operand::Constant(value @ Constant::Int { .. }),
+            default,
+            cases,
+        } => Some(
+            cases
+                .iter()
+                .find(|(case, _)| case == value)
+                .map_or(default, |(_, arg)| arg)
+                .clone(),
+        ),
+        _ => None,
+    }
+}
+
+struct Solver<'a> {
+    code: &'a FunctionDefinition,
+    values: HashMap<RegisterId, Lattice>,
+    executable: HashSet<BlockId>,
+    /// The blocks using each register defined in another block.
+    users: HashMap<RegisterId, Vec<BlockId>>,
+    /// The executable blocks to visit, as their inputs are changed.
+    worklist: VecDeque<BlockId>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(code: &'a FunctionDefinition) -> Self {
+        let mut users = HashMap::<_, Vec<_>>::new();
+        for (bid, block) in &code.blocks {
+            let operands = block
+                .instructions
+                .iter()
+                .flat_map(|instruction| instruction.operands())
+                .chain(block.exit.operands());
+            for operand in operands {
+                let Some((rid, _)) = operand.get_register() else {
+                    continue;
+                };
+                if is_defined_outside(rid, *bid) {
+                    let blocks = users.entry(*rid).or_default();
+                    if !blocks.contains(bid) {
+                        blocks.push(*bid);
+                    }
+                }
+            }
+        }
+
+        Self {
+            code,
+            values: HashMap::new(),
+            executable: HashSet::new(),
+            users,
+            worklist: VecDeque::new(),
+        }
+    }
+
+    fn solve(mut self) -> (HashMap<RegisterId, Lattice>, HashSet<BlockId>) {
+        let bid_init = self.code.bid_init;
+        // The parameters are not constant.
+        if let Some(block) = self.code.blocks.get(&bid_init) {
+            for aid in 0..block.phinodes.len() {
+                let _unused = self.lower(RegisterId::arg(bid_init, aid), Lattice::Bottom);
+            }
+        }
+        let _unused = self.executable.insert(bid_init);
+        self.worklist.push_back(bid_init);
+
+        while let Some(bid) = self.worklist.pop_front() {
+            self.visit(bid);
+        }
+        (self.values, self.executable)
+    }
+
+    fn visit(&mut self, bid: BlockId) {
+        let Some(block) = self.code.blocks.get(&bid) else {
+            return;
+        };
+
+        for (iid, instruction) in block.instructions.iter().enumerate() {
+            let value = self.evaluate(instruction);
+            let _unused = self.lower(RegisterId::temp(bid, iid), value);
+        }
+
+        for arg in self.executable_jump_args(&block.exit) {
+            let mut is_changed = self.executable.insert(arg.bid);
+            for (aid, operand) in arg.args.iter().enumerate() {
+                let value = self.operand(operand);
+                is_changed |= self.lower(RegisterId::arg(arg.bid, aid), value);
+            }
+            if is_changed {
+                self.push(arg.bid);
+            }
+        }
+    }
+
+    /// Lowers the value of `rid` to its meet with `value`, visiting the executable blocks using it
+    /// again if it is changed. Returns whether it is changed.
+    fn lower(&mut self, rid: RegisterId, value: Lattice) -> bool {
+        let old = self.values.get(&rid).cloned().unwrap_or(Lattice::Top);
+        let new = old.clone().meet(value);
+        if new == old {
+            return false;
+        }
+
+        let _unused = self.values.insert(rid, new);
+        for bid in self.users.get(&rid).cloned().unwrap_or_default() {
+            if self.executable.contains(&bid) {
+                self.push(bid);
+            }
+        }
+        true
+    }
+
+    fn push(&mut self, bid: BlockId) {
+        if !self.worklist.contains(&bid) {
+            self.worklist.push_back(bid);
+        }
+    }
+
+    /// Returns the jumps of `exit` which may be taken.
+    fn executable_jump_args<'b>(&self, exit: &'b BlockExit) -> Vec<&'b JumpArg> {
+        match exit {
+            BlockExit::ConditionalJump {
+                condition,
+                arg_then,
+                arg_else,
+            } => match self.operand(condition) {
+                Lattice::Top => Vec::new(),
+                Lattice::Constant(Constant::Int { value, .. }) => {
+                    vec![if value == 1 { arg_then } else { arg_else }]
+                }
+                _ => vec![arg_then, arg_else],
+            },
+            BlockExit::Switch {
+                value,
+                default,
+                cases,
+            } => match self.operand(value) {
+                Lattice::Top => Vec::new(),
+                Lattice::Constant(value @ Constant::Int { .. }) => vec![
+                    cases
+                        .iter()
+                        .find(|(case, _)| *case == value)
+                        .map_or(default, |(_, arg)| arg),
+                ],
+                _ => exit.jump_args(),
+            },
+            _ => exit.jump_args(),
+        }
+    }
+
+    fn operand(&self, operand: &Operand) -> Lattice {
+        match operand {
+            Operand::Constant(Constant::GlobalVariable { .. }) => Lattice::Bottom,
+            Operand::Constant(constant) => Lattice::Constant(constant.clone()),
+            Operand::Register {
+                rid: RegisterId::Local { .. },
+                ..
+            } => Lattice::Bottom,
+            Operand::Register { rid, .. } => self.values.get(rid).cloned().unwrap_or(Lattice::Top),
+        }
+    }
+
+    fn evaluate(&self, instruction: &Instruction) -> Lattice {
+        match instruction {
+            Instruction::Value { value } => self.operand(value),
+            Instruction::BinOp {
+                op,
+                lhs,
+                rhs,
+                dtype,
+            } => self.fold(&[lhs, rhs], dtype, |operands| {
+                let [lhs, rhs] = operands else { unreachable!() };
+                calculate_binary(op, lhs, rhs)
+            }),
+            Instruction::UnaryOp { op, operand, dtype } => {
+                self.fold(&[operand], dtype, |operands| {
+                    calculate_unary(op, &operands[0])
+                })
+            }
+            Instruction::TypeCast {
+                value,
+                target_dtype,
+            } => self.fold(&[value], target_dtype, |operands| {
+                calculate_typecast(&operands[0], target_dtype)
+            }),
+            _ => Lattice::Bottom,
+        }
+    }
+
+    /// Folds an instruction of `dtype` with `calculate` if its operands are constants.
+    fn fold<F>(&self, operands: &[&Operand], dtype: &Dtype, calculate: F) -> Lattice
+    where
+        F: FnOnce(&[Constant]) -> Option<Constant>,
+    {
+        let mut constants = Vec::new();
+        let mut is_top = false;
+        for operand in operands {
+            match self.operand(operand) {
+                Lattice::Top => is_top = true,
+                Lattice::Constant(constant) if !constant.is_undef() => constants.push(constant),
+                _ => return Lattice::Bottom,
+            }
+        }
+        if is_top {
+            return Lattice::Top;
+        }
+
+        calculate(&constants)
+            .filter(|constant| constant.dtype() == dtype.clone().set_const(false))
+            .map_or(Lattice::Bottom, Lattice::Constant)
+    }
+}
+
+/// Calculates a binary operator with [`calculator`], unless it is undefined or not supported by
+/// the calculator, e.g., a division by zero.
+fn calculate_binary(op: &ast::BinaryOperator, lhs: &Constant, rhs: &Constant) -> Option<Constant> {
+    use ast::BinaryOperator::*;
+
+    let is_supported = match (lhs, rhs) {
+        (
+            Constant::Int {
+                width, is_signed, ..
+            },
+            Constant::Int {
+                value: rhs,
+                width: rhs_width,
+                is_signed: rhs_is_signed,
+            },
+        ) => {
+            width == rhs_width
+                && is_signed == rhs_is_signed
+                && *width <= Dtype::SIZE_OF_LONGLONG * Dtype::BITS_OF_BYTE
+                && match op {
+                    Plus | Minus | Multiply | BitwiseAnd | BitwiseXor | BitwiseOr | Equals
+                    | NotEquals | Less | Greater | LessOrEqual | GreaterOrEqual => true,
+                    Divide | Modulo => *rhs != 0,
+                    ShiftLeft | ShiftRight => (*rhs as i128) >= 0 && *rhs < *width as u128,
+                    _ => false,
+                }
+        }
+        (
+            Constant::Float { value: lhs, width },
+            Constant::Float {
+                value: rhs,
+                width: rhs_width,
+            },
+        ) => {
+            width == rhs_width
+                && match op {
+                    Plus | Minus | Multiply | Less | Greater | LessOrEqual | GreaterOrEqual => true,
+                    Divide => !rhs.is_zero(),
+                    Equals | NotEquals => !lhs.is_nan() && !rhs.is_nan(),
+                    _ => false,
+                }
+        }
+        _ => false,
+    };
+    if !is_supported {
+        return None;
+    }
+
+    let lhs = Value::try_from(lhs.clone()).ok()?;
+    let rhs = Value::try_from(rhs.clone()).ok()?;
+    let result = calculator::calculate_binary_operator_expression(op, lhs, rhs).ok()?;
+    Constant::try_from(result).ok()
+}
+
+/// Calculates a unary operator with [`calculator`], unless it is not supported by the calculator.
+fn calculate_unary(op: &ast::UnaryOperator, operand: &Constant) -> Option<Constant> {
+    use ast::UnaryOperator::*;
+
+    let is_supported = match operand {
+        Constant::Int { width, .. } => match op {
+            Plus | Minus => true,
+            Negate => *width == 1,
+            _ => false,
+        },
+        Constant::Float { .. } => matches!(op, Plus | Minus),
+        _ => false,
+    };
+    if !is_supported {
+        return None;
+    }
+
+    let operand = Value::try_from(operand.clone()).ok()?;
+    let result = calculator::calculate_unary_operator_expression(op, operand).ok()?;
+    Constant::try_from(result).ok()
+}
+
+/// Calculates a type cast between integers and floating point numbers with [`calculator`].
+fn calculate_typecast(value: &Constant, target_dtype: &Dtype) -> Option<Constant> {
+    if !matches!(value, Constant::Int { .. } | Constant::Float { .. })
+        || !matches!(target_dtype, Dtype::Int { .. } | Dtype::Float { .. })
+    {
+        return None;
+    }
+
+    let value = Value::try_from(value.clone()).ok()?;
+    let result = calculator::calculate_typecast(value, target_dtype.clone()).ok()?;
+    Constant::try_from(result).ok()
+}
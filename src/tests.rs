@@ -46,14 +46,16 @@ fn ast_initializer(number: i32) -> ast::Initializer {
 fn modify_ir(unit: &mut ir::TranslationUnit, rand_num: i32) {
     for (name, decl) in &mut unit.decls {
         if name == NONCE_NAME {
-            let (dtype, _) = decl.get_variable().expect("`decl` must be variable");
-            let initializer = ast_initializer(rand_num);
-            let new_decl = ir::Declaration::Variable {
-                dtype: dtype.clone(),
-                initializer: Some(initializer),
+            let ir::Declaration::Variable {
+                initializer,
+                is_definition,
+                ..
+            } = decl
+            else {
+                panic!("`decl` must be variable");
             };
-
-            *decl = new_decl;
+            *initializer = Some(ast_initializer(rand_num));
+            *is_definition = true;
         }
     }
 }
@@ -76,8 +78,13 @@ const SKIP_TEST: i32 = 102;
 
 /// Tests write_c.
 pub fn test_write_c(path: &Path) {
+    test_write_c_with_style(path, &CodeStyle::default());
+}
+
+/// Tests write_c with the code style `style`.
+pub fn test_write_c_with_style(path: &Path, style: &CodeStyle) {
     assert_eq!(path.extension(), Some(std::ffi::OsStr::new("c")));
-    let unit = Parse
+    let unit = Parse::default()
         .translate(&path)
         .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
 
@@ -85,9 +92,9 @@ pub fn test_write_c(path: &Path) {
     let temp_file_path = temp_dir.path().join("temp.c");
     let mut temp_file = File::create(&temp_file_path).unwrap();
 
-    write(&unit, &mut temp_file).unwrap();
+    style.write(&unit, &mut temp_file).unwrap();
 
-    let new_unit = Parse
+    let new_unit = Parse::default()
         .translate(&temp_file_path.as_path())
         .expect("parse failed while parsing the output from implemented printer");
 
@@ -103,11 +110,39 @@ pub fn test_write_c(path: &Path) {
     }
 }
 
+/// Tests the preprocessor.
+pub fn test_preprocess<P1: AsRef<Path>, P2: AsRef<Path>>(
+    from: &P1,
+    to: &P2,
+    preprocess: Preprocess,
+) {
+    let unit = Parse {
+        preprocess: preprocess.clone(),
+        ..Default::default()
+    }
+    .translate(from)
+    .unwrap_or_else(|e| panic!("parse failed {}: {e:?}", from.as_ref().display()));
+    let expected = Parse::default()
+        .translate(to)
+        .unwrap_or_else(|e| panic!("parse failed {}: {e:?}", to.as_ref().display()));
+
+    if !unit.is_equiv(&expected) {
+        let output = preprocess
+            .clone()
+            .translate(from)
+            .expect("preprocessing must succeed if parsing does");
+        panic!(
+            "[preprocess] Failed to correctly preprocess {:?}.\n\n[incorrect result]\n\n{output}",
+            from.as_ref()
+        );
+    }
+}
+
 /// Tests irgen.
 pub fn test_irgen(path: &Path) {
     // Check if the file has .c extension
     assert_eq!(path.extension(), Some(std::ffi::OsStr::new("c")));
-    let unit = Parse
+    let unit = Parse::default()
         .translate(&path)
         .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
 
@@ -205,16 +240,42 @@ pub fn test_irgen(path: &Path) {
     }
 }
 
+/// Tests that irgen translates a C file to the IR in an IR file.
+pub fn test_irgen_output<P1: AsRef<Path>, P2: AsRef<Path>>(from: &P1, to: &P2) {
+    let unit = Parse::default()
+        .translate(from)
+        .unwrap_or_else(|e| panic!("parse failed {}: {e:?}", from.as_ref().display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{irgen_error}"));
+    let expected = ir::Parse::default()
+        .translate(to)
+        .unwrap_or_else(|_| panic!("parse failed {}", to.as_ref().display()));
+
+    if ir != expected {
+        let mut stderr = io::stderr().lock();
+        stderr
+            .write_fmt(format_args!(
+                "[irgen] Failed to correctly generate {:?}.\n\n[incorrect ir]",
+                from.as_ref()
+            ))
+            .unwrap();
+        write(&ir, &mut stderr).unwrap();
+        drop(stderr);
+        panic!("[irgen]");
+    }
+}
+
 /// Tests irparse.
 pub fn test_irparse(path: &Path) {
     // Check if the file has .c extension
     assert_eq!(path.extension(), Some(std::ffi::OsStr::new("c")));
-    let unit = Parse
+    let unit = Parse::default()
         .translate(&path)
         .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
 
     // Test parse
-    let _unused = Parse
+    let _unused = Parse::default()
         .translate(&path)
         .expect("failed to parse the given program");
 
@@ -300,6 +361,105 @@ pub fn test_opt<P1: AsRef<Path>, P2: AsRef<Path>, O: Optimize<ir::TranslationUni
     }
 }
 
+/// Tests the interpreter on an IR file, returning the parsed IR.
+pub fn test_interp(path: &Path, expected: i32) -> ir::TranslationUnit {
+    let ir = ir::Parse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    let result =
+        ir::interp(&ir, Vec::new()).unwrap_or_else(|interp_error| panic!("{}", interp_error));
+    let (value, width, is_signed) = result.get_int().expect("non-integer value occurs");
+    assert_eq!(width, 32);
+    assert!(is_signed);
+    assert_eq!(value as i32, expected);
+
+    ir
+}
+
+/// Tests the interpreter on an IR file.
+///
+/// The IR is also printed and parsed again, which must result in the same IR.
+pub fn test_irrun(path: &Path, expected: i32) {
+    let ir = test_interp(path, expected);
+
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("temp.ir");
+    let mut temp_file = File::create(&temp_file_path).unwrap();
+    write(&ir, &mut temp_file).unwrap();
+
+    let new_ir = ir::Parse::default()
+        .translate(&temp_file_path.as_path())
+        .expect("parse failed while parsing the output from implemented printer");
+    drop(temp_file);
+    assert_eq!(ir, new_ir);
+
+    temp_dir.close().expect("temp dir deletion failed");
+}
+
+/// Tests the verifier on an IR file, which must be well-formed.
+pub fn test_verify(path: &Path) {
+    let ir = ir::Parse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    ir::verify(&ir).unwrap_or_else(|verify_error| panic!("{}: {}", path.display(), verify_error));
+}
+
+/// Tests that an optimization preserves the result of interpreting an IR file, and produces
+/// well-formed IR.
+pub fn test_opt_interp<O: Optimize<ir::TranslationUnit>>(path: &Path, opt: &mut O) {
+    let mut ir = ir::Parse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let expected = ir::interp(&ir, Vec::new());
+
+    let _ = opt.optimize(&mut ir);
+    ir::verify(&ir).unwrap_or_else(|verify_error| panic!("{}: {}", path.display(), verify_error));
+    assert_eq!(
+        ir::interp(&ir, Vec::new()),
+        expected,
+        "{}: result changed by optimization",
+        path.display()
+    );
+}
+
+/// Tests the linker on IR files, interpreting the linked IR.
+///
+/// The linked IR is also printed and parsed again, which must result in the same IR.
+pub fn test_link(paths: &[&Path], expected: i32) {
+    let units = paths
+        .iter()
+        .map(|path| {
+            ir::Parse::default()
+                .translate(path)
+                .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+        })
+        .collect::<Vec<_>>();
+    let ir = ir::Link
+        .translate(&units)
+        .unwrap_or_else(|link_error| panic!("{}", link_error));
+
+    let result =
+        ir::interp(&ir, Vec::new()).unwrap_or_else(|interp_error| panic!("{}", interp_error));
+    let (value, width, is_signed) = result.get_int().expect("non-integer value occurs");
+    assert_eq!(width, 32);
+    assert!(is_signed);
+    assert_eq!(value as i32, expected);
+
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("temp.ir");
+    let mut temp_file = File::create(&temp_file_path).unwrap();
+    write(&ir, &mut temp_file).unwrap();
+
+    let new_ir = ir::Parse::default()
+        .translate(&temp_file_path.as_path())
+        .expect("parse failed while parsing the output from implemented printer");
+    drop(temp_file);
+    assert_eq!(ir, new_ir);
+
+    temp_dir.close().expect("temp dir deletion failed");
+}
+
 /// Tests asmgen.
 pub fn test_asmgen(path: &Path) {
     // Check if the file has .ir extension
@@ -339,8 +499,9 @@ pub fn test_asmgen(path: &Path) {
     write(&asm, &mut buffer).unwrap();
 
     // Compile the assembly code
-    if !Command::new("riscv64-linux-gnu-gcc")
-        .args(["-static", &asm_path_str, "-o", &bin_path_str])
+    let toolchain = Toolchain::default();
+    if !toolchain
+        .link(&[&asm_path_str], Path::new(&bin_path_str))
         .stderr(Stdio::null())
         .status()
         .unwrap()
@@ -350,8 +511,8 @@ pub fn test_asmgen(path: &Path) {
     }
 
     // Emulate the executable
-    let mut child = Command::new("qemu-riscv64-static")
-        .args([&bin_path_str])
+    let mut child = toolchain
+        .run(Path::new(&bin_path_str))
         .stderr(Stdio::piped())
         .spawn()
         .expect("failed to execute the compiled executable");
@@ -388,13 +549,35 @@ pub fn test_asmgen(path: &Path) {
     assert_eq!(value as u8, qemu_status as u8);
 }
 
+/// Tests that asmgen translates an IR file to the assembly in an assembly file, comparing them
+/// as text.
+pub fn test_asmgen_output<P1: AsRef<Path>, P2: AsRef<Path>>(from: &P1, to: &P2) {
+    let ir = ir::Parse::default()
+        .translate(from)
+        .unwrap_or_else(|_| panic!("parse failed {}", from.as_ref().display()));
+    let asm = Asmgen::default()
+        .translate(&ir)
+        .expect("fail to create riscv assembly code");
+    let mut output = Vec::new();
+    write(&asm, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let expected = fs::read_to_string(to).expect("`to` must exist");
+
+    assert_eq!(
+        output,
+        expected,
+        "[asmgen] Failed to correctly generate {:?}.",
+        from.as_ref()
+    );
+}
+
 /// Tests end-to-end translation.
 pub fn test_end_to_end(path: &Path) {
     // Check if the file has .c extension
     assert_eq!(path.extension(), Some(std::ffi::OsStr::new("c")));
 
     // Test parse
-    let unit = Parse
+    let unit = Parse::default()
         .translate(&path)
         .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
 
@@ -498,8 +681,9 @@ pub fn test_end_to_end(path: &Path) {
     write(&asm, &mut buffer).unwrap();
 
     // Compile the assembly code
-    if !Command::new("riscv64-linux-gnu-gcc")
-        .args(["-static", &asm_path_str, "-o", &bin_path_str])
+    let toolchain = Toolchain::default();
+    if !toolchain
+        .link(&[&asm_path_str], Path::new(&bin_path_str))
         .stderr(Stdio::null())
         .status()
         .unwrap()
@@ -509,8 +693,8 @@ pub fn test_end_to_end(path: &Path) {
     }
 
     // Emulate the executable
-    let mut child = Command::new("qemu-riscv64-static")
-        .args([&bin_path_str])
+    let mut child = toolchain
+        .run(Path::new(&bin_path_str))
         .stderr(Stdio::piped())
         .spawn()
         .expect("failed to execute the compiled executable");
@@ -0,0 +1,137 @@
+//! Cross toolchain to assemble, link and execute the generated RISC-V assembly.
+
+use std::path::Path;
+use std::process::Command;
+
+/// A RISC-V cross toolchain, consisting of a C compiler driver, which assembles and links, and an
+/// emulator, which executes the linked executables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toolchain {
+    /// The C compiler driver, e.g., `riscv64-linux-gnu-gcc`.
+    pub cc: String,
+    /// The root directory of the target headers and libraries, if not the default one of `cc`.
+    pub sysroot: Option<String>,
+    /// The user-mode emulator, e.g., `qemu-riscv64-static`.
+    pub emulator: String,
+}
+
+impl Default for Toolchain {
+    fn default() -> Self {
+        Self {
+            cc: "riscv64-linux-gnu-gcc".to_string(),
+            sysroot: None,
+            emulator: "qemu-riscv64-static".to_string(),
+        }
+    }
+}
+
+impl Toolchain {
+    /// Returns the command assembling the assembly file `input` to the object file `output`.
+    pub fn assemble(&self, input: &Path, output: &Path) -> Command {
+        let mut command = self.cc_command();
+        let _unused = command.arg("-c").arg(input).arg("-o").arg(output);
+        command
+    }
+
+    /// Returns the command linking the assembly and the object files `inputs` to the static
+    /// executable `output`.
+    pub fn link<P: AsRef<Path>>(&self, inputs: &[P], output: &Path) -> Command {
+        let mut command = self.cc_command();
+        let _unused = command
+            .arg("-static")
+            .args(inputs.iter().map(AsRef::as_ref))
+            .arg("-o")
+            .arg(output);
+        command
+    }
+
+    /// Returns the command executing the executable `path` in the emulator.
+    pub fn run(&self, path: &Path) -> Command {
+        let mut command = Command::new(&self.emulator);
+        if let Some(sysroot) = &self.sysroot {
+            let _unused = command.arg("-L").arg(sysroot);
+        }
+        let _unused = command.arg(path);
+        command
+    }
+
+    fn cc_command(&self) -> Command {
+        let mut command = Command::new(&self.cc);
+        if let Some(sysroot) = &self.sysroot {
+            let _unused = command.arg(format!("--sysroot={sysroot}"));
+        }
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    fn command_line(command: &Command) -> Vec<&OsStr> {
+        let mut line = vec![command.get_program()];
+        line.extend(command.get_args());
+        line
+    }
+
+    #[test]
+    fn default_command_lines() {
+        let toolchain = Toolchain::default();
+        assert_eq!(
+            command_line(&toolchain.assemble(Path::new("a.s"), Path::new("a.o"))),
+            ["riscv64-linux-gnu-gcc", "-c", "a.s", "-o", "a.o"]
+        );
+        assert_eq!(
+            command_line(&toolchain.link(&["a.s", "b.o"], Path::new("a.out"))),
+            [
+                "riscv64-linux-gnu-gcc",
+                "-static",
+                "a.s",
+                "b.o",
+                "-o",
+                "a.out"
+            ]
+        );
+        assert_eq!(
+            command_line(&toolchain.run(Path::new("a.out"))),
+            ["qemu-riscv64-static", "a.out"]
+        );
+    }
+
+    #[test]
+    fn sysroot_command_lines() {
+        let toolchain = Toolchain {
+            cc: "clang".to_string(),
+            sysroot: Some("/opt/riscv/sysroot".to_string()),
+            emulator: "qemu-riscv64".to_string(),
+        };
+        assert_eq!(
+            command_line(&toolchain.assemble(Path::new("a.s"), Path::new("a.o"))),
+            [
+                "clang",
+                "--sysroot=/opt/riscv/sysroot",
+                "-c",
+                "a.s",
+                "-o",
+                "a.o"
+            ]
+        );
+        assert_eq!(
+            command_line(&toolchain.link(&["a.o"], Path::new("a.out"))),
+            [
+                "clang",
+                "--sysroot=/opt/riscv/sysroot",
+                "-static",
+                "a.o",
+                "-o",
+                "a.out"
+            ]
+        );
+        assert_eq!(
+            command_line(&toolchain.run(Path::new("a.out"))),
+            ["qemu-riscv64", "-L", "/opt/riscv/sysroot", "a.out"]
+        );
+    }
+}
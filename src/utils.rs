@@ -1,3 +1,5 @@
+use crate::c::Diagnostics;
+
 #[macro_export]
 /// Ok or exiting the process.
 macro_rules! ok_or_exit {
@@ -38,13 +40,10 @@ pub trait Translate<S> {
 
 /// Trait to check if a type can be translated.
 pub trait AssertSupported {
-    /// Assert that the type can be translated.
-    ///
-    /// # Panics
+    /// Checks that the type can be translated.
     ///
-    /// Panics if the type can't be translated.
-    // TODO: should return a boolean.
-    fn assert_supported(&self);
+    /// Every construct that can't be translated is reported to `diags`.
+    fn assert_supported(&self, diags: &mut Diagnostics);
 }
 
 /// Essentially the same as [`PartialEq`].
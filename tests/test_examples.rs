@@ -98,6 +98,18 @@ fn test_examples_write_c() {
     });
 }
 
+#[test]
+fn test_examples_write_c_with_style() {
+    let style = CodeStyle {
+        indent_width: 2,
+        brace_style: BraceStyle::NextLine,
+    };
+    test_dir(Path::new("examples/c"), OsStr::new("c"), |path| {
+        println!("[testing write_c with {style:?} for {path:?}]");
+        test_write_c_with_style(path, &style);
+    });
+}
+
 #[test]
 fn test_examples_irgen_small() {
     println!("[testing irgen for \"examples/c/{HELLO_MAIN}.c\"]");
@@ -208,6 +220,51 @@ fn test_examples_gvn() {
     );
 }
 
+#[test]
+fn test_examples_sccp() {
+    test_opt(
+        &Path::new("examples/sccp/sccp.input.ir"),
+        &Path::new("examples/sccp/sccp.output.ir"),
+        &mut Sccp::default(),
+    );
+    test_irrun(Path::new("examples/sccp/sccp.output.ir"), 4);
+
+    for dir in ASMGEN_TEST_DIR_LIST {
+        test_dir(Path::new(dir), OsStr::new("ir"), |path| {
+            // It fails to be interpreted before optimizations.
+            if path.ends_with("struct3.ir") {
+                return;
+            }
+            test_opt_interp(path, &mut Sccp::default());
+        });
+    }
+}
+
+#[test]
+fn test_examples_inline() {
+    test_opt(
+        &Path::new("examples/inline/inline.input.ir"),
+        &Path::new("examples/inline/inline.output.ir"),
+        &mut Inline::default(),
+    );
+    test_irrun(Path::new("examples/inline/inline.output.ir"), 15);
+
+    let mut ir = ir::Parse::default()
+        .translate(&Path::new("examples/inline/inline.input.ir"))
+        .unwrap();
+    assert!(!Inline { threshold: 0 }.optimize(&mut ir));
+
+    for dir in ASMGEN_TEST_DIR_LIST {
+        test_dir(Path::new(dir), OsStr::new("ir"), |path| {
+            // It fails to be interpreted before optimizations.
+            if path.ends_with("struct3.ir") {
+                return;
+            }
+            test_opt_interp(path, &mut Inline::default());
+        });
+    }
+}
+
 #[test]
 fn test_examples_optimize() {
     test_opt_between_dirs(
@@ -268,3 +325,389 @@ fn test_examples_end_to_end() {
         test_end_to_end(path);
     });
 }
+
+#[test]
+fn test_examples_unsupported() {
+    let path = Path::new("examples/unsupported/diagnostics.c");
+    let diags = match Parse::default().translate(&path) {
+        Err(ParseError::Unsupported(diags)) => diags,
+        result => panic!("unsupported constructs must be reported: {result:?}"),
+    };
+
+    let lines = diags.iter().map(|diag| diag.line).collect::<Vec<_>>();
+    assert_eq!(lines, [1, 4, 5]);
+    assert!(
+        diags
+            .iter()
+            .all(|diag| diag.file.ends_with("diagnostics.c"))
+    );
+}
+
+#[test]
+fn test_examples_sema() {
+    let path = Path::new("examples/sema/errors.c");
+    let diags = match Parse::default().translate(&path) {
+        Err(ParseError::Semantic(diags)) => diags,
+        result => panic!("semantic errors must be reported: {result:?}"),
+    };
+
+    let lines = diags
+        .iter()
+        .filter(|diag| diag.severity == Severity::Error)
+        .map(|diag| diag.line)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            7, 12, 13, 14, 15, 16, 17, 18, 19, 21, 25, 26, 35, 39, 44, 44, 45, 46, 43, 53
+        ]
+    );
+    assert_eq!(diags[0].message, "conflicting types for `f`");
+    assert_eq!(
+        diags.last().unwrap().message,
+        "`va_start` used in function with fixed arguments"
+    );
+}
+
+#[test]
+fn test_examples_lint() {
+    let path = Path::new("examples/lint/warnings.c");
+    let mut lints = Lints::default();
+    lints.apply("all").unwrap();
+    let mut parse = Parse {
+        lints: lints.clone(),
+        ..Default::default()
+    };
+    let _unused = parse
+        .translate(&path)
+        .unwrap_or_else(|e| panic!("warnings must not be errors: {e:?}"));
+
+    let warnings = parse
+        .warnings
+        .iter()
+        .map(|diag| (diag.line, diag.lint.unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        warnings,
+        [
+            (8, Lint::SignCompare),
+            (11, Lint::Conversion),
+            (12, Lint::SignCompare),
+            (1, Lint::UnusedParameter),
+            (2, Lint::UnusedVariable),
+            (19, Lint::Conversion),
+            (20, Lint::Conversion),
+        ]
+    );
+
+    lints.apply("error").unwrap();
+    parse.lints = lints.clone();
+    match parse.translate(&path) {
+        Err(ParseError::Semantic(diags)) => assert_eq!(diags.len(), warnings.len()),
+        result => panic!("warnings must be errors with `-Werror`: {result:?}"),
+    }
+
+    let ir = IrParse::default()
+        .translate(&Path::new("examples/ir0/temp.ir"))
+        .unwrap();
+    let warnings = lints.check_ir(&ir);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].lint, Some(Lint::ReturnType));
+}
+
+#[test]
+fn test_examples_link() {
+    let main = Path::new("examples/link/main.ir");
+    let lib = Path::new("examples/link/lib.ir");
+    test_link(&[main, lib], 22);
+    test_link(&[lib, main], 22);
+
+    // `main.ir` only declares `total`, which is defined in `lib.ir`.
+    let parse = |path: &Path| ir::Parse::default().translate(&path).unwrap();
+    let linked = IrLink.translate(&vec![parse(main), parse(lib)]).unwrap();
+    assert!(matches!(
+        linked.decls["total"],
+        ir::Declaration::Variable {
+            is_definition: true,
+            ..
+        }
+    ));
+
+    assert_eq!(
+        IrLink.translate(&vec![parse(lib), parse(lib)]),
+        Err(IrLinkError::MultipleDefinitions {
+            name: "add".to_string()
+        })
+    );
+    assert_eq!(
+        IrLink.translate(&vec![
+            parse(main),
+            parse(Path::new("examples/link/conflict.ir"))
+        ]),
+        Err(IrLinkError::ConflictingTypes {
+            name: "add".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_examples_verify() {
+    for dir in ASMGEN_TEST_DIR_LIST {
+        test_dir(Path::new(dir), OsStr::new("ir"), test_verify);
+    }
+    for dir in [
+        "examples/simplify_cfg",
+        "examples/mem2reg",
+        "examples/gvn",
+        "examples/deadcode",
+    ] {
+        test_dir(Path::new(dir), OsStr::new("ir"), test_verify);
+    }
+
+    let verify = |name: &str| {
+        let path = Path::new("examples/verify").join(name);
+        let ir = ir::Parse::default().translate(&path.as_path()).unwrap();
+        ir::verify(&ir).unwrap_err()
+    };
+    let bid = ir::BlockId;
+    assert_eq!(verify("init.ir").location, ir::Location::Init);
+    assert_eq!(
+        verify("dominance.ir").location,
+        ir::Location::Exit { bid: bid(3) }
+    );
+    assert_eq!(
+        verify("jump_arg.ir").location,
+        ir::Location::Exit { bid: bid(0) }
+    );
+    assert_eq!(
+        verify("operand_dtype.ir").location,
+        ir::Location::Exit { bid: bid(0) }
+    );
+    assert_eq!(verify("switch.ir").msg, "duplicate case 1");
+}
+
+#[test]
+fn test_pass_manager() {
+    let pass_manager = "simplify-cfg, repeat(mem2reg, gvn, deadcode, simplify-cfg)"
+        .parse::<PassManager>()
+        .unwrap();
+    let names = pass_manager
+        .statistics()
+        .iter()
+        .map(|statistics| statistics.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["simplify-cfg", "mem2reg", "gvn", "deadcode"]);
+    assert!(
+        pass_manager
+            .statistics()
+            .iter()
+            .all(|statistics| statistics.runs == 0)
+    );
+
+    let error = |pipeline: &str| pipeline.parse::<PassManager>().err();
+    assert_eq!(
+        error("gvn,licm"),
+        Some(PipelineError::UnknownPass {
+            name: "licm".to_string()
+        })
+    );
+    assert_eq!(
+        error("repeat(gvn"),
+        Some(PipelineError::Syntax {
+            expected: "`,` or `)`",
+            column: 11
+        })
+    );
+    assert_eq!(
+        error("repeat gvn"),
+        Some(PipelineError::Syntax {
+            expected: "`(`",
+            column: 8
+        })
+    );
+    assert_eq!(
+        error("gvn,,deadcode"),
+        Some(PipelineError::Syntax {
+            expected: "a pass name",
+            column: 5
+        })
+    );
+    assert_eq!(
+        error("gvn)"),
+        Some(PipelineError::Syntax {
+            expected: "`,`",
+            column: 4
+        })
+    );
+}
+
+#[test]
+fn test_examples_preprocess() {
+    let from = Path::new("examples/preprocess/macros.input.c");
+    let preprocess = Preprocess {
+        include_dirs: vec!["examples/preprocess/include".into()],
+        macros: vec![
+            CommandLineMacro::Define("OFFSET=7".to_string()),
+            CommandLineMacro::Define("UNDEFINED".to_string()),
+            CommandLineMacro::Undefine("UNDEFINED".to_string()),
+        ],
+    };
+    test_preprocess(
+        &from,
+        &Path::new("examples/preprocess/macros.output.c"),
+        preprocess.clone(),
+    );
+
+    let mut preprocess = Preprocess {
+        macros: Vec::new(),
+        ..preprocess
+    };
+    let error = preprocess
+        .translate(&from)
+        .expect_err("`#error` must be reported");
+    assert_eq!(error.line, 7);
+}
+
+#[test]
+fn test_examples_union() {
+    test_irrun(Path::new("examples/union/union.ir"), 514);
+    test_irgen_output(
+        &Path::new("examples/union/global.c"),
+        &Path::new("examples/union/global.ir"),
+    );
+    test_asmgen_output(
+        &Path::new("examples/union/global.ir"),
+        &Path::new("examples/union/global.s"),
+    );
+}
+
+#[test]
+fn test_examples_enum() {
+    test_irgen_output(
+        &Path::new("examples/enum/enum.c"),
+        &Path::new("examples/enum/enum.ir"),
+    );
+}
+
+#[test]
+fn test_examples_bitfield() {
+    test_irrun(Path::new("examples/bitfield/bitfield.ir"), 130);
+    test_irgen_output(
+        &Path::new("examples/bitfield/global.c"),
+        &Path::new("examples/bitfield/global.ir"),
+    );
+    test_asmgen_output(
+        &Path::new("examples/bitfield/global.ir"),
+        &Path::new("examples/bitfield/global.s"),
+    );
+}
+
+#[test]
+fn test_examples_initializer() {
+    test_irrun(Path::new("examples/initializer/initializer.ir"), 127);
+    test_irgen_output(
+        &Path::new("examples/initializer/global.c"),
+        &Path::new("examples/initializer/global.ir"),
+    );
+    test_asmgen_output(
+        &Path::new("examples/initializer/global.ir"),
+        &Path::new("examples/initializer/global.s"),
+    );
+}
+
+#[test]
+fn test_examples_string() {
+    test_irrun(Path::new("examples/string/string.ir"), 161);
+    test_irgen_output(
+        &Path::new("examples/string/global.c"),
+        &Path::new("examples/string/global.ir"),
+    );
+    test_asmgen_output(
+        &Path::new("examples/string/global.ir"),
+        &Path::new("examples/string/global.s"),
+    );
+}
+
+#[test]
+fn test_examples_variadic() {
+    test_irrun(Path::new("examples/variadic/variadic.ir"), 31);
+    test_irgen_output(
+        &Path::new("examples/variadic/declaration.c"),
+        &Path::new("examples/variadic/declaration.ir"),
+    );
+}
+
+#[test]
+fn test_examples_linkage() {
+    test_irrun(Path::new("examples/linkage/static.ir"), 18);
+    test_irgen_output(
+        &Path::new("examples/linkage/global.c"),
+        &Path::new("examples/linkage/global.ir"),
+    );
+    test_asmgen_output(
+        &Path::new("examples/linkage/global.ir"),
+        &Path::new("examples/linkage/global.s"),
+    );
+}
+
+#[test]
+fn test_examples_local_type() {
+    test_irrun(Path::new("examples/local_type/local_struct.ir"), 40);
+}
+
+#[test]
+fn test_examples_long_double() {
+    test_irrun(Path::new("examples/long_double/long_double.ir"), 127);
+    test_irgen_output(
+        &Path::new("examples/long_double/global.c"),
+        &Path::new("examples/long_double/global.ir"),
+    );
+    test_asmgen_output(
+        &Path::new("examples/long_double/global.ir"),
+        &Path::new("examples/long_double/global.s"),
+    );
+}
+
+#[test]
+fn test_examples_alignment() {
+    test_irrun(Path::new("examples/alignment/alignas.ir"), 12);
+    test_irgen_output(
+        &Path::new("examples/alignment/global.c"),
+        &Path::new("examples/alignment/global.ir"),
+    );
+    test_asmgen_output(
+        &Path::new("examples/alignment/global.ir"),
+        &Path::new("examples/alignment/global.s"),
+    );
+
+    // The alignment of `declared` is kept when it is defined in another translation unit.
+    let parse = |path: &str| ir::Parse::default().translate(&Path::new(path)).unwrap();
+    let linked = IrLink
+        .translate(&vec![
+            parse("examples/alignment/global.ir"),
+            parse("examples/alignment/definition.ir"),
+        ])
+        .unwrap();
+    assert!(matches!(
+        linked.decls["declared"],
+        ir::Declaration::Variable {
+            is_definition: true,
+            alignment: Some(64),
+            ..
+        }
+    ));
+    let asm = Asmgen::default().translate(&linked).unwrap();
+    let mut output = Vec::new();
+    write(&asm, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(".align\t6\ndeclared:\n"));
+}
+
+#[test]
+fn test_examples_kr_function() {
+    test_irrun(Path::new("examples/kr_function/kr_function.ir"), 7);
+    test_irgen_output(
+        &Path::new("examples/kr_function/declaration.c"),
+        &Path::new("examples/kr_function/declaration.ir"),
+    );
+}